@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
 
 /// A graph of the include statement relationships between files.
@@ -9,6 +9,10 @@ pub struct DependencyGraph {
     pub includes: HashMap<PathBuf, Vec<PathBuf>>,
     // Key is included by values.
     pub included_by: HashMap<PathBuf, Vec<PathBuf>>,
+    // Key includes values with its own include statements.
+    pub direct_includes: HashMap<PathBuf, Vec<PathBuf>>,
+    // Key has include statements for values, as written, that could not be found.
+    pub unresolved_includes: HashMap<PathBuf, Vec<String>>,
 }
 
 impl DependencyGraph {
@@ -31,7 +35,87 @@ impl DependencyGraph {
         self.includes.insert(path.to_path_buf(), included_paths);
     }
 
+    pub fn update_direct_includes(&mut self, path: &Path, included_paths: Vec<PathBuf>) {
+        self.direct_includes
+            .insert(path.to_path_buf(), included_paths);
+    }
+
+    pub fn update_unresolved_includes(&mut self, path: &Path, includes: Vec<String>) {
+        if includes.is_empty() {
+            self.unresolved_includes.remove(path);
+        } else {
+            self.unresolved_includes
+                .insert(path.to_path_buf(), includes);
+        }
+    }
+
+    /// The length of the shortest chain of include statements that leads to each
+    /// file from a file no other file includes. Files only reachable through an
+    /// include cycle have no depth.
+    #[must_use]
+    pub fn include_depths(&self) -> HashMap<PathBuf, usize> {
+        let included: HashSet<&PathBuf> = self.direct_includes.values().flatten().collect();
+        let mut queue: VecDeque<(&PathBuf, usize)> = self
+            .direct_includes
+            .keys()
+            .filter(|path| !included.contains(path))
+            .map(|path| (path, 0))
+            .collect();
+        let mut depths = HashMap::new();
+        while let Some((path, depth)) = queue.pop_front() {
+            if depths.contains_key(path) {
+                continue;
+            }
+            depths.insert(path.clone(), depth);
+            if let Some(includes) = self.direct_includes.get(path) {
+                queue.extend(includes.iter().map(|include| (include, depth + 1)));
+            }
+        }
+        depths
+    }
+
+    /// The files that include `path`.
+    #[must_use]
+    pub fn dependents(&self, path: &Path) -> Vec<PathBuf> {
+        self.included_by.get(path).cloned().unwrap_or_default()
+    }
+
+    /// The files that include `path`, either directly or through other includes.
+    #[must_use]
+    pub fn transitive_dependents(&self, path: &Path) -> HashSet<PathBuf> {
+        let mut dependents = HashSet::new();
+        let mut stack = self.dependents(path);
+        while let Some(current) = stack.pop() {
+            if dependents.insert(current.clone()) {
+                stack.extend(self.dependents(&current));
+            }
+        }
+        dependents
+    }
+
+    /// Whether `from` includes `to`, either directly or through other includes.
+    #[must_use]
+    pub fn is_reachable(&self, from: &Path, to: &Path) -> bool {
+        let mut visited: HashSet<&Path> = HashSet::new();
+        let mut stack = vec![from];
+        while let Some(current) = stack.pop() {
+            if !visited.insert(current) {
+                continue;
+            }
+            let Some(includes) = self.includes.get(current) else {
+                continue;
+            };
+            if includes.iter().any(|p| p == to) {
+                return true;
+            }
+            stack.extend(includes.iter().map(PathBuf::as_path));
+        }
+        false
+    }
+
     pub fn remove(&mut self, path: &Path) -> Vec<PathBuf> {
+        self.direct_includes.remove(path);
+        self.unresolved_includes.remove(path);
         if let Some(included_files) = self.includes.remove(path) {
             for included_path in included_files {
                 if let Some(included_by) = self.included_by.get_mut(&included_path) {
@@ -92,6 +176,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_transitive_dependents() {
+        let mut graph = DependencyGraph::default();
+        let path_a = PathBuf::from("a.fbs");
+        let path_b = PathBuf::from("b.fbs");
+        let path_c = PathBuf::from("c.fbs");
+
+        graph.update(&path_a, vec![path_b.clone()]);
+        graph.update(&path_b, vec![path_c.clone()]);
+
+        assert_eq!(
+            graph.transitive_dependents(&path_c),
+            HashSet::from([path_a.clone(), path_b.clone()])
+        );
+        assert_eq!(
+            graph.transitive_dependents(&path_b),
+            HashSet::from([path_a.clone()])
+        );
+        assert!(graph.transitive_dependents(&path_a).is_empty());
+    }
+
     #[test]
     fn test_update_and_remove() {
         let mut graph = DependencyGraph::default();
@@ -106,4 +211,57 @@ mod tests {
         assert!(graph.includes.is_empty());
         assert!(graph.included_by.get(&path_b).unwrap().is_empty());
     }
+
+    #[test]
+    fn test_dependents() {
+        let mut graph = DependencyGraph::default();
+        let path_a = PathBuf::from("a.fbs");
+        let path_b = PathBuf::from("b.fbs");
+        let path_c = PathBuf::from("c.fbs");
+
+        graph.update(&path_a, vec![path_c.clone()]);
+        graph.update(&path_b, vec![path_c.clone()]);
+
+        assert_eq!(
+            graph.dependents(&path_c),
+            vec![path_a.clone(), path_b.clone()]
+        );
+        assert!(graph.dependents(&path_a).is_empty());
+
+        graph.update(&path_a, vec![]);
+        assert_eq!(graph.dependents(&path_c), vec![path_b.clone()]);
+    }
+
+    #[test]
+    fn test_include_depths() {
+        let mut graph = DependencyGraph::default();
+        let path_a = PathBuf::from("a.fbs");
+        let path_b = PathBuf::from("b.fbs");
+        let path_c = PathBuf::from("c.fbs");
+        let path_d = PathBuf::from("d.fbs");
+
+        graph.update_direct_includes(&path_a, vec![path_b.clone(), path_d.clone()]);
+        graph.update_direct_includes(&path_b, vec![path_c.clone()]);
+        graph.update_direct_includes(&path_c, vec![path_d.clone()]);
+
+        assert_eq!(
+            graph.include_depths(),
+            HashMap::from([(path_a, 0), (path_b, 1), (path_c, 2), (path_d, 1)])
+        );
+    }
+
+    #[test]
+    fn test_is_reachable() {
+        let mut graph = DependencyGraph::default();
+        let path_a = PathBuf::from("a.fbs");
+        let path_b = PathBuf::from("b.fbs");
+        let path_c = PathBuf::from("c.fbs");
+
+        graph.update(&path_a, vec![path_b.clone()]);
+        graph.update(&path_b, vec![path_c.clone(), path_a.clone()]);
+
+        assert!(graph.is_reachable(&path_a, &path_b));
+        assert!(graph.is_reachable(&path_a, &path_c));
+        assert!(!graph.is_reachable(&path_c, &path_a));
+    }
 }
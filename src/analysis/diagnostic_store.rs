@@ -1,6 +1,7 @@
+use crate::diagnostics::suppression::Suppressions;
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use tower_lsp_server::lsp_types::Diagnostic;
+use tower_lsp_server::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
 
 /// A store for diagnostics that tracks their published state.
 #[derive(Debug, Clone, Default, PartialEq)]
@@ -8,17 +9,20 @@ pub struct DiagnosticStore {
     per_file: HashMap<PathBuf, Vec<Diagnostic>>,
     /// File paths with diagnostics that have not been published.
     unpublished: HashSet<PathBuf>,
+    /// The version of each open document that its diagnostics were computed from.
+    versions: HashMap<PathBuf, i32>,
+    /// The diagnostics each file has suppressed with comments.
+    suppressions: HashMap<PathBuf, Suppressions>,
 }
 
 impl DiagnosticStore {
     /// Update the store with the latest diagnostics.
     pub fn update(&mut self, diagnostics: HashMap<PathBuf, Vec<Diagnostic>>) {
-        for (path, mut new_diags) in diagnostics {
-            new_diags.sort_by(|a, b| {
-                a.message
-                    .cmp(&b.message)
-                    .then_with(|| a.range.start.cmp(&b.range.start))
-            });
+        for (path, new_diags) in diagnostics {
+            let mut new_diags = merge(new_diags);
+            if let Some(suppressions) = self.suppressions.get(&path) {
+                new_diags.retain(|d| !suppressions.suppresses(d));
+            }
 
             let old_diags = self.per_file.get(&path);
             let has_changed = old_diags.is_none_or(|d| *d != new_diags);
@@ -30,6 +34,30 @@ impl DiagnosticStore {
         }
     }
 
+    /// Record the version of the document the next diagnostics for `path` are computed from.
+    /// `None` for documents that are not open in the client.
+    pub fn set_version(&mut self, path: &Path, version: Option<i32>) {
+        match version {
+            Some(version) => self.versions.insert(path.to_path_buf(), version),
+            None => self.versions.remove(path),
+        };
+    }
+
+    /// Drop the diagnostics that `path` suppresses from the next update on.
+    pub fn set_suppressions(&mut self, path: &Path, suppressions: Suppressions) {
+        if suppressions == Suppressions::default() {
+            self.suppressions.remove(path);
+        } else {
+            self.suppressions.insert(path.to_path_buf(), suppressions);
+        }
+    }
+
+    /// The document version that the diagnostics for `path` were computed from.
+    #[must_use]
+    pub fn version(&self, path: &Path) -> Option<i32> {
+        self.versions.get(path).copied()
+    }
+
     /// Mark all unpublished diagnostics as published and return them.
     /// Caller takes responsibility for publishing them.
     pub fn mark_published(&mut self) -> HashMap<PathBuf, Vec<Diagnostic>> {
@@ -47,6 +75,8 @@ impl DiagnosticStore {
     pub fn remove(&mut self, path: &Path) {
         self.per_file.remove(path);
         self.unpublished.remove(path);
+        self.versions.remove(path);
+        self.suppressions.remove(path);
     }
 
     pub fn remove_dir(&mut self, dir: &Path) {
@@ -64,10 +94,73 @@ impl DiagnosticStore {
     }
 }
 
+/// Collapse diagnostics with the same range, code and message into one, keeping the
+/// highest severity and all of their related information. The result is ordered by
+/// position and code so that it is the same on every run.
+fn merge(diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+    let mut merged: Vec<Diagnostic> = Vec::with_capacity(diagnostics.len());
+    for diagnostic in diagnostics {
+        let Some(existing) = merged.iter_mut().find(|d| {
+            d.range == diagnostic.range
+                && d.code == diagnostic.code
+                && d.message == diagnostic.message
+        }) else {
+            merged.push(diagnostic);
+            continue;
+        };
+
+        if severity_rank(&diagnostic) < severity_rank(existing) {
+            existing.severity = diagnostic.severity;
+        }
+        for info in diagnostic.related_information.into_iter().flatten() {
+            let related = existing.related_information.get_or_insert_default();
+            if !related.contains(&info) {
+                related.push(info);
+            }
+        }
+        for tag in diagnostic.tags.into_iter().flatten() {
+            let tags = existing.tags.get_or_insert_default();
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+    }
+
+    merged.sort_by(|a, b| {
+        a.range
+            .start
+            .cmp(&b.range.start)
+            .then_with(|| code_key(a).cmp(&code_key(b)))
+            .then_with(|| severity_rank(a).cmp(&severity_rank(b)))
+            .then_with(|| a.message.cmp(&b.message))
+    });
+    merged
+}
+
+fn code_key(diagnostic: &Diagnostic) -> (i32, &str) {
+    match &diagnostic.code {
+        Some(NumberOrString::Number(n)) => (*n, ""),
+        Some(NumberOrString::String(s)) => (0, s.as_str()),
+        None => (0, ""),
+    }
+}
+
+/// Orders severities from most to least severe. Clients usually treat a missing severity as an error.
+fn severity_rank(diagnostic: &Diagnostic) -> u8 {
+    match diagnostic.severity {
+        Some(DiagnosticSeverity::WARNING) => 1,
+        Some(DiagnosticSeverity::INFORMATION) => 2,
+        Some(DiagnosticSeverity::HINT) => 3,
+        _ => 0,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tower_lsp_server::lsp_types::{Position, Range};
+    use tower_lsp_server::lsp_types::{
+        DiagnosticRelatedInformation, Location, Position, Range, Uri,
+    };
 
     fn make_diagnostic(message: &str) -> Diagnostic {
         Diagnostic::new_simple(
@@ -124,6 +217,27 @@ mod tests {
         assert!(store.unpublished.is_empty());
     }
 
+    #[test]
+    fn test_version_change_alone_is_not_republished() {
+        let mut store = DiagnosticStore::default();
+        let path = PathBuf::from("a.fbs");
+        let mut diagnostics = HashMap::new();
+        diagnostics.insert(path.clone(), vec![make_diagnostic("error")]);
+
+        store.set_version(&path, Some(1));
+        store.update(diagnostics.clone());
+        assert_eq!(store.mark_published().len(), 1);
+        assert_eq!(store.version(&path), Some(1));
+
+        store.set_version(&path, Some(2));
+        store.update(diagnostics);
+        assert!(store.mark_published().is_empty());
+        assert_eq!(store.version(&path), Some(2));
+
+        store.remove(&path);
+        assert_eq!(store.version(&path), None);
+    }
+
     #[test]
     fn test_mark_published() {
         let mut store = DiagnosticStore::default();
@@ -137,4 +251,93 @@ mod tests {
         assert_eq!(store.mark_published().len(), 2);
         assert!(store.mark_published().is_empty());
     }
+
+    #[test]
+    fn test_duplicate_diagnostics_are_merged() {
+        let mut store = DiagnosticStore::default();
+        let path = PathBuf::from("a.fbs");
+        let related = |message: &str| DiagnosticRelatedInformation {
+            location: Location {
+                uri: "file:///a.fbs".parse::<Uri>().unwrap(),
+                range: Range::default(),
+            },
+            message: message.to_string(),
+        };
+
+        // The same problem reported by two passes, each with its own related information.
+        let first = Diagnostic {
+            severity: Some(DiagnosticSeverity::WARNING),
+            related_information: Some(vec![related("first")]),
+            ..make_diagnostic("duplicate")
+        };
+        let second = Diagnostic {
+            severity: Some(DiagnosticSeverity::ERROR),
+            related_information: Some(vec![related("second")]),
+            ..make_diagnostic("duplicate")
+        };
+        let hint = Diagnostic {
+            severity: Some(DiagnosticSeverity::HINT),
+            ..make_diagnostic("a hint")
+        };
+        let mut diagnostics = HashMap::new();
+        diagnostics.insert(path.clone(), vec![hint, first, second]);
+
+        store.update(diagnostics);
+        let published = store.mark_published();
+        let merged = published.get(&path).unwrap();
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].message, "duplicate");
+        assert_eq!(merged[0].severity, Some(DiagnosticSeverity::ERROR));
+        assert_eq!(
+            merged[0].related_information,
+            Some(vec![related("first"), related("second")])
+        );
+        assert_eq!(merged[1].message, "a hint");
+    }
+
+    #[test]
+    fn test_diagnostics_are_ordered_by_position_and_code() {
+        let mut store = DiagnosticStore::default();
+        let path = PathBuf::from("a.fbs");
+        let at = |line: u32, code: &str| Diagnostic {
+            range: Range::new(Position::new(line, 0), Position::new(line, 1)),
+            code: Some(NumberOrString::String(code.to_string())),
+            ..make_diagnostic(code)
+        };
+        let mut diagnostics = HashMap::new();
+        diagnostics.insert(
+            path.clone(),
+            vec![at(2, "b"), at(1, "b"), at(2, "a"), at(0, "c")],
+        );
+
+        store.update(diagnostics);
+        let published = store.mark_published();
+        let order: Vec<_> = published
+            .get(&path)
+            .unwrap()
+            .iter()
+            .map(|d| (d.range.start.line, d.message.as_str()))
+            .collect();
+        assert_eq!(order, vec![(0, "c"), (1, "b"), (2, "a"), (2, "b")]);
+    }
+
+    #[test]
+    fn test_ignored_file_has_no_diagnostics() {
+        let mut store = DiagnosticStore::default();
+        let path = PathBuf::from("a.fbs");
+        let mut diagnostics = HashMap::new();
+        diagnostics.insert(path.clone(), vec![make_diagnostic("error")]);
+
+        let ignore_file = Suppressions {
+            ignore_file: true,
+            ..Suppressions::default()
+        };
+        store.set_suppressions(&path, ignore_file);
+        store.update(diagnostics.clone());
+        assert!(store.mark_published().get(&path).unwrap().is_empty());
+
+        store.set_suppressions(&path, Suppressions::default());
+        store.update(diagnostics);
+        assert_eq!(store.mark_published().get(&path).unwrap().len(), 1);
+    }
 }
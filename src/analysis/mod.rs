@@ -7,24 +7,50 @@ pub mod workspace_index;
 
 pub use crate::analysis::snapshot::WorkspaceSnapshot;
 use crate::analysis::workspace_index::WorkspaceIndex;
+use crate::diagnostics::include_depth::DEFAULT_MAX_INCLUDE_DEPTH;
+use crate::diagnostics::{include_not_found, namespace_mismatch, suppression};
 use crate::document_store::DocumentStore;
-use crate::parser::Parser;
+use crate::parser::{ParseResult, Parser};
 use crate::utils::paths::{is_flatbuffer_schema, uri_to_path_buf};
 use crate::workspace_layout::WorkspaceLayout;
 use log::info;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
 use tokio::sync::RwLock;
+use tokio::time::Instant;
 use tower_lsp_server::lsp_types::{Diagnostic, FileChangeType, FileEvent, Uri};
 use tower_lsp_server::UriExt;
 
+/// Called with the percentage (0-100) of files parsed so far.
+pub type ProgressCallback<'a> =
+    dyn Fn(u32) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> + Send + Sync + 'a;
+
 /// A semantic analyzer for a workspace.
 #[derive(Debug)]
 pub struct Analyzer {
     index: RwLock<WorkspaceIndex>,
     documents: Arc<DocumentStore>,
     pub layout: RwLock<WorkspaceLayout>,
+    /// Whether to compute the workspace-wide unreferenced type diagnostics.
+    pub report_unreferenced_types: AtomicBool,
+    /// Whether to check that each file's namespace matches its directory.
+    pub report_namespace_mismatch: AtomicBool,
+    /// Whether to record how long each file takes to parse.
+    pub record_parse_times: AtomicBool,
+    /// How many includes deep to follow from each parsed file. Includes nested
+    /// deeper are reported and not followed.
+    pub max_include_depth: AtomicUsize,
+    /// Parse durations recorded since they were last taken.
+    parse_times: Mutex<Vec<(PathBuf, Duration)>>,
+    /// Edited files whose dependents have not been revalidated since.
+    pending_revalidation: Mutex<HashSet<PathBuf>>,
+    /// Incremented on every edit so that only the last edit of a burst revalidates.
+    revalidation_generation: AtomicU64,
 }
 
 impl Analyzer {
@@ -34,6 +60,13 @@ impl Analyzer {
             index: RwLock::new(WorkspaceIndex::new()),
             documents,
             layout: RwLock::new(WorkspaceLayout::new()),
+            report_unreferenced_types: AtomicBool::new(false),
+            report_namespace_mismatch: AtomicBool::new(false),
+            record_parse_times: AtomicBool::new(false),
+            max_include_depth: AtomicUsize::new(DEFAULT_MAX_INCLUDE_DEPTH),
+            parse_times: Mutex::new(Vec::new()),
+            pending_revalidation: Mutex::new(HashSet::new()),
+            revalidation_generation: AtomicU64::new(0),
         }
     }
 
@@ -70,12 +103,12 @@ impl Analyzer {
             }
         }
 
-        // Handling additions is simpler as a full reparse, so we
-        // do that. This also means we can ignore the more targeted
-        // removal parse list in this case.
+        // Handling additions is simpler as a full reparse, so we do that.
+        // Files affected by a removal may be outside every root, so they
+        // are kept in the parse list.
         if !added_paths.is_empty() {
             let mut layout = self.layout.write().await;
-            to_parse = layout.discover_files().into_iter().collect();
+            to_parse.extend(layout.discover_files());
         }
 
         diagnostics.extend(self.parse(to_parse).await.into_iter());
@@ -89,21 +122,20 @@ impl Analyzer {
 
     /// Remove the given workspace folder and return affected files.
     async fn remove_workspace_folder(&self, folder: &PathBuf) -> FolderRemoval {
-        let mut files_to_reparse;
-
         let mut layout = self.layout.write().await;
         let mut index = self.index.write().await;
         let to_remove = layout.known_matching_files(folder);
 
-        files_to_reparse = HashSet::new();
+        // Files that include a removed file through other files may also
+        // use its types, so collect them before the include edges are removed.
+        let mut files_to_reparse = HashSet::new();
         for path in &to_remove {
-            let affected_files = index
-                .remove(path)
-                .into_iter()
-                .filter(|p| !to_remove.contains(p))
-                .collect::<Vec<_>>();
-            files_to_reparse.extend(affected_files);
+            files_to_reparse.extend(index.dependencies.transitive_dependents(path));
         }
+        for path in &to_remove {
+            index.remove(path);
+        }
+        files_to_reparse.retain(|p| !to_remove.contains(p));
 
         layout.remove_root(folder);
         index.diagnostics.remove_dir(folder);
@@ -114,51 +146,199 @@ impl Analyzer {
         }
     }
 
+    /// Discard the index and every document not open in the client, then
+    /// rediscover and parse the workspace from disk. Returns the diagnostics
+    /// for every file, including empty ones to clear files that no longer exist.
+    pub async fn reindex(
+        &self,
+        progress: Option<&ProgressCallback<'_>>,
+    ) -> Vec<(PathBuf, Vec<Diagnostic>)> {
+        let previous = {
+            let mut index = self.index.write().await;
+            let previous: Vec<PathBuf> = index.diagnostics.all().keys().cloned().collect();
+            *index = WorkspaceIndex::new();
+            previous
+        };
+        self.documents.clear_unopened();
+
+        // Open documents outside of the workspace roots are kept as well.
+        let mut files = self.layout.write().await.discover_files();
+        files.extend(
+            self.documents
+                .document_map
+                .iter()
+                .map(|entry| entry.key().clone()),
+        );
+        let mut diagnostics: HashMap<PathBuf, Vec<Diagnostic>> =
+            previous.into_iter().map(|path| (path, vec![])).collect();
+        diagnostics.extend(self.parse_with_progress(files, progress).await);
+        diagnostics.into_iter().collect()
+    }
+
+    /// Record an edit to `path` whose dependents should be revalidated once edits
+    /// settle. Returns the generation to pass to [`Analyzer::revalidate_dependents`],
+    /// or `None` if nothing includes `path`.
+    pub async fn schedule_revalidation(&self, path: &Path) -> Option<u64> {
+        if self
+            .index
+            .read()
+            .await
+            .dependencies
+            .dependents(path)
+            .is_empty()
+        {
+            return None;
+        }
+        self.pending_revalidation
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(path.to_path_buf());
+        Some(self.revalidation_generation.fetch_add(1, Ordering::AcqRel) + 1)
+    }
+
+    /// Reparse every file that transitively includes a file edited since the last
+    /// revalidation. Does nothing if another edit was made after `generation`, as
+    /// that edit schedules its own revalidation.
+    ///
+    /// Parsing an edited file only reparses its dependents when the names it defines
+    /// change, which keeps each keystroke cheap. Other changes, like a struct becoming
+    /// a table, are caught here instead.
+    pub async fn revalidate_dependents(&self, generation: u64) -> Vec<(PathBuf, Vec<Diagnostic>)> {
+        if self.revalidation_generation.load(Ordering::Acquire) != generation {
+            return vec![];
+        }
+        let edited: Vec<PathBuf> = self
+            .pending_revalidation
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .drain()
+            .collect();
+        let dependents: HashSet<PathBuf> = {
+            let index = self.index.read().await;
+            edited
+                .iter()
+                .flat_map(|path| index.dependencies.dependents(path))
+                .collect()
+        };
+        if dependents.is_empty() {
+            return vec![];
+        }
+        info!("revalidating {} dependents", dependents.len());
+        self.parse(dependents).await
+    }
+
     /// Parse a set of files and return the set of new diagnostics
     /// to publish as a result.
     pub async fn parse(
         &self,
         paths: impl IntoIterator<Item = PathBuf>,
     ) -> Vec<(PathBuf, Vec<Diagnostic>)> {
-        let mut parsed_in_scan = HashSet::new();
+        self.parse_with_progress(paths, None).await
+    }
+
+    /// Like [`Analyzer::parse`], but calls `progress` whenever the
+    /// percentage of `paths` that have been parsed changes.
+    pub async fn parse_with_progress(
+        &self,
+        paths: impl IntoIterator<Item = PathBuf>,
+        progress: Option<&ProgressCallback<'_>>,
+    ) -> Vec<(PathBuf, Vec<Diagnostic>)> {
+        let paths: Vec<PathBuf> = paths.into_iter().collect();
+        let total = paths.len();
+        let mut last_percentage = None;
+
+        let mut parsed_in_scan = HashMap::new();
         let mut all_diagnostics = Vec::new();
-        for path in paths {
-            if !parsed_in_scan.contains(&path) {
-                let mut diags = self.parse_single(&path, &mut parsed_in_scan).await;
+        for (i, path) in paths.iter().enumerate() {
+            if parsed_in_scan.get(path) != Some(&0) {
+                let mut diags = self.parse_single(path, &mut parsed_in_scan).await;
                 all_diagnostics.append(&mut diags);
             }
+
+            if let Some(progress) = progress {
+                let percentage = u32::try_from((i + 1) * 100 / total).unwrap_or(100);
+                if last_percentage != Some(percentage) {
+                    last_percentage = Some(percentage);
+                    progress(percentage).await;
+                }
+            }
         }
+
+        // Unreferenced types, type suggestions and include depths depend on the
+        // whole workspace, so they are updated once all of the files have been parsed.
+        let mut index = self.index.write().await;
+        index.update_unreferenced_types(self.report_unreferenced_types.load(Ordering::Acquire));
+        index.update_type_suggestions();
+        index.update_include_depth(
+            &self.documents.document_map,
+            self.max_include_depth.load(Ordering::Acquire),
+        );
+        let updated = index.diagnostics.mark_published();
+        all_diagnostics.retain(|(path, _)| !updated.contains_key(path));
+        all_diagnostics.extend(updated);
         all_diagnostics
     }
 
     async fn parse_single(
         &self,
         path: &Path,
-        parsed_files: &mut HashSet<PathBuf>,
+        parsed_files: &mut HashMap<PathBuf, usize>,
     ) -> Vec<(PathBuf, Vec<Diagnostic>)> {
         let layout = self.layout.read().await;
         let mut index = self.index.write().await;
-        let search_paths: Vec<_> = layout.search_paths.iter().map(PathBuf::from).collect();
 
-        let mut files_to_parse = vec![path.to_path_buf()];
-        let mut newly_parsed_files = HashSet::new();
+        let max_include_depth = self.max_include_depth.load(Ordering::Acquire);
+        // Each file is paired with how many includes deep it is from a file parsed directly.
+        // Files are visited breadth first so that this is the length of the shortest include chain.
+        let mut files_to_parse = VecDeque::from([(path.to_path_buf(), 0)]);
 
-        while let Some(path) = files_to_parse.pop() {
-            if !parsed_files.insert(path.clone()) {
+        while let Some((path, depth)) = files_to_parse.pop_front() {
+            if let Some(&reached) = parsed_files.get(&path) {
+                if reached <= depth {
+                    continue;
+                }
+                // Already parsed, but reached more shallowly now, so includes that were
+                // too deep before may be within the limit. Following them again keeps the
+                // files that are parsed independent of the order they are reached in.
+                if let Some(includes) = index
+                    .dependencies
+                    .direct_includes
+                    .get(&path)
+                    .filter(|_| depth < max_include_depth)
+                {
+                    files_to_parse.extend(
+                        includes
+                            .iter()
+                            .map(|included| (included.clone(), depth + 1)),
+                    );
+                }
+                parsed_files.insert(path, depth);
                 continue;
             }
-            newly_parsed_files.insert(path.clone());
+            parsed_files.insert(path.clone(), depth);
 
+            // Read the version first so that diagnostics are never attributed
+            // to a newer version than the content they were computed from.
+            let version = self.documents.version(&path);
             let content = if let Some(doc) = self.documents.document_map.get(&path) {
                 doc.value().to_string()
             } else {
-                match tokio::fs::read_to_string(&path).await {
-                    Ok(text) => {
+                match tokio::fs::read(&path).await.map(String::from_utf8) {
+                    Ok(Ok(text)) => {
                         self.documents
                             .document_map
                             .insert(path.clone(), ropey::Rope::from_str(&text));
                         text
                     }
+                    Ok(Err(e)) => {
+                        log::error!("file is not valid UTF-8: {}", path.display());
+                        let valid_up_to = e.utf8_error().valid_up_to();
+                        index.update(
+                            &path,
+                            ParseResult::invalid_utf8(&path, e.as_bytes(), valid_up_to),
+                        );
+                        continue;
+                    }
                     Err(e) => {
                         log::error!("failed to read file {}: {}", path.display(), e);
                         continue;
@@ -167,20 +347,68 @@ impl Analyzer {
             };
 
             log::info!("parsing: {}", path.display());
-            let result = crate::parser::FlatcFFIParser.parse(&path, &content, &search_paths);
+            let start = Instant::now();
+            let search_paths = layout.search_paths_for(&path);
+            let mut result = crate::parser::FlatcFFIParser.parse(&path, &content, &search_paths);
+            include_not_found::suggest_includes(&mut result.diagnostics, &layout);
+            if self.report_namespace_mismatch.load(Ordering::Acquire) {
+                namespace_mismatch::analyze_namespace_mismatch(
+                    &path,
+                    &content,
+                    &layout,
+                    &mut result.diagnostics,
+                );
+            }
 
-            for included_path in &result.includes {
-                if !parsed_files.contains(included_path) {
-                    files_to_parse.push(included_path.clone());
+            if self.record_parse_times.load(Ordering::Acquire) {
+                self.parse_times
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .push((path.clone(), start.elapsed()));
+            }
+
+            // Includes past the limit are reported by `update_include_depth`
+            // once the whole workspace has been parsed.
+            if depth < max_include_depth {
+                for included_path in &result.direct_includes {
+                    if !parsed_files.contains_key(included_path) {
+                        files_to_parse.push_back((included_path.clone(), depth + 1));
+                    }
                 }
             }
 
+            let previous_symbols = index.symbols.symbol_names(&path);
+            index.diagnostics.set_version(&path, version);
+            index
+                .diagnostics
+                .set_suppressions(&path, suppression::Suppressions::parse(&content));
             index.update(&path, result);
+
+            // Files that include this one may reference symbols that were just
+            // added or removed, so their diagnostics need to be refreshed too.
+            if index.symbols.symbol_names(&path) != previous_symbols {
+                for dependent in index.dependencies.dependents(&path) {
+                    if !parsed_files.contains_key(&dependent) {
+                        files_to_parse.push_back((dependent, 0));
+                    }
+                }
+            }
         }
 
         index.diagnostics.mark_published().into_iter().collect()
     }
 
+    /// The parse durations recorded since the last call, in the order the files
+    /// were parsed. Only recorded while [`Analyzer::record_parse_times`] is set.
+    pub fn take_parse_times(&self) -> Vec<(PathBuf, Duration)> {
+        std::mem::take(
+            &mut *self
+                .parse_times
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        )
+    }
+
     pub async fn handle_file_changes(
         &self,
         changes: Vec<FileEvent>,
@@ -200,10 +428,15 @@ impl Analyzer {
             let mut index = self.index.write().await;
 
             for event in changes {
-                // Canonicalize will fail for deleted files, so fall back to non-canonical.
-                // TODO: Figure out a better heuristic (resolve parents or store client<>canonical map or scan).
+                // Canonicalize will fail for deleted files, so prefer the path of a document
+                // the client opened with this URI, then fall back to non-canonical.
                 let non_canonical = event.uri.to_file_path().map(|p| p.to_path_buf());
-                let Some(path) = uri_to_path_buf(&event.uri).ok().or(non_canonical) else {
+                let Some(path) = self
+                    .documents
+                    .path(&event.uri)
+                    .or_else(|| uri_to_path_buf(&event.uri).ok())
+                    .or(non_canonical)
+                else {
                     continue;
                 };
 
@@ -211,9 +444,17 @@ impl Analyzer {
                 if !is_flatbuffer_schema(&event.uri) && has_ext {
                     continue;
                 }
+                if layout.is_ignored(&path) {
+                    continue;
+                }
 
                 match event.typ {
                     FileChangeType::CREATED => {
+                        // Files that failed to include this one can now resolve it.
+                        files_to_reparse.extend(include_not_found::files_missing(
+                            &index.dependencies.unresolved_includes,
+                            &path,
+                        ));
                         files_to_reparse.insert(path.clone());
                         layout.add_file(path);
                     }
@@ -221,6 +462,8 @@ impl Analyzer {
                         // NOTE: This doubles the work done on save,
                         // but allows us to capture file changes made
                         // outside of the client (e.g. git checkout).
+                        self.documents.clear_if_unopened(&path);
+                        files_to_reparse.extend(index.dependencies.dependents(&path));
                         files_to_reparse.insert(path);
                     }
                     FileChangeType::DELETED => {
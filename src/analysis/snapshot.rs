@@ -1,6 +1,7 @@
 use crate::analysis::workspace_index::WorkspaceIndex;
 use crate::ext::range::RangeExt;
 use crate::symbol_table::{self, Field, RpcService, Symbol, SymbolKind, Union};
+use crate::utils::parsed_type::ParsedType;
 use crate::utils::paths::uri_to_path_buf;
 use dashmap::DashMap;
 use ropey::Rope;
@@ -105,6 +106,112 @@ impl<'a> WorkspaceSnapshot<'a> {
         None
     }
 
+    /// The table or struct, and its field, whose field name is under the cursor.
+    pub fn find_field_at(&self, uri: &Uri, position: Position) -> Option<(&Symbol, &Symbol)> {
+        let path = uri_to_path_buf(uri).ok()?;
+        self.symbols
+            .per_file
+            .get(&path)?
+            .iter()
+            .filter_map(|key| self.symbols.global.get(key))
+            .find_map(|parent| {
+                let fields = match &parent.kind {
+                    SymbolKind::Table(t) => &t.fields,
+                    SymbolKind::Struct(s) => &s.fields,
+                    _ => return None,
+                };
+                fields
+                    .iter()
+                    .find(|field| field.info.location.range.contains(position))
+                    .map(|field| (parent, field))
+            })
+    }
+
+    /// The field whose type, including any vector or array brackets, is under the cursor.
+    pub fn find_field_type_at(&self, uri: &Uri, position: Position) -> Option<&Field> {
+        let path = uri_to_path_buf(uri).ok()?;
+        self.symbols
+            .per_file
+            .get(&path)?
+            .iter()
+            .filter_map(|key| self.symbols.global.get(key))
+            .find_map(|parent| {
+                let fields = match &parent.kind {
+                    SymbolKind::Table(t) => &t.fields,
+                    SymbolKind::Struct(s) => &s.fields,
+                    _ => return None,
+                };
+                fields.iter().find_map(|field| match &field.kind {
+                    SymbolKind::Field(f) if f.type_range.contains(position) => Some(f),
+                    _ => None,
+                })
+            })
+    }
+
+    /// Resolve the namespace prefix under the cursor when it is on a namespace part
+    /// of a type reference. For example, `Two` in `a: One.Two.X` resolves to `["One", "Two"]`.
+    pub fn resolve_namespace_at(&self, uri: &Uri, position: Position) -> Option<Vec<String>> {
+        let path = uri_to_path_buf(uri).ok()?;
+
+        let mut parsed_types: Vec<&ParsedType> = Vec::new();
+        if let Some(root_type_info) = self.root_types.root_types.get(&path) {
+            parsed_types.push(&root_type_info.parsed_type);
+        }
+
+        if let Some(symbol_at_cursor) = self
+            .symbols
+            .global
+            .values()
+            .find_map(|symbol| symbol.find_symbol(&path, position))
+        {
+            match &symbol_at_cursor.kind {
+                SymbolKind::Field(f) => {
+                    parsed_types.push(&f.parsed_type);
+                    parsed_types.extend(f.nested_flatbuffer.as_ref().map(|n| &n.parsed_type));
+                }
+                SymbolKind::Union(u) => {
+                    parsed_types.extend(u.variants.iter().map(|v| &v.parsed_type));
+                }
+                SymbolKind::RpcService(r) => {
+                    for method in &r.methods {
+                        parsed_types.push(&method.request_type.parsed);
+                        parsed_types.push(&method.response_type.parsed);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        parsed_types.into_iter().find_map(|parsed| {
+            let idx = parsed
+                .namespace
+                .iter()
+                .position(|part| part.range.contains(position))?;
+            Some(
+                parsed.namespace[..=idx]
+                    .iter()
+                    .map(|part| part.text.clone())
+                    .collect(),
+            )
+        })
+    }
+
+    /// All definitions in `namespace` or a namespace nested within it, in a stable order.
+    #[must_use]
+    pub fn symbols_in_namespace(&self, namespace: &[String]) -> Vec<&Symbol> {
+        let mut symbols: Vec<&Symbol> = self
+            .symbols
+            .global
+            .values()
+            .filter(|symbol| symbol.info.namespace.starts_with(namespace))
+            .collect();
+        symbols.sort_by(|a, b| {
+            (&a.info.location.path, a.info.location.range.start)
+                .cmp(&(&b.info.location.path, b.info.location.range.start))
+        });
+        symbols
+    }
+
     #[must_use]
     pub fn find_enclosing_table(&self, path: &PathBuf, position: Position) -> Option<&Symbol> {
         let mut symbols_before_cursor: Vec<_> = self
@@ -172,9 +279,9 @@ impl<'a> WorkspaceSnapshot<'a> {
     ) -> Option<ResolvedSymbol<'a>> {
         if field.type_range.contains(position) {
             // Check if the cursor is on one of the namespace parts
+            // Namespace parts are handled by `resolve_namespace_at`.
             for part in &field.parsed_type.namespace {
                 if part.range.contains(position) {
-                    // TODO: Add support for go-to-definition on namespace parts
                     return None;
                 }
             }
@@ -197,9 +304,47 @@ impl<'a> WorkspaceSnapshot<'a> {
             }
         }
 
+        // The enum whose variant is the default value, e.g. `= Hard`.
+        if field.default_value_contains(position) {
+            return self.resolve_enum_default(field);
+        }
+
+        // The root type named by `nested_flatbuffer: "..."`.
+        if let Some(nested) = &field.nested_flatbuffer {
+            if nested.parsed_type.type_name.range.contains(position) {
+                let target_symbol = self.symbols.global.get(&nested.type_name)?;
+                return Some(ResolvedSymbol {
+                    target: target_symbol,
+                    range: nested.parsed_type.type_name.range,
+                    ref_name: nested.type_name.clone(),
+                });
+            }
+        }
+
         None
     }
 
+    /// The enum of `field`, by its fully-qualified type name, if the field's
+    /// default value names one of its variants. Bit flags may name several,
+    /// e.g. `"Red Blue"`.
+    fn resolve_enum_default(&'a self, field: &Field) -> Option<ResolvedSymbol<'a>> {
+        let default = field.default_value.as_ref()?;
+        let target = self.symbols.global.get(&field.type_name)?;
+        let SymbolKind::Enum(e) = &target.kind else {
+            return None;
+        };
+        let names_variant = default
+            .text
+            .trim_matches('"')
+            .split_whitespace()
+            .any(|name| e.variants.iter().any(|variant| variant.name == name));
+        names_variant.then(|| ResolvedSymbol {
+            target,
+            range: default.range,
+            ref_name: field.type_name.clone(),
+        })
+    }
+
     fn resolve_symbol_in_rpc_service(
         &'a self,
         service: &RpcService,
@@ -214,9 +359,9 @@ impl<'a> WorkspaceSnapshot<'a> {
             };
 
             if matching_type.range.contains(position) {
+                // Namespace parts are handled by `resolve_namespace_at`.
                 for part in &matching_type.parsed.namespace {
                     if part.range.contains(position) {
-                        // TODO: Add support for go-to-definition on namespace parts
                         return None;
                     }
                 }
@@ -425,6 +570,25 @@ mod tests {
         assert!(matches!(symbol.target.kind, SymbolKind::Table(_)));
     }
 
+    #[tokio::test]
+    async fn test_resolve_symbol_at_enum_default() {
+        let schema = "namespace Game;\nenum Difficulty : byte { Easy, Hard }\nnamespace Other;\ntable T { level: Game.Difficulty = Hard; count: int = 1; }\n";
+        let (analyzer, path, _dir) = setup_snapshot(schema).await;
+        let snapshot = analyzer.snapshot().await;
+        let uri = path_buf_to_uri(&path).unwrap();
+        let symbol = snapshot
+            .resolve_symbol_at(&uri, Position::new(3, 37))
+            .unwrap();
+        assert_eq!(symbol.target.info.qualified_name(), "Game.Difficulty");
+        assert_eq!(
+            symbol.range,
+            Range::new(Position::new(3, 35), Position::new(3, 39))
+        );
+        assert!(snapshot
+            .resolve_symbol_at(&uri, Position::new(3, 54))
+            .is_none());
+    }
+
     #[tokio::test]
     async fn test_find_enclosing_table_inside() {
         let schema = "table MyTable {\n  my_field: int;\n}\n";
@@ -467,4 +631,27 @@ mod tests {
         let symbol = snapshot.find_enclosing_table(&path, position);
         assert!(symbol.is_none());
     }
+
+    #[tokio::test]
+    async fn test_resolve_namespace_at_field_type() {
+        let schema = "namespace One.Two;\n\ntable X {}\ntable Y { x: One.Two.X; }\n";
+        let (analyzer, path, _dir) = setup_snapshot(schema).await;
+        let snapshot = analyzer.snapshot().await;
+        let uri = path_buf_to_uri(&path).unwrap();
+
+        assert!(snapshot
+            .resolve_symbol_at(&uri, Position::new(3, 18))
+            .is_none());
+        let namespace = snapshot
+            .resolve_namespace_at(&uri, Position::new(3, 18))
+            .unwrap();
+        assert_eq!(namespace, vec!["One", "Two"]);
+
+        let names: Vec<&str> = snapshot
+            .symbols_in_namespace(&namespace)
+            .iter()
+            .map(|s| s.info.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["X", "Y"]);
+    }
 }
@@ -1,7 +1,7 @@
 use crate::symbol_table::{Location, Symbol, SymbolInfo, SymbolKind, SymbolTable};
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use tower_lsp_server::lsp_types::Range;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -9,6 +9,20 @@ pub struct Attribute {
     pub name: String,
     pub doc: String,
     pub restricted_to_types: Option<Vec<String>>,
+    pub target: AttributeTarget,
+}
+
+/// What an attribute can be written on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeTarget {
+    /// Anywhere attributes are accepted.
+    Any,
+    /// A field whose type is a table or struct, or a vector of them.
+    TableOrStructField,
+    /// A struct declaration.
+    Struct,
+    /// A table declaration.
+    Table,
 }
 
 /// An index of known workspace symbols.
@@ -18,6 +32,10 @@ pub struct SymbolIndex {
     pub global: HashMap<String, Symbol>,
     /// Map from a file path to the list of symbol keys defined in it.
     pub per_file: HashMap<PathBuf, Vec<String>>,
+    /// Map from a fully-qualified name to each file's definition of it. Only the
+    /// last definition is kept in `global`, so more than one definition means
+    /// unrelated files define the same name.
+    pub definitions: HashMap<String, Vec<Symbol>>,
     /// Pre-populated, immutable map of built-in symbols.
     pub builtins: Arc<HashMap<String, Symbol>>,
     /// Pre-populated, immutable map of keywords.
@@ -28,6 +46,12 @@ pub struct SymbolIndex {
     pub user_defined_attributes: HashMap<String, Attribute>,
     /// Map from a file path to the list of user-defined attributes declared in it.
     pub user_defined_attributes_per_file: HashMap<PathBuf, Vec<String>>,
+    /// Map from a fully qualified name to the places it is used as a type.
+    pub reference_index: HashMap<String, Vec<Location>>,
+    /// Lazily computed [`SymbolIndex::namespaces`]. Cleared when the symbols change.
+    namespaces: OnceLock<HashSet<String>>,
+    /// Lazily computed [`SymbolIndex::collisions`]. Cleared when the symbols change.
+    collisions: OnceLock<HashMap<String, Vec<Symbol>>>,
 }
 
 impl SymbolIndex {
@@ -45,20 +69,20 @@ impl SymbolIndex {
         Self {
             global: HashMap::new(),
             per_file: HashMap::new(),
+            definitions: HashMap::new(),
             builtins: Arc::new(builtins),
             keywords: Arc::new(keywords),
             builtin_attributes: Arc::new(builtin_attributes),
             user_defined_attributes: HashMap::new(),
             user_defined_attributes_per_file: HashMap::new(),
+            reference_index: HashMap::new(),
+            namespaces: OnceLock::new(),
+            collisions: OnceLock::new(),
         }
     }
 
     pub fn update_symbols(&mut self, path: &Path, st: SymbolTable) {
-        if let Some(old_symbol_keys) = self.per_file.remove(path) {
-            for key in old_symbol_keys {
-                self.global.remove(&key);
-            }
-        }
+        self.remove_symbols(path);
 
         let symbol_map = st.into_inner();
         let new_symbol_keys: Vec<String> = symbol_map
@@ -69,9 +93,74 @@ impl SymbolIndex {
             .collect();
 
         for (key, symbol) in symbol_map {
+            if symbol.info.location.path == path {
+                for (name, range) in type_uses(&symbol) {
+                    let location = Location {
+                        path: path.to_path_buf(),
+                        range,
+                    };
+                    self.add_reference(name, location);
+                }
+                self.definitions
+                    .entry(key.clone())
+                    .or_default()
+                    .push(symbol.clone());
+            }
             self.global.insert(key, symbol);
         }
         self.per_file.insert(path.to_path_buf(), new_symbol_keys);
+        self.clear_derived();
+    }
+
+    /// Record a use of the type `name` at `location`. Builtin types are not recorded.
+    pub fn add_reference(&mut self, name: String, location: Location) {
+        if self.builtins.contains_key(&name) {
+            return;
+        }
+        self.reference_index.entry(name).or_default().push(location);
+    }
+
+    /// The places where the type `name` is used.
+    #[must_use]
+    pub fn references(&self, name: &str) -> &[Location] {
+        self.reference_index.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Forget the values derived from `global` so they are recomputed on next use.
+    fn clear_derived(&mut self) {
+        self.namespaces = OnceLock::new();
+        self.collisions = OnceLock::new();
+    }
+
+    /// Remove the symbols defined in `path`. A symbol that another file also
+    /// defines is replaced in `global` by that file's definition.
+    fn remove_symbols(&mut self, path: &Path) {
+        self.reference_index.retain(|_, locations| {
+            locations.retain(|location| location.path != path);
+            !locations.is_empty()
+        });
+        let Some(old_symbol_keys) = self.per_file.remove(path) else {
+            return;
+        };
+        for key in old_symbol_keys {
+            let remaining = self.definitions.get_mut(&key).and_then(|symbols| {
+                symbols.retain(|symbol| symbol.info.location.path != path);
+                symbols.last().cloned()
+            });
+            if remaining.is_none() {
+                self.definitions.remove(&key);
+            }
+            if self
+                .global
+                .get(&key)
+                .is_some_and(|symbol| symbol.info.location.path == path)
+            {
+                match remaining {
+                    Some(symbol) => self.global.insert(key, symbol),
+                    None => self.global.remove(&key),
+                };
+            }
+        }
     }
 
     pub fn update_attributes(&mut self, path: &Path, attributes: HashMap<String, String>) {
@@ -91,6 +180,7 @@ impl SymbolIndex {
                     name: attr_name,
                     doc,
                     restricted_to_types: None,
+                    target: AttributeTarget::Any,
                 },
             );
         }
@@ -101,11 +191,8 @@ impl SymbolIndex {
     }
 
     pub fn remove(&mut self, path: &Path) {
-        if let Some(old_symbol_keys) = self.per_file.remove(path) {
-            for key in old_symbol_keys {
-                self.global.remove(&key);
-            }
-        }
+        self.remove_symbols(path);
+        self.clear_derived();
         if let Some(old_attr_keys) = self.user_defined_attributes_per_file.remove(path) {
             for key in old_attr_keys {
                 self.user_defined_attributes.remove(&key);
@@ -113,34 +200,82 @@ impl SymbolIndex {
         }
     }
 
+    /// The fully-qualified names of the symbols defined in `path`.
     #[must_use]
-    pub fn namespaces(&self) -> HashSet<String> {
-        self.global
-            .values()
-            .map(|s| &s.info.namespace)
-            .filter(|ns| !ns.is_empty())
-            .map(|ns| ns.join("."))
-            .collect()
+    pub fn symbol_names(&self, path: &Path) -> HashSet<String> {
+        self.per_file
+            .get(path)
+            .map(|keys| keys.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn namespaces(&self) -> &HashSet<String> {
+        self.namespaces.get_or_init(|| {
+            self.global
+                .values()
+                .map(|s| &s.info.namespace)
+                .filter(|ns| !ns.is_empty())
+                .map(|ns| ns.join("."))
+                .collect()
+        })
     }
 
     /// Returns a map from unqualified name to symbols that share that name.
     #[must_use]
-    pub fn collisions(&self) -> HashMap<String, Vec<Symbol>> {
-        let mut by_name: HashMap<String, Vec<Symbol>> = HashMap::new();
-        for sym in self.global.values() {
-            by_name
-                .entry(sym.info.name.clone())
-                .or_default()
-                .push(sym.clone());
-        }
+    pub fn collisions(&self) -> &HashMap<String, Vec<Symbol>> {
+        self.collisions.get_or_init(|| {
+            let mut by_name: HashMap<String, Vec<Symbol>> = HashMap::new();
+            for sym in self.global.values() {
+                by_name
+                    .entry(sym.info.name.clone())
+                    .or_default()
+                    .push(sym.clone());
+            }
 
-        by_name.retain(|_, v| v.len() > 1);
-        by_name
+            by_name.retain(|_, v| v.len() > 1);
+            by_name
+        })
     }
 }
 
 // --- Built-in definitions ---
 
+/// The types named by a symbol's fields, union variants or rpc methods, with
+/// the range of each type name.
+fn type_uses(symbol: &Symbol) -> Vec<(String, Range)> {
+    let mut uses = Vec::new();
+    let fields = match &symbol.kind {
+        SymbolKind::Table(t) => &t.fields,
+        SymbolKind::Struct(s) => &s.fields,
+        SymbolKind::Union(u) => {
+            for variant in &u.variants {
+                uses.push((variant.name.clone(), variant.parsed_type.type_name.range));
+            }
+            return uses;
+        }
+        SymbolKind::RpcService(r) => {
+            for method in &r.methods {
+                for t in [&method.request_type, &method.response_type] {
+                    uses.push((t.name.clone(), t.parsed.type_name.range));
+                }
+            }
+            return uses;
+        }
+        _ => return uses,
+    };
+    for field in fields {
+        let SymbolKind::Field(f) = &field.kind else {
+            continue;
+        };
+        uses.push((f.type_name.clone(), f.parsed_type.type_name.range));
+        if let Some(nested) = &f.nested_flatbuffer {
+            uses.push((nested.type_name.clone(), nested.parsed_type.type_name.range));
+        }
+    }
+    uses
+}
+
 fn populate_builtins(symbols: &mut HashMap<String, Symbol>) {
     let scalar_types = [
         ("bool", "8-bit boolean"),
@@ -295,6 +430,34 @@ buf, err := os.ReadFile("discog.dat")
 // handle err
 discography := example.GetRootAsDiscography(buf, 0)
 ```
+"#,
+        ),
+        (
+            "file_identifier",
+            r#"Sets a 4 character identifier written into binaries whose root is this file's `root_type`.
+
+Readers can check it to verify a buffer holds the expected schema.
+
+```flatbuffers
+table Monster {}
+
+root_type Monster;
+file_identifier "MONS";
+```
+"#,
+        ),
+        (
+            "file_extension",
+            r#"Sets the default file extension for binaries generated from this schema, e.g. by `flatc --binary`.
+
+Defaults to `bin` when not given.
+
+```flatbuffers
+table Monster {}
+
+root_type Monster;
+file_extension "mon";
+```
 "#,
         ),
         (
@@ -362,17 +525,49 @@ fn populate_builtin_attributes(attributes: &mut HashMap<String, Attribute>) {
             "Mark this field as containing FlexBuffer data.",
             Some(&["[ubyte]", "[uint8]"]),
         ),
+        ("native_default", "Default value for this field in the object API type, e.g. `native_default: \"{}\"`.", None),
         // ("bit_flags", "This enum's values are bit masks", None), // Only valid on enums. TODO: Support non-field attributes.
         // ("original_order", "Keep the original order of fields.", None), // Docs basically say don't use this.
     ];
 
+    // Attributes for the C++ object API that only apply to some declarations or fields.
+    const OBJECT_API_ATTRIBUTES: &[(&str, &str, AttributeTarget)] = &[
+        (
+            "native_inline",
+            "Store this table or struct field inline in the object API type, instead of behind a pointer.",
+            AttributeTarget::TableOrStructField,
+        ),
+        ("native_type", "Use this existing C++ type for the struct in the object API.", AttributeTarget::Struct),
+        (
+            "native_type_pack_name",
+            "Suffix of the pack and unpack functions for a `native_type`, e.g. `native_type_pack_name: \"Vec3\"`.",
+            AttributeTarget::Struct,
+        ),
+        (
+            "native_custom_alloc",
+            "Use this allocator for the vectors of the object API type, e.g. `native_custom_alloc: \"custom_allocator\"`.",
+            AttributeTarget::Table,
+        ),
+    ];
+
     let attributes_data: Vec<Attribute> = BUILTIN_ATTRIBUTES
         .iter()
         .map(|(name, doc, restricted)| Attribute {
             name: (*name).into(),
             doc: (*doc).into(),
             restricted_to_types: restricted.map(|r| r.iter().map(|&s| s.into()).collect()),
+            target: AttributeTarget::Any,
         })
+        .chain(
+            OBJECT_API_ATTRIBUTES
+                .iter()
+                .map(|(name, doc, target)| Attribute {
+                    name: (*name).into(),
+                    doc: (*doc).into(),
+                    restricted_to_types: None,
+                    target: *target,
+                }),
+        )
         .collect();
 
     for attr in attributes_data {
@@ -406,6 +601,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_native_attributes_are_builtin() {
+        let index = SymbolIndex::new();
+        for (name, target) in [
+            ("native_default", AttributeTarget::Any),
+            ("native_inline", AttributeTarget::TableOrStructField),
+            ("native_type", AttributeTarget::Struct),
+            ("native_type_pack_name", AttributeTarget::Struct),
+            ("native_custom_alloc", AttributeTarget::Table),
+        ] {
+            let attr = index.builtin_attributes.get(name).unwrap();
+            assert!(!attr.doc.is_empty());
+            assert_eq!(attr.restricted_to_types, None);
+            assert_eq!(attr.target, target);
+        }
+    }
+
     #[test]
     fn test_update_symbols() {
         let mut index = SymbolIndex::new();
@@ -429,6 +641,48 @@ mod tests {
         assert!(index.per_file.get(&path_a).unwrap().is_empty());
     }
 
+    #[test]
+    fn test_definitions_in_multiple_files() {
+        let mut index = SymbolIndex::new();
+        let path_a = PathBuf::from("a.fbs");
+        let path_b = PathBuf::from("b.fbs");
+
+        for path in [&path_a, &path_b] {
+            let mut st = SymbolTable::new(path.clone());
+            st.insert("NS.Foo".to_string(), make_symbol("NS.Foo", path));
+            index.update_symbols(path, st);
+        }
+        assert_eq!(index.definitions["NS.Foo"].len(), 2);
+        assert_eq!(index.global["NS.Foo"].info.location.path, path_b);
+
+        // Removing the file whose definition was overwritten keeps the other one.
+        index.remove(&path_a);
+        assert_eq!(index.definitions["NS.Foo"].len(), 1);
+        assert_eq!(index.global["NS.Foo"].info.location.path, path_b);
+
+        index.remove(&path_b);
+        assert!(index.definitions.is_empty());
+        assert!(index.global.is_empty());
+    }
+
+    #[test]
+    fn test_definition_is_restored_when_the_kept_one_is_removed() {
+        let mut index = SymbolIndex::new();
+        let path_a = PathBuf::from("a.fbs");
+        let path_b = PathBuf::from("b.fbs");
+
+        for path in [&path_a, &path_b] {
+            let mut st = SymbolTable::new(path.clone());
+            st.insert("NS.Foo".to_string(), make_symbol("NS.Foo", path));
+            index.update_symbols(path, st);
+        }
+        assert_eq!(index.global["NS.Foo"].info.location.path, path_b);
+
+        index.update_symbols(&path_b, SymbolTable::new(path_b.clone()));
+        assert_eq!(index.definitions["NS.Foo"].len(), 1);
+        assert_eq!(index.global["NS.Foo"].info.location.path, path_a);
+    }
+
     #[test]
     fn test_update_attributes() {
         let mut index = SymbolIndex::new();
@@ -528,8 +782,33 @@ mod tests {
                 "com.foo".to_string(),
                 "single".to_string()
             ]),
-            index.namespaces()
+            *index.namespaces()
+        );
+    }
+
+    #[test]
+    fn test_namespaces_are_recomputed_when_symbols_change() {
+        let mut index = SymbolIndex::new();
+        let path_a = PathBuf::from("a.fbs");
+        let path_b = PathBuf::from("b.fbs");
+
+        let mut st = SymbolTable::new(path_a.clone());
+        st.insert("one.A".to_string(), make_symbol("one.A", &path_a));
+        index.update_symbols(&path_a, st);
+        assert_eq!(HashSet::from(["one".to_string()]), *index.namespaces());
+
+        let mut st = SymbolTable::new(path_b.clone());
+        st.insert("two.A".to_string(), make_symbol("two.A", &path_b));
+        index.update_symbols(&path_b, st);
+        assert_eq!(
+            HashSet::from(["one".to_string(), "two".to_string()]),
+            *index.namespaces()
         );
+        assert!(index.collisions().contains_key("A"));
+
+        index.remove(&path_a);
+        assert_eq!(HashSet::from(["two".to_string()]), *index.namespaces());
+        assert!(index.collisions().is_empty());
     }
 
     #[test]
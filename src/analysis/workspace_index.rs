@@ -1,8 +1,18 @@
 use crate::analysis::diagnostic_store::DiagnosticStore;
 use crate::analysis::root_type_store::RootTypeStore;
 use crate::analysis::symbol_index::SymbolIndex;
+use crate::diagnostics::codes::DiagnosticCode;
+use crate::diagnostics::{include_depth, include_not_found, semantic, undefined_type};
+use crate::symbol_table::{Location, Symbol, SymbolKind};
 use crate::{analysis::dependency_graph::DependencyGraph, parser::ParseResult};
+use dashmap::DashMap;
+use ropey::Rope;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use tower_lsp_server::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, DiagnosticTag, NumberOrString,
+};
 
 /// An index of workspace semantic information.
 #[derive(Debug, Clone, Default)]
@@ -25,35 +35,285 @@ impl WorkspaceIndex {
     }
 
     pub fn update(&mut self, path: &Path, result: ParseResult) {
+        let mut diagnostics = result.diagnostics;
+
         // If a parse error occurred and there is no symbol table, we don't want to
         // clear the old symbol table as it may be useful to the user while they are
         // editing (e.g. for completions).
         if let Some(st) = result.symbol_table {
+            semantic::analyze_builtin_shadowing(&st, &self.symbols.builtins, &mut diagnostics);
+            semantic::analyze_keyword_names(&st, &self.symbols.keywords, &mut diagnostics);
+            semantic::analyze_rpc_method_types(&st, &self.symbols.global, &mut diagnostics);
+
+            let root_type_use = result.root_type_info.as_ref().map(|rti| {
+                let location = Location {
+                    path: path.to_path_buf(),
+                    range: rti.parsed_type.type_name.range,
+                };
+                (rti.type_name.clone(), location)
+            });
             match result.root_type_info {
                 Some(rti) => self.root_types.root_types.insert(path.to_path_buf(), rti),
                 None => self.root_types.root_types.remove(path),
             };
 
             self.symbols.update_symbols(path, st);
+            if let Some((name, location)) = root_type_use {
+                self.symbols.add_reference(name, location);
+            }
             self.symbols
                 .update_attributes(path, result.user_defined_attributes);
         }
 
         self.dependencies.update(path, result.includes.clone());
+        self.dependencies
+            .update_direct_includes(path, result.direct_includes);
+
+        semantic::analyze_root_type(
+            path,
+            &result.includes,
+            &self.symbols.global,
+            &mut diagnostics,
+        );
 
-        let mut diagnostics = result.diagnostics;
         // Absence in parse result implies there were no diagnostics for this file.
-        diagnostics.entry(path.to_path_buf()).or_default();
+        let own_diagnostics = diagnostics.entry(path.to_path_buf()).or_default();
+        // Taken before suppressions are applied, so that suppressed
+        // includes are still retried when the missing file appears.
+        self.dependencies.update_unresolved_includes(
+            path,
+            include_not_found::unresolved_includes(own_diagnostics),
+        );
 
         self.diagnostics.update(diagnostics);
+        self.update_cross_file_duplicates();
     }
 
     pub fn remove(&mut self, path: &PathBuf) -> Vec<PathBuf> {
         self.symbols.remove(path);
         self.root_types.root_types.remove(path);
         self.diagnostics.remove(path);
+        self.update_cross_file_duplicates();
 
         // Return the affected files.
         self.dependencies.remove(path)
     }
+
+    /// Replace the duplicate definition diagnostics for names defined by more
+    /// than one file. flatc reports duplicates within a file and its includes,
+    /// so only files that do not include each other are checked here.
+    fn update_cross_file_duplicates(&mut self) {
+        let mut duplicates = self.cross_file_duplicates();
+
+        let updated = self
+            .diagnostics
+            .all()
+            .iter()
+            .filter(|(path, diagnostics)| {
+                duplicates.contains_key(*path) || diagnostics.iter().any(is_cross_file_duplicate)
+            })
+            .map(|(path, diagnostics)| {
+                let mut diagnostics: Vec<Diagnostic> = diagnostics
+                    .iter()
+                    .filter(|d| !is_cross_file_duplicate(d))
+                    .cloned()
+                    .collect();
+                diagnostics.extend(duplicates.remove(path).unwrap_or_default());
+                (path.clone(), diagnostics)
+            })
+            .collect();
+        self.diagnostics.update(updated);
+    }
+
+    fn cross_file_duplicates(&self) -> HashMap<PathBuf, Vec<Diagnostic>> {
+        let mut diagnostics: HashMap<PathBuf, Vec<Diagnostic>> = HashMap::new();
+        for (name, symbols) in &self.symbols.definitions {
+            if symbols.len() < 2 {
+                continue;
+            }
+            for location in symbols.iter().map(|symbol| &symbol.info.location) {
+                let related_information: Vec<DiagnosticRelatedInformation> = symbols
+                    .iter()
+                    .map(|symbol| &symbol.info.location)
+                    .filter(|other| {
+                        other.path != location.path
+                            && !self.dependencies.is_reachable(&location.path, &other.path)
+                            && !self.dependencies.is_reachable(&other.path, &location.path)
+                    })
+                    .map(|other| DiagnosticRelatedInformation {
+                        location: other.clone().into(),
+                        message: format!("`{name}` is also defined here"),
+                    })
+                    .collect();
+                if related_information.is_empty() {
+                    continue;
+                }
+                diagnostics
+                    .entry(location.path.clone())
+                    .or_default()
+                    .push(Diagnostic {
+                        range: location.range,
+                        severity: Some(DiagnosticSeverity::ERROR),
+                        code: Some(DiagnosticCode::DuplicateDefinition.into()),
+                        message: format!(
+                            "the name `{name}` is defined multiple times in the workspace"
+                        ),
+                        related_information: Some(related_information),
+                        data: Some(json!({ "crossFile": true })),
+                        ..Default::default()
+                    });
+            }
+        }
+        diagnostics
+    }
+
+    /// Suggest similarly named types for the undefined types in every file.
+    /// This depends on the symbols of the whole workspace, so it is done after parsing.
+    pub fn update_type_suggestions(&mut self) {
+        let code = Some(NumberOrString::from(DiagnosticCode::UndefinedType));
+        let mut updated: HashMap<PathBuf, Vec<Diagnostic>> = self
+            .diagnostics
+            .all()
+            .iter()
+            .filter(|(_, diagnostics)| diagnostics.iter().any(|d| d.code == code))
+            .map(|(path, diagnostics)| (path.clone(), diagnostics.clone()))
+            .collect();
+        undefined_type::suggest_types(&mut updated, &self.symbols.global);
+        self.diagnostics.update(updated);
+    }
+
+    /// Replace the include depth diagnostics in every file. Include chains span
+    /// the whole workspace, so this is done after parsing.
+    pub fn update_include_depth(&mut self, documents: &DashMap<PathBuf, Rope>, max_depth: usize) {
+        let code = Some(NumberOrString::from(DiagnosticCode::IncludeTooDeep));
+        let mut too_deep =
+            include_depth::analyze_include_depth(&self.dependencies, documents, max_depth);
+
+        let updated = self
+            .diagnostics
+            .all()
+            .iter()
+            .map(|(path, diagnostics)| {
+                let mut diagnostics: Vec<Diagnostic> = diagnostics
+                    .iter()
+                    .filter(|d| d.code != code)
+                    .cloned()
+                    .collect();
+                diagnostics.extend(too_deep.remove(path).unwrap_or_default());
+                (path.clone(), diagnostics)
+            })
+            .collect();
+        self.diagnostics.update(updated);
+    }
+
+    /// Replace the unreferenced type diagnostics in every file. When `enabled`
+    /// is false, any previously reported ones are cleared.
+    pub fn update_unreferenced_types(&mut self, enabled: bool) {
+        let code = Some(NumberOrString::from(DiagnosticCode::UnreferencedType));
+        let mut unreferenced = if enabled {
+            self.unreferenced_types()
+        } else {
+            HashMap::new()
+        };
+
+        let updated = self
+            .diagnostics
+            .all()
+            .iter()
+            .map(|(path, diagnostics)| {
+                let mut diagnostics: Vec<Diagnostic> = diagnostics
+                    .iter()
+                    .filter(|d| d.code != code)
+                    .cloned()
+                    .collect();
+                diagnostics.extend(unreferenced.remove(path).unwrap_or_default());
+                (path.clone(), diagnostics)
+            })
+            .collect();
+        self.diagnostics.update(updated);
+    }
+
+    /// Diagnostics for the types that cannot be reached from any `root_type`
+    /// or `rpc_service` by following field, union variant and rpc method types.
+    fn unreferenced_types(&self) -> HashMap<PathBuf, Vec<Diagnostic>> {
+        let global = &self.symbols.global;
+        let mut to_visit: Vec<&String> = self
+            .root_types
+            .root_types
+            .values()
+            .map(|root_type| &root_type.type_name)
+            .chain(
+                global
+                    .iter()
+                    .filter(|(_, symbol)| matches!(symbol.kind, SymbolKind::RpcService(_)))
+                    .map(|(name, _)| name),
+            )
+            .collect();
+
+        let mut reachable = HashSet::new();
+        while let Some(name) = to_visit.pop() {
+            if !reachable.insert(name.as_str()) {
+                continue;
+            }
+            let Some(symbol) = global.get(name) else {
+                continue;
+            };
+            match &symbol.kind {
+                SymbolKind::Table(t) => to_visit.extend(t.fields.iter().filter_map(field_type)),
+                SymbolKind::Struct(s) => to_visit.extend(s.fields.iter().filter_map(field_type)),
+                SymbolKind::Union(u) => to_visit.extend(u.variants.iter().map(|v| &v.name)),
+                SymbolKind::RpcService(r) => to_visit.extend(
+                    r.methods
+                        .iter()
+                        .flat_map(|m| [&m.request_type.name, &m.response_type.name]),
+                ),
+                SymbolKind::Enum(_) | SymbolKind::Field(_) | SymbolKind::Scalar => {}
+            }
+        }
+
+        let mut diagnostics: HashMap<PathBuf, Vec<Diagnostic>> = HashMap::new();
+        for (name, symbol) in global {
+            let is_type = matches!(
+                symbol.kind,
+                SymbolKind::Table(_)
+                    | SymbolKind::Struct(_)
+                    | SymbolKind::Enum(_)
+                    | SymbolKind::Union(_)
+            );
+            if !is_type || symbol.info.builtin || reachable.contains(name.as_str()) {
+                continue;
+            }
+            diagnostics
+                .entry(symbol.info.location.path.clone())
+                .or_default()
+                .push(Diagnostic {
+                    range: symbol.info.location.range,
+                    severity: Some(DiagnosticSeverity::HINT),
+                    code: Some(DiagnosticCode::UnreferencedType.into()),
+                    tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+                    message: format!(
+                        "`{name}` is not reachable from any `root_type` or `rpc_service`"
+                    ),
+                    ..Default::default()
+                });
+        }
+        diagnostics
+    }
+}
+
+/// Whether `diagnostic` was reported by [`WorkspaceIndex::cross_file_duplicates`]
+/// rather than by flatc.
+fn is_cross_file_duplicate(diagnostic: &Diagnostic) -> bool {
+    diagnostic.code == Some(NumberOrString::from(DiagnosticCode::DuplicateDefinition))
+        && diagnostic
+            .data
+            .as_ref()
+            .is_some_and(|data| data["crossFile"] == true)
+}
+
+fn field_type(field: &Symbol) -> Option<&String> {
+    match &field.kind {
+        SymbolKind::Field(f) => Some(&f.type_name),
+        _ => None,
+    }
 }
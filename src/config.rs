@@ -0,0 +1,141 @@
+use crate::diagnostics::codes::DiagnosticCode;
+use serde::Deserialize;
+use std::path::PathBuf;
+use tower_lsp_server::lsp_types::{Diagnostic, NumberOrString};
+
+/// Settings from the client's `flatbuffers` configuration section, or its
+/// `initializationOptions` when `workspace/configuration` is not supported.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Config {
+    /// Path to a `flatc` executable. Commands that run code generation are
+    /// disabled unless this is set.
+    pub flatc_path: Option<PathBuf>,
+    /// Extra directories to search for included files. Relative paths are
+    /// resolved against each workspace root.
+    pub include_dirs: Vec<PathBuf>,
+    /// Gitignore-style patterns for files and directories to skip when
+    /// scanning each workspace root, e.g. `"gen/"`. `target/` and
+    /// `node_modules/` are always skipped unless re-included with `!`.
+    pub ignore_patterns: Vec<String>,
+    /// Also scan files that `.gitignore` excludes, e.g. for schemas that are
+    /// generated but still meant to be edited.
+    pub scan_gitignored: bool,
+    /// The most verbose log level sent to the client, e.g. `"info"`. Unset
+    /// keeps the current level.
+    pub log_level: Option<String>,
+    /// Show how many times a type is used, and in how many files, when
+    /// hovering it.
+    pub hover_reference_counts: bool,
+    /// How many includes deep to follow from each parsed file. Includes nested
+    /// deeper are reported and not followed. Unset uses the default of 64.
+    pub max_include_depth: Option<usize>,
+    pub diagnostics: DiagnosticsConfig,
+}
+
+/// Toggles for the optional (non-error) diagnostics.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiagnosticsConfig {
+    pub unused_includes: bool,
+    pub non_snake_case: bool,
+    pub deprecated: bool,
+    /// Off by default since libraries often define types for others to use.
+    pub unreferenced_types: bool,
+    /// Off by default since not every project lays out files by namespace.
+    pub namespace_mismatch: bool,
+}
+
+impl Default for DiagnosticsConfig {
+    fn default() -> Self {
+        Self {
+            unused_includes: true,
+            non_snake_case: true,
+            deprecated: true,
+            unreferenced_types: false,
+            namespace_mismatch: false,
+        }
+    }
+}
+
+impl Config {
+    /// Read settings from a JSON value, falling back to defaults if it is
+    /// missing or malformed.
+    #[must_use]
+    pub fn from_value(value: Option<serde_json::Value>) -> Self {
+        value
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_default()
+    }
+}
+
+impl DiagnosticsConfig {
+    /// Whether `diagnostic` should be published to the client.
+    #[must_use]
+    pub fn is_enabled(&self, diagnostic: &Diagnostic) -> bool {
+        let Some(NumberOrString::String(code)) = &diagnostic.code else {
+            return true;
+        };
+        match DiagnosticCode::try_from(code.clone()) {
+            Ok(DiagnosticCode::UnusedInclude) => self.unused_includes,
+            Ok(DiagnosticCode::NonSnakeCase) => self.non_snake_case,
+            Ok(DiagnosticCode::Deprecated) => self.deprecated,
+            Ok(DiagnosticCode::UnreferencedType) => self.unreferenced_types,
+            Ok(DiagnosticCode::NamespaceMismatch) => self.namespace_mismatch,
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_value() {
+        let config = Config::from_value(Some(json!({
+            "flatcPath": "/usr/bin/flatc",
+            "includeDirs": ["third_party"],
+            "ignorePatterns": ["gen/"],
+            "scanGitignored": true,
+            "logLevel": "warn",
+            "hoverReferenceCounts": true,
+            "maxIncludeDepth": 8,
+            "diagnostics": { "unusedIncludes": false },
+        })));
+        assert_eq!(config.flatc_path, Some(PathBuf::from("/usr/bin/flatc")));
+        assert_eq!(config.include_dirs, vec![PathBuf::from("third_party")]);
+        assert_eq!(config.ignore_patterns, vec!["gen/".to_string()]);
+        assert!(config.scan_gitignored);
+        assert_eq!(config.log_level.as_deref(), Some("warn"));
+        assert!(config.hover_reference_counts);
+        assert_eq!(config.max_include_depth, Some(8));
+        assert!(!config.diagnostics.unused_includes);
+        assert!(config.diagnostics.non_snake_case);
+    }
+
+    #[test]
+    fn test_from_missing_or_malformed_value() {
+        assert_eq!(Config::from_value(None), Config::default());
+        assert_eq!(
+            Config::from_value(Some(json!({ "flatcPath": 1 }))),
+            Config::default()
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_is_enabled() {
+        let config = DiagnosticsConfig {
+            unused_includes: false,
+            ..DiagnosticsConfig::default()
+        };
+        let diagnostic = |code: DiagnosticCode| Diagnostic {
+            code: Some(code.into()),
+            ..Diagnostic::default()
+        };
+        assert!(!config.is_enabled(&diagnostic(DiagnosticCode::UnusedInclude)));
+        assert!(config.is_enabled(&diagnostic(DiagnosticCode::NonSnakeCase)));
+        assert!(config.is_enabled(&Diagnostic::default()));
+    }
+}
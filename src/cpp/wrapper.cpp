@@ -116,6 +116,15 @@ bool is_parser_success(struct FlatbuffersParser* parser) {
     return !parser->error;
 }
 
+size_t serialize_binary_schema(struct FlatbuffersParser* parser, const unsigned char** buffer) {
+    if (!parser || parser->error || !buffer) {
+        return 0;
+    }
+    parser->impl.Serialize();
+    *buffer = parser->impl.builder_.GetBufferPointer();
+    return parser->impl.builder_.GetSize();
+}
+
 // Functions for structs and tables
 int get_num_structs(struct FlatbuffersParser* parser) {
     if (!parser) return 0;
@@ -299,7 +308,7 @@ int get_num_fields(struct FlatbuffersParser* parser, int struct_index) {
 }
 
 struct FieldDefinitionInfo get_field_info(struct FlatbuffersParser* parser, int struct_index, int field_index) {
-    struct FieldDefinitionInfo info = { nullptr, nullptr, nullptr, nullptr, 0, 0, {}, nullptr, false, false, 0 };
+    struct FieldDefinitionInfo info = { nullptr, nullptr, nullptr, nullptr, 0, 0, {}, nullptr, false, false, 0, nullptr, nullptr };
     if (!parser || struct_index < 0 || static_cast<size_t>(struct_index) >= parser->impl.structs_.vec.size()) {
         return info;
     }
@@ -360,6 +369,20 @@ struct FieldDefinitionInfo get_field_info(struct FlatbuffersParser* parser, int
         info.id = std::stoi(id_attr->constant);
     }
 
+    auto force_align_attr = field_def->attributes.Lookup("force_align");
+    if (force_align_attr) {
+        info.force_align = force_align_attr->constant.c_str();
+    }
+
+    auto nested_def = field_def->nested_flatbuffer;
+    if (nested_def) {
+        std::string fqn = nested_def->defined_namespace
+            ? nested_def->defined_namespace->GetFullyQualifiedName(nested_def->name)
+            : nested_def->name;
+        auto result = parser->string_cache.insert(fqn);
+        info.nested_flatbuffer = result.first->c_str();
+    }
+
     return info;
 }
 
@@ -406,7 +429,7 @@ int get_num_rpc_methods(struct FlatbuffersParser* parser, int service_index) {
 }
 
 struct RpcMethodDefinitionInfo get_rpc_method_info(struct FlatbuffersParser* parser, int service_index, int method_index) {
-    struct RpcMethodDefinitionInfo info = { nullptr, nullptr, 0, 0, nullptr, {}, nullptr, nullptr, {}, nullptr };
+    struct RpcMethodDefinitionInfo info = { nullptr, nullptr, 0, 0, nullptr, {}, nullptr, nullptr, {}, nullptr, nullptr };
 
     if (!parser || service_index < 0 || static_cast<size_t>(service_index) >= parser->impl.services_.vec.size()) {
         return info;
@@ -451,6 +474,11 @@ struct RpcMethodDefinitionInfo get_rpc_method_info(struct FlatbuffersParser* par
 
     info.response_source = call_def->response_decl_text.c_str();
 
+    auto streaming_attr = call_def->attributes.Lookup("streaming");
+    if (streaming_attr) {
+        info.streaming = streaming_attr->constant.c_str();
+    }
+
     return info;
 }
 
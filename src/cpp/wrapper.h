@@ -67,6 +67,8 @@ struct FieldDefinitionInfo {
     bool deprecated;
     bool has_id;
     int id;
+    const char* force_align; // the force_align attribute's value, if set
+    const char* nested_flatbuffer; // fully-qualified name of the nested_flatbuffer attribute's root type, if set
 };
 
 struct RootTypeDefinitionInfo {
@@ -96,6 +98,7 @@ struct RpcMethodDefinitionInfo {
     const char* response_type_name; // fully qualified name of the type
     struct Range response_range;
     const char* response_source; // text of the type declaration
+    const char* streaming; // the streaming attribute's value, if set
 };
 
 // Parses a schema and returns a pointer to the Parser object.
@@ -110,6 +113,11 @@ const char* get_parser_error(struct FlatbuffersParser* parser);
 // Returns true if the parser has no errors.
 bool is_parser_success(struct FlatbuffersParser* parser);
 
+// Serializes the parsed schema to a binary schema (.bfbs).
+// Points buffer at the serialized bytes, which are owned by the parser, and returns their size.
+// Returns 0 if the schema failed to parse.
+size_t serialize_binary_schema(struct FlatbuffersParser* parser, const unsigned char** buffer);
+
 // Functions for structs and tables
 int get_num_structs(struct FlatbuffersParser* parser);
 struct StructDefinitionInfo get_struct_info(struct FlatbuffersParser* parser, int index);
@@ -3,7 +3,7 @@ use std::result::Result;
 use serde::{Deserialize, Serialize};
 use tower_lsp_server::lsp_types::NumberOrString;
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum DiagnosticCode {
     ExpectingToken,
@@ -12,6 +12,20 @@ pub enum DiagnosticCode {
     UndefinedType,
     Deprecated,
     DuplicateDefinition,
+    IncludeNotFound,
+    ShadowedBuiltin,
+    UnreferencedType,
+    NamespaceMismatch,
+    InvalidRootType,
+    InvalidForceAlign,
+    UnknownAttribute,
+    InvalidNestedFlatbuffer,
+    VariantNameCollision,
+    MixedFieldIds,
+    ReservedKeyword,
+    TypeNameCollision,
+    InvalidRpcType,
+    IncludeTooDeep,
 }
 
 impl DiagnosticCode {
@@ -24,6 +38,20 @@ impl DiagnosticCode {
             DiagnosticCode::UndefinedType => "undefined-type",
             DiagnosticCode::Deprecated => "deprecated",
             DiagnosticCode::DuplicateDefinition => "duplicate-definition",
+            DiagnosticCode::IncludeNotFound => "include-not-found",
+            DiagnosticCode::ShadowedBuiltin => "shadowed-builtin",
+            DiagnosticCode::UnreferencedType => "unreferenced-type",
+            DiagnosticCode::NamespaceMismatch => "namespace-mismatch",
+            DiagnosticCode::InvalidRootType => "invalid-root-type",
+            DiagnosticCode::InvalidForceAlign => "invalid-force-align",
+            DiagnosticCode::UnknownAttribute => "unknown-attribute",
+            DiagnosticCode::InvalidNestedFlatbuffer => "invalid-nested-flatbuffer",
+            DiagnosticCode::VariantNameCollision => "variant-name-collision",
+            DiagnosticCode::MixedFieldIds => "mixed-field-ids",
+            DiagnosticCode::ReservedKeyword => "reserved-keyword",
+            DiagnosticCode::TypeNameCollision => "type-name-collision",
+            DiagnosticCode::InvalidRpcType => "invalid-rpc-type",
+            DiagnosticCode::IncludeTooDeep => "include-too-deep",
         }
     }
 }
@@ -39,6 +67,20 @@ impl TryFrom<String> for DiagnosticCode {
             "undefined-type" => Ok(DiagnosticCode::UndefinedType),
             "deprecated" => Ok(DiagnosticCode::Deprecated),
             "duplicate-definition" => Ok(DiagnosticCode::DuplicateDefinition),
+            "include-not-found" => Ok(DiagnosticCode::IncludeNotFound),
+            "shadowed-builtin" => Ok(DiagnosticCode::ShadowedBuiltin),
+            "unreferenced-type" => Ok(DiagnosticCode::UnreferencedType),
+            "namespace-mismatch" => Ok(DiagnosticCode::NamespaceMismatch),
+            "invalid-root-type" => Ok(DiagnosticCode::InvalidRootType),
+            "invalid-force-align" => Ok(DiagnosticCode::InvalidForceAlign),
+            "unknown-attribute" => Ok(DiagnosticCode::UnknownAttribute),
+            "invalid-nested-flatbuffer" => Ok(DiagnosticCode::InvalidNestedFlatbuffer),
+            "variant-name-collision" => Ok(DiagnosticCode::VariantNameCollision),
+            "mixed-field-ids" => Ok(DiagnosticCode::MixedFieldIds),
+            "reserved-keyword" => Ok(DiagnosticCode::ReservedKeyword),
+            "type-name-collision" => Ok(DiagnosticCode::TypeNameCollision),
+            "invalid-rpc-type" => Ok(DiagnosticCode::InvalidRpcType),
+            "include-too-deep" => Ok(DiagnosticCode::IncludeTooDeep),
             _ => Err(()),
         }
     }
@@ -1,10 +1,10 @@
-use std::{fs, path::PathBuf};
+use std::path::{Path, PathBuf};
 
+use crate::utils::paths::canonicalize_or_self;
 use crate::{
-    diagnostics::{codes::DiagnosticCode, ErrorDiagnosticHandler},
-    utils::as_pos_idx,
+    diagnostics::{codes::DiagnosticCode, utf16_column, ErrorDiagnosticHandler},
+    utils::utf16_len,
 };
-use log::error;
 use regex::Regex;
 use tower_lsp_server::{
     lsp_types::{
@@ -24,23 +24,18 @@ static DUPLICATE_RE: std::sync::LazyLock<Regex> = std::sync::LazyLock::new(|| {
 pub struct DuplicateDefinitionHandler;
 
 impl ErrorDiagnosticHandler for DuplicateDefinitionHandler {
-    fn handle(&self, line: &str, _content: &str) -> Option<(PathBuf, Diagnostic)> {
+    fn handle(&self, line: &str, content: &str) -> Option<(PathBuf, Diagnostic)> {
         if let Some(captures) = DUPLICATE_RE.captures(line) {
             let file_path = captures[1].trim();
-            let Ok(file_path) = fs::canonicalize(file_path) else {
-                error!("failed to canonicalize file: {file_path} in duplicate definition handler");
-                return None;
-            };
+            let file_path = canonicalize_or_self(Path::new(file_path));
 
             let name = captures[5].trim().to_string();
             let unqualified_name = name.split('.').next_back().unwrap_or(name.as_str());
-            let unqualified_name_length = as_pos_idx(unqualified_name.chars().count());
+            let unqualified_name_length = utf16_len(unqualified_name);
 
             let message = format!("the name `{name}` is defined multiple times");
             let curr_line = captures[2].parse().unwrap_or(1) - 1;
-            let curr_char = captures[3]
-                .parse()
-                .unwrap_or(0u32)
+            let curr_char = utf16_column(content, curr_line, captures[3].parse().unwrap_or(0u32))
                 .saturating_sub(unqualified_name_length);
             let range = Range {
                 start: Position {
@@ -54,10 +49,14 @@ impl ErrorDiagnosticHandler for DuplicateDefinitionHandler {
             };
 
             let prev_line = captures[7].parse().unwrap_or(1u32).saturating_sub(1);
-            let prev_char = captures[8]
-                .parse()
-                .unwrap_or(0u32)
-                .saturating_sub(unqualified_name_length);
+            let prev_col = captures[8].parse().unwrap_or(0u32);
+            // `content` is only available for the file with the error.
+            let prev_char = if canonicalize_or_self(Path::new(captures[6].trim())) == file_path {
+                utf16_column(content, prev_line, prev_col)
+            } else {
+                prev_col
+            }
+            .saturating_sub(unqualified_name_length);
             let previous_location = Location {
                 uri: Uri::from_file_path(captures[6].trim())?,
                 range: Range {
@@ -1,9 +1,9 @@
-use std::{fs, path::PathBuf};
+use std::path::{Path, PathBuf};
 
 use crate::diagnostics::ErrorDiagnosticHandler;
-use crate::utils::as_pos_idx;
+use crate::utils::paths::canonicalize_or_self;
+use crate::utils::{as_pos_idx, byte_to_utf16_col, utf16_len};
 use crate::{diagnostics::codes::DiagnosticCode, utils::paths::path_buf_to_uri};
-use log::error;
 use regex::Regex;
 use serde_json;
 use tower_lsp_server::lsp_types::{
@@ -22,10 +22,7 @@ impl ErrorDiagnosticHandler for ExpectingTokenHandler {
     fn handle(&self, line: &str, content: &str) -> Option<(PathBuf, Diagnostic)> {
         if let Some(captures) = RE.captures(line) {
             let file_path = captures[1].trim();
-            let Ok(file_path) = fs::canonicalize(file_path) else {
-                error!("failed to canonicalize file: {file_path} in expecting token handler");
-                return None;
-            };
+            let file_path = canonicalize_or_self(Path::new(file_path));
             let Ok(file_url) = path_buf_to_uri(&file_path) else {
                 return None;
             };
@@ -47,7 +44,9 @@ impl ErrorDiagnosticHandler for ExpectingTokenHandler {
                 .find(&cleaned_token)
                 .map_or(error_col_num, as_pos_idx);
 
-            let line_before_error = &line_content[..adjusted_col as usize];
+            let line_before_error = line_content
+                .get(..adjusted_col as usize)
+                .unwrap_or(line_content);
 
             // This is the start position (inclusive) of where the token would be if it were inserted.
             let diagnostic_pos = if line_before_error.trim().is_empty()
@@ -81,26 +80,28 @@ impl ErrorDiagnosticHandler for ExpectingTokenHandler {
                     .lines()
                     .nth(diagnostic_line_num as usize)
                     .unwrap_or("");
-                let diagnostic_col_num = as_pos_idx(diagnostic_line_content.chars().count());
+                let diagnostic_col_num = utf16_len(diagnostic_line_content);
                 Position::new(diagnostic_line_num, diagnostic_col_num)
             } else {
                 // The error is on the same line as the context; the fix belongs on this line.
-                Position::new(error_line_num, adjusted_col)
+                Position::new(
+                    error_line_num,
+                    byte_to_utf16_col(line_content.chars(), adjusted_col),
+                )
             };
             // Range is [start, end).
             let range = Range::new(
                 diagnostic_pos,
                 Position::new(
                     diagnostic_pos.line,
-                    diagnostic_pos.character + as_pos_idx(expected_token.chars().count()),
+                    diagnostic_pos.character + utf16_len(&expected_token),
                 ),
             );
             let diagnostic_line_content = content
                 .lines()
                 .nth(diagnostic_pos.line as usize)
                 .unwrap_or("");
-            let is_eol =
-                diagnostic_line_content.chars().count() == diagnostic_pos.character as usize;
+            let is_eol = utf16_len(diagnostic_line_content) == diagnostic_pos.character;
 
             let mut related_information = vec![];
 
@@ -112,10 +113,16 @@ impl ErrorDiagnosticHandler for ExpectingTokenHandler {
                     .map_or(error_col_num, as_pos_idx);
 
                 let unexpected_token_range = Range::new(
-                    Position::new(error_line_num, adjusted_col),
                     Position::new(
                         error_line_num,
-                        adjusted_col + as_pos_idx(cleaned_token.len()),
+                        byte_to_utf16_col(line_content.chars(), adjusted_col),
+                    ),
+                    Position::new(
+                        error_line_num,
+                        byte_to_utf16_col(
+                            line_content.chars(),
+                            adjusted_col + as_pos_idx(cleaned_token.len()),
+                        ),
                     ),
                 );
                 related_information.push(DiagnosticRelatedInformation {
@@ -0,0 +1,224 @@
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use crate::diagnostics::codes::DiagnosticCode;
+use crate::diagnostics::ErrorDiagnosticHandler;
+use crate::utils::byte_offset_to_position;
+use crate::utils::paths::canonicalize_or_self;
+use crate::utils::scanner::strip_comments_and_strings;
+use regex::Regex;
+use tower_lsp_server::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, Position, Range,
+    TextEdit, Uri,
+};
+use tower_lsp_server::UriExt;
+
+static RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(.+?):\s*(\d+):\s*(\d+):\s*error:\s*either all fields or no fields must have an 'id' attribute$",
+    )
+    .expect("field ids regex failed to compile")
+});
+
+/// The name of the table whose body starts right after the match.
+static TABLE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\btable\s+(\w+)[^{};]*$").expect("table name regex failed to compile")
+});
+
+static FIELD_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*(\w+)\s*:").expect("field regex failed to compile"));
+
+static ID_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\bid\s*:\s*(\d+)").expect("id regex failed to compile"));
+
+/// Handles flatc rejecting a table where only some fields have an `id`.
+pub struct MixedFieldIdsHandler;
+
+impl ErrorDiagnosticHandler for MixedFieldIdsHandler {
+    fn handle(&self, line: &str, content: &str) -> Option<(PathBuf, Diagnostic)> {
+        let captures = RE.captures(line)?;
+        let file_path = canonicalize_or_self(Path::new(captures[1].trim()));
+        let line_num: usize = captures[2].parse::<usize>().ok()?.saturating_sub(1);
+        let col_num: usize = captures[3].parse::<usize>().ok()?.saturating_sub(1);
+
+        // flatc reports the end of the table, so look back for its body.
+        let code = strip_comments_and_strings(content);
+        let line_start: usize = code
+            .split_inclusive('\n')
+            .take(line_num)
+            .map(str::len)
+            .sum();
+        let end = (line_start + col_num + 1).min(code.len());
+        let close = code.get(..end).unwrap_or(&code).rfind('}')?;
+        let open = matching_open_brace(&code, close)?;
+        let name = TABLE_RE.captures(&code[..open])?.get(1)?;
+
+        let uri = Uri::from_file_path(&file_path)?;
+        let missing: Vec<WrittenField> = written_fields(&code, open)
+            .into_iter()
+            .filter(|field| field.id.is_none())
+            .collect();
+        let names: Vec<String> = missing.iter().map(|f| format!("`{}`", f.name)).collect();
+        let related_information = missing
+            .iter()
+            .map(|field| DiagnosticRelatedInformation {
+                location: Location::new(uri.clone(), field.range),
+                message: format!("`{}` has no id", field.name),
+            })
+            .collect();
+
+        Some((
+            file_path,
+            Diagnostic {
+                range: Range::new(
+                    byte_offset_to_position(&code, name.start()),
+                    byte_offset_to_position(&code, name.end()),
+                ),
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(DiagnosticCode::MixedFieldIds.into()),
+                message: format!(
+                    "either all fields or no fields must have an `id` attribute; missing on {}",
+                    names.join(", ")
+                ),
+                related_information: Some(related_information),
+                ..Default::default()
+            },
+        ))
+    }
+}
+
+/// A field of a table as written in the source.
+struct WrittenField {
+    name: String,
+    /// The range of the field's name.
+    range: Range,
+    id: Option<u32>,
+    /// Where an `id` attribute is added: just inside the attribute list, or
+    /// before the `;` when the field has none.
+    insert_at: usize,
+    /// The text written before and after the `id` attribute at `insert_at`.
+    surround: (&'static str, &'static str),
+}
+
+/// Edits giving every field of the table declared at `position` an id, numbered
+/// after the largest id already written.
+#[must_use]
+pub fn missing_id_edits(content: &str, position: Position) -> Vec<TextEdit> {
+    let code = strip_comments_and_strings(content);
+    let line_start: usize = code
+        .split_inclusive('\n')
+        .take(position.line as usize)
+        .map(str::len)
+        .sum();
+    let Some(open) = code[line_start..].find('{').map(|i| line_start + i) else {
+        return vec![];
+    };
+
+    let fields = written_fields(&code, open);
+    let mut next_id = fields
+        .iter()
+        .filter_map(|field| field.id)
+        .max()
+        .map_or(0, |id| id + 1);
+    fields
+        .iter()
+        .filter(|field| field.id.is_none())
+        .map(|field| {
+            let (before, after) = field.surround;
+            let new_text = format!("{before}id: {next_id}{after}");
+            next_id += 1;
+            let at = byte_offset_to_position(&code, field.insert_at);
+            TextEdit::new(Range::new(at, at), new_text)
+        })
+        .collect()
+}
+
+/// The `{` matching the `}` at `close`.
+fn matching_open_brace(code: &str, close: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in code[..=close].char_indices().rev() {
+        match c {
+            '}' => depth += 1,
+            '{' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The fields in the body that opens at `open`. `code` must have its
+/// comments and strings removed.
+fn written_fields(code: &str, open: usize) -> Vec<WrittenField> {
+    let body_start = open + 1;
+    let body_end = code[body_start..]
+        .find('}')
+        .map_or(code.len(), |i| body_start + i);
+
+    let mut fields = Vec::new();
+    let mut start = body_start;
+    for statement in code[body_start..body_end].split_inclusive(';') {
+        let statement_start = start;
+        start += statement.len();
+        let Some(semicolon) = statement.rfind(';') else {
+            continue;
+        };
+        let Some(name) = FIELD_RE.captures(statement).and_then(|c| c.get(1)) else {
+            continue;
+        };
+
+        let attributes = statement.find('(');
+        let id = attributes
+            .and_then(|paren| ID_RE.captures(&statement[paren..]))
+            .and_then(|c| c[1].parse().ok());
+        let (insert_at, surround) = match attributes {
+            Some(paren) if statement[paren + 1..].trim_start().starts_with(')') => {
+                (statement_start + paren + 1, ("", ""))
+            }
+            Some(paren) => (statement_start + paren + 1, ("", ", ")),
+            None => (
+                statement_start + statement[..semicolon].trim_end().len(),
+                (" (", ")"),
+            ),
+        };
+        fields.push(WrittenField {
+            name: name.as_str().to_string(),
+            range: Range::new(
+                byte_offset_to_position(code, statement_start + name.start()),
+                byte_offset_to_position(code, statement_start + name.end()),
+            ),
+            id,
+            insert_at,
+            surround,
+        });
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_id_edits() {
+        let content = "table T {\n  a: int (id: 0);\n  b: int = 1;\n  c: int (deprecated);\n}\n";
+        let edits = missing_id_edits(content, Position::new(0, 6));
+        assert_eq!(
+            edits,
+            vec![
+                TextEdit::new(
+                    Range::new(Position::new(2, 12), Position::new(2, 12)),
+                    " (id: 1)".to_string()
+                ),
+                TextEdit::new(
+                    Range::new(Position::new(3, 10), Position::new(3, 10)),
+                    "id: 2, ".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use std::path::{Path, PathBuf};
+
+use crate::diagnostics::codes::DiagnosticCode;
+use crate::diagnostics::{utf16_column, ErrorDiagnosticHandler};
+use crate::utils::paths::canonicalize_or_self;
+use crate::utils::{as_pos_idx, byte_to_utf16_col};
+use regex::Regex;
+use tower_lsp_server::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
+
+/// The largest alignment flatc accepts (`FLATBUFFERS_MAX_ALIGNMENT`).
+pub const MAX_ALIGNMENT: u64 = 32;
+
+static RE: std::sync::LazyLock<Regex> = std::sync::LazyLock::new(|| {
+    Regex::new(
+        r"^(.+?):\s*(\d+):\s*(\d+):\s*error:\s*unexpected force_align value '(.*)', alignment must be a power of two integer ranging from the type's natural alignment (\d+) to (\d+)$",
+    )
+    .expect("force align regex failed to compile")
+});
+
+static VALUE_RE: std::sync::LazyLock<Regex> = std::sync::LazyLock::new(|| {
+    Regex::new(r#"force_align\s*:\s*("[^"]*"|[^\s,)]+)"#)
+        .expect("force align value regex failed to compile")
+});
+
+/// Handles flatc rejecting the `force_align` of a struct.
+pub struct ForceAlignHandler;
+
+impl ErrorDiagnosticHandler for ForceAlignHandler {
+    fn handle(&self, line: &str, content: &str) -> Option<(PathBuf, Diagnostic)> {
+        let captures = RE.captures(line)?;
+        let file_path = canonicalize_or_self(Path::new(captures[1].trim()));
+        let line_num: u32 = captures[2].parse().unwrap_or(1u32).saturating_sub(1);
+        let col_num: u32 = captures[3].parse().unwrap_or(1u32).saturating_sub(1);
+        let min: u64 = captures[5].parse().ok()?;
+        let max: u64 = captures[6].parse().ok()?;
+
+        // flatc reports the end of the struct, so look back for the attribute.
+        let range = force_align_value_range(content, line_num).unwrap_or_else(|| Range {
+            start: Position::new(line_num, utf16_column(content, line_num, col_num)),
+            end: Position::new(line_num, u32::MAX),
+        });
+
+        Some((file_path, invalid_force_align(range, min, max)))
+    }
+}
+
+#[must_use]
+pub fn invalid_force_align(range: Range, min: u64, max: u64) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::ERROR),
+        code: Some(DiagnosticCode::InvalidForceAlign.into()),
+        message: format!("force_align must be a power of two ≥ {min} and ≤ {max}"),
+        ..Default::default()
+    }
+}
+
+/// The range of the `force_align` value on the last line at or before `line_num` that sets one.
+#[must_use]
+pub fn force_align_value_range(content: &str, line_num: u32) -> Option<Range> {
+    content
+        .lines()
+        .enumerate()
+        .take(line_num as usize + 1)
+        .filter_map(|(idx, line)| {
+            let value = VALUE_RE.captures(line)?.get(1)?;
+            let line_num = as_pos_idx(idx);
+            Some(Range::new(
+                Position::new(
+                    line_num,
+                    byte_to_utf16_col(line.chars(), as_pos_idx(value.start())),
+                ),
+                Position::new(
+                    line_num,
+                    byte_to_utf16_col(line.chars(), as_pos_idx(value.end())),
+                ),
+            ))
+        })
+        .last()
+}
@@ -1,7 +1,7 @@
-use std::{fs, path::PathBuf};
+use std::path::{Path, PathBuf};
 
-use crate::diagnostics::ErrorDiagnosticHandler;
-use log::error;
+use crate::diagnostics::{utf16_column, ErrorDiagnosticHandler};
+use crate::utils::paths::canonicalize_or_self;
 use regex::Regex;
 use tower_lsp_server::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
 
@@ -13,13 +13,10 @@ static RE: std::sync::LazyLock<Regex> = std::sync::LazyLock::new(|| {
 pub struct GenericDiagnosticHandler;
 
 impl ErrorDiagnosticHandler for GenericDiagnosticHandler {
-    fn handle(&self, line: &str, _content: &str) -> Option<(PathBuf, Diagnostic)> {
+    fn handle(&self, line: &str, content: &str) -> Option<(PathBuf, Diagnostic)> {
         if let Some(captures) = RE.captures(line) {
             let file_path = captures.get(0)?.as_str().split(':').next()?;
-            let Ok(file_path) = fs::canonicalize(file_path) else {
-                error!("failed to canonicalize file: {file_path} in generic handler");
-                return None;
-            };
+            let file_path = canonicalize_or_self(Path::new(file_path));
 
             let line_num_str = captures.get(5).map_or_else(
                 || captures.get(1).map_or("1", |m| m.as_str()),
@@ -40,7 +37,7 @@ impl ErrorDiagnosticHandler for GenericDiagnosticHandler {
             let message = captures[4].trim().to_string();
 
             let range = Range {
-                start: Position::new(line_num, col_num),
+                start: Position::new(line_num, utf16_column(content, line_num, col_num)),
                 end: Position::new(line_num, u32::MAX),
             };
 
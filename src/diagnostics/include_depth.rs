@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::analysis::dependency_graph::DependencyGraph;
+use crate::diagnostics::codes::DiagnosticCode;
+use crate::utils::{as_pos_idx, byte_to_utf16_col};
+use dashmap::DashMap;
+use ropey::Rope;
+use tower_lsp_server::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
+
+/// How many includes deep a file can be nested by default.
+pub const DEFAULT_MAX_INCLUDE_DEPTH: usize = 64;
+
+/// Report the include statements that nest a file more than `max_depth` includes
+/// below any file that is not itself included. Depths are the shortest include
+/// chains in the workspace, so they do not depend on the order files are parsed in.
+#[must_use]
+pub fn analyze_include_depth(
+    graph: &DependencyGraph,
+    documents: &DashMap<PathBuf, Rope>,
+    max_depth: usize,
+) -> HashMap<PathBuf, Vec<Diagnostic>> {
+    let depths = graph.include_depths();
+    let mut diagnostics: HashMap<PathBuf, Vec<Diagnostic>> = HashMap::new();
+    for (path, includes) in &graph.direct_includes {
+        let Some(&depth) = depths.get(path).filter(|depth| **depth >= max_depth) else {
+            continue;
+        };
+        let Some(doc) = documents.get(path) else {
+            continue;
+        };
+        let content = doc.to_string();
+        for included in includes {
+            if depths.get(included) == Some(&(depth + 1)) {
+                diagnostics
+                    .entry(path.clone())
+                    .or_default()
+                    .push(include_too_deep(&content, included, max_depth));
+            }
+        }
+    }
+    diagnostics
+}
+
+/// Report that the include of `included` in `content` is nested more than
+/// `max_depth` includes deep.
+#[must_use]
+fn include_too_deep(content: &str, included: &Path, max_depth: usize) -> Diagnostic {
+    Diagnostic {
+        range: include_range(content, included).unwrap_or_default(),
+        severity: Some(DiagnosticSeverity::WARNING),
+        code: Some(DiagnosticCode::IncludeTooDeep.into()),
+        message: format!("include is nested more than {max_depth} levels deep"),
+        ..Default::default()
+    }
+}
+
+/// The range of the quoted path of the include statement in `content` that names
+/// `included`, matching on the trailing path components that are written out.
+fn include_range(content: &str, included: &Path) -> Option<Range> {
+    content.lines().enumerate().find_map(|(idx, line)| {
+        if !line.trim_start().starts_with("include") {
+            return None;
+        }
+        let start = line.find('"')? + 1;
+        let len = line[start..].find('"')?;
+        let written = Path::new(&line[start..start + len]);
+        let matches = included.ends_with(written) || included.file_name() == written.file_name();
+        if !matches {
+            return None;
+        }
+        let line_num = as_pos_idx(idx);
+        Some(Range::new(
+            Position::new(line_num, byte_to_utf16_col(line.chars(), as_pos_idx(start))),
+            Position::new(
+                line_num,
+                byte_to_utf16_col(line.chars(), as_pos_idx(start + len)),
+            ),
+        ))
+    })
+}
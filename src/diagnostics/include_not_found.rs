@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::diagnostics::codes::DiagnosticCode;
+use crate::diagnostics::{utf16_column, ErrorDiagnosticHandler};
+use crate::utils::paths::{canonicalize_or_self, path_buf_to_uri};
+use crate::utils::{as_pos_idx, byte_to_utf16_col, edit_distance};
+use crate::workspace_layout::WorkspaceLayout;
+use regex::Regex;
+use serde_json::json;
+use tower_lsp_server::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString,
+    Position, Range,
+};
+
+static RE: std::sync::LazyLock<Regex> = std::sync::LazyLock::new(|| {
+    Regex::new(
+        r"^(.+?):\s*(\d+):\s*(\d+):\s*error:\s*unable to (?:locate|load) include file: (.+)$",
+    )
+    .expect("include not found regex failed to compile")
+});
+
+pub struct IncludeNotFoundHandler;
+
+impl ErrorDiagnosticHandler for IncludeNotFoundHandler {
+    fn handle(&self, line: &str, content: &str) -> Option<(PathBuf, Diagnostic)> {
+        let captures = RE.captures(line)?;
+        let file_path = canonicalize_or_self(Path::new(captures[1].trim()));
+        let line_num: u32 = captures[2].parse().unwrap_or(1u32).saturating_sub(1);
+        let col_num: u32 = captures[3].parse().unwrap_or(1u32).saturating_sub(1);
+        let include = captures[4].trim().to_string();
+
+        // flatc reports the position after the include statement, so
+        // look for the quoted path to narrow the range to it.
+        let quoted = format!("\"{include}\"");
+        let range = content
+            .lines()
+            .enumerate()
+            .take(line_num as usize + 1)
+            .filter(|(_, l)| l.trim_start().starts_with("include"))
+            .find_map(|(idx, l)| l.find(&quoted).map(|start| (idx, l, start + 1)))
+            .map_or_else(
+                || Range {
+                    start: Position::new(line_num, utf16_column(content, line_num, col_num)),
+                    end: Position::new(line_num, u32::MAX),
+                },
+                |(idx, l, start)| {
+                    let start = as_pos_idx(start);
+                    let end = start + as_pos_idx(include.len());
+                    Range::new(
+                        Position::new(as_pos_idx(idx), byte_to_utf16_col(l.chars(), start)),
+                        Position::new(as_pos_idx(idx), byte_to_utf16_col(l.chars(), end)),
+                    )
+                },
+            );
+
+        Some((
+            file_path,
+            Diagnostic {
+                range,
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(DiagnosticCode::IncludeNotFound.into()),
+                message: format!("cannot find included file `{include}`"),
+                data: Some(json!({ "include": include })),
+                ..Default::default()
+            },
+        ))
+    }
+}
+
+/// Attach the closest known schema file to every include-not-found diagnostic,
+/// measured by edit distance against the path as it would be written from
+/// each directory searched for the including file.
+pub fn suggest_includes<S: std::hash::BuildHasher>(
+    diagnostics: &mut HashMap<PathBuf, Vec<Diagnostic>, S>,
+    layout: &WorkspaceLayout,
+) {
+    for (path, diagnostics) in diagnostics.iter_mut() {
+        for diagnostic in diagnostics.iter_mut() {
+            if diagnostic.code != Some(NumberOrString::from(DiagnosticCode::IncludeNotFound)) {
+                continue;
+            }
+            let Some(include) = diagnostic
+                .data
+                .as_ref()
+                .and_then(|data| data.get("include"))
+                .and_then(|include| include.as_str())
+                .map(ToString::to_string)
+            else {
+                continue;
+            };
+            let Some((suggestion, target)) = closest_include(path, &include, layout) else {
+                continue;
+            };
+            let Ok(uri) = path_buf_to_uri(&target) else {
+                continue;
+            };
+
+            diagnostic.related_information = Some(vec![DiagnosticRelatedInformation {
+                location: Location {
+                    uri,
+                    range: Range::default(),
+                },
+                message: format!("did you mean `{suggestion}`?"),
+            }]);
+            diagnostic.data = Some(json!({ "include": include, "suggestion": suggestion }));
+        }
+    }
+}
+
+/// The known file whose include path is nearest to `include`, along with that path.
+/// Candidates further than half the length of `include` are ignored.
+fn closest_include(
+    path: &Path,
+    include: &str,
+    layout: &WorkspaceLayout,
+) -> Option<(String, PathBuf)> {
+    let dirs = path
+        .parent()
+        .map(Path::to_path_buf)
+        .into_iter()
+        .chain(layout.search_paths_for(path));
+
+    let max_distance = include.chars().count() / 2;
+    let mut best: Option<(usize, String, PathBuf)> = None;
+    for dir in dirs {
+        for file in layout.known_matching_files(&dir) {
+            if file == path {
+                continue;
+            }
+            let Ok(relative) = file.strip_prefix(&dir) else {
+                continue;
+            };
+            let candidate = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let distance = edit_distance(include, &candidate);
+            if distance > max_distance {
+                continue;
+            }
+            let is_better = best
+                .as_ref()
+                .is_none_or(|(best_distance, best_candidate, _)| {
+                    (distance, candidate.len(), &candidate)
+                        < (*best_distance, best_candidate.len(), best_candidate)
+                });
+            if is_better {
+                best = Some((distance, candidate, file));
+            }
+        }
+    }
+    best.map(|(_, suggestion, target)| (suggestion, target))
+}
+
+/// The include paths, as written, that the include-not-found diagnostics
+/// in `diagnostics` could not resolve.
+#[must_use]
+pub fn unresolved_includes(diagnostics: &[Diagnostic]) -> Vec<String> {
+    diagnostics
+        .iter()
+        .filter(|diagnostic| {
+            diagnostic.code == Some(NumberOrString::from(DiagnosticCode::IncludeNotFound))
+        })
+        .filter_map(|diagnostic| diagnostic.data.as_ref()?.get("include")?.as_str())
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Files with an unresolved include that `created` may satisfy.
+/// Matching on file name alone over-approximates, but a spurious reparse is
+/// cheap compared to leaving a stale error behind.
+pub fn files_missing<S: std::hash::BuildHasher>(
+    unresolved_includes: &HashMap<PathBuf, Vec<String>, S>,
+    created: &Path,
+) -> Vec<PathBuf> {
+    let Some(file_name) = created.file_name() else {
+        return vec![];
+    };
+    unresolved_includes
+        .iter()
+        .filter(|(_, includes)| {
+            includes
+                .iter()
+                .any(|include| Path::new(include).file_name() == Some(file_name))
+        })
+        .map(|(path, _)| path.clone())
+        .collect()
+}
@@ -1,3 +1,5 @@
+use crate::utils::byte_to_utf16_col;
+use crate::utils::paths::canonicalize_or_self;
 use std::{
     borrow::Cow,
     collections::HashMap,
@@ -9,15 +11,32 @@ use tower_lsp_server::lsp_types::Diagnostic;
 pub mod codes;
 pub mod duplicate_definition;
 pub mod expecting_token;
+pub mod field_ids;
+pub mod force_align;
 pub mod generic;
+pub mod include_depth;
+pub mod include_not_found;
+pub mod namespace_mismatch;
+pub mod root_type;
 pub mod semantic;
 pub mod snake_case_warning;
+pub mod suppression;
 pub mod undefined_type;
+pub mod unknown_attribute;
+pub mod unknown_enum_value;
 
 pub trait ErrorDiagnosticHandler {
     fn handle(&self, line: &str, content: &str) -> Option<(PathBuf, Diagnostic)>;
 }
 
+/// Convert flatc's byte column on `line` of `content` to a UTF-16 column.
+fn utf16_column(content: &str, line: u32, byte_col: u32) -> u32 {
+    content
+        .lines()
+        .nth(line as usize)
+        .map_or(byte_col, |l| byte_to_utf16_col(l.chars(), byte_col))
+}
+
 #[must_use]
 pub fn generate_diagnostics_from_error_string(
     error_str: &str,
@@ -28,7 +47,13 @@ pub fn generate_diagnostics_from_error_string(
     let handlers: Vec<Box<dyn ErrorDiagnosticHandler>> = vec![
         Box::new(duplicate_definition::DuplicateDefinitionHandler),
         Box::new(expecting_token::ExpectingTokenHandler),
+        Box::new(field_ids::MixedFieldIdsHandler),
+        Box::new(force_align::ForceAlignHandler),
+        Box::new(include_not_found::IncludeNotFoundHandler),
+        Box::new(root_type::RootTypeHandler),
         Box::new(undefined_type::UndefinedTypeHandler),
+        Box::new(unknown_attribute::UnknownAttributeHandler),
+        Box::new(unknown_enum_value::UnknownEnumValueHandler),
         Box::new(snake_case_warning::SnakeCaseWarningHandler),
         Box::new(generic::GenericDiagnosticHandler),
     ];
@@ -40,9 +65,7 @@ pub fn generate_diagnostics_from_error_string(
             continue;
         };
 
-        let Ok(canonical_path) = fs::canonicalize(file_path_str) else {
-            continue;
-        };
+        let canonical_path = canonicalize_or_self(Path::new(file_path_str));
 
         let content = if canonical_path == root_path {
             Cow::Borrowed(root_content)
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::path::{Path, PathBuf};
+
+use crate::diagnostics::codes::DiagnosticCode;
+use crate::utils::{as_pos_idx, byte_to_utf16_col};
+use crate::workspace_layout::WorkspaceLayout;
+use serde_json::json;
+use tower_lsp_server::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
+
+/// Warn when the `namespace` of the file at `path` does not match its directory
+/// relative to the workspace root, e.g. `game/core/player.fbs` expects `namespace game.core;`.
+///
+/// Files directly inside a root, and directories that are not valid identifiers, are skipped.
+pub fn analyze_namespace_mismatch<S: BuildHasher>(
+    path: &Path,
+    content: &str,
+    layout: &WorkspaceLayout,
+    diagnostics: &mut HashMap<PathBuf, Vec<Diagnostic>, S>,
+) {
+    let Some(expected) = expected_namespace(path, layout) else {
+        return;
+    };
+
+    let diagnostic = match namespace_statement(content) {
+        Some((namespace, _)) if namespace == expected => return,
+        Some((namespace, range)) => Diagnostic {
+            range,
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(DiagnosticCode::NamespaceMismatch.into()),
+            message: format!(
+                "namespace `{namespace}` does not match the file's directory, expected `{expected}`"
+            ),
+            data: Some(json!({ "expected": expected })),
+            ..Default::default()
+        },
+        None => Diagnostic {
+            range: Range::default(),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(DiagnosticCode::NamespaceMismatch.into()),
+            message: format!("missing namespace, expected `{expected}` from the file's directory"),
+            data: Some(json!({ "expected": expected })),
+            ..Default::default()
+        },
+    };
+
+    diagnostics
+        .entry(path.to_path_buf())
+        .or_default()
+        .push(diagnostic);
+}
+
+/// The namespace implied by the directories between the workspace root and `path`.
+fn expected_namespace(path: &Path, layout: &WorkspaceLayout) -> Option<String> {
+    let root = layout.root_for(path)?;
+    let dir = path.parent()?.strip_prefix(root).ok()?;
+
+    let parts = dir
+        .components()
+        .map(|c| c.as_os_str().to_str().filter(|part| is_identifier(part)))
+        .collect::<Option<Vec<_>>>()?;
+    if parts.is_empty() {
+        return None;
+    }
+    Some(parts.join("."))
+}
+
+/// The first `namespace` statement in `content` and the range of its name.
+fn namespace_statement(content: &str) -> Option<(String, Range)> {
+    content.lines().enumerate().find_map(|(idx, line)| {
+        let rest = line.trim_start().strip_prefix("namespace")?;
+        let name = rest.split(';').next()?.trim();
+        if name.is_empty() || !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let start = line.len() - rest.trim_start().len();
+        let line_num = as_pos_idx(idx);
+        Some((
+            name.to_string(),
+            Range::new(
+                Position::new(line_num, byte_to_utf16_col(line.chars(), as_pos_idx(start))),
+                Position::new(
+                    line_num,
+                    byte_to_utf16_col(line.chars(), as_pos_idx(start + name.len())),
+                ),
+            ),
+        ))
+    })
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -0,0 +1,85 @@
+use std::path::{Path, PathBuf};
+
+use crate::diagnostics::codes::DiagnosticCode;
+use crate::diagnostics::{utf16_column, ErrorDiagnosticHandler};
+use crate::utils::paths::canonicalize_or_self;
+use crate::utils::{as_pos_idx, byte_to_utf16_col};
+use regex::Regex;
+use serde_json::json;
+use tower_lsp_server::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
+
+static RE: std::sync::LazyLock<Regex> = std::sync::LazyLock::new(|| {
+    Regex::new(
+        r"^(.+?):\s*(\d+):\s*(\d+):\s*error:\s*(?:unknown root type: (.+)|root type must be a table)$",
+    )
+    .expect("root type regex failed to compile")
+});
+
+/// Handles flatc rejecting a `root_type` statement. flatc reports both undefined
+/// types and non-table types as unknown, so the diagnostic is refined once the
+/// workspace symbols are known (see [`crate::diagnostics::semantic::analyze_root_type`]).
+pub struct RootTypeHandler;
+
+impl ErrorDiagnosticHandler for RootTypeHandler {
+    fn handle(&self, line: &str, content: &str) -> Option<(PathBuf, Diagnostic)> {
+        let captures = RE.captures(line)?;
+        let file_path = canonicalize_or_self(Path::new(captures[1].trim()));
+        let line_num: u32 = captures[2].parse().unwrap_or(1u32).saturating_sub(1);
+        let col_num: u32 = captures[3].parse().unwrap_or(1u32).saturating_sub(1);
+
+        let statement = root_type_statement(content, line_num);
+        let unknown = captures.get(4).map(|m| m.as_str().trim().to_string());
+        let message = unknown.as_ref().map_or_else(
+            || "root_type must be a table".to_string(),
+            |name| format!("unknown root type: {name}"),
+        );
+        let type_name = unknown.or_else(|| statement.as_ref().map(|(name, _)| name.clone()))?;
+        let range = statement.map_or_else(
+            || Range {
+                start: Position::new(line_num, utf16_column(content, line_num, col_num)),
+                end: Position::new(line_num, u32::MAX),
+            },
+            |(_, range)| range,
+        );
+
+        Some((
+            file_path,
+            Diagnostic {
+                range,
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(DiagnosticCode::InvalidRootType.into()),
+                message,
+                data: Some(json!({ "type_name": type_name })),
+                ..Default::default()
+            },
+        ))
+    }
+}
+
+/// The type name and its range in the last `root_type` statement at or before `line_num`.
+fn root_type_statement(content: &str, line_num: u32) -> Option<(String, Range)> {
+    content
+        .lines()
+        .enumerate()
+        .take(line_num as usize + 1)
+        .filter_map(|(idx, line)| {
+            let rest = line.trim_start().strip_prefix("root_type")?;
+            let name = rest.split(';').next()?.trim();
+            if name.is_empty() || !rest.starts_with(char::is_whitespace) {
+                return None;
+            }
+            let start = line.len() - rest.trim_start().len();
+            let line_num = as_pos_idx(idx);
+            Some((
+                name.to_string(),
+                Range::new(
+                    Position::new(line_num, byte_to_utf16_col(line.chars(), as_pos_idx(start))),
+                    Position::new(
+                        line_num,
+                        byte_to_utf16_col(line.chars(), as_pos_idx(start + name.len())),
+                    ),
+                ),
+            ))
+        })
+        .last()
+}
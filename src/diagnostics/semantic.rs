@@ -1,13 +1,99 @@
 use crate::diagnostics::codes::DiagnosticCode;
-use crate::utils::as_pos_idx;
+use crate::diagnostics::force_align::{
+    force_align_value_range, invalid_force_align, MAX_ALIGNMENT,
+};
+use crate::utils::scanner::strip_comments_and_strings;
+use crate::utils::{as_pos_idx, byte_to_utf16_col, utf16_len};
+use regex::Regex;
+use serde_json::json;
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::hash::BuildHasher;
 use std::path::{Path, PathBuf};
 
-use tower_lsp_server::lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag, Position, Range};
+use tower_lsp_server::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, DiagnosticTag, NumberOrString,
+    Position, Range,
+};
 
-use crate::symbol_table::{RootTypeInfo, SymbolKind, SymbolTable};
+use crate::symbol_table::{scalar_size, RootTypeInfo, Symbol, SymbolKind, SymbolTable};
+
+/// Flag definitions whose name is also a built-in type. A namespaced definition
+/// can still be referred to by its qualified name, so it is only a warning.
+pub fn analyze_builtin_shadowing<S: BuildHasher, T: BuildHasher>(
+    st: &SymbolTable,
+    builtins: &HashMap<String, Symbol, T>,
+    diagnostics: &mut HashMap<PathBuf, Vec<Diagnostic>, S>,
+) {
+    for symbol in st.values() {
+        if symbol.info.location.path != st.path || !builtins.contains_key(&symbol.info.name) {
+            continue;
+        }
+
+        let severity = if symbol.info.namespace.is_empty() {
+            DiagnosticSeverity::ERROR
+        } else {
+            DiagnosticSeverity::WARNING
+        };
+        diagnostics
+            .entry(symbol.info.location.path.clone())
+            .or_default()
+            .push(Diagnostic {
+                range: symbol.info.location.range,
+                code: Some(DiagnosticCode::ShadowedBuiltin.into()),
+                severity: Some(severity),
+                message: format!(
+                    "`{}` shadows the built-in type `{}`",
+                    symbol.info.qualified_name(),
+                    symbol.info.name
+                ),
+                ..Default::default()
+            });
+    }
+}
+
+/// Flag types and fields named after a keyword. flatc parses keywords as plain
+/// identifiers, so it only complains once the name is used somewhere it is ambiguous.
+pub fn analyze_keyword_names<S: BuildHasher, T: BuildHasher>(
+    st: &SymbolTable,
+    keywords: &HashMap<String, String, T>,
+    diagnostics: &mut HashMap<PathBuf, Vec<Diagnostic>, S>,
+) {
+    for symbol in st.values() {
+        if symbol.info.location.path != st.path {
+            continue;
+        }
+        let fields = match &symbol.kind {
+            SymbolKind::Table(t) => t.fields.as_slice(),
+            SymbolKind::Struct(s) => s.fields.as_slice(),
+            _ => &[],
+        };
+
+        for named in std::iter::once(symbol).chain(fields) {
+            if !keywords.contains_key(&named.info.name) {
+                continue;
+            }
+            diagnostics
+                .entry(named.info.location.path.clone())
+                .or_default()
+                .push(Diagnostic {
+                    range: named.info.location.range,
+                    code: Some(DiagnosticCode::ReservedKeyword.into()),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    message: format!(
+                        "`{}` is a reserved keyword and cannot be used as a {} name",
+                        named.info.name,
+                        named.type_name()
+                    ),
+                    data: Some(json!({
+                        "original_name": named.info.name,
+                        "replacement_name": format!("{}_", named.info.name),
+                    })),
+                    ..Default::default()
+                });
+        }
+    }
+}
 
 pub fn analyze_deprecated_fields<S: BuildHasher>(
     st: &SymbolTable,
@@ -50,6 +136,368 @@ pub fn analyze_deprecated_fields<S: BuildHasher>(
     }
 }
 
+/// Check the `force_align` of vector fields, which flatc only validates when serializing data.
+/// It must be a power of two no smaller than the element's natural alignment.
+pub fn analyze_force_align<S: BuildHasher>(
+    st: &SymbolTable,
+    content: &str,
+    diagnostics: &mut HashMap<PathBuf, Vec<Diagnostic>, S>,
+) {
+    for symbol in st.values() {
+        if symbol.info.location.path != st.path {
+            continue;
+        }
+        let SymbolKind::Table(table) = &symbol.kind else {
+            continue;
+        };
+
+        for field in &table.fields {
+            let SymbolKind::Field(field_def) = &field.kind else {
+                continue;
+            };
+            let Some(force_align) = &field_def.force_align else {
+                continue;
+            };
+            if !field_def.parsed_type.is_vector || field_def.parsed_type.array_size.is_some() {
+                continue;
+            }
+
+            let min = natural_alignment(st, &field_def.type_name);
+            let is_valid = force_align.parse::<u64>().is_ok_and(|align| {
+                align.is_power_of_two() && (min..=MAX_ALIGNMENT).contains(&align)
+            });
+            if is_valid {
+                continue;
+            }
+
+            let line = field.info.location.range.start.line;
+            let range = force_align_value_range(content, line).unwrap_or(field.info.location.range);
+            diagnostics
+                .entry(field.info.location.path.clone())
+                .or_default()
+                .push(invalid_force_align(range, min, MAX_ALIGNMENT));
+        }
+    }
+}
+
+/// Check that the root type named by each `nested_flatbuffer` attribute is a table.
+/// flatc reports unknown names, but accepts a struct.
+pub fn analyze_nested_flatbuffers<S: BuildHasher>(
+    st: &SymbolTable,
+    diagnostics: &mut HashMap<PathBuf, Vec<Diagnostic>, S>,
+) {
+    for symbol in st.values() {
+        if symbol.info.location.path != st.path {
+            continue;
+        }
+        let SymbolKind::Table(table) = &symbol.kind else {
+            continue;
+        };
+
+        for field in &table.fields {
+            let SymbolKind::Field(field_def) = &field.kind else {
+                continue;
+            };
+            let Some(nested) = &field_def.nested_flatbuffer else {
+                continue;
+            };
+            let Some(target) = st.get(&nested.type_name) else {
+                continue;
+            };
+            if matches!(target.kind, SymbolKind::Table(_)) {
+                continue;
+            }
+
+            diagnostics
+                .entry(field.info.location.path.clone())
+                .or_default()
+                .push(Diagnostic {
+                    range: nested.parsed_type.type_name.range,
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(DiagnosticCode::InvalidNestedFlatbuffer.into()),
+                    message: format!(
+                        "nested_flatbuffer root type must be a table, not {} `{}`",
+                        target.type_name(),
+                        target.info.qualified_name()
+                    ),
+                    related_information: Some(vec![DiagnosticRelatedInformation {
+                        location: target.info.location.clone().into(),
+                        message: format!("`{}` is defined here", target.info.name),
+                    }]),
+                    ..Default::default()
+                });
+        }
+    }
+}
+
+/// Check that the request and response of each rpc method are tables. flatc rejects
+/// other types, except for a struct that is only defined after the service.
+pub fn analyze_rpc_method_types<S: BuildHasher, T: BuildHasher>(
+    st: &SymbolTable,
+    symbols: &HashMap<String, Symbol, T>,
+    diagnostics: &mut HashMap<PathBuf, Vec<Diagnostic>, S>,
+) {
+    for symbol in st.values() {
+        if symbol.info.location.path != st.path {
+            continue;
+        }
+        let SymbolKind::RpcService(service) = &symbol.kind else {
+            continue;
+        };
+
+        for method in &service.methods {
+            for (role, method_type) in [
+                ("request", &method.request_type),
+                ("response", &method.response_type),
+            ] {
+                let Some(target) = st
+                    .get(&method_type.name)
+                    .or_else(|| symbols.get(&method_type.name))
+                else {
+                    continue;
+                };
+                if matches!(target.kind, SymbolKind::Table(_)) {
+                    continue;
+                }
+
+                diagnostics
+                    .entry(symbol.info.location.path.clone())
+                    .or_default()
+                    .push(Diagnostic {
+                        range: method_type.range,
+                        severity: Some(DiagnosticSeverity::ERROR),
+                        code: Some(DiagnosticCode::InvalidRpcType.into()),
+                        message: format!(
+                            "rpc {role} type must be a table, not {} `{}`",
+                            target.type_name(),
+                            target.info.qualified_name()
+                        ),
+                        related_information: Some(vec![DiagnosticRelatedInformation {
+                            location: target.info.location.clone().into(),
+                            message: format!("`{}` is defined here", target.info.name),
+                        }]),
+                        ..Default::default()
+                    });
+            }
+        }
+    }
+}
+
+/// Flag an enum or union named the same as a table or struct in its namespace.
+/// flatc keeps them apart, but most generators emit a type for each (a union
+/// generates an enum of its own name), which then collide.
+pub fn analyze_type_name_collision<S: BuildHasher>(
+    st: &SymbolTable,
+    existing: &Symbol,
+    symbol: &Symbol,
+    diagnostics: &mut HashMap<PathBuf, Vec<Diagnostic>, S>,
+) {
+    if symbol.info.location.path != st.path {
+        return;
+    }
+
+    let qualified_name = symbol.info.qualified_name();
+    let message = match &symbol.kind {
+        SymbolKind::Union(_) => format!(
+            "union `{qualified_name}` generates an enum that collides with {} `{qualified_name}`",
+            existing.type_name()
+        ),
+        _ => format!(
+            "{} `{qualified_name}` collides with {} `{qualified_name}` in generated code",
+            symbol.type_name(),
+            existing.type_name()
+        ),
+    };
+    diagnostics
+        .entry(symbol.info.location.path.clone())
+        .or_default()
+        .push(Diagnostic {
+            range: symbol.info.location.range,
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(DiagnosticCode::TypeNameCollision.into()),
+            message,
+            related_information: Some(vec![DiagnosticRelatedInformation {
+                location: existing.info.location.clone().into(),
+                message: format!(
+                    "{} `{}` is defined here",
+                    existing.type_name(),
+                    existing.info.name
+                ),
+            }]),
+            ..Default::default()
+        });
+}
+
+/// Flag enum values and union members named the same as the enum or union
+/// containing them, which makes for confusing generated code.
+pub fn analyze_variant_names<S: BuildHasher>(
+    st: &SymbolTable,
+    content: &str,
+    diagnostics: &mut HashMap<PathBuf, Vec<Diagnostic>, S>,
+) {
+    for symbol in st.values() {
+        if symbol.info.location.path != st.path {
+            continue;
+        }
+        let name = &symbol.info.name;
+        let ranges: Vec<Range> = match &symbol.kind {
+            SymbolKind::Enum(e) => e
+                .variants
+                .iter()
+                .filter(|variant| &variant.name == name)
+                .map(|_| {
+                    enum_variant_range(content, symbol.info.location.range.start.line, name)
+                        .unwrap_or(symbol.info.location.range)
+                })
+                .collect(),
+            SymbolKind::Union(u) => u
+                .variants
+                .iter()
+                .filter(|variant| variant.name.rsplit('.').next() == Some(name.as_str()))
+                .map(|variant| variant.parsed_type.type_name.range)
+                .collect(),
+            _ => continue,
+        };
+
+        for range in ranges {
+            diagnostics
+                .entry(symbol.info.location.path.clone())
+                .or_default()
+                .push(Diagnostic {
+                    range,
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    code: Some(DiagnosticCode::VariantNameCollision.into()),
+                    message: format!(
+                        "{} variant `{name}` has the same name as its {}",
+                        symbol.type_name(),
+                        symbol.type_name()
+                    ),
+                    related_information: Some(vec![DiagnosticRelatedInformation {
+                        location: symbol.info.location.clone().into(),
+                        message: format!("`{name}` is defined here"),
+                    }]),
+                    ..Default::default()
+                });
+        }
+    }
+}
+
+/// The range of the value `name` in the body of the enum declared on `line`.
+fn enum_variant_range(content: &str, line: u32, name: &str) -> Option<Range> {
+    let code = strip_comments_and_strings(content);
+    let word = Regex::new(&format!(r"\b{}\b", regex::escape(name))).ok()?;
+    let mut in_body = false;
+    for (idx, text) in code.lines().enumerate().skip(line as usize) {
+        let mut start = 0;
+        if !in_body {
+            let Some(brace) = text.find('{') else {
+                continue;
+            };
+            start = brace + 1;
+            in_body = true;
+        }
+        let end = text[start..].find('}').map_or(text.len(), |i| start + i);
+        if let Some(m) = word.find(&text[start..end]) {
+            let line = as_pos_idx(idx);
+            return Some(Range::new(
+                Position::new(
+                    line,
+                    byte_to_utf16_col(text.chars(), as_pos_idx(start + m.start())),
+                ),
+                Position::new(
+                    line,
+                    byte_to_utf16_col(text.chars(), as_pos_idx(start + m.end())),
+                ),
+            ));
+        }
+        if end < text.len() {
+            return None;
+        }
+    }
+    None
+}
+
+/// The alignment of a vector element of type `type_name`.
+fn natural_alignment(st: &SymbolTable, type_name: &str) -> u64 {
+    match st.get(type_name).map(|symbol| &symbol.kind) {
+        Some(SymbolKind::Struct(s)) => s.alignment,
+        Some(SymbolKind::Enum(e)) => scalar_size(&e.underlying_type).unwrap_or(1),
+        // Tables, strings and unions are stored as offsets.
+        Some(_) => 4,
+        None => scalar_size(type_name).unwrap_or(4),
+    }
+}
+
+/// Refine `root_type` errors for the file at `path` using the symbols it can see,
+/// i.e. those defined in it or in one of its `includes`.
+///
+/// A type that is not visible becomes an undefined type, so it gets the same quick fixes
+/// as any other. A type that is visible, but not a table, points at its definition.
+pub fn analyze_root_type<S: BuildHasher, T: BuildHasher>(
+    path: &Path,
+    includes: &[PathBuf],
+    symbols: &HashMap<String, Symbol, T>,
+    diagnostics: &mut HashMap<PathBuf, Vec<Diagnostic>, S>,
+) {
+    let visible: HashMap<&String, &Symbol> = symbols
+        .iter()
+        .filter(|(_, symbol)| {
+            let defined_in = &symbol.info.location.path;
+            defined_in == path || includes.contains(defined_in)
+        })
+        .collect();
+
+    let code = Some(NumberOrString::from(DiagnosticCode::InvalidRootType));
+    for diagnostic in diagnostics.values_mut().flatten() {
+        if diagnostic.code != code {
+            continue;
+        }
+        let Some(type_name) = diagnostic
+            .data
+            .as_ref()
+            .and_then(|data| data.get("type_name"))
+            .and_then(|name| name.as_str())
+        else {
+            continue;
+        };
+        let Some(symbol) = resolve_type_name(&visible, type_name) else {
+            diagnostic.code = Some(DiagnosticCode::UndefinedType.into());
+            diagnostic.message =
+                format!("type referenced but not defined (check namespace): {type_name}");
+            continue;
+        };
+        if matches!(symbol.kind, SymbolKind::Table(_)) {
+            continue;
+        }
+
+        diagnostic.message = format!(
+            "root_type must be a table, not {} `{}`",
+            symbol.type_name(),
+            symbol.info.qualified_name()
+        );
+        diagnostic.related_information = Some(vec![DiagnosticRelatedInformation {
+            location: symbol.info.location.clone().into(),
+            message: format!("`{}` is defined here", symbol.info.name),
+        }]);
+    }
+}
+
+/// Find the symbol for a possibly unqualified type name, preferring an exact match.
+fn resolve_type_name<'a>(
+    symbols: &HashMap<&String, &'a Symbol>,
+    type_name: &str,
+) -> Option<&'a Symbol> {
+    if let Some(symbol) = symbols.get(&type_name.to_string()) {
+        return Some(symbol);
+    }
+    let suffix = format!(".{type_name}");
+    symbols
+        .iter()
+        .filter(|(name, _)| name.ends_with(&suffix))
+        .min_by_key(|(name, _)| name.as_str())
+        .map(|(_, symbol)| *symbol)
+}
+
 struct IncludeStatement {
     canonical: PathBuf,
     /// text inside the quoted string
@@ -138,7 +586,7 @@ pub fn analyze_unused_includes<S: BuildHasher>(
             canonical: abs_path,
             text: path.to_string(),
             line: as_pos_idx(idx),
-            line_length: as_pos_idx(line.len()),
+            line_length: utf16_len(line),
         })
         .collect();
 
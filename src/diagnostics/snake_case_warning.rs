@@ -1,10 +1,13 @@
 use std::sync::LazyLock;
-use std::{fs, path::PathBuf, str::FromStr};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
-use crate::diagnostics::ErrorDiagnosticHandler;
-use crate::{diagnostics::codes::DiagnosticCode, utils::as_pos_idx};
+use crate::diagnostics::{utf16_column, ErrorDiagnosticHandler};
+use crate::utils::paths::canonicalize_or_self;
+use crate::{diagnostics::codes::DiagnosticCode, utils::utf16_len};
 use heck::ToSnakeCase;
-use log::error;
 use regex::Regex;
 use tower_lsp_server::lsp_types::{
     CodeDescription, Diagnostic, DiagnosticSeverity, Position, Range, Uri,
@@ -22,18 +25,15 @@ static SNAKE_CASE_RE: LazyLock<Regex> = LazyLock::new(|| {
 pub struct SnakeCaseWarningHandler;
 
 impl ErrorDiagnosticHandler for SnakeCaseWarningHandler {
-    fn handle(&self, line: &str, _content: &str) -> Option<(PathBuf, Diagnostic)> {
+    fn handle(&self, line: &str, content: &str) -> Option<(PathBuf, Diagnostic)> {
         let captures = SNAKE_CASE_RE.captures(line)?;
         let file_path = captures[1].trim();
-        let Ok(file_path) = fs::canonicalize(file_path) else {
-            error!("failed to canonicalize file: {file_path} in snake case handler");
-            return None;
-        };
+        let file_path = canonicalize_or_self(Path::new(file_path));
 
         let line_num: u32 = captures[2].parse().unwrap_or(1u32).saturating_sub(1);
-        let col_num: u32 = captures[3].parse().unwrap_or(1);
+        let col_num = utf16_column(content, line_num, captures[3].parse().unwrap_or(1));
         let name = captures[4].trim();
-        let name_length = as_pos_idx(name.chars().count());
+        let name_length = utf16_len(name);
 
         let replacement = name.to_snake_case();
         let message = format!("field `{name}` should be in snake_case e.g. `{replacement}`");
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use tower_lsp_server::lsp_types::{Diagnostic, NumberOrString};
+
+use crate::diagnostics::codes::DiagnosticCode;
+use crate::utils::as_pos_idx;
+
+/// The directive that turns off diagnostics for a whole file when it appears
+/// in one of the comments at the top of the file.
+pub const IGNORE_FILE_DIRECTIVE: &str = "fbls:ignore-file";
+
+/// Matches `// fbls:allow(code, ...)` comments, capturing the list of codes.
+static ALLOW_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"//.*?fbls:allow\(([^)]*)\)").expect("allow directive regex failed to compile")
+});
+
+/// The diagnostics a file has opted out of with comments.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Suppressions {
+    /// Whether the file has an [`IGNORE_FILE_DIRECTIVE`].
+    pub ignore_file: bool,
+    /// The codes allowed on each line by a `// fbls:allow(...)` comment.
+    pub allowed: HashMap<u32, Vec<DiagnosticCode>>,
+}
+
+impl Suppressions {
+    #[must_use]
+    pub fn parse(content: &str) -> Self {
+        let allowed = content
+            .lines()
+            .enumerate()
+            .filter_map(|(idx, line)| {
+                let codes = ALLOW_RE.captures(line)?[1]
+                    .split(',')
+                    .filter_map(|code| DiagnosticCode::try_from(code.trim().to_string()).ok())
+                    .collect::<Vec<_>>();
+                (!codes.is_empty()).then_some((as_pos_idx(idx), codes))
+            })
+            .collect();
+        Self {
+            ignore_file: ignores_file(content),
+            allowed,
+        }
+    }
+
+    /// Whether `diagnostic` should be dropped rather than reported.
+    #[must_use]
+    pub fn suppresses(&self, diagnostic: &Diagnostic) -> bool {
+        if self.ignore_file {
+            return true;
+        }
+        let Some(NumberOrString::String(code)) = &diagnostic.code else {
+            return false;
+        };
+        let Ok(code) = DiagnosticCode::try_from(code.clone()) else {
+            return false;
+        };
+        self.allowed
+            .get(&diagnostic.range.start.line)
+            .is_some_and(|codes| codes.contains(&code))
+    }
+}
+
+/// Whether the leading comments of `content` contain [`IGNORE_FILE_DIRECTIVE`].
+#[must_use]
+pub fn ignores_file(content: &str) -> bool {
+    content
+        .lines()
+        .map(str::trim)
+        .take_while(|line| line.is_empty() || line.starts_with("//"))
+        .filter_map(|line| line.strip_prefix("//"))
+        .any(|comment| comment.trim_start_matches('/').trim() == IGNORE_FILE_DIRECTIVE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp_server::lsp_types::{Position, Range};
+
+    #[test]
+    fn test_ignores_file() {
+        assert!(ignores_file("// fbls:ignore-file\ntable T {}"));
+        assert!(ignores_file(
+            "\n// Generated, do not edit.\n//fbls:ignore-file\ntable T {}"
+        ));
+        assert!(!ignores_file("table T {}\n// fbls:ignore-file"));
+        assert!(!ignores_file("// fbls:ignore-file-please\ntable T {}"));
+    }
+
+    #[test]
+    fn test_allow_comments() {
+        let suppressions = Suppressions::parse(
+            "include \"a.fbs\"; // fbls:allow(unused-include)\n\
+             table T { Bad: int; } // fbls:allow(deprecated, non-snake-case, not-a-code)\n",
+        );
+        let diagnostic = |line: u32, code: DiagnosticCode| Diagnostic {
+            range: Range::new(Position::new(line, 0), Position::new(line, 1)),
+            code: Some(code.into()),
+            ..Diagnostic::default()
+        };
+        assert!(suppressions.suppresses(&diagnostic(0, DiagnosticCode::UnusedInclude)));
+        assert!(suppressions.suppresses(&diagnostic(1, DiagnosticCode::NonSnakeCase)));
+        assert!(!suppressions.suppresses(&diagnostic(1, DiagnosticCode::UnusedInclude)));
+        assert!(!suppressions.suppresses(&diagnostic(2, DiagnosticCode::NonSnakeCase)));
+        assert!(!suppressions.suppresses(&Diagnostic::default()));
+    }
+}
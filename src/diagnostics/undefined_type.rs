@@ -1,11 +1,19 @@
-use std::{fs, path::PathBuf};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-use crate::diagnostics::ErrorDiagnosticHandler;
-use crate::{diagnostics::codes::DiagnosticCode, utils::as_pos_idx};
-use log::error;
+use crate::diagnostics::{utf16_column, ErrorDiagnosticHandler};
+use crate::symbol_table::{Symbol, SymbolKind};
+use crate::utils::paths::{canonicalize_or_self, path_buf_to_uri};
+use crate::{
+    diagnostics::codes::DiagnosticCode,
+    utils::{as_pos_idx, byte_to_utf16_col, edit_distance},
+};
 use regex::Regex;
 use serde_json::json;
-use tower_lsp_server::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
+use tower_lsp_server::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString,
+    Position, Range,
+};
 
 static RE: std::sync::LazyLock<Regex> = std::sync::LazyLock::new(|| {
     Regex::new(r"^.+?:(\d+):\s*(\d+):\s+(error|warning):\s+(.+?)(?:, originally at: (.+?):(\d+)(?::(\d+)-(\d+):(\d+))?)?$")
@@ -44,6 +52,15 @@ impl ErrorDiagnosticHandler for UndefinedTypeHandler {
                 ) {
                     // Case 1: "originally at" with a full range exists. Use these values.
                     file_path = original_path.as_str();
+                    // `content` is only available for the file with the error.
+                    let is_same_file = captures.get(0)?.as_str().split(':').next()? == file_path;
+                    let to_utf16 = |line: u32, byte_col: u32| {
+                        if is_same_file {
+                            utf16_column(content, line, byte_col)
+                        } else {
+                            byte_col
+                        }
+                    };
 
                     // Convert 1-based line from regex to 0-based for LSP.
                     let start_line = start_line_str
@@ -51,14 +68,15 @@ impl ErrorDiagnosticHandler for UndefinedTypeHandler {
                         .parse()
                         .unwrap_or(1u32)
                         .saturating_sub(1);
-                    let start_char = start_col_str.as_str().parse().unwrap_or(0u32);
+                    let start_char =
+                        to_utf16(start_line, start_col_str.as_str().parse().unwrap_or(0u32));
                     let end_line = end_line_str
                         .as_str()
                         .parse()
                         .unwrap_or(1u32)
                         .saturating_sub(1);
                     // The end character in LSP is exclusive.
-                    let end_char = end_col_str.as_str().parse().unwrap_or(0u32);
+                    let end_char = to_utf16(end_line, end_col_str.as_str().parse().unwrap_or(0u32));
 
                     range = Range {
                         start: Position {
@@ -91,7 +109,7 @@ impl ErrorDiagnosticHandler for UndefinedTypeHandler {
                     let mut temp_range = Range {
                         start: Position {
                             line: line_num,
-                            character: col_num,
+                            character: utf16_column(content, line_num, col_num),
                         },
                         end: Position {
                             line: line_num,
@@ -104,18 +122,27 @@ impl ErrorDiagnosticHandler for UndefinedTypeHandler {
                         if let Some(line_content) = content.lines().nth(line_num as usize) {
                             if let Some(start) = line_content.find(type_name.as_str()) {
                                 let end = start + type_name.as_str().len();
-                                temp_range.start.character = as_pos_idx(start);
-                                temp_range.end.character = as_pos_idx(end);
+                                temp_range.start.character =
+                                    byte_to_utf16_col(line_content.chars(), as_pos_idx(start));
+                                temp_range.end.character =
+                                    byte_to_utf16_col(line_content.chars(), as_pos_idx(end));
                             }
                         }
                     }
                     range = temp_range;
                 }
 
-                let Ok(file_path) = fs::canonicalize(file_path) else {
-                    error!("failed to canonicalize file: {file_path} in undefined type handler");
-                    return None;
-                };
+                // `content` is only available for the file with the error.
+                let is_same_file = captures.get(0)?.as_str().split(':').next()? == file_path;
+                let range = undefined_type_captures
+                    .get(1)
+                    .filter(|_| is_same_file)
+                    .and_then(|type_name| {
+                        nested_flatbuffer_value_range(content, range, type_name.as_str())
+                    })
+                    .unwrap_or(range);
+
+                let file_path = canonicalize_or_self(Path::new(file_path));
 
                 let severity = if &captures[3] == "error" {
                     DiagnosticSeverity::ERROR
@@ -143,3 +170,154 @@ impl ErrorDiagnosticHandler for UndefinedTypeHandler {
         None
     }
 }
+
+/// flatc records the use of a `nested_flatbuffer` root type at the end of the field's
+/// attributes rather than at the name. When `range` does not cover `type_name`, find it
+/// in the quoted value of a `nested_flatbuffer` attribute in the same declaration.
+fn nested_flatbuffer_value_range(content: &str, range: Range, type_name: &str) -> Option<Range> {
+    let lines: Vec<&str> = content.lines().collect();
+    let end_line = range.end.line as usize;
+    if text_in_range(lines.get(range.start.line as usize)?, range) == type_name {
+        return None;
+    }
+
+    let value_re = Regex::new(&format!(
+        r#"nested_flatbuffer\s*:\s*"({})""#,
+        regex::escape(type_name)
+    ))
+    .ok()?;
+    for line_num in (0..=end_line.min(lines.len().checked_sub(1)?)).rev() {
+        let line = lines[line_num];
+        if let Some(value) = value_re
+            .captures_iter(line)
+            .last()
+            .and_then(|captures| captures.get(1))
+        {
+            let line_num = as_pos_idx(line_num);
+            return Some(Range::new(
+                Position::new(
+                    line_num,
+                    byte_to_utf16_col(line.chars(), as_pos_idx(value.start())),
+                ),
+                Position::new(
+                    line_num,
+                    byte_to_utf16_col(line.chars(), as_pos_idx(value.end())),
+                ),
+            ));
+        }
+        // Stop at the end of the previous declaration.
+        if line_num != end_line && line.contains(';') {
+            break;
+        }
+    }
+    None
+}
+
+/// The text of `line` covered by the UTF-16 columns of `range`, which is assumed to be on one line.
+fn text_in_range(line: &str, range: Range) -> String {
+    let mut col = 0;
+    line.chars()
+        .filter(|c| {
+            let is_in_range = col >= range.start.character && col < range.end.character;
+            col += as_pos_idx(c.len_utf16());
+            is_in_range
+        })
+        .collect()
+}
+
+/// Point every undefined type diagnostic at the known type with the nearest name,
+/// e.g. `Widget` for `Widgt` or `widget`, and mention it in the message.
+/// Diagnostics that already have a suggestion are left as they are.
+pub fn suggest_types<S: std::hash::BuildHasher, T: std::hash::BuildHasher>(
+    diagnostics: &mut HashMap<PathBuf, Vec<Diagnostic>, S>,
+    symbols: &HashMap<String, Symbol, T>,
+) {
+    for diagnostic in diagnostics.values_mut().flatten() {
+        if diagnostic.code != Some(NumberOrString::from(DiagnosticCode::UndefinedType)) {
+            continue;
+        }
+        let Some(data) = diagnostic.data.as_ref() else {
+            continue;
+        };
+        if data.get("suggestion").is_some() {
+            continue;
+        }
+        let Some(type_name) = data
+            .get("type_name")
+            .and_then(|type_name| type_name.as_str())
+            .map(ToString::to_string)
+        else {
+            continue;
+        };
+        let Some(symbol) = closest_type(&type_name, symbols) else {
+            continue;
+        };
+        let Ok(uri) = path_buf_to_uri(&symbol.info.location.path) else {
+            continue;
+        };
+
+        let suggestion = symbol.info.qualified_name();
+        diagnostic.message = format!("{} (did you mean `{suggestion}`?)", diagnostic.message);
+        diagnostic.related_information = Some(vec![DiagnosticRelatedInformation {
+            location: Location {
+                uri,
+                range: symbol.info.location.range,
+            },
+            message: format!("`{suggestion}` is defined here"),
+        }]);
+        diagnostic.data = Some(json!({ "type_name": type_name, "suggestion": suggestion }));
+    }
+}
+
+/// The type whose name is nearest to `type_name`, ignoring case. Qualified names
+/// are compared in full. Candidates further than half the length of `type_name`
+/// are ignored, and there is no suggestion if the name exists as written.
+fn closest_type<'a, S: std::hash::BuildHasher>(
+    type_name: &str,
+    symbols: &'a HashMap<String, Symbol, S>,
+) -> Option<&'a Symbol> {
+    let is_qualified = type_name.contains('.');
+    let types = symbols.iter().filter(|(_, symbol)| {
+        matches!(
+            symbol.kind,
+            SymbolKind::Table(_)
+                | SymbolKind::Struct(_)
+                | SymbolKind::Enum(_)
+                | SymbolKind::Union(_)
+        )
+    });
+    let candidates = types.map(|(qualified_name, symbol)| {
+        let candidate = if is_qualified {
+            qualified_name.as_str()
+        } else {
+            symbol.info.name.as_str()
+        };
+        (candidate, qualified_name, symbol)
+    });
+
+    // A type with exactly this name just needs an include, which the quick fixes cover.
+    if candidates
+        .clone()
+        .any(|(candidate, _, _)| candidate == type_name)
+    {
+        return None;
+    }
+
+    let max_distance = type_name.chars().count() / 2;
+    candidates
+        .filter_map(|(candidate, qualified_name, symbol)| {
+            let distance = edit_distance(&type_name.to_lowercase(), &candidate.to_lowercase());
+            (distance <= max_distance).then(|| {
+                (
+                    distance,
+                    edit_distance(type_name, candidate),
+                    qualified_name,
+                    symbol,
+                )
+            })
+        })
+        .min_by_key(|&(distance, exact_distance, qualified_name, _)| {
+            (distance, exact_distance, qualified_name)
+        })
+        .map(|(_, _, _, symbol)| symbol)
+}
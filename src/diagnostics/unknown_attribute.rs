@@ -0,0 +1,88 @@
+use std::path::{Path, PathBuf};
+
+use crate::diagnostics::codes::DiagnosticCode;
+use crate::diagnostics::{utf16_column, ErrorDiagnosticHandler};
+use crate::utils::paths::canonicalize_or_self;
+use crate::utils::{as_pos_idx, byte_to_utf16_col};
+use regex::Regex;
+use serde_json::json;
+use tower_lsp_server::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
+
+static RE: std::sync::LazyLock<Regex> = std::sync::LazyLock::new(|| {
+    Regex::new(
+        r"^(.+?):\s*(\d+):\s*(\d+):\s*error:\s*user define attributes must be declared before use: (.+)$",
+    )
+    .expect("unknown attribute regex failed to compile")
+});
+
+/// Handles flatc rejecting an attribute that is neither built in nor declared with
+/// `attribute "name";`. flatc stops at the first one, so only it is reported.
+pub struct UnknownAttributeHandler;
+
+impl ErrorDiagnosticHandler for UnknownAttributeHandler {
+    fn handle(&self, line: &str, content: &str) -> Option<(PathBuf, Diagnostic)> {
+        let captures = RE.captures(line)?;
+        let file_path = canonicalize_or_self(Path::new(captures[1].trim()));
+        let line_num: u32 = captures[2].parse().unwrap_or(1u32).saturating_sub(1);
+        // flatc reports the position just after the attribute name.
+        let col_num: u32 = captures[3].parse().unwrap_or(0u32);
+        let name = captures[4].trim().to_string();
+
+        let range = attribute_name_range(content, line_num, col_num, &name).unwrap_or_else(|| {
+            let end = utf16_column(content, line_num, col_num);
+            Range {
+                start: Position::new(line_num, end.saturating_sub(as_pos_idx(name.len()))),
+                end: Position::new(line_num, end),
+            }
+        });
+
+        Some((
+            file_path,
+            Diagnostic {
+                range,
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(DiagnosticCode::UnknownAttribute.into()),
+                message: format!("unknown attribute `{name}`"),
+                data: Some(json!({ "attribute": name })),
+                ..Default::default()
+            },
+        ))
+    }
+}
+
+/// The range of the last occurrence of `name` on `line_num` that ends at or before `byte_col`.
+fn attribute_name_range(content: &str, line_num: u32, byte_col: u32, name: &str) -> Option<Range> {
+    let line = content.lines().nth(line_num as usize)?;
+    let end = line.len().min(byte_col as usize);
+    let start = line.get(..end)?.rfind(name)?;
+    Some(Range::new(
+        Position::new(line_num, byte_to_utf16_col(line.chars(), as_pos_idx(start))),
+        Position::new(
+            line_num,
+            byte_to_utf16_col(line.chars(), as_pos_idx(start + name.len())),
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attribute_name_range() {
+        let content = "table T {\n  a: int (mystery);\n}";
+        assert_eq!(
+            attribute_name_range(content, 1, 17, "mystery"),
+            Some(Range::new(Position::new(1, 10), Position::new(1, 17)))
+        );
+    }
+
+    #[test]
+    fn test_quoted_attribute_name_range() {
+        let content = "table T {\n  a: int (id: 0, \"mystery\");\n}";
+        assert_eq!(
+            attribute_name_range(content, 1, 26, "mystery"),
+            Some(Range::new(Position::new(1, 18), Position::new(1, 25)))
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use crate::diagnostics::{utf16_column, ErrorDiagnosticHandler};
+use crate::utils::paths::canonicalize_or_self;
+use crate::utils::{as_pos_idx, byte_to_utf16_col};
+use regex::Regex;
+use tower_lsp_server::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
+
+static RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(.+?):\s*(\d+):\s*(\d+):\s*error:\s*unknown enum value: (.+)$")
+        .expect("unknown enum value regex failed to compile")
+});
+
+/// The type of a field whose default value is being written, e.g. `Game.Difficulty`
+/// in `level: Game.Difficulty = Hard`.
+static FIELD_TYPE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\w+\s*:\s*([\w.]+)\s*=\s*"?[^;=]*$"#).expect("field type regex failed to compile")
+});
+
+/// Handles flatc rejecting a default value that is not a variant of the field's enum.
+pub struct UnknownEnumValueHandler;
+
+impl ErrorDiagnosticHandler for UnknownEnumValueHandler {
+    fn handle(&self, line: &str, content: &str) -> Option<(PathBuf, Diagnostic)> {
+        let captures = RE.captures(line)?;
+        let file_path = canonicalize_or_self(Path::new(captures[1].trim()));
+        let line_num: u32 = captures[2].parse().unwrap_or(1u32).saturating_sub(1);
+        // flatc reports the position just after the value.
+        let col_num: u32 = captures[3].parse().unwrap_or(0u32);
+        let value = captures[4].trim().to_string();
+
+        let source_line = content.lines().nth(line_num as usize).unwrap_or_default();
+        let before = source_line
+            .get(..source_line.len().min(col_num as usize))
+            .unwrap_or(source_line);
+        let range = before.rfind(&value).map_or_else(
+            || {
+                let end = utf16_column(content, line_num, col_num);
+                Range::new(
+                    Position::new(line_num, end.saturating_sub(as_pos_idx(value.len()))),
+                    Position::new(line_num, end),
+                )
+            },
+            |start| {
+                Range::new(
+                    Position::new(
+                        line_num,
+                        byte_to_utf16_col(source_line.chars(), as_pos_idx(start)),
+                    ),
+                    Position::new(
+                        line_num,
+                        byte_to_utf16_col(source_line.chars(), as_pos_idx(start + value.len())),
+                    ),
+                )
+            },
+        );
+
+        let message = match FIELD_TYPE_RE.captures(before) {
+            Some(field) => format!("`{value}` is not a value of enum `{}`", &field[1]),
+            None => format!("unknown enum value `{value}`"),
+        };
+
+        Some((
+            file_path,
+            Diagnostic {
+                range,
+                severity: Some(DiagnosticSeverity::ERROR),
+                message,
+                ..Default::default()
+            },
+        ))
+    }
+}
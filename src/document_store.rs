@@ -1,16 +1,24 @@
-use crate::utils::paths::{is_flatbuffer_schema, uri_to_path_buf};
+use crate::utils::paths::{is_flatbuffer_schema, path_buf_to_uri, uri_to_path_buf};
+use crate::utils::position_encoding::PositionEncoding;
 use dashmap::DashMap;
 use log::debug;
 use ropey::Rope;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tower_lsp_server::lsp_types::{
     DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
-    DidSaveTextDocumentParams,
+    DidSaveTextDocumentParams, Position, TextDocumentContentChangeEvent, Uri,
 };
+use tower_lsp_server::UriExt;
 
 #[derive(Debug)]
 pub struct DocumentStore {
     pub document_map: DashMap<PathBuf, Rope>,
+    /// Documents currently open in the client, whose contents may differ from disk,
+    /// and the latest version the client sent for each.
+    open_documents: DashMap<PathBuf, i32>,
+    /// The URI the client opened a document with, for documents whose canonical
+    /// path is different, e.g. because they were opened through a symlink.
+    client_uris: DashMap<PathBuf, Uri>,
 }
 
 impl Default for DocumentStore {
@@ -24,6 +32,8 @@ impl DocumentStore {
     pub fn new() -> Self {
         Self {
             document_map: DashMap::new(),
+            open_documents: DashMap::new(),
+            client_uris: DashMap::new(),
         }
     }
 
@@ -33,24 +43,49 @@ impl DocumentStore {
             return None;
         }
         let path = uri_to_path_buf(&params.text_document.uri).ok()?;
+        if params.text_document.uri.to_file_path().as_deref() == Some(path.as_path()) {
+            self.client_uris.remove(&path);
+        } else {
+            self.client_uris
+                .insert(path.clone(), params.text_document.uri.clone());
+        }
 
         self.document_map.insert(
             path.clone(),
             ropey::Rope::from_str(&params.text_document.text),
         );
+        self.open_documents
+            .insert(path.clone(), params.text_document.version);
         Some(path)
     }
 
-    pub fn handle_did_change(&self, mut params: DidChangeTextDocumentParams) -> Option<PathBuf> {
+    pub fn handle_did_change(
+        &self,
+        params: DidChangeTextDocumentParams,
+        encoding: PositionEncoding,
+    ) -> Option<PathBuf> {
         debug!("changed: {}", params.text_document.uri.path());
         if !is_flatbuffer_schema(&params.text_document.uri) {
             return None;
         }
+        if params.content_changes.is_empty() {
+            return None;
+        }
         let path = uri_to_path_buf(&params.text_document.uri).ok()?;
 
-        let content = params.content_changes.remove(0).text;
-        self.document_map
-            .insert(path.clone(), ropey::Rope::from_str(&content));
+        // Clients may batch several changes into one notification. They must
+        // be applied in order, each against the result of the previous one.
+        let mut doc = self
+            .document_map
+            .get(&path)
+            .map(|doc| doc.clone())
+            .unwrap_or_default();
+        for change in &params.content_changes {
+            apply_change(&mut doc, change, encoding);
+        }
+        self.document_map.insert(path.clone(), doc);
+        self.open_documents
+            .insert(path.clone(), params.text_document.version);
         Some(path)
     }
 
@@ -73,8 +108,173 @@ impl DocumentStore {
     pub fn handle_did_close(&self, params: &DidCloseTextDocumentParams) {
         debug!("closed: {}", params.text_document.uri.path());
         if !is_flatbuffer_schema(&params.text_document.uri) {
-            #[allow(clippy::needless_return)]
             return;
         }
+        if let Ok(path) = uri_to_path_buf(&params.text_document.uri) {
+            self.open_documents.remove(&path);
+            self.client_uris.remove(&path);
+        }
+    }
+
+    /// The latest version of `path` sent by the client, if it is open.
+    #[must_use]
+    pub fn version(&self, path: &Path) -> Option<i32> {
+        self.open_documents.get(path).map(|version| *version)
+    }
+
+    /// The URI the client knows `path` by, which is the URI of `path` itself
+    /// unless the client opened it through a different one.
+    /// # Errors
+    ///
+    /// Will return `Err` if `path` cannot be converted to a URI.
+    pub fn uri(&self, path: &Path) -> Result<Uri, String> {
+        self.client_uris
+            .get(path)
+            .map_or_else(|| path_buf_to_uri(path), |uri| Ok(uri.clone()))
+    }
+
+    /// Replace `uri` with the URI the client opened its document with, if that
+    /// is a different one.
+    pub fn to_client_uri(&self, uri: &mut Uri) {
+        if self.client_uris.is_empty() {
+            return;
+        }
+        let Ok(path) = uri_to_path_buf(uri) else {
+            return;
+        };
+        if let Some(client_uri) = self.client_uris.get(&path) {
+            *uri = client_uri.clone();
+        }
+    }
+
+    /// The canonical path of a document the client opened as `uri`, if it
+    /// was opened through a URI other than its canonical one.
+    #[must_use]
+    pub fn path(&self, uri: &Uri) -> Option<PathBuf> {
+        self.client_uris
+            .iter()
+            .find(|entry| entry.value() == uri)
+            .map(|entry| entry.key().clone())
+    }
+
+    /// Forget `path` if it is not open in the client, so it is read from disk
+    /// again the next time it is parsed.
+    pub fn clear_if_unopened(&self, path: &Path) {
+        if !self.open_documents.contains_key(path) {
+            self.document_map.remove(path);
+        }
+    }
+
+    /// Forget every document that is not open in the client, so it is read
+    /// from disk again the next time it is parsed.
+    pub fn clear_unopened(&self) {
+        self.document_map
+            .retain(|path, _| self.open_documents.contains_key(path));
+    }
+}
+
+fn apply_change(
+    doc: &mut Rope,
+    change: &TextDocumentContentChangeEvent,
+    encoding: PositionEncoding,
+) {
+    let Some(range) = change.range else {
+        *doc = Rope::from_str(&change.text);
+        return;
+    };
+
+    let start = position_to_char(doc, range.start, encoding);
+    let end = position_to_char(doc, range.end, encoding).max(start);
+    doc.remove(start..end);
+    doc.insert(start, &change.text);
+}
+
+/// Convert a position to a char index, clamping to the end of the line or document.
+fn position_to_char(doc: &Rope, position: Position, encoding: PositionEncoding) -> usize {
+    let line = position.line as usize;
+    if line >= doc.len_lines() {
+        return doc.len_chars();
+    }
+    let line_slice = doc.line(line);
+    let line_len = line_slice.len_chars()
+        - line_slice
+            .chars()
+            .rev()
+            .take_while(|c| *c == '\n' || *c == '\r')
+            .count();
+    doc.line_to_char(line)
+        + encoding
+            .char_col(line_slice.chars(), position.character)
+            .min(line_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp_server::lsp_types::Range;
+
+    fn full(text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    fn edit(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: Some(Range::new(
+                Position::new(start.0, start.1),
+                Position::new(end.0, end.1),
+            )),
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_apply_full_changes_keeps_last() {
+        let mut doc = Rope::from_str("table A {}");
+        for change in [full("table B {}"), full("table C {}")] {
+            apply_change(&mut doc, &change, PositionEncoding::Utf16);
+        }
+        assert_eq!(doc.to_string(), "table C {}");
+    }
+
+    #[test]
+    fn test_apply_incremental_changes_in_order() {
+        let mut doc = Rope::from_str("table A {\n}\n");
+        for change in [
+            edit((0, 6), (0, 7), "Widget"),
+            edit((0, 14), (0, 14), "\n  a: int;"),
+        ] {
+            apply_change(&mut doc, &change, PositionEncoding::Utf16);
+        }
+        assert_eq!(doc.to_string(), "table Widget {\n  a: int;\n}\n");
+    }
+
+    #[test]
+    fn test_apply_change_in_each_encoding() {
+        // '😀' is 4 bytes and 2 UTF-16 code units, but a single char.
+        let mut doc = Rope::from_str("a 😀 b");
+        apply_change(
+            &mut doc,
+            &edit((0, 5), (0, 6), "c"),
+            PositionEncoding::Utf16,
+        );
+        assert_eq!(doc.to_string(), "a 😀 c");
+        apply_change(&mut doc, &edit((0, 7), (0, 8), "d"), PositionEncoding::Utf8);
+        assert_eq!(doc.to_string(), "a 😀 d");
+    }
+
+    #[test]
+    fn test_apply_change_clamps_out_of_range() {
+        let mut doc = Rope::from_str("table A {}");
+        apply_change(
+            &mut doc,
+            &edit((0, 10), (5, 0), "\n"),
+            PositionEncoding::Utf16,
+        );
+        assert_eq!(doc.to_string(), "table A {}\n");
     }
 }
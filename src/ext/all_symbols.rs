@@ -0,0 +1,47 @@
+use crate::symbol_table::{Symbol, SymbolKind};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tower_lsp_server::lsp_types::{self, request::Request};
+
+pub enum AllSymbols {}
+
+impl Request for AllSymbols {
+    type Params = ();
+    /// Map from a fully-qualified name to its definition.
+    type Result = HashMap<String, IndexedSymbol>;
+    const METHOD: &'static str = "test/allSymbols";
+}
+
+/// A serializable view of a symbol in the workspace index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedSymbol {
+    pub name: String,
+    pub namespace: Vec<String>,
+    pub kind: lsp_types::SymbolKind,
+    pub location: lsp_types::Location,
+    pub documentation: Option<String>,
+    /// Fields, enum values, union member types or rpc methods, in declaration order.
+    pub members: Vec<String>,
+}
+
+impl From<&Symbol> for IndexedSymbol {
+    fn from(symbol: &Symbol) -> Self {
+        let members = match &symbol.kind {
+            SymbolKind::Table(t) => t.fields.iter().map(|f| f.info.name.clone()).collect(),
+            SymbolKind::Struct(s) => s.fields.iter().map(|f| f.info.name.clone()).collect(),
+            SymbolKind::Enum(e) => e.variants.iter().map(|v| v.name.clone()).collect(),
+            SymbolKind::Union(u) => u.variants.iter().map(|v| v.name.clone()).collect(),
+            SymbolKind::RpcService(r) => r.methods.iter().map(|m| m.name.clone()).collect(),
+            SymbolKind::Field(_) | SymbolKind::Scalar => vec![],
+        };
+        Self {
+            name: symbol.info.name.clone(),
+            namespace: symbol.info.namespace.clone(),
+            kind: (&symbol.kind).into(),
+            location: symbol.info.location.clone().into(),
+            documentation: symbol.info.documentation.clone(),
+            members,
+        }
+    }
+}
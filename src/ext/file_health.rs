@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tower_lsp_server::lsp_types::{request::Request, Diagnostic, DiagnosticSeverity, Uri};
+
+/// Summarizes the published diagnostics of every indexed file, so that clients
+/// can show the health of the project without tracking `publishDiagnostics`.
+pub enum FileHealth {}
+
+impl Request for FileHealth {
+    type Params = ();
+    #[allow(
+        clippy::mutable_key_type,
+        reason = "for consistency with lsp_types::notification::PublishDiagnosticsParams"
+    )]
+    type Result = HashMap<Uri, DiagnosticCounts>;
+    const METHOD: &'static str = "flatbuffers/fileHealth";
+}
+
+/// The number of diagnostics of each severity in a file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticCounts {
+    pub errors: usize,
+    pub warnings: usize,
+    pub information: usize,
+    pub hints: usize,
+}
+
+impl<'a> FromIterator<&'a Diagnostic> for DiagnosticCounts {
+    fn from_iter<I: IntoIterator<Item = &'a Diagnostic>>(diagnostics: I) -> Self {
+        let mut counts = Self::default();
+        for diagnostic in diagnostics {
+            match diagnostic.severity {
+                Some(DiagnosticSeverity::WARNING) => counts.warnings += 1,
+                Some(DiagnosticSeverity::INFORMATION) => counts.information += 1,
+                Some(DiagnosticSeverity::HINT) => counts.hints += 1,
+                // Clients usually treat a missing severity as an error.
+                _ => counts.errors += 1,
+            }
+        }
+        counts
+    }
+}
@@ -1,4 +1,6 @@
 pub mod all_diagnostics;
+pub mod all_symbols;
 pub mod duration;
+pub mod file_health;
 pub mod range;
 pub mod sync;
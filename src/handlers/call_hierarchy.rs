@@ -0,0 +1,120 @@
+//! Call hierarchy, repurposed to model include relationships between files.
+//!
+//! - Incoming calls for a file are the files that directly include it.
+//! - Outgoing calls for a file are the files it directly includes.
+
+use crate::analysis::WorkspaceSnapshot;
+use crate::handlers::includes::{include_statements, resolve_include};
+use crate::utils::as_pos_idx;
+use crate::utils::paths::{path_buf_to_uri, uri_to_path_buf};
+use std::path::Path;
+use tower_lsp_server::lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+    CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
+    Position, Range, SymbolKind,
+};
+
+pub fn prepare_call_hierarchy(
+    snapshot: &WorkspaceSnapshot<'_>,
+    params: &CallHierarchyPrepareParams,
+) -> Option<Vec<CallHierarchyItem>> {
+    let position = params.text_document_position_params.position;
+    let path = uri_to_path_buf(&params.text_document_position_params.text_document.uri).ok()?;
+    let doc = snapshot.documents.get(&path)?;
+
+    // Anchor on the included file when the cursor is on an include statement.
+    let anchor = include_statements(&doc)
+        .into_iter()
+        .find(|include| include.range.start.line == position.line)
+        .and_then(|include| resolve_include(snapshot, &path, &include.text))
+        .unwrap_or(path);
+
+    file_item(snapshot, &anchor).map(|item| vec![item])
+}
+
+pub fn incoming_calls(
+    snapshot: &WorkspaceSnapshot<'_>,
+    params: &CallHierarchyIncomingCallsParams,
+) -> Option<Vec<CallHierarchyIncomingCall>> {
+    let path = uri_to_path_buf(&params.item.uri).ok()?;
+    let includers = snapshot.dependencies.included_by.get(&path)?;
+
+    let mut calls = Vec::new();
+    for includer in includers {
+        let Some(doc) = snapshot.documents.get(includer) else {
+            continue;
+        };
+        // `included_by` also tracks transitive includes. Only report
+        // files that have an include statement for this file.
+        let from_ranges: Vec<Range> = include_statements(&doc)
+            .into_iter()
+            .filter(|include| {
+                resolve_include(snapshot, includer, &include.text).is_some_and(|p| p == path)
+            })
+            .map(|include| include.range)
+            .collect();
+        if from_ranges.is_empty() {
+            continue;
+        }
+        if let Some(from) = file_item(snapshot, includer) {
+            calls.push(CallHierarchyIncomingCall { from, from_ranges });
+        }
+    }
+    calls.sort_by(|a, b| a.from.uri.as_str().cmp(b.from.uri.as_str()));
+
+    Some(calls)
+}
+
+pub fn outgoing_calls(
+    snapshot: &WorkspaceSnapshot<'_>,
+    params: &CallHierarchyOutgoingCallsParams,
+) -> Option<Vec<CallHierarchyOutgoingCall>> {
+    let path = uri_to_path_buf(&params.item.uri).ok()?;
+    let doc = snapshot.documents.get(&path)?;
+
+    let mut calls: Vec<CallHierarchyOutgoingCall> = Vec::new();
+    for include in include_statements(&doc) {
+        let Some(included) = resolve_include(snapshot, &path, &include.text) else {
+            continue;
+        };
+        let Some(to) = file_item(snapshot, &included) else {
+            continue;
+        };
+        // The same file may be included more than once.
+        if let Some(existing) = calls.iter_mut().find(|c| c.to.uri == to.uri) {
+            existing.from_ranges.push(include.range);
+        } else {
+            calls.push(CallHierarchyOutgoingCall {
+                to,
+                from_ranges: vec![include.range],
+            });
+        }
+    }
+
+    Some(calls)
+}
+
+fn file_item(snapshot: &WorkspaceSnapshot<'_>, path: &Path) -> Option<CallHierarchyItem> {
+    let uri = path_buf_to_uri(path).ok()?;
+    let name = path.file_name()?.to_string_lossy().into_owned();
+
+    let end = snapshot
+        .documents
+        .get(path)
+        .map_or_else(Position::default, |doc| {
+            let last_line = doc.len_lines().saturating_sub(1);
+            let last_line_len = doc.line(last_line).len_chars();
+            Position::new(as_pos_idx(last_line), as_pos_idx(last_line_len))
+        });
+
+    Some(CallHierarchyItem {
+        name,
+        kind: SymbolKind::FILE,
+        tags: None,
+        detail: path.parent().map(|p| p.display().to_string()),
+        uri,
+        range: Range::new(Position::default(), end),
+        selection_range: Range::new(Position::default(), Position::default()),
+        data: None,
+    })
+}
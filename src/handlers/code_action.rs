@@ -1,14 +1,20 @@
 use crate::analysis::WorkspaceSnapshot;
 use crate::diagnostics::codes::DiagnosticCode;
+use crate::diagnostics::field_ids;
+use crate::handlers::completion::generate_include_text_edit;
+use crate::handlers::rename;
 use crate::utils::as_pos_idx;
 use crate::utils::paths::uri_to_path_buf;
 
+use ropey::Rope;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::string::ToString;
 use tower_lsp_server::lsp_types::{
     CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionResponse,
-    Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range, TextEdit, Uri, WorkspaceEdit,
+    Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range, RenameParams,
+    TextDocumentIdentifier, TextDocumentPositionParams, TextEdit, Uri, WorkDoneProgressParams,
+    WorkspaceEdit,
 };
 
 /// Handles incoming code action requests from the LSP client.
@@ -130,18 +136,143 @@ pub fn handle_code_action(
                 code_actions.push(CodeActionOrCommand::CodeAction(code_action));
             }
             DiagnosticCode::UndefinedType => {
+                code_actions.extend(generate_type_suggestion_code_action(
+                    snapshot,
+                    &uri,
+                    &diagnostic,
+                ));
                 code_actions.extend(generate_undefined_type_code_actions(
                     snapshot,
                     &uri,
                     &diagnostic,
                 ));
             }
-            DiagnosticCode::Deprecated | DiagnosticCode::DuplicateDefinition => {}
+            DiagnosticCode::IncludeNotFound => {
+                let Some(suggestion) = diagnostic
+                    .data
+                    .as_ref()
+                    .and_then(|data| data.get("suggestion"))
+                    .and_then(|v| v.as_str())
+                else {
+                    continue;
+                };
+                let text_edit = TextEdit {
+                    range: diagnostic.range,
+                    new_text: suggestion.to_string(),
+                };
+                code_actions.push(create_quickfix(
+                    &uri,
+                    &diagnostic,
+                    format!("Change include to `{suggestion}`"),
+                    vec![text_edit],
+                ));
+            }
+            DiagnosticCode::NamespaceMismatch => {
+                code_actions.extend(generate_namespace_mismatch_code_action(
+                    snapshot,
+                    &uri,
+                    &diagnostic,
+                ));
+            }
+            DiagnosticCode::UnknownAttribute => {
+                code_actions.extend(generate_declare_attribute_code_action(
+                    snapshot,
+                    &uri,
+                    &diagnostic,
+                ));
+            }
+            DiagnosticCode::ReservedKeyword => {
+                code_actions.extend(generate_reserved_keyword_code_action(
+                    snapshot,
+                    &uri,
+                    &diagnostic,
+                ));
+            }
+            DiagnosticCode::MixedFieldIds => {
+                code_actions.extend(generate_field_ids_code_action(snapshot, &uri, &diagnostic));
+            }
+            DiagnosticCode::Deprecated
+            | DiagnosticCode::DuplicateDefinition
+            | DiagnosticCode::ShadowedBuiltin
+            | DiagnosticCode::UnreferencedType
+            | DiagnosticCode::InvalidRootType
+            | DiagnosticCode::InvalidNestedFlatbuffer
+            | DiagnosticCode::VariantNameCollision
+            | DiagnosticCode::TypeNameCollision
+            | DiagnosticCode::InvalidRpcType
+            | DiagnosticCode::IncludeTooDeep
+            | DiagnosticCode::InvalidForceAlign => {}
         }
     }
     Some(code_actions)
 }
 
+/// Give every field of the table an id, continuing from the largest one already written.
+fn generate_field_ids_code_action(
+    snapshot: &WorkspaceSnapshot<'_>,
+    uri: &Uri,
+    diagnostic: &Diagnostic,
+) -> Option<CodeActionOrCommand> {
+    let path = uri_to_path_buf(uri).ok()?;
+    let content = snapshot.documents.get(&path)?.to_string();
+    let edits = field_ids::missing_id_edits(&content, diagnostic.range.start);
+    if edits.is_empty() {
+        return None;
+    }
+    Some(create_quickfix(
+        uri,
+        diagnostic,
+        "Add ids to all fields".to_string(),
+        edits,
+    ))
+}
+
+/// Rename an identifier that is a reserved keyword by appending `_`. Types are
+/// renamed everywhere they are referenced; fields only where they are declared.
+fn generate_reserved_keyword_code_action(
+    snapshot: &WorkspaceSnapshot<'_>,
+    uri: &Uri,
+    diagnostic: &Diagnostic,
+) -> Option<CodeActionOrCommand> {
+    let data = diagnostic.data.as_ref()?;
+    let original_name = data.get("original_name")?.as_str()?;
+    let replacement_name = data.get("replacement_name")?.as_str()?;
+
+    let edit = if snapshot
+        .resolve_symbol_at(uri, diagnostic.range.start)
+        .is_some()
+    {
+        let params = RenameParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: diagnostic.range.start,
+            },
+            new_name: replacement_name.to_string(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        rename::rename(snapshot, params, false).ok().flatten()?
+    } else {
+        #[allow(clippy::mutable_key_type, reason = "external type definition")]
+        let changes = HashMap::from([(
+            uri.clone(),
+            vec![TextEdit::new(
+                diagnostic.range,
+                replacement_name.to_string(),
+            )],
+        )]);
+        WorkspaceEdit::new(changes)
+    };
+
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: format!("Rename `{original_name}` to `{replacement_name}`"),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(edit),
+        is_preferred: Some(true),
+        ..Default::default()
+    }))
+}
+
 /// Creates a `CodeActionOrCommand` representing a quick fix.
 fn create_quickfix(
     uri: &Uri,
@@ -168,6 +299,148 @@ fn create_quickfix(
     CodeActionOrCommand::CodeAction(code_action)
 }
 
+/// Generates a code action that sets the namespace expected from the file's directory,
+/// either by replacing the existing namespace or by adding one after the includes.
+fn generate_namespace_mismatch_code_action(
+    snapshot: &WorkspaceSnapshot,
+    uri: &Uri,
+    diagnostic: &Diagnostic,
+) -> Option<CodeActionOrCommand> {
+    let expected = diagnostic.data.as_ref()?.get("expected")?.as_str()?;
+
+    let edit = if diagnostic.range.start == diagnostic.range.end {
+        let path = uri_to_path_buf(uri).ok()?;
+        let doc = snapshot.documents.get(&path)?;
+        let (line, new_text) = match last_line_starting_with(&doc, &["include "]) {
+            Some(line) => (line + 1, format!("\nnamespace {expected};\n")),
+            None => (0, format!("namespace {expected};\n\n")),
+        };
+        TextEdit {
+            range: Range::new(Position::new(line, 0), Position::new(line, 0)),
+            new_text,
+        }
+    } else {
+        TextEdit {
+            range: diagnostic.range,
+            new_text: expected.to_string(),
+        }
+    };
+
+    Some(create_quickfix(
+        uri,
+        diagnostic,
+        format!("Set file namespace to `{expected}`"),
+        vec![edit],
+    ))
+}
+
+/// Generates a code action that declares an unknown attribute after the file's
+/// includes, namespace and other attribute declarations.
+fn generate_declare_attribute_code_action(
+    snapshot: &WorkspaceSnapshot,
+    uri: &Uri,
+    diagnostic: &Diagnostic,
+) -> Option<CodeActionOrCommand> {
+    let name = diagnostic.data.as_ref()?.get("attribute")?.as_str()?;
+    let path = uri_to_path_buf(uri).ok()?;
+    let doc = snapshot.documents.get(&path)?;
+
+    let declaration = format!("attribute \"{name}\";\n");
+    let (line, new_text) =
+        match last_line_starting_with(&doc, &["include ", "namespace ", "attribute "]) {
+            Some(line)
+                if doc
+                    .line(line as usize)
+                    .to_string()
+                    .trim()
+                    .starts_with("attribute ") =>
+            {
+                (line + 1, declaration)
+            }
+            Some(line) => (line + 1, format!("\n{declaration}")),
+            None => (0, format!("{declaration}\n")),
+        };
+    let edit = TextEdit {
+        range: Range::new(Position::new(line, 0), Position::new(line, 0)),
+        new_text,
+    };
+
+    Some(create_quickfix(
+        uri,
+        diagnostic,
+        format!("Declare attribute `{name}`"),
+        vec![edit],
+    ))
+}
+
+/// The index of the last line in `doc` that starts with one of `prefixes`.
+fn last_line_starting_with(doc: &Rope, prefixes: &[&str]) -> Option<u32> {
+    doc.lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            let line = line.to_string();
+            prefixes
+                .iter()
+                .any(|prefix| line.trim().starts_with(prefix))
+        })
+        .last()
+        .map(|(i, _)| as_pos_idx(i))
+}
+
+/// The namespace declared in `doc`, if any.
+fn file_namespace(doc: &Rope) -> Option<Vec<String>> {
+    doc.lines().find_map(|line| {
+        line.to_string()
+            .trim()
+            .strip_prefix("namespace ")
+            .and_then(|ns| ns.strip_suffix(';'))
+            .map(|ns| ns.trim().split('.').map(ToString::to_string).collect())
+    })
+}
+
+/// Generates a code action that replaces a misspelled type with the similarly named
+/// type suggested by the diagnostic, adding an include for it if needed.
+fn generate_type_suggestion_code_action(
+    snapshot: &WorkspaceSnapshot,
+    uri: &Uri,
+    diagnostic: &Diagnostic,
+) -> Option<CodeActionOrCommand> {
+    let data = diagnostic.data.as_ref()?;
+    let type_name = data.get("type_name")?.as_str()?;
+    let suggestion = data.get("suggestion")?.as_str()?;
+    let symbol = snapshot.symbols.global.get(suggestion)?;
+    let path = uri_to_path_buf(uri).ok()?;
+    let doc = snapshot.documents.get(&path)?;
+
+    // Keep the type unqualified when it was written that way and resolves as-is.
+    let new_text = if !type_name.contains('.')
+        && file_namespace(&doc).unwrap_or_default() == symbol.info.namespace
+    {
+        symbol.info.name.clone()
+    } else {
+        symbol.info.qualified_name()
+    };
+
+    let (include_edits, include_preview) = generate_include_text_edit(snapshot, &path, symbol);
+    let mut edits = include_edits.unwrap_or_default();
+    edits.push(TextEdit {
+        range: diagnostic.range,
+        new_text: new_text.clone(),
+    });
+    let title = match include_preview {
+        Some(include) => format!("Change to `{new_text}` and add `{include}`"),
+        None => format!("Change to `{new_text}`"),
+    };
+
+    let CodeActionOrCommand::CodeAction(mut code_action) =
+        create_quickfix(uri, diagnostic, title, edits)
+    else {
+        return None;
+    };
+    code_action.is_preferred = Some(true);
+    Some(CodeActionOrCommand::CodeAction(code_action))
+}
+
 /// Generates a list of code actions for an "`UndefinedType`" diagnostic.
 ///
 /// This function searches the workspace for symbols that match the undefined type
@@ -213,20 +486,9 @@ fn generate_undefined_type_code_actions(
         return vec![];
     }
 
-    let file_namespace: Option<Vec<String>> = doc.lines().find_map(|line| {
-        line.to_string()
-            .trim()
-            .strip_prefix("namespace ")
-            .and_then(|ns| ns.strip_suffix(';'))
-            .map(|ns| ns.trim().split('.').map(ToString::to_string).collect())
-    });
+    let file_namespace = file_namespace(&doc);
 
-    let last_include_line = doc
-        .lines()
-        .enumerate()
-        .filter(|(_, line)| line.to_string().trim().starts_with("include "))
-        .last()
-        .map(|(i, _)| as_pos_idx(i));
+    let last_include_line = last_line_starting_with(&doc, &["include "]);
     let include_insert_line = last_include_line.map_or(0, |line| line + 1);
     let include_insert_pos = Position::new(include_insert_line, 0);
 
@@ -1,19 +1,103 @@
+use crate::analysis::symbol_index::AttributeTarget;
 use crate::analysis::WorkspaceSnapshot;
 use crate::symbol_table::SymbolKind;
-use crate::utils::as_pos_idx;
-use std::{cmp::max, path::PathBuf};
+use crate::utils::position_encoding::utf16_to_byte_col;
+use crate::utils::scanner::strip_comments_and_strings;
+use crate::utils::utf16_len;
+use regex::Regex;
+use ropey::Rope;
+use std::path::PathBuf;
+use std::sync::LazyLock;
 use tower_lsp_server::lsp_types::{
-    CompletionItem, CompletionItemKind, CompletionResponse, CompletionTextEdit, Documentation,
-    MarkupContent, MarkupKind, Position, Range, TextEdit,
+    CompletionItem, CompletionItemKind, CompletionItemLabelDetails, CompletionResponse,
+    CompletionTextEdit, Documentation, InsertTextFormat, MarkupContent, MarkupKind, Position,
+    Range, TextEdit,
 };
 
+/// Values accepted by `force_align`: powers of two up to flatc's maximum alignment.
+const FORCE_ALIGN_VALUES: [(&str, &str); 6] = [
+    ("1", "1 byte"),
+    ("2", "2 bytes"),
+    ("4", "4 bytes"),
+    ("8", "8 bytes"),
+    ("16", "16 bytes"),
+    ("32", "32 bytes"),
+];
+
+/// Hash algorithms accepted by `hash`.
+const HASH_VALUES: [(&str, &str); 4] = [
+    ("\"fnv1_32\"", "32-bit FNV-1"),
+    ("\"fnv1a_32\"", "32-bit FNV-1a"),
+    ("\"fnv1_64\"", "64-bit FNV-1"),
+    ("\"fnv1a_64\"", "64-bit FNV-1a"),
+];
+
+/// Stream directions accepted by the `streaming` attribute of an rpc method.
+const STREAMING_VALUES: [(&str, &str); 4] = [
+    ("\"none\"", "single request and response"),
+    ("\"client\"", "client-streaming"),
+    ("\"server\"", "server-streaming"),
+    ("\"bidi\"", "bidirectional streaming"),
+];
+
+/// Attributes of an rpc method, which are separate from those of fields.
+const RPC_METHOD_ATTRIBUTES: [(&str, &str, &str); 2] = [
+    (
+        "streaming",
+        "streaming:",
+        "Stream the requests, the responses or both: `\"none\"`, `\"client\"`, `\"server\"` or `\"bidi\"`.",
+    ),
+    (
+        "idempotent",
+        "idempotent",
+        "Mark the method as safe to retry, since calling it more than once has the same effect as calling it once.",
+    ),
+];
+
+/// An rpc method up to the inside of its attribute parens, e.g. `Read(Req):Res (stre`.
+static RPC_METHOD_ATTRIBUTES_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*\w+\s*\([^)]*\)\s*:\s*[\w.]+\s*\((?<attributes>[^)]*)$")
+        .expect("rpc method attributes regex failed to compile")
+});
+
+static ID_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\bid\s*:(\s*)(\d+)").expect("id regex failed to compile"));
+
+/// A field whose attributes include `key`, e.g. `name: string (key);`.
+static KEY_FIELD_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(\w+)\s*:[^;{}()]*\([^)]*\bkey\b[^)]*\)")
+        .expect("key field regex failed to compile")
+});
+
+/// A table or struct declaration up to its attribute parens, e.g. `struct Vec3 `.
+static DECLARATION_BEFORE_ATTRIBUTES_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(?<kind>table|struct)\s+\w+\s*$")
+        .expect("declaration before attributes regex failed to compile")
+});
+
+/// A field name and the type it starts with, e.g. `pos: Vec3` or `enemies: [Monster`.
+static FIELD_TYPE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*\w+\s*:\s*\[?(?<type>[\w.]+)").expect("field type regex failed to compile")
+});
+
+/// A field name and type followed by whitespace, e.g. `hp: short `.
+static FIELD_BEFORE_ATTRIBUTES_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*\w+\s*:\s*\[?[\w.]+(?::\s*\d+)?\]?(?:\s*=\s*[^\s;]+)?\s+$")
+        .expect("field before attributes regex failed to compile")
+});
+
 #[allow(clippy::too_many_lines)]
 pub fn handle_attribute_completion(
     snapshot: &WorkspaceSnapshot,
     path: &PathBuf,
     position: Position,
     line: &str,
+    in_rpc_service: bool,
 ) -> Option<CompletionResponse> {
+    if in_rpc_service {
+        return rpc_method_attribute_completion(line, position).map(CompletionResponse::Array);
+    }
+
     if let Some(start_paren) = line[..position.character as usize].rfind('(') {
         // Ignore if inside a comment
         let comment_start = line.find("//");
@@ -33,6 +117,10 @@ pub fn handle_attribute_completion(
             .next_back()
             .unwrap_or("");
 
+        if let Some(items) = attribute_value_completion(trigger_text, position) {
+            return Some(CompletionResponse::Array(items));
+        }
+
         let mut items = Vec::new();
         let common_attributes = ["deprecated", "required", "key", "id"];
         let trigger_char = line[start_paren..position.character as usize]
@@ -42,76 +130,22 @@ pub fn handle_attribute_completion(
         let attribute_prefix = if trigger_char == ',' { " " } else { "" };
 
         // ID completion
-        if "id".starts_with(last_word) {
-            if let Some(table_symbol) = snapshot.find_enclosing_table(path, position) {
-                if let SymbolKind::Table(table) = &table_symbol.kind {
-                    let mut max_id = -1;
-                    let mut style_with_space = true;
-
-                    for field in &table.fields {
-                        if let SymbolKind::Field(f) = &field.kind {
-                            if let Some(id) = f.id {
-                                max_id = max(max_id, id);
-                            }
-
-                            // Check styling
-                            if let Some(line) = snapshot.documents.get(path).and_then(|doc| {
-                                doc.lines()
-                                    .nth(field.info.location.range.start.line as usize)
-                                    .map(|line| line.to_string())
-                            }) {
-                                if let Some(id_attr) = line.find("id:") {
-                                    if line.chars().nth(id_attr + 3).unwrap_or(' ') != ' ' {
-                                        style_with_space = false;
-                                    }
-                                }
-                            }
-                        }
-                    }
-
-                    let has_id_attribute = line.contains("id:");
-                    if !has_id_attribute {
-                        let next_id = max_id + 1;
-                        let label = if style_with_space {
-                            format!("id: {next_id}")
-                        } else {
-                            format!("id:{next_id}")
-                        };
-
-                        let range = Range {
-                            start: Position {
-                                line: position.line,
-                                character: (position.character - as_pos_idx(last_word.len())),
-                            },
-                            end: position,
-                        };
-
-                        let insert_text = Some(attribute_prefix.to_string() + &label);
-                        items.push(CompletionItem {
-                            label,
-                            insert_text,
-                            kind: Some(CompletionItemKind::PROPERTY),
-                            detail: Some("next available id".to_string()),
-                            documentation: Some(Documentation::MarkupContent(MarkupContent {
-                                kind: MarkupKind::Markdown,
-                                value: "The next available field id for this table. IDs must be contiguous and start at 0.".to_string(),
-                            })),
-                            sort_text: Some("00".to_string()),
-                            text_edit: Some(CompletionTextEdit::Edit(
-                                TextEdit {
-                                    range,
-                                    new_text: if style_with_space {
-                                        format!("id: {next_id}")
-                                    } else {
-                                        format!("id:{next_id}")
-                                    },
-                                },
-                            )),
-                            ..Default::default()
-                        });
-                    }
-                }
-            }
+        if "id".starts_with(last_word) && !line.contains("id:") {
+            let range = Range::new(
+                Position::new(
+                    position.line,
+                    position.character.saturating_sub(utf16_len(last_word)),
+                ),
+                position,
+            );
+            items.extend(id_completion(
+                snapshot,
+                path,
+                position,
+                range,
+                attribute_prefix,
+                false,
+            ));
         }
 
         // Other attributes
@@ -121,7 +155,18 @@ pub fn handle_attribute_completion(
             .min()
             .unwrap_or(line.len());
         let attribute_list = &line[start_paren..attr_end];
-        let value_attributes = ["force_align", "nested_flatbuffer", "hash"]; // attributes that require a value
+        // attributes that require a value
+        let value_attributes = [
+            "force_align",
+            "nested_flatbuffer",
+            "hash",
+            "native_default",
+            "native_type",
+            "native_type_pack_name",
+            "native_custom_alloc",
+        ];
+        let existing_key = existing_key_field(snapshot, path, position);
+        let before_paren = &line[..start_paren];
         for entry in snapshot
             .symbols
             .builtin_attributes
@@ -138,13 +183,29 @@ pub fn handle_attribute_completion(
                     continue;
                 }
             }
+            if !accepts_target(snapshot, attr.target, before_paren) {
+                continue;
+            }
 
             if name.starts_with(last_word) {
-                let sort_text = if common_attributes.contains(&name.as_str()) {
+                // Object API attributes are rarely needed, so they sort last.
+                let mut sort_text = if common_attributes.contains(&name.as_str()) {
                     format!("0_{name}")
+                } else if name.starts_with("native_") {
+                    format!("2_{name}")
                 } else {
                     format!("1_{name}")
                 };
+                let mut detail = None;
+                let mut doc = attr.doc.clone();
+                if let (Some(other), "key") = (&existing_key, name.as_str()) {
+                    // A table can only have one key, so steer away from a second one.
+                    sort_text = format!("3_{name}");
+                    detail = Some(format!("`{other}` is already the key"));
+                    doc = format!(
+                        "**Warning:** `{other}` already has `(key)`. A table can only have one key field.\n\n{doc}"
+                    );
+                }
                 let insert_suffix = if value_attributes.contains(&name.as_str()) {
                     ":"
                 } else {
@@ -154,16 +215,310 @@ pub fn handle_attribute_completion(
                     label: name.clone(),
                     insert_text: Some(attribute_prefix.to_string() + name + insert_suffix),
                     kind: Some(CompletionItemKind::PROPERTY),
+                    detail,
                     documentation: Some(Documentation::MarkupContent(MarkupContent {
                         kind: MarkupKind::Markdown,
-                        value: attr.doc.clone(),
+                        value: doc,
                     })),
                     sort_text: Some(sort_text),
                     ..Default::default()
                 });
+                if name == "deprecated" && line[..start_paren].contains(':') {
+                    items.extend(deprecated_with_note_completion(
+                        line,
+                        position,
+                        last_word,
+                        attribute_prefix,
+                    ));
+                }
             }
         }
         return Some(CompletionResponse::Array(items));
     }
+
+    // After the type of a table field, offer to start the attributes with the next id.
+    if FIELD_BEFORE_ATTRIBUTES_RE.is_match(&line[..position.character as usize])
+        && !line.contains('(')
+    {
+        let range = Range::new(position, position);
+        let item = id_completion(snapshot, path, position, range, "", true)?;
+        return Some(CompletionResponse::Array(vec![item]));
+    }
     None
 }
+
+/// Whether attributes with `target` can be written in the parens that follow `before_paren`.
+fn accepts_target(
+    snapshot: &WorkspaceSnapshot,
+    target: AttributeTarget,
+    before_paren: &str,
+) -> bool {
+    let declaration = DECLARATION_BEFORE_ATTRIBUTES_RE
+        .captures(before_paren)
+        .and_then(|c| c.name("kind"))
+        .map(|kind| kind.as_str());
+    match target {
+        AttributeTarget::Any => true,
+        AttributeTarget::Struct => declaration == Some("struct"),
+        AttributeTarget::Table => declaration == Some("table"),
+        AttributeTarget::TableOrStructField => FIELD_TYPE_RE
+            .captures(before_paren)
+            .and_then(|c| c.name("type"))
+            .is_some_and(|type_name| is_table_or_struct(snapshot, type_name.as_str())),
+    }
+}
+
+/// Whether `type_name`, as written in a field, names a table or struct. The
+/// namespace it is written in is not known here, so any namespace matches.
+fn is_table_or_struct(snapshot: &WorkspaceSnapshot, type_name: &str) -> bool {
+    let suffix = format!(".{type_name}");
+    snapshot.symbols.global.iter().any(|(name, symbol)| {
+        (name == type_name || name.ends_with(&suffix))
+            && matches!(symbol.kind, SymbolKind::Table(_) | SymbolKind::Struct(_))
+    })
+}
+
+/// Completions inside the attribute parens of an rpc method, e.g. `Read(Req):Res ($0)`.
+fn rpc_method_attribute_completion(line: &str, position: Position) -> Option<Vec<CompletionItem>> {
+    let line_upto_cursor = line.get(..position.character as usize)?;
+    let captures = RPC_METHOD_ATTRIBUTES_RE.captures(line_upto_cursor)?;
+    let trigger_text = captures.name("attributes")?.as_str();
+
+    if let Some(items) = attribute_value_completion(trigger_text, position) {
+        return Some(items);
+    }
+
+    let last_word = trigger_text
+        .split(|c: char| c.is_whitespace() || c == ',' || c == ':')
+        .next_back()
+        .unwrap_or("");
+    let attribute_prefix = if trigger_text.ends_with(',') { " " } else { "" };
+    Some(
+        RPC_METHOD_ATTRIBUTES
+            .iter()
+            .filter(|(name, _, _)| name.starts_with(last_word) && !trigger_text.contains(name))
+            .map(|(name, insert_text, doc)| CompletionItem {
+                label: (*name).to_string(),
+                insert_text: Some(format!("{attribute_prefix}{insert_text}")),
+                kind: Some(CompletionItemKind::PROPERTY),
+                documentation: Some(Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: (*doc).to_string(),
+                })),
+                ..Default::default()
+            })
+            .collect(),
+    )
+}
+
+/// A variant of the `deprecated` completion for a field that also adds a
+/// `// replaced by ...` comment at the end of the line, with the replacement as a placeholder.
+/// Sorts after the plain `deprecated` so that it is only used when picked.
+fn deprecated_with_note_completion(
+    line: &str,
+    position: Position,
+    last_word: &str,
+    prefix: &str,
+) -> Option<CompletionItem> {
+    let cursor = utf16_to_byte_col(line.chars(), position.character) as usize;
+    let rest = line.get(cursor..)?.trim_end();
+    if rest.contains("//") {
+        return None;
+    }
+    // Snippet syntax gives `$`, `}` and `\` special meaning.
+    let rest_escaped = rest
+        .replace('\\', "\\\\")
+        .replace('$', "\\$")
+        .replace('}', "\\}");
+    let range = Range::new(
+        Position::new(
+            position.line,
+            position.character.saturating_sub(utf16_len(last_word)),
+        ),
+        Position::new(position.line, position.character + utf16_len(rest)),
+    );
+    Some(CompletionItem {
+        label: "deprecated".to_string(),
+        label_details: Some(CompletionItemLabelDetails {
+            detail: None,
+            description: Some("with replacement note".to_string()),
+        }),
+        kind: Some(CompletionItemKind::SNIPPET),
+        documentation: Some(Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value:
+                "Deprecate the field and note what replaces it, e.g. `// replaced by new_field`."
+                    .to_string(),
+        })),
+        filter_text: Some("deprecated".to_string()),
+        sort_text: Some("0_deprecated_note".to_string()),
+        insert_text_format: Some(InsertTextFormat::SNIPPET),
+        text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+            range,
+            new_text: format!("{prefix}deprecated{rest_escaped} // replaced by ${{1:field}}"),
+        })),
+        ..Default::default()
+    })
+}
+
+/// A completion for the next available id in the table enclosing `position`,
+/// written in the same style as the ids already in the table.
+fn id_completion(
+    snapshot: &WorkspaceSnapshot,
+    path: &PathBuf,
+    position: Position,
+    range: Range,
+    prefix: &str,
+    in_parens: bool,
+) -> Option<CompletionItem> {
+    let table_symbol = snapshot.find_enclosing_table(path, position)?;
+    let SymbolKind::Table(table) = &table_symbol.kind else {
+        return None;
+    };
+
+    // The parsed ids may be stale while typing, so also read them from the text.
+    let table_text = snapshot
+        .documents
+        .get(path)
+        .map(|doc| table_text(&doc, table_symbol.info.location.range.start.line as usize))
+        .unwrap_or_default();
+    let written_ids: Vec<_> = ID_RE.captures_iter(&table_text).collect();
+    let style_with_space = written_ids.iter().all(|c| !c[1].is_empty());
+    let max_id = table
+        .fields
+        .iter()
+        .filter_map(|field| match &field.kind {
+            SymbolKind::Field(f) => f.id,
+            _ => None,
+        })
+        .chain(written_ids.iter().filter_map(|c| c[2].parse().ok()))
+        .max()
+        .unwrap_or(-1);
+
+    let next_id = max_id + 1;
+    let attribute = if style_with_space {
+        format!("id: {next_id}")
+    } else {
+        format!("id:{next_id}")
+    };
+    let label = if in_parens {
+        format!("({attribute})")
+    } else {
+        attribute
+    };
+
+    Some(CompletionItem {
+        label: label.clone(),
+        insert_text: Some(prefix.to_string() + &label),
+        kind: Some(CompletionItemKind::PROPERTY),
+        detail: Some("next available id".to_string()),
+        documentation: Some(Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value:
+                "The next available field id for this table. IDs must be contiguous and start at 0."
+                    .to_string(),
+        })),
+        sort_text: Some("00".to_string()),
+        text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+            range,
+            new_text: label,
+        })),
+        ..Default::default()
+    })
+}
+
+/// The name of a field in the table enclosing `position`, other than the one
+/// on the cursor's line, that is already marked `(key)`.
+fn existing_key_field(
+    snapshot: &WorkspaceSnapshot,
+    path: &PathBuf,
+    position: Position,
+) -> Option<String> {
+    let table_symbol = snapshot.find_enclosing_table(path, position)?;
+    let start_line = table_symbol.info.location.range.start.line as usize;
+    let doc = snapshot.documents.get(path)?;
+    let text = table_text(&doc, start_line);
+    // Skip the lines before the cursor's line to find where it starts in `text`.
+    let cursor_line_start: usize = text
+        .split_inclusive('\n')
+        .take((position.line as usize).saturating_sub(start_line))
+        .map(str::len)
+        .sum();
+    let cursor_line_end = text[cursor_line_start..]
+        .find('\n')
+        .map_or(text.len(), |i| cursor_line_start + i);
+    KEY_FIELD_RE
+        .captures_iter(&text)
+        .find(|c| {
+            let m = c.get(0).map_or(0..0, |m| m.range());
+            m.end <= cursor_line_start || m.start >= cursor_line_end
+        })
+        .map(|c| c[1].to_string())
+}
+
+/// The code of the block that starts on `start_line`, with comments and strings removed.
+fn table_text(doc: &Rope, start_line: usize) -> String {
+    let start = doc.line_to_char(start_line.min(doc.len_lines()));
+    let code = strip_comments_and_strings(&doc.slice(start..).to_string());
+    let mut depth = 0;
+    for (i, c) in code.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return code[..=i].to_string();
+                }
+            }
+            _ => {}
+        }
+    }
+    code
+}
+
+/// Completions for the value of the attribute being written at the end of `trigger_text`,
+/// e.g. `force_align: $0`. Returns `None` if the cursor is not at a value with known options.
+fn attribute_value_completion(
+    trigger_text: &str,
+    position: Position,
+) -> Option<Vec<CompletionItem>> {
+    let current = trigger_text.rsplit(',').next().unwrap_or(trigger_text);
+    let (name, typed) = current.split_once(':')?;
+    let values: &[(&str, &str)] = match name.trim() {
+        "force_align" => &FORCE_ALIGN_VALUES,
+        "hash" => &HASH_VALUES,
+        "streaming" => &STREAMING_VALUES,
+        _ => return None,
+    };
+
+    let typed = typed.trim_start();
+    let range = Range::new(
+        Position::new(
+            position.line,
+            position.character.saturating_sub(utf16_len(typed)),
+        ),
+        position,
+    );
+    Some(
+        values
+            .iter()
+            .enumerate()
+            .filter(|(_, (value, _))| {
+                value
+                    .trim_start_matches('"')
+                    .starts_with(typed.trim_start_matches('"'))
+            })
+            .map(|(i, (value, detail))| CompletionItem {
+                label: (*value).to_string(),
+                kind: Some(CompletionItemKind::VALUE),
+                detail: Some((*detail).to_string()),
+                sort_text: Some(format!("{i:02}")),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    new_text: (*value).to_string(),
+                })),
+                ..Default::default()
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,187 @@
+use crate::analysis::WorkspaceSnapshot;
+use crate::symbol_table::{Symbol, SymbolKind};
+use crate::utils::utf16_len;
+use regex::Regex;
+use ropey::Rope;
+use std::sync::LazyLock;
+use tower_lsp_server::lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionResponse, CompletionTextEdit, Documentation,
+    MarkupContent, MarkupKind, Position, Range, TextEdit,
+};
+
+static DEFAULT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*\w+\s*:\s*([\w\.]+)\s*=\s*(\w*)$")
+        .expect("default value regex failed to compile")
+});
+
+/// Completions for the default value of a field, e.g. `enabled: bool = $0` or
+/// `level: Game.Difficulty = $0`.
+pub fn handle_default_value_completion(
+    snapshot: &WorkspaceSnapshot,
+    doc: &Rope,
+    line: &str,
+    position: Position,
+) -> Option<CompletionResponse> {
+    bool_default_completion(line, position)
+        .or_else(|| enum_default_completion(snapshot, doc, line, position))
+}
+
+/// The type as written and the partial value before the cursor.
+fn default_value_context(line: &str, position: Position) -> Option<(&str, &str)> {
+    let line_upto_cursor = line.get(..position.character as usize)?;
+    let captures = DEFAULT_RE.captures(line_upto_cursor)?;
+    Some((captures.get(1)?.as_str(), captures.get(2)?.as_str()))
+}
+
+fn typed_range(position: Position, typed: &str) -> Range {
+    Range::new(
+        Position::new(
+            position.line,
+            position.character.saturating_sub(utf16_len(typed)),
+        ),
+        position,
+    )
+}
+
+fn bool_default_completion(line: &str, position: Position) -> Option<CompletionResponse> {
+    let (type_name, typed) = default_value_context(line, position)?;
+    if type_name != "bool" {
+        return None;
+    }
+
+    let range = typed_range(position, typed);
+    let items = ["true", "false"]
+        .iter()
+        .enumerate()
+        .filter(|(_, value)| value.starts_with(typed))
+        .map(|(i, value)| CompletionItem {
+            label: (*value).to_string(),
+            kind: Some(CompletionItemKind::KEYWORD),
+            sort_text: Some(format!("{i:02}")),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: (*value).to_string(),
+            })),
+            ..Default::default()
+        })
+        .collect();
+    Some(CompletionResponse::Array(items))
+}
+
+/// The variants of the field's enum type, in declaration order. flatc only
+/// accepts the unqualified variant name, even when the type is qualified.
+fn enum_default_completion(
+    snapshot: &WorkspaceSnapshot,
+    doc: &Rope,
+    line: &str,
+    position: Position,
+) -> Option<CompletionResponse> {
+    let (type_name, typed) = default_value_context(line, position)?;
+    let namespace = namespace_before(doc, position.line);
+    let symbol = resolve_type(snapshot, type_name, &namespace)?;
+    let SymbolKind::Enum(e) = &symbol.kind else {
+        return None;
+    };
+
+    let range = typed_range(position, typed);
+    let items = e
+        .variants
+        .iter()
+        .enumerate()
+        .filter(|(_, variant)| variant.name.starts_with(typed))
+        .map(|(i, variant)| CompletionItem {
+            label: variant.name.clone(),
+            kind: Some(CompletionItemKind::ENUM_MEMBER),
+            detail: Some(format!(
+                "{}.{} = {}",
+                symbol.info.name, variant.name, variant.value
+            )),
+            documentation: variant.documentation.as_ref().map(|doc| {
+                Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: doc.clone(),
+                })
+            }),
+            sort_text: Some(format!("{i:04}")),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: variant.name.clone(),
+            })),
+            ..Default::default()
+        })
+        .collect();
+    Some(CompletionResponse::Array(items))
+}
+
+/// The namespace in effect on `line`: the last `namespace` statement above it.
+fn namespace_before(doc: &Rope, line: u32) -> Vec<String> {
+    doc.lines()
+        .take(line as usize)
+        .filter_map(|line| {
+            line.to_string()
+                .trim()
+                .strip_prefix("namespace")
+                .and_then(|ns| ns.strip_suffix(';'))
+                .map(|ns| {
+                    ns.split('.')
+                        .map(str::trim)
+                        .filter(|part| !part.is_empty())
+                        .map(ToString::to_string)
+                        .collect()
+                })
+        })
+        .last()
+        .unwrap_or_default()
+}
+
+/// Look up a type as written from inside `namespace`, the way flatc does: in
+/// the namespace itself first, then in each enclosing namespace.
+fn resolve_type<'a>(
+    snapshot: &'a WorkspaceSnapshot,
+    written: &str,
+    namespace: &[String],
+) -> Option<&'a Symbol> {
+    (0..=namespace.len()).rev().find_map(|depth| {
+        let qualified = namespace[..depth]
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(written))
+            .collect::<Vec<_>>()
+            .join(".");
+        snapshot.symbols.global.get(&qualified)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(line: &str) -> Option<Vec<String>> {
+        let position = Position::new(0, utf16_len(line));
+        bool_default_completion(line, position).map(|response| match response {
+            CompletionResponse::Array(items) => items.into_iter().map(|item| item.label).collect(),
+            CompletionResponse::List(list) => {
+                list.items.into_iter().map(|item| item.label).collect()
+            }
+        })
+    }
+
+    #[test]
+    fn test_bool_default_value_completion() {
+        assert_eq!(
+            labels("  enabled: bool = "),
+            Some(vec!["true".into(), "false".into()])
+        );
+        assert_eq!(labels("  enabled: bool=f"), Some(vec!["false".into()]));
+        assert_eq!(labels("  count: int = "), None);
+        assert_eq!(labels("  enabled: bool "), None);
+    }
+
+    #[test]
+    fn test_namespace_before() {
+        let doc = Rope::from_str("namespace A.B;\ntable T {}\nnamespace C;\ntable U {}\n");
+        assert_eq!(namespace_before(&doc, 0), Vec::<String>::new());
+        assert_eq!(namespace_before(&doc, 1), vec!["A", "B"]);
+        assert_eq!(namespace_before(&doc, 3), vec!["C"]);
+    }
+}
@@ -1,6 +1,13 @@
 use crate::utils::as_pos_idx;
-use crate::{analysis::WorkspaceSnapshot, handlers::completion::util::generate_include_text_edit};
+use crate::{
+    analysis::WorkspaceSnapshot,
+    handlers::completion::util::{
+        include_preview, namespace_commit_characters, symbol_completion_data,
+    },
+};
 use regex::Regex;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap};
 use std::iter::once;
 use std::path::PathBuf;
 use std::sync::LazyLock;
@@ -20,15 +27,12 @@ pub fn handle_field_type_completion(
     line: &str,
     position: Position,
 ) -> Option<CompletionResponse> {
-    let curr_char = line.chars().last();
-    let prev_char = line.chars().nth(line.chars().count().saturating_sub(2));
-    if curr_char == Some(' ') && prev_char != Some(':') {
-        return None;
-    }
     let (range, partial_text) = get_field_type_completion_context(line, position)?;
-    if partial_text.trim_start().contains(char::is_whitespace) {
-        // Cannot have spaces within a type.
-        return None;
+    if let Some(items) = partial_text
+        .strip_suffix('.')
+        .and_then(|prefix| namespace_member_completion(snapshot, path, prefix, position))
+    {
+        return Some(CompletionResponse::Array(items));
     }
     let captures = FIELD_RE.captures(line)?;
     let field_name = captures.get(1).map_or("", |m| m.as_str());
@@ -76,13 +80,11 @@ pub fn handle_field_type_completion(
                 base_name.clone()
             };
 
-            let (additional_text_edits, preview_text) =
-                generate_include_text_edit(snapshot, path, symbol);
+            let preview_text = include_preview(snapshot, path, symbol);
 
             items.push(CompletionItem {
                 label: base_name.clone(),
                 text_edit: Some(CompletionTextEdit::Edit(TextEdit { range, new_text })),
-                additional_text_edits,
                 filter_text: Some(qualified_name.clone()),
                 sort_text: Some(sort_text),
                 kind: Some(kind),
@@ -91,12 +93,7 @@ pub fn handle_field_type_completion(
                     detail: None, // for function signatures or type annotations, neither of which are relevant for us.
                     description: preview_text.or(symbol.info.namespace_str()), // for fully qualified name or file path.
                 }),
-                documentation: symbol.info.documentation.as_ref().map(|doc| {
-                    Documentation::MarkupContent(MarkupContent {
-                        kind: MarkupKind::Markdown,
-                        value: doc.clone(),
-                    })
-                }),
+                data: Some(symbol_completion_data(path, symbol)),
                 ..Default::default()
             });
         }
@@ -149,12 +146,125 @@ pub fn handle_field_type_completion(
                 sort_text: Some(sort_text),
                 kind: Some(CompletionItemKind::MODULE),
                 detail: Some("namespace".to_string()),
+                commit_characters: namespace_commit_characters(),
                 ..Default::default()
             });
         }
     }
 
-    Some(CompletionResponse::Array(items))
+    Some(CompletionResponse::Array(dedupe(items)))
+}
+
+/// Merge items that would insert the same text under the same label, such as
+/// a table `Foo` and a namespace `Foo`, keeping the most specific kind in the
+/// position of the first.
+fn dedupe(items: Vec<CompletionItem>) -> Vec<CompletionItem> {
+    let mut deduped: Vec<CompletionItem> = Vec::with_capacity(items.len());
+    let mut seen: HashMap<(String, String), usize> = HashMap::new();
+    for item in items {
+        let insert_text = match &item.text_edit {
+            Some(CompletionTextEdit::Edit(edit)) => edit.new_text.clone(),
+            _ => item.label.clone(),
+        };
+        match seen.entry((item.label.clone(), insert_text)) {
+            Entry::Occupied(entry) => {
+                let existing = &mut deduped[*entry.get()];
+                if kind_specificity(item.kind) > kind_specificity(existing.kind) {
+                    *existing = item;
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(deduped.len());
+                deduped.push(item);
+            }
+        }
+    }
+    deduped
+}
+
+/// Namespaces are the least specific since they only lead to other types, and
+/// builtins are less specific than the user's own types.
+fn kind_specificity(kind: Option<CompletionItemKind>) -> u8 {
+    match kind {
+        Some(CompletionItemKind::MODULE) => 0,
+        Some(CompletionItemKind::KEYWORD) => 1,
+        _ => 2,
+    }
+}
+
+/// Completions after a complete namespace and a trailing dot (`One.Two.`): the
+/// symbols declared directly in that namespace and the next segment of any deeper
+/// namespaces. Only the text after the dot is inserted.
+///
+/// `None` if `prefix` is not a namespace, so partial namespaces fall back to
+/// [`field_sort_text`] matching.
+fn namespace_member_completion(
+    snapshot: &WorkspaceSnapshot,
+    path: &PathBuf,
+    prefix: &str,
+    position: Position,
+) -> Option<Vec<CompletionItem>> {
+    let namespaces = snapshot.symbols.namespaces();
+    let nested_prefix = format!("{prefix}.");
+    if !namespaces
+        .iter()
+        .any(|ns| ns == prefix || ns.starts_with(&nested_prefix))
+    {
+        return None;
+    }
+
+    let range = Range {
+        start: position,
+        end: position,
+    };
+    let mut items = Vec::new();
+
+    for symbol in snapshot.symbols.global.values() {
+        let kind: CompletionItemKind = (&symbol.kind).into();
+        if kind == CompletionItemKind::FIELD || symbol.info.namespace.join(".") != prefix {
+            continue;
+        }
+        let name = &symbol.info.name;
+        items.push(CompletionItem {
+            label: name.clone(),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: name.clone(),
+            })),
+            sort_text: Some(format!("0_{name}")),
+            kind: Some(kind),
+            detail: Some(format!("{} in {prefix}", symbol.type_name())),
+            label_details: Some(CompletionItemLabelDetails {
+                detail: None,
+                description: include_preview(snapshot, path, symbol)
+                    .or(symbol.info.namespace_str()),
+            }),
+            data: Some(symbol_completion_data(path, symbol)),
+            ..Default::default()
+        });
+    }
+
+    let segments: BTreeSet<&str> = namespaces
+        .iter()
+        .filter_map(|ns| ns.strip_prefix(&nested_prefix))
+        .filter_map(|rest| rest.split('.').next())
+        .collect();
+    for segment in segments {
+        items.push(CompletionItem {
+            label: segment.to_string(),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: segment.to_string(),
+            })),
+            sort_text: Some(format!("1_{segment}")),
+            kind: Some(CompletionItemKind::MODULE),
+            detail: Some(format!("namespace {nested_prefix}{segment}")),
+            commit_characters: namespace_commit_characters(),
+            ..Default::default()
+        });
+    }
+
+    Some(items)
 }
 
 /// Determines if a symbol is a relevant completion and calculates its sort order.
@@ -231,10 +341,14 @@ fn field_sort_text(
     (is_match, sort_text)
 }
 
+/// The range and text of the partial type before the cursor. `None` unless the cursor
+/// is within the type itself, so anything after a complete type (a default value or
+/// attributes) is left to the other completions.
 fn get_field_type_completion_context(line: &str, position: Position) -> Option<(Range, String)> {
     let line_upto_cursor = &line[..position.character as usize];
     FIELD_RE.captures(line_upto_cursor).and_then(|captures| {
-        captures.get(2).map(|partial_match| {
+        let partial_match = captures.get(2)?;
+        (partial_match.end() == line_upto_cursor.len()).then(|| {
             let start_char = as_pos_idx(line_upto_cursor[..partial_match.start()].chars().count());
             let range = Range {
                 start: Position {
@@ -290,11 +404,42 @@ mod tests {
         }
 
         {
-            let line = "  field: int i";
-            let (range, partial) = get_field_type_completion_context(line, pos(14)).unwrap();
-            assert_eq!(partial, "int i");
+            let line = "  field: [";
+            let (range, partial) = get_field_type_completion_context(line, pos(10)).unwrap();
+            assert_eq!(partial, "");
+            assert_eq!(range.start.character, 10);
+            assert_eq!(range.end.character, 10);
+        }
+
+        {
+            let line = "  field: [Ns.";
+            let (range, partial) = get_field_type_completion_context(line, pos(13)).unwrap();
+            assert_eq!(partial, "Ns.");
+            assert_eq!(range.start.character, 10);
+            assert_eq!(range.end.character, 13);
+        }
+
+        {
+            let line = "  field: ";
+            let (range, partial) = get_field_type_completion_context(line, pos(9)).unwrap();
+            assert_eq!(partial, "");
             assert_eq!(range.start.character, 9);
-            assert_eq!(range.end.character, 14);
+            assert_eq!(range.end.character, 9);
+        }
+
+        // The cursor is past the end of a complete type.
+        for (line, cursor) in [
+            ("  field: int i", 14),
+            ("  field: int ", 13),
+            ("  field: int (", 14),
+            ("  field: int = ", 15),
+            ("  field: [int] ", 15),
+        ] {
+            assert_eq!(
+                get_field_type_completion_context(line, pos(cursor)),
+                None,
+                "{line}"
+            );
         }
     }
 
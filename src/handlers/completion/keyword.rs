@@ -1,15 +1,28 @@
 use crate::analysis::WorkspaceSnapshot;
+use crate::utils::as_pos_idx;
 use tower_lsp_server::lsp_types::{
-    CompletionItem, CompletionItemKind, CompletionResponse, Documentation, MarkupContent,
-    MarkupKind,
+    CompletionItem, CompletionItemKind, CompletionItemLabelDetails, CompletionResponse,
+    CompletionTextEdit, Documentation, InsertTextFormat, MarkupContent, MarkupKind, Position,
+    Range, TextEdit,
 };
 
+/// Skeletons for new definitions, offered at statement position.
+const DEFINITION_SNIPPETS: [(&str, &str); 5] = [
+    ("table", "table ${1:Name} {\n\t$0\n}"),
+    ("struct", "struct ${1:Name} {\n\t$0\n}"),
+    ("enum", "enum ${1:Name} : ${2:byte} {\n\t$0\n}"),
+    ("union", "union ${1:Name} {\n\t$0\n}"),
+    ("rpc_service", "rpc_service ${1:Name} {\n\t$0\n}"),
+];
+
 pub fn handle_keyword_completion(
     snapshot: &WorkspaceSnapshot,
     line: &str,
+    position: Position,
+    top_level: bool,
 ) -> Option<CompletionResponse> {
     let partial_keyword = line.trim();
-    let items: Vec<CompletionItem> = snapshot
+    let mut items: Vec<CompletionItem> = snapshot
         .symbols
         .keywords
         .iter()
@@ -25,9 +38,61 @@ pub fn handle_keyword_completion(
         })
         .collect();
 
+    if top_level {
+        // A snippet takes the place of the keyword it expands, keeping its documentation.
+        for mut snippet in definition_snippets(line, position) {
+            if let Some(idx) = items.iter().position(|item| item.label == snippet.label) {
+                snippet.documentation = items.swap_remove(idx).documentation;
+            }
+            items.push(snippet);
+        }
+    }
+
     if items.is_empty() {
         None
     } else {
         Some(CompletionResponse::Array(items))
     }
 }
+
+fn definition_snippets(line: &str, position: Position) -> Vec<CompletionItem> {
+    let before_cursor: String = line.chars().take(position.character as usize).collect();
+    let typed = before_cursor.trim_start();
+    let word = typed.trim_end();
+    if word.contains(char::is_whitespace) {
+        return vec![];
+    }
+    // Once a space follows the keyword, only that exact keyword can expand.
+    let exact = typed.len() != word.len();
+
+    let start = before_cursor.chars().count() - typed.chars().count();
+    let range = Range::new(Position::new(position.line, as_pos_idx(start)), position);
+
+    DEFINITION_SNIPPETS
+        .iter()
+        .filter(|(keyword, _)| {
+            if exact {
+                *keyword == word
+            } else {
+                keyword.starts_with(word)
+            }
+        })
+        .map(|(keyword, snippet)| CompletionItem {
+            label: (*keyword).to_string(),
+            kind: Some(CompletionItemKind::SNIPPET),
+            label_details: Some(CompletionItemLabelDetails {
+                detail: None,
+                description: Some(format!("new {keyword}")),
+            }),
+            filter_text: Some((*keyword).to_string()),
+            // A snippet expands to a whole definition, so it is only accepted explicitly.
+            commit_characters: Some(vec![]),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: (*snippet).to_string(),
+            })),
+            ..Default::default()
+        })
+        .collect()
+}
@@ -1,16 +1,23 @@
 mod attributes;
+mod default_value;
 mod field_type;
 mod keyword;
+mod resolve;
 mod root_type;
 mod rpc_method;
 mod util;
 
+pub use resolve::{completion_item_path, handle_completion_resolve};
+pub(crate) use util::generate_include_text_edit;
+
 use crate::ext::duration::DurationFormat;
+use crate::handlers::completion::default_value::handle_default_value_completion;
 use crate::handlers::completion::field_type::handle_field_type_completion;
 use crate::handlers::completion::keyword::handle_keyword_completion;
 use crate::handlers::completion::root_type::handle_root_type_completion;
 use crate::handlers::completion::rpc_method::handle_rpc_method_completion;
 use crate::utils::paths::uri_to_path_buf;
+use crate::utils::scanner::{brace_depth, code_before};
 use crate::{
     analysis::WorkspaceSnapshot, handlers::completion::attributes::handle_attribute_completion,
 };
@@ -19,6 +26,11 @@ use ropey::Rope;
 use std::time::Instant;
 use tower_lsp_server::lsp_types::{CompletionParams, CompletionResponse, Position};
 
+/// Characters that accept the selected completion before being typed, e.g. `;` after a
+/// field type. `.` is left out so that typing a qualified name keeps filtering; namespace
+/// completions accept it on their own.
+pub const COMMIT_CHARACTERS: [&str; 3] = [";", "(", " "];
+
 pub fn handle_completion(
     snapshot: &WorkspaceSnapshot<'_>,
     params: &CompletionParams,
@@ -45,16 +57,23 @@ pub fn handle_completion(
             .take_if(|_| last_keyword.as_deref() == Some("rpc_service"))
     {
         Some(response)
-    } else if let Some(response) = handle_attribute_completion(snapshot, &path, position, &line)
-        .take_if(|_| last_keyword.as_deref() != Some("rpc_service"))
-    {
+    } else if let Some(response) = handle_attribute_completion(
+        snapshot,
+        &path,
+        position,
+        &line,
+        last_keyword.as_deref() == Some("rpc_service"),
+    ) {
         Some(response)
     } else if let Some(response) = handle_root_type_completion(snapshot, &path, &line, position) {
         Some(response)
+    } else if let Some(response) = handle_default_value_completion(snapshot, &doc, &line, position)
+    {
+        Some(response)
     } else if let Some(response) = handle_field_type_completion(snapshot, &path, &line, position) {
         Some(response)
     } else {
-        handle_keyword_completion(snapshot, &line)
+        handle_keyword_completion(snapshot, &line, position, is_top_level(&doc, position))
     };
 
     let elapsed = start.elapsed();
@@ -76,32 +95,13 @@ pub fn handle_completion(
 // Returns the symbol kind of the first keyword (table, enum, rpc_service) that
 // that appears before this position (either on the same line or a prior line).
 fn preceding_symbol_kind(doc: &Rope, position: Position) -> Option<String> {
+    let code = code_before(doc, position);
     let mut balance = 0;
 
-    // Iterate backwards from the current line
-    for i in (0..=position.line as usize).rev() {
-        let line_chunk = doc.line(i);
-        let line_str = line_chunk.to_string();
-
-        // Determine the text segment to analyze:
-        // - If on the current line, stop at the cursor character.
-        // - If on a previous line, analyze the whole line.
-        let text_segment = if i == position.line as usize {
-            let char_idx = position.character as usize;
-            if char_idx < line_str.len() {
-                &line_str[..char_idx]
-            } else {
-                &line_str[..]
-            }
-        } else {
-            &line_str[..]
-        };
-
-        // Strip comments (simple check for "//")
-        let clean_text = text_segment.split("//").next().unwrap_or("");
-
+    // Iterate backwards from the cursor, one line at a time.
+    for line in code.lines().rev() {
         // Scan characters in reverse to check brace balance
-        for c in clean_text.chars().rev() {
+        for c in line.chars().rev() {
             match c {
                 '}' => balance += 1,
                 '{' => balance -= 1,
@@ -112,7 +112,7 @@ fn preceding_symbol_kind(doc: &Rope, position: Position) -> Option<String> {
         // If balance drops below zero, we found the opening brace for the current context.
         // Check this line for the defining keyword.
         if balance < 0 {
-            let trimmed = clean_text.trim();
+            let trimmed = line.trim();
             if trimmed.contains("rpc_service") {
                 return Some("rpc_service".to_string());
             } else if trimmed.contains("table") {
@@ -132,6 +132,11 @@ fn preceding_symbol_kind(doc: &Rope, position: Position) -> Option<String> {
     None
 }
 
+/// Whether `position` is outside of every `{ ... }` block.
+fn is_top_level(doc: &Rope, position: Position) -> bool {
+    brace_depth(&code_before(doc, position)) <= 0
+}
+
 fn should_suppress_completion(doc: &Rope, position: Position) -> bool {
     if (position.line as usize) >= doc.len_lines() {
         return false;
@@ -148,20 +153,6 @@ fn should_suppress_completion(doc: &Rope, position: Position) -> bool {
         return false;
     }
 
-    let mut open_braces = 0;
-    let mut close_braces = 0;
-
-    // Count braces on previous lines
-    for i in 0..position.line {
-        // This is safe because we checked position.line < doc.len_lines()
-        let prev_line = doc.line(i as usize);
-        let line_str = prev_line.to_string();
-        // A bit naive, doesn't account for braces in comments or strings.
-        // But probably good enough for now.
-        open_braces += line_str.matches('{').count();
-        close_braces += line_str.matches('}').count();
-    }
-
-    // If we have more open than close braces, we are inside a block.
-    open_braces > close_braces
+    // If previous lines have more open than close braces, we are inside a block.
+    brace_depth(&code_before(doc, Position::new(position.line, 0))) > 0
 }
@@ -0,0 +1,48 @@
+use crate::analysis::WorkspaceSnapshot;
+use crate::handlers::completion::util::generate_include_text_edit;
+use log::debug;
+use serde_json::Value;
+use std::path::PathBuf;
+use tower_lsp_server::lsp_types::{CompletionItem, Documentation, MarkupContent, MarkupKind};
+
+/// Attach documentation and include edits to a symbol completion the client selected.
+pub fn handle_completion_resolve(
+    snapshot: &WorkspaceSnapshot<'_>,
+    mut item: CompletionItem,
+) -> CompletionItem {
+    let Some(data) = &item.data else {
+        return item;
+    };
+    let (Some(path), Some(qualified_name)) = (
+        completion_item_path(&item),
+        data.get("qualified_name").and_then(Value::as_str),
+    ) else {
+        return item;
+    };
+
+    let Some(symbol) = snapshot.symbols.global.get(qualified_name) else {
+        debug!("completion resolve: {qualified_name} no longer exists");
+        return item;
+    };
+
+    let (additional_text_edits, _) = generate_include_text_edit(snapshot, &path, symbol);
+    item.additional_text_edits = additional_text_edits;
+    item.documentation = symbol.info.documentation.as_ref().map(|doc| {
+        Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: doc.clone(),
+        })
+    });
+
+    item
+}
+
+/// The document a symbol completion was made in, from its `data`.
+#[must_use]
+pub fn completion_item_path(item: &CompletionItem) -> Option<PathBuf> {
+    item.data
+        .as_ref()?
+        .get("path")
+        .and_then(Value::as_str)
+        .map(PathBuf::from)
+}
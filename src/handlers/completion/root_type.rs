@@ -1,12 +1,17 @@
 use crate::symbol_table::SymbolKind;
 use crate::utils::as_pos_idx;
-use crate::{analysis::WorkspaceSnapshot, handlers::completion::util::generate_include_text_edit};
+use crate::{
+    analysis::WorkspaceSnapshot,
+    handlers::completion::util::{
+        include_preview, namespace_commit_characters, symbol_completion_data,
+    },
+};
 use regex::Regex;
 use std::path::PathBuf;
 use std::sync::LazyLock;
 use tower_lsp_server::lsp_types::{
     CompletionItem, CompletionItemKind, CompletionItemLabelDetails, CompletionResponse,
-    CompletionTextEdit, Documentation, MarkupContent, MarkupKind, Position, Range, TextEdit,
+    CompletionTextEdit, Position, Range, TextEdit,
 };
 
 static ROOT_TYPE_RE: LazyLock<Regex> = LazyLock::new(|| {
@@ -41,25 +46,18 @@ pub fn handle_root_type_completion(
                     base_name.clone()
                 };
 
-                let (additional_text_edits, preview_text) =
-                    generate_include_text_edit(snapshot, path, symbol);
+                let preview_text = include_preview(snapshot, path, symbol);
 
                 items.push(CompletionItem {
                     label: base_name.clone(),
                     text_edit: Some(CompletionTextEdit::Edit(TextEdit { range, new_text })),
-                    additional_text_edits,
                     kind: Some((&symbol.kind).into()),
                     detail: Some(symbol.type_name().to_string()),
                     label_details: Some(CompletionItemLabelDetails {
                         detail: None,
                         description: preview_text.or(symbol.info.namespace_str()), // for fully qualified name or file path.
                     }),
-                    documentation: symbol.info.documentation.as_ref().map(|doc| {
-                        Documentation::MarkupContent(MarkupContent {
-                            kind: MarkupKind::Markdown,
-                            value: doc.clone(),
-                        })
-                    }),
+                    data: Some(symbol_completion_data(path, symbol)),
                     ..Default::default()
                 });
             }
@@ -79,6 +77,7 @@ pub fn handle_root_type_completion(
             })),
             kind: Some(CompletionItemKind::MODULE),
             detail: Some("namespace".to_string()),
+            commit_characters: namespace_commit_characters(),
             ..Default::default()
         });
     }
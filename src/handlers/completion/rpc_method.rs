@@ -2,14 +2,14 @@ use std::{path::PathBuf, sync::LazyLock};
 
 use crate::{
     analysis::WorkspaceSnapshot,
-    handlers::completion::util::generate_include_text_edit,
+    handlers::completion::util::{include_preview, symbol_completion_data},
     symbol_table::{Symbol, SymbolKind},
     utils::as_pos_idx,
 };
 use regex::Regex;
 use tower_lsp_server::lsp_types::{
-    CompletionItem, CompletionItemLabelDetails, CompletionResponse, CompletionTextEdit,
-    Documentation, MarkupContent, MarkupKind, Position, Range, TextEdit,
+    CompletionItem, CompletionItemLabelDetails, CompletionResponse, CompletionTextEdit, Position,
+    Range, TextEdit,
 };
 
 static REQ_RE: LazyLock<Regex> = LazyLock::new(|| {
@@ -56,8 +56,7 @@ pub fn handle_rpc_method_completion(
                 base_name.clone()
             };
 
-            let (additional_text_edits, preview_text) =
-                generate_include_text_edit(snapshot, path, &symbol);
+            let preview_text = include_preview(snapshot, path, &symbol);
 
             let sort_priority = i32::from(
                 !base_name
@@ -72,7 +71,6 @@ pub fn handle_rpc_method_completion(
                     range: captures.completion_range(position),
                     new_text,
                 })),
-                additional_text_edits,
                 filter_text: Some(qualified_name.clone()),
                 sort_text: Some(sort_text),
                 kind: Some((&symbol.kind).into()),
@@ -81,12 +79,7 @@ pub fn handle_rpc_method_completion(
                     detail: None, // for function signatures or type annotations, neither of which are relevant for us.
                     description: preview_text.or(symbol.info.namespace_str()), // for fully qualified name or file path.
                 }),
-                documentation: symbol.info.documentation.as_ref().map(|doc| {
-                    Documentation::MarkupContent(MarkupContent {
-                        kind: MarkupKind::Markdown,
-                        value: doc.clone(),
-                    })
-                }),
+                data: Some(symbol_completion_data(path, &symbol)),
                 ..Default::default()
             }
         })
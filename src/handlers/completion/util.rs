@@ -2,37 +2,71 @@ use crate::analysis::WorkspaceSnapshot;
 use crate::symbol_table::Symbol;
 use crate::utils::as_pos_idx;
 use ropey::Rope;
-use std::path::PathBuf;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
 use tower_lsp_server::lsp_types::{Position, Range, TextEdit};
 
+/// Key stored in `CompletionItem.data` for symbol completions. Documentation and
+/// include edits are looked up from it on `completionItem/resolve`.
+pub fn symbol_completion_data(path: &Path, symbol: &Symbol) -> Value {
+    json!({
+        "path": path.to_string_lossy(),
+        "qualified_name": symbol.info.qualified_name(),
+    })
+}
+
+/// Namespaces are accepted by `.`, which then triggers completion of their members,
+/// rather than by the server-wide commit characters.
+pub fn namespace_commit_characters() -> Option<Vec<String>> {
+    Some(vec![".".to_string()])
+}
+
+/// A short preview of the include statement that accepting `symbol` would add, if any.
+pub fn include_preview(
+    snapshot: &WorkspaceSnapshot,
+    path: &PathBuf,
+    symbol: &Symbol,
+) -> Option<String> {
+    missing_include_path(snapshot, path, symbol)
+        .map(|relative_path| format!("include \"{relative_path}\""))
+}
+
 pub fn generate_include_text_edit(
     snapshot: &WorkspaceSnapshot,
     path: &PathBuf,
     symbol: &Symbol,
 ) -> (Option<Vec<TextEdit>>, Option<String>) {
-    if symbol.info.location.path != *path {
-        let is_already_included = snapshot
-            .dependencies
-            .includes
-            .get(path)
-            .is_some_and(|includes| includes.iter().any(|p| p == &symbol.info.location.path));
-
-        if !is_already_included {
-            if let Some(relative_path) = path
-                .parent()
-                .and_then(|parent| pathdiff::diff_paths(&symbol.info.location.path, parent))
-            {
-                if let Some(doc) = snapshot.documents.get(path) {
-                    let edit = generate_include_edit(&doc, &relative_path.to_string_lossy());
-                    let preview = edit.new_text.trim().strip_suffix(";").map(String::from);
-                    return (Some(vec![edit]), preview);
-                }
-            }
+    if let Some(relative_path) = missing_include_path(snapshot, path, symbol) {
+        if let Some(doc) = snapshot.documents.get(path) {
+            let edit = generate_include_edit(&doc, &relative_path);
+            let preview = edit.new_text.trim().strip_suffix(";").map(String::from);
+            return (Some(vec![edit]), preview);
         }
     }
     (None, None)
 }
 
+/// The path to `symbol`'s file relative to `path`, if `path` does not already include it.
+fn missing_include_path(
+    snapshot: &WorkspaceSnapshot,
+    path: &PathBuf,
+    symbol: &Symbol,
+) -> Option<String> {
+    if symbol.info.location.path == *path {
+        return None;
+    }
+    // A type that is available through another include does not need a direct one.
+    if snapshot
+        .dependencies
+        .is_reachable(path, &symbol.info.location.path)
+    {
+        return None;
+    }
+    path.parent()
+        .and_then(|parent| pathdiff::diff_paths(&symbol.info.location.path, parent))
+        .map(|relative_path| relative_path.to_string_lossy().into_owned())
+}
+
 fn generate_include_edit(doc: &Rope, relative_path: &str) -> TextEdit {
     let last_include_line = doc
         .lines()
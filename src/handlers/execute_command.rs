@@ -0,0 +1,429 @@
+//! Custom commands run through `workspace/executeCommand`.
+
+use crate::analysis::WorkspaceSnapshot;
+use crate::ext::duration::DurationFormat;
+use crate::handlers::includes::{include_statements, resolve_include};
+use crate::lsp_logger;
+use crate::parser::FlatcFFIParser;
+use crate::server::Backend;
+use crate::utils::paths::{path_buf_to_uri, uri_to_path_buf};
+use crate::utils::position_encoding::Direction;
+use log::info;
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::process::Command;
+use tokio::time::Instant;
+use tower_lsp_server::jsonrpc::{Error, Result};
+use tower_lsp_server::lsp_types::{
+    ExecuteCommandParams, Location, MessageType, NumberOrString, Position, Uri,
+};
+
+/// Returns the workspace include graph as `{ nodes, edges }`.
+pub const SHOW_INCLUDE_GRAPH: &str = "flatbuffers.showIncludeGraph";
+
+/// Writes the binary schema (`.bfbs`) for the file URI in the first argument
+/// next to it and returns the output path.
+pub const GENERATE_BINARY_SCHEMA: &str = "flatbuffers.generateBinarySchema";
+
+/// Runs `flatc` code generation for the file URI in the first argument and the
+/// language in the second, returning the generated files as `[{ name, text }]`.
+/// Requires the `flatcPath` setting. flatc reads the file from disk, so
+/// unsaved changes are not included.
+pub const PREVIEW_GENERATED_CODE: &str = "flatbuffers.previewGeneratedCode";
+
+/// Discards the workspace index and every document not open in the client,
+/// then rescans the workspace from disk and republishes all diagnostics.
+pub const REINDEX_WORKSPACE: &str = "flatbuffers.reindexWorkspace";
+
+/// Returns the `Location` of the table named by the `root_type` of the file URI
+/// in the first argument. When the file declares no `root_type`, the first one
+/// declared by its includes is used instead.
+pub const GOTO_ROOT_TYPE: &str = "flatbuffers.gotoRootType";
+
+/// Returns the fully-qualified name of the symbol at the file URI and
+/// `Position` in the first two arguments, for the client to copy.
+pub const COPY_QUALIFIED_NAME: &str = "flatbuffers.copyQualifiedName";
+
+/// Sets the most verbose log level sent to the client from the name in the
+/// first argument (`off`, `error`, `warn`, `info`, `debug` or `trace`) and
+/// returns the applied level.
+pub const SET_LOG_LEVEL: &str = "flatbuffers.setLogLevel";
+
+/// Every command advertised by the server.
+pub const COMMANDS: &[&str] = &[
+    SHOW_INCLUDE_GRAPH,
+    GENERATE_BINARY_SCHEMA,
+    PREVIEW_GENERATED_CODE,
+    REINDEX_WORKSPACE,
+    GOTO_ROOT_TYPE,
+    COPY_QUALIFIED_NAME,
+    SET_LOG_LEVEL,
+];
+
+/// Languages accepted by [`PREVIEW_GENERATED_CODE`]. Each is passed to `flatc` as `--<language>`.
+const CODEGEN_LANGUAGES: &[&str] = &[
+    "cpp",
+    "csharp",
+    "dart",
+    "go",
+    "java",
+    "jsonschema",
+    "kotlin",
+    "lobster",
+    "lua",
+    "nim",
+    "php",
+    "python",
+    "rust",
+    "swift",
+    "ts",
+];
+
+/// # Errors
+///
+/// Will return `Err` if the command is not one of [`COMMANDS`] or its arguments are invalid.
+pub async fn handle_execute_command(
+    backend: &Backend,
+    params: ExecuteCommandParams,
+) -> Result<Option<Value>> {
+    match params.command.as_str() {
+        SHOW_INCLUDE_GRAPH => {
+            let snapshot = backend.analyzer.snapshot().await;
+            Ok(Some(include_graph(&snapshot)))
+        }
+        GENERATE_BINARY_SCHEMA => {
+            let path = file_argument(&params)?;
+            match generate_binary_schema(backend, &path).await {
+                Ok(output) => Ok(Some(json!(output.display().to_string()))),
+                Err(message) => {
+                    backend
+                        .client
+                        .show_message(MessageType::ERROR, &message)
+                        .await;
+                    Ok(None)
+                }
+            }
+        }
+        PREVIEW_GENERATED_CODE => {
+            let path = file_argument(&params)?;
+            let language = params
+                .arguments
+                .get(1)
+                .and_then(Value::as_str)
+                .filter(|language| CODEGEN_LANGUAGES.contains(language))
+                .ok_or_else(|| {
+                    Error::invalid_params(format!(
+                        "{PREVIEW_GENERATED_CODE} expects a language argument, one of: {}.",
+                        CODEGEN_LANGUAGES.join(", ")
+                    ))
+                })?;
+            match preview_generated_code(backend, &path, language).await {
+                Ok(files) => Ok(Some(files)),
+                Err(message) => {
+                    backend
+                        .client
+                        .show_message(MessageType::ERROR, &message)
+                        .await;
+                    Ok(None)
+                }
+            }
+        }
+        REINDEX_WORKSPACE => {
+            reindex_workspace(backend).await;
+            Ok(None)
+        }
+        GOTO_ROOT_TYPE => {
+            let path = file_argument(&params)?;
+            let (mut location, declared_in) = {
+                let snapshot = backend.analyzer.snapshot().await;
+                match root_type_location(&snapshot, &path) {
+                    Some(found) => found,
+                    None => return Ok(None),
+                }
+            };
+            if declared_in != path {
+                let message = format!(
+                    "{} declares no root_type; using the root_type from {}.",
+                    path.display(),
+                    declared_in.display()
+                );
+                backend
+                    .client
+                    .show_message(MessageType::INFO, message)
+                    .await;
+            }
+            backend
+                .positions(Direction::ToClient)
+                .location(&mut location);
+            Ok(Some(json!(location)))
+        }
+        COPY_QUALIFIED_NAME => {
+            let path = file_argument(&params)?;
+            let mut position = position_argument(&params)?;
+            let Ok(uri) = path_buf_to_uri(&path) else {
+                return Ok(None);
+            };
+            backend
+                .positions(Direction::ToServer)
+                .position(&uri, &mut position);
+            let snapshot = backend.analyzer.snapshot().await;
+            Ok(snapshot
+                .resolve_symbol_at(&uri, position)
+                .map(|resolved| json!(resolved.target.info.qualified_name())))
+        }
+        SET_LOG_LEVEL => {
+            let level = params
+                .arguments
+                .first()
+                .and_then(Value::as_str)
+                .and_then(lsp_logger::set_level)
+                .ok_or_else(|| {
+                    Error::invalid_params(format!(
+                        "{SET_LOG_LEVEL} expects a log level argument, one of: off, error, warn, info, debug, trace."
+                    ))
+                })?;
+            info!("log level set to {level}");
+            Ok(Some(json!(level.as_str().to_ascii_lowercase())))
+        }
+        command => Err(Error::invalid_params(format!("Unknown command: {command}"))),
+    }
+}
+
+async fn reindex_workspace(backend: &Backend) {
+    let start = Instant::now();
+    let token = NumberOrString::String("reindex-workspace".to_string());
+    backend.begin_scan_progress(token.clone()).await;
+
+    let report = |percentage| -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(backend.report_scan_progress(token.clone(), percentage))
+    };
+    let diagnostics = backend.analyzer.reindex(Some(&report)).await;
+    info!(
+        "reindexed workspace in {}: {} files",
+        start.elapsed().log_str(),
+        diagnostics.len()
+    );
+    backend.publish_diagnostics(diagnostics).await;
+
+    backend.end_scan_progress(token).await;
+}
+
+/// The file path given as a URI in the first argument.
+fn file_argument(params: &ExecuteCommandParams) -> Result<PathBuf> {
+    params
+        .arguments
+        .first()
+        .and_then(|arg| serde_json::from_value::<Uri>(arg.clone()).ok())
+        .and_then(|uri| uri_to_path_buf(&uri).ok())
+        .ok_or_else(|| {
+            Error::invalid_params(format!("{} expects a file URI argument.", params.command))
+        })
+}
+
+/// The `Position` in the second argument.
+fn position_argument(params: &ExecuteCommandParams) -> Result<Position> {
+    params
+        .arguments
+        .get(1)
+        .and_then(|arg| serde_json::from_value::<Position>(arg.clone()).ok())
+        .ok_or_else(|| {
+            Error::invalid_params(format!("{} expects a position argument.", params.command))
+        })
+}
+
+async fn generate_binary_schema(
+    backend: &Backend,
+    path: &Path,
+) -> std::result::Result<PathBuf, String> {
+    let content = backend
+        .documents
+        .document_map
+        .get(path)
+        .map(|doc| doc.to_string());
+    let search_paths = search_paths(backend, path).await;
+    let path = path.to_path_buf();
+
+    tokio::task::spawn_blocking(move || write_binary_schema(&path, content, &search_paths))
+        .await
+        .map_err(|err| format!("Binary schema generation did not complete: {err}"))?
+}
+
+/// Parse the schema at `path` and write its binary schema next to it. `content` is
+/// the text of the open document, if any, which is used instead of the file on disk.
+fn write_binary_schema(
+    path: &Path,
+    content: Option<String>,
+    search_paths: &[PathBuf],
+) -> std::result::Result<PathBuf, String> {
+    let content = match content {
+        Some(content) => content,
+        None => fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read {}: {err}", path.display()))?,
+    };
+
+    let bytes = FlatcFFIParser
+        .binary_schema(path, &content, search_paths)
+        .map_err(|err| format!("Failed to generate binary schema: {err}"))?;
+
+    let output = path.with_extension("bfbs");
+    fs::write(&output, bytes)
+        .map_err(|err| format!("Failed to write {}: {err}", output.display()))?;
+    info!("wrote binary schema: {}", output.display());
+    Ok(output)
+}
+
+async fn preview_generated_code(
+    backend: &Backend,
+    path: &Path,
+    language: &str,
+) -> std::result::Result<Value, String> {
+    let Some(flatc) = backend.config.read().await.flatc_path.clone() else {
+        return Err(format!(
+            "Set `flatcPath` in the `flatbuffers` settings to use {PREVIEW_GENERATED_CODE}."
+        ));
+    };
+    let search_paths = search_paths(backend, path).await;
+    let path = path.to_path_buf();
+    let language = language.to_string();
+
+    tokio::task::spawn_blocking(move || run_flatc(&flatc, &path, &language, &search_paths))
+        .await
+        .map_err(|err| format!("flatc did not complete: {err}"))?
+}
+
+/// Run `flatc` code generation into a temporary directory and collect the output.
+fn run_flatc(
+    flatc: &Path,
+    path: &Path,
+    language: &str,
+    search_paths: &[PathBuf],
+) -> std::result::Result<Value, String> {
+    let out_dir =
+        tempfile::tempdir().map_err(|err| format!("Failed to create output directory: {err}"))?;
+
+    let mut command = Command::new(flatc);
+    command.arg(format!("--{language}"));
+    command.arg("-o").arg(out_dir.path());
+    for search_path in search_paths {
+        command.arg("-I").arg(search_path);
+    }
+    command.arg(path);
+
+    let output = command
+        .output()
+        .map_err(|err| format!("Failed to run {}: {err}", flatc.display()))?;
+    if !output.status.success() {
+        return Err(format!(
+            "flatc failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let mut files = Vec::new();
+    let mut dirs = vec![out_dir.path().to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries =
+            fs::read_dir(&dir).map_err(|err| format!("Failed to read {}: {err}", dir.display()))?;
+        for entry in entries.flatten() {
+            let entry_path = entry.path();
+            if entry_path.is_dir() {
+                dirs.push(entry_path);
+                continue;
+            }
+            let Ok(text) = fs::read_to_string(&entry_path) else {
+                continue;
+            };
+            let name = entry_path
+                .strip_prefix(out_dir.path())
+                .unwrap_or(&entry_path)
+                .to_string_lossy()
+                .into_owned();
+            files.push((name, text));
+        }
+    }
+    files.sort();
+
+    Ok(Value::Array(
+        files
+            .into_iter()
+            .map(|(name, text)| json!({ "name": name, "text": text }))
+            .collect(),
+    ))
+}
+
+async fn search_paths(backend: &Backend, path: &Path) -> Vec<PathBuf> {
+    backend.analyzer.layout.read().await.search_paths_for(path)
+}
+
+/// The definition of the root type for `path` and the file whose `root_type`
+/// declared it, falling back to the file's includes.
+fn root_type_location(
+    snapshot: &WorkspaceSnapshot<'_>,
+    path: &Path,
+) -> Option<(Location, PathBuf)> {
+    let root_types = &snapshot.root_types.root_types;
+    let declared_in = if root_types.contains_key(path) {
+        path.to_path_buf()
+    } else {
+        snapshot
+            .dependencies
+            .includes
+            .get(path)?
+            .iter()
+            .find(|included| root_types.contains_key(*included))?
+            .clone()
+    };
+    let info = root_types.get(&declared_in)?;
+    let symbol = snapshot.symbols.global.get(&info.type_name)?;
+    Some((symbol.info.location.clone().into(), declared_in))
+}
+
+/// Serialize the include graph with nodes and edges keyed by URI.
+///
+/// The dependency graph also tracks transitive includes, so each edge records
+/// whether the including file has an include statement for the target.
+fn include_graph(snapshot: &WorkspaceSnapshot<'_>) -> Value {
+    let includes = &snapshot.dependencies.includes;
+    let nodes: BTreeSet<&PathBuf> = includes
+        .iter()
+        .flat_map(|(path, included)| std::iter::once(path).chain(included))
+        .collect();
+
+    let mut edges = Vec::new();
+    for (path, included) in includes {
+        let direct = direct_includes(snapshot, path);
+        for target in included {
+            let (Ok(from), Ok(to)) = (path_buf_to_uri(path), path_buf_to_uri(target)) else {
+                continue;
+            };
+            edges.push(json!({
+                "from": from,
+                "to": to,
+                "direct": direct.contains(target),
+            }));
+        }
+    }
+    edges.sort_by_key(|edge| (edge["from"].to_string(), edge["to"].to_string()));
+
+    let nodes: Vec<Value> = nodes
+        .into_iter()
+        .filter_map(|path| path_buf_to_uri(path).ok())
+        .map(|uri| json!({ "uri": uri }))
+        .collect();
+
+    json!({ "nodes": nodes, "edges": edges })
+}
+
+fn direct_includes(snapshot: &WorkspaceSnapshot<'_>, path: &Path) -> Vec<PathBuf> {
+    let Some(doc) = snapshot.documents.get(path) else {
+        return vec![];
+    };
+    include_statements(&doc)
+        .into_iter()
+        .filter_map(|include| resolve_include(snapshot, path, &include.text))
+        .collect()
+}
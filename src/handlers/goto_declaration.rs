@@ -0,0 +1,54 @@
+use crate::analysis::WorkspaceSnapshot;
+use crate::handlers::includes::{include_statements, resolve_include};
+use crate::utils::paths::uri_to_path_buf;
+use tower_lsp_server::lsp_types::request::{GotoDeclarationParams, GotoDeclarationResponse};
+use tower_lsp_server::lsp_types::Location;
+
+/// Go to the `include` statement that makes the type under the cursor available in
+/// this file. Types defined in this file (or not reachable through an include) go to
+/// their definition instead.
+pub fn handle_goto_declaration(
+    snapshot: &WorkspaceSnapshot<'_>,
+    params: GotoDeclarationParams,
+) -> Option<GotoDeclarationResponse> {
+    let uri = params.text_document_position_params.text_document.uri;
+    let position = params.text_document_position_params.position;
+
+    let resolved = snapshot.resolve_symbol_at(&uri, position)?;
+    if resolved.target.info.builtin {
+        return None;
+    }
+
+    let definition = GotoDeclarationResponse::Scalar(resolved.target.info.location.clone().into());
+
+    let path = uri_to_path_buf(&uri).ok()?;
+    let target_path = &resolved.target.info.location.path;
+    if *target_path == path {
+        return Some(definition);
+    }
+
+    let Some(doc) = snapshot.documents.get(&path) else {
+        return Some(definition);
+    };
+    let includes: Vec<_> = include_statements(&doc)
+        .into_iter()
+        .filter_map(|include| {
+            resolve_include(snapshot, &path, &include.text).map(|p| (include.range, p))
+        })
+        .collect();
+
+    // Prefer a direct include of the defining file, then one that includes it transitively.
+    let include_range = includes
+        .iter()
+        .find(|(_, included)| included == target_path)
+        .or_else(|| {
+            includes
+                .iter()
+                .find(|(_, included)| snapshot.dependencies.is_reachable(included, target_path))
+        })
+        .map(|(range, _)| *range);
+
+    Some(include_range.map_or(definition, |range| {
+        GotoDeclarationResponse::Scalar(Location { uri, range })
+    }))
+}
@@ -1,5 +1,8 @@
 use crate::analysis::WorkspaceSnapshot;
-use tower_lsp_server::lsp_types::{GotoDefinitionParams, GotoDefinitionResponse};
+use crate::utils::paths::uri_to_path_buf;
+use crate::utils::position_encoding::utf16_to_byte_col;
+use std::path::Path;
+use tower_lsp_server::lsp_types::{GotoDefinitionParams, GotoDefinitionResponse, Location, Range};
 
 pub fn handle_goto_definition(
     snapshot: &WorkspaceSnapshot<'_>,
@@ -8,13 +11,58 @@ pub fn handle_goto_definition(
     let uri = params.text_document_position_params.text_document.uri;
     let position = params.text_document_position_params.position;
 
-    let resolved = snapshot.resolve_symbol_at(&uri, position)?;
+    let Some(resolved) = snapshot.resolve_symbol_at(&uri, position) else {
+        // A namespace part (e.g. `Two` in `One.Two.X`) goes to every definition within it.
+        let namespace = snapshot.resolve_namespace_at(&uri, position)?;
+        let locations: Vec<Location> = snapshot
+            .symbols_in_namespace(&namespace)
+            .into_iter()
+            .map(|symbol| symbol.info.location.clone().into())
+            .collect();
+        return (!locations.is_empty()).then_some(GotoDefinitionResponse::Array(locations));
+    };
 
     if resolved.target.info.builtin {
         return None;
     }
 
-    Some(GotoDefinitionResponse::Scalar(
-        resolved.target.info.location.clone().into(),
-    ))
+    let definition: Location = resolved.target.info.location.clone().into();
+
+    // An unqualified reference to a name defined in several namespaces offers
+    // each of them, starting with the one flatc resolved it to.
+    let path = uri_to_path_buf(&uri).ok()?;
+    let is_reference = definition.uri != uri || resolved.range != definition.range;
+    if let Some(others) = snapshot
+        .symbols
+        .collisions()
+        .get(&resolved.target.info.name)
+        .filter(|_| is_reference && is_unqualified(snapshot, &path, resolved.range))
+    {
+        let mut others: Vec<_> = others
+            .iter()
+            .filter(|symbol| symbol.info.qualified_name() != resolved.ref_name)
+            .collect();
+        others.sort_by_key(|symbol| symbol.info.qualified_name());
+        let locations = std::iter::once(definition)
+            .chain(others.into_iter().map(|s| s.info.location.clone().into()))
+            .collect();
+        return Some(GotoDefinitionResponse::Array(locations));
+    }
+
+    Some(GotoDefinitionResponse::Scalar(definition))
+}
+
+/// Whether the reference at `range` is written without a namespace, e.g. `Foo`
+/// rather than `NS.Foo`.
+fn is_unqualified(snapshot: &WorkspaceSnapshot<'_>, path: &Path, range: Range) -> bool {
+    let Some(doc) = snapshot.documents.get(path) else {
+        return false;
+    };
+    let Some(line) = doc.get_line(range.start.line as usize) else {
+        return false;
+    };
+    let col = utf16_to_byte_col(line.chars(), range.start.character) as usize;
+    let line = line.to_string();
+    line.get(..col)
+        .is_some_and(|before| !before.trim_end().ends_with('.'))
 }
@@ -0,0 +1,45 @@
+use crate::analysis::WorkspaceSnapshot;
+use crate::symbol_table::{Symbol, SymbolKind};
+use tower_lsp_server::lsp_types::request::{GotoImplementationParams, GotoImplementationResponse};
+use tower_lsp_server::lsp_types::Location;
+
+/// Go from a union to the definitions of its members, or from a table to the unions
+/// that it is a member of.
+pub fn handle_goto_implementation(
+    snapshot: &WorkspaceSnapshot<'_>,
+    params: GotoImplementationParams,
+) -> Option<GotoImplementationResponse> {
+    let uri = params.text_document_position_params.text_document.uri;
+    let position = params.text_document_position_params.position;
+
+    let resolved = snapshot.resolve_symbol_at(&uri, position)?;
+    let targets: Vec<&Symbol> = match &resolved.target.kind {
+        SymbolKind::Union(union) => union
+            .variants
+            .iter()
+            .filter_map(|variant| snapshot.symbols.global.get(&variant.name))
+            .collect(),
+        SymbolKind::Table(_) => {
+            let name = resolved.target.info.qualified_name();
+            let mut unions: Vec<&Symbol> = snapshot
+                .symbols
+                .global
+                .values()
+                .filter(|symbol| match &symbol.kind {
+                    SymbolKind::Union(union) => union.variants.iter().any(|v| v.name == name),
+                    _ => false,
+                })
+                .collect();
+            unions.sort_by_key(|symbol| symbol.info.qualified_name());
+            unions
+        }
+        _ => return None,
+    };
+
+    let locations: Vec<Location> = targets
+        .into_iter()
+        .filter(|symbol| !symbol.info.builtin)
+        .map(|symbol| symbol.info.location.clone().into())
+        .collect();
+    (!locations.is_empty()).then_some(GotoImplementationResponse::Array(locations))
+}
@@ -1,23 +1,55 @@
+use crate::analysis::symbol_index::Attribute;
 use crate::analysis::WorkspaceSnapshot;
 use crate::ext::duration::DurationFormat;
-use crate::utils::as_pos_idx;
+use crate::ext::range::RangeExt;
+use crate::symbol_table::{Field, Symbol, SymbolKind};
 use crate::utils::paths::uri_to_path_buf;
+use crate::utils::position_encoding::utf16_to_byte_col;
+use crate::utils::{as_pos_idx, byte_to_utf16_col};
 use log::debug;
+use regex::Regex;
 use ropey::Rope;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
 use std::time::Instant;
 use tower_lsp_server::lsp_types::{
-    Hover, HoverContents, HoverParams, MarkupContent, MarkupKind, Position, Range,
+    Hover, HoverContents, HoverParams, MarkupContent, MarkupKind, Position, Range, Uri,
 };
 
-fn find_word_at_pos(line: &str, char_pos: u32) -> (usize, usize) {
-    let char_pos = char_pos as usize;
-    let start = line[..char_pos]
+/// A `file_identifier` declaration at the start of a line.
+static FILE_IDENTIFIER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?m)^\s*file_identifier\s+"([^"]*)"\s*;"#)
+        .expect("file identifier regex failed to compile")
+});
+
+/// A user-defined attribute declaration, e.g. `attribute "priority";`.
+static ATTRIBUTE_DECL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*attribute\s+"?(\w+)"?\s*;"#)
+        .expect("attribute declaration regex failed to compile")
+});
+
+/// The byte range of the word around the UTF-16 column `col`, or `None` if
+/// the column is past the end of `line`.
+fn find_word_at_pos(line: &str, col: u32) -> Option<(usize, usize)> {
+    let byte_pos = utf16_to_byte_col(line.chars(), col) as usize;
+    let start = line
+        .get(..byte_pos)?
         .rfind(|c: char| !c.is_alphanumeric() && c != '_')
         .map_or(0, |i| i + 1);
-    let end = line[char_pos..]
+    let end = line
+        .get(byte_pos..)?
         .find(|c: char| !c.is_alphanumeric() && c != '_')
-        .map_or(line.len(), |i| char_pos + i);
-    (start, end)
+        .map_or(line.len(), |i| byte_pos + i);
+    Some((start, end))
+}
+
+/// The UTF-16 range on line `line_num` covering the bytes `start..end` of `line`.
+fn word_range(line: &str, line_num: u32, start: usize, end: usize) -> Range {
+    Range::new(
+        Position::new(line_num, byte_to_utf16_col(line.chars(), as_pos_idx(start))),
+        Position::new(line_num, byte_to_utf16_col(line.chars(), as_pos_idx(end))),
+    )
 }
 
 fn is_inside_braces(doc: &Rope, position: Position) -> bool {
@@ -36,7 +68,8 @@ fn is_inside_braces(doc: &Rope, position: Position) -> bool {
     // Count braces on current line up to cursor
     if let Some(line) = doc.lines().nth(position.line as usize) {
         let line_str = line.to_string();
-        let line_before_cursor = &line_str[..position.character as usize];
+        let cursor = utf16_to_byte_col(line_str.chars(), position.character) as usize;
+        let line_before_cursor = line_str.get(..cursor).unwrap_or(&line_str);
         open_braces += line_before_cursor.matches('{').count();
         close_braces += line_before_cursor.matches('}').count();
     }
@@ -45,7 +78,165 @@ fn is_inside_braces(doc: &Rope, position: Position) -> bool {
     open_braces > close_braces
 }
 
-pub fn handle_hover(snapshot: &WorkspaceSnapshot<'_>, params: HoverParams) -> Option<Hover> {
+/// The vector or array field whose type is under the cursor, and the symbol for its element type.
+/// Namespace parts of the type are excluded so they can be resolved on their own.
+fn vector_element_at<'a>(
+    snapshot: &'a WorkspaceSnapshot<'_>,
+    uri: &Uri,
+    pos: Position,
+) -> Option<(&'a Field, &'a Symbol)> {
+    let field = snapshot.find_field_type_at(uri, pos)?;
+    if !field.parsed_type.is_vector
+        || field
+            .parsed_type
+            .namespace
+            .iter()
+            .any(|part| part.range.contains(pos))
+    {
+        return None;
+    }
+    let element = snapshot
+        .symbols
+        .global
+        .get(&field.type_name)
+        .or_else(|| snapshot.symbols.builtins.get(&field.type_name))?;
+    Some((field, element))
+}
+
+/// The element's own hover, followed by the shape of the vector or array holding it.
+/// Fixed-size arrays also include their total size.
+fn vector_hover_markdown(field: &Field, element: &Symbol) -> String {
+    let name = element.info.qualified_name();
+    let shape = match &field.parsed_type.array_size {
+        Some(count) => {
+            let mut shape = format!("Array of {} `{name}`", count.text);
+            if let (Ok(count), Some(size)) = (count.text.parse::<u64>(), element.inline_size()) {
+                shape.push_str(&format!(
+                    "\n\nSize: {} bytes ({size} × {count})",
+                    size * count
+                ));
+            }
+            shape
+        }
+        None => format!("Vector of `{name}`"),
+    };
+    format!("{}\n\n---\n\n{shape}", element.hover_markdown())
+}
+
+/// The deprecated field whose name is under the cursor.
+fn deprecated_field_at<'a>(
+    snapshot: &'a WorkspaceSnapshot<'_>,
+    uri: &Uri,
+    pos: Position,
+) -> Option<&'a Symbol> {
+    let (_, field) = snapshot.find_field_at(uri, pos)?;
+    match &field.kind {
+        SymbolKind::Field(f) if f.deprecated => Some(field),
+        _ => None,
+    }
+}
+
+/// The attribute under the cursor, either in an attribute list such as
+/// `(id: 1, priority)` or in its `attribute "priority";` declaration.
+fn attribute_at<'a>(
+    snapshot: &'a WorkspaceSnapshot<'_>,
+    path: &Path,
+    pos: Position,
+) -> Option<(&'a Attribute, Range)> {
+    let line = snapshot
+        .documents
+        .get(path)?
+        .lines()
+        .nth(pos.line as usize)?
+        .to_string();
+    let (start, end) = find_word_at_pos(&line, pos.character)?;
+    let word = &line[start..end];
+    let before = &line[..start];
+    let in_list = before
+        .rfind('(')
+        .is_some_and(|open| !before[open..].contains(')'));
+    let in_declaration = ATTRIBUTE_DECL_RE
+        .captures(&line)
+        .is_some_and(|captures| &captures[1] == word);
+    if word.is_empty() || !(in_list || in_declaration) {
+        return None;
+    }
+
+    let attribute = snapshot
+        .symbols
+        .builtin_attributes
+        .get(word)
+        .or_else(|| snapshot.symbols.user_defined_attributes.get(word))?;
+    Some((attribute, word_range(&line, pos.line, start, end)))
+}
+
+/// The attribute's declaration followed by its documentation: the built-in
+/// description or the `///` comment above a user-defined attribute.
+fn attribute_hover_markdown(snapshot: &WorkspaceSnapshot<'_>, attribute: &Attribute) -> String {
+    let builtin = if snapshot
+        .symbols
+        .builtin_attributes
+        .contains_key(&attribute.name)
+    {
+        " // built-in"
+    } else {
+        ""
+    };
+    let mut markdown = format!(
+        "```flatbuffers\nattribute \"{}\";{builtin}\n```",
+        attribute.name
+    );
+    if !attribute.doc.is_empty() {
+        markdown.push_str("\n\n---\n\n");
+        markdown.push_str(&attribute.doc);
+    }
+    markdown
+}
+
+/// What a buffer of this file's `root_type` contains: the root table, how many
+/// fields it has and the `file_identifier`, if one is declared.
+fn root_type_summary(snapshot: &WorkspaceSnapshot<'_>, path: &Path, doc: &Rope) -> Option<String> {
+    let root_type = snapshot.root_types.root_types.get(path)?;
+    let table = snapshot.symbols.global.get(&root_type.type_name)?;
+    let SymbolKind::Table(t) = &table.kind else {
+        return None;
+    };
+    let fields = match t.fields.len() {
+        1 => "1 field".to_string(),
+        n => format!("{n} fields"),
+    };
+    let mut summary = format!("Root type: `{}` ({fields})", table.info.qualified_name());
+    if let Some(identifier) = FILE_IDENTIFIER_RE.captures(&doc.to_string()) {
+        summary.push_str(&format!("\n\nFile identifier: `{}`", &identifier[1]));
+    }
+    Some(summary)
+}
+
+/// How widely the type `name` is used, e.g. "Referenced in 3 files (7 uses)".
+fn reference_summary(snapshot: &WorkspaceSnapshot<'_>, name: &str) -> String {
+    let references = snapshot.symbols.references(name);
+    if references.is_empty() {
+        return "Not referenced".to_string();
+    }
+    let files: HashSet<&PathBuf> = references.iter().map(|location| &location.path).collect();
+    let files = match files.len() {
+        1 => "1 file".to_string(),
+        n => format!("{n} files"),
+    };
+    let uses = match references.len() {
+        1 => "1 use".to_string(),
+        n => format!("{n} uses"),
+    };
+    format!("Referenced in {files} ({uses})")
+}
+
+/// Hover for the symbol at the cursor. With `reference_counts`, the hover for a
+/// user-defined type also says how widely it is used.
+pub fn handle_hover(
+    snapshot: &WorkspaceSnapshot<'_>,
+    params: HoverParams,
+    reference_counts: bool,
+) -> Option<Hover> {
     let start = Instant::now();
     let uri = params.text_document_position_params.text_document.uri;
     let pos = params.text_document_position_params.position;
@@ -53,29 +244,67 @@ pub fn handle_hover(snapshot: &WorkspaceSnapshot<'_>, params: HoverParams) -> Op
 
     let path = uri_to_path_buf(&uri).ok()?;
 
-    if let Some(resolved) = snapshot.resolve_symbol_at(&uri, pos) {
+    if let Some((field, element)) = vector_element_at(snapshot, &uri, pos) {
+        res = Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: vector_hover_markdown(field, element),
+            }),
+            range: Some(field.type_range),
+        });
+    } else if let Some(resolved) = snapshot.resolve_symbol_at(&uri, pos) {
+        let mut value = resolved.target.hover_markdown();
+        if reference_counts && !resolved.target.info.builtin {
+            value.push_str("\n\n---\n\n");
+            value.push_str(&reference_summary(snapshot, &resolved.ref_name));
+        }
         res = Some(Hover {
             contents: HoverContents::Markup(MarkupContent {
                 kind: MarkupKind::Markdown,
-                value: resolved.target.hover_markdown(),
+                value,
             }),
             range: Some(resolved.range),
         });
+    } else if let Some(field) = deprecated_field_at(snapshot, &uri, pos) {
+        res = Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: field.hover_markdown(),
+            }),
+            range: Some(field.info.location.range),
+        });
+    } else if let Some((attribute, range)) = attribute_at(snapshot, &path, pos) {
+        res = Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: attribute_hover_markdown(snapshot, attribute),
+            }),
+            range: Some(range),
+        });
     } else if let Some(doc) = snapshot.documents.get(&path) {
         if !is_inside_braces(&doc, pos) {
-            if let Some(line) = doc.lines().nth(pos.line as usize) {
-                let (start_char, end_char) = find_word_at_pos(&line.to_string(), pos.character);
-                let word = &line.to_string()[start_char..end_char];
-
-                if let Some(doc) = snapshot.symbols.keywords.get(word) {
-                    let range = Range {
-                        start: Position::new(pos.line, as_pos_idx(start_char)),
-                        end: Position::new(pos.line, as_pos_idx(end_char)),
+            let line = doc
+                .lines()
+                .nth(pos.line as usize)
+                .map(|line| line.to_string());
+            if let Some(line) = line {
+                let (start_char, end_char) =
+                    find_word_at_pos(&line, pos.character).unwrap_or_default();
+                let word = &line[start_char..end_char];
+
+                if let Some(keyword_doc) = snapshot.symbols.keywords.get(word) {
+                    let range = word_range(&line, pos.line, start_char, end_char);
+                    let summary = (word == "root_type")
+                        .then(|| root_type_summary(snapshot, &path, &doc))
+                        .flatten();
+                    let value = match summary {
+                        Some(summary) => format!("{summary}\n\n---\n\n{keyword_doc}"),
+                        None => keyword_doc.clone(),
                     };
                     res = Some(Hover {
                         contents: HoverContents::Markup(MarkupContent {
                             kind: MarkupKind::Markdown,
-                            value: doc.clone(),
+                            value,
                         }),
                         range: Some(range),
                     });
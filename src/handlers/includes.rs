@@ -0,0 +1,55 @@
+//! Helpers for working with the `include` statements of a document.
+
+use crate::analysis::WorkspaceSnapshot;
+use crate::utils::{as_pos_idx, utf16_len};
+use ropey::Rope;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tower_lsp_server::lsp_types::{Position, Range};
+
+pub struct IncludeStatement {
+    /// Text inside the quoted string.
+    pub text: String,
+    /// The range of the statement, excluding leading and trailing whitespace.
+    pub range: Range,
+}
+
+pub fn include_statements(doc: &Rope) -> Vec<IncludeStatement> {
+    doc.lines()
+        .enumerate()
+        .filter_map(|(idx, line)| {
+            let line = line.to_string();
+            let trimmed = line.trim();
+            if !trimmed.starts_with("include") {
+                return None;
+            }
+            let text = trimmed.split('"').nth(1)?.to_string();
+            let start = line.chars().take_while(|c| c.is_whitespace()).count();
+            let end = as_pos_idx(start) + utf16_len(trimmed);
+            Some(IncludeStatement {
+                text,
+                range: Range::new(
+                    Position::new(as_pos_idx(idx), as_pos_idx(start)),
+                    Position::new(as_pos_idx(idx), end),
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Resolve the text of an include statement in `path` to a known included file.
+pub fn resolve_include(
+    snapshot: &WorkspaceSnapshot<'_>,
+    path: &Path,
+    text: &str,
+) -> Option<PathBuf> {
+    let includes = snapshot.dependencies.includes.get(path)?;
+
+    // Prefer a path relative to the including file, then fall back to
+    // any known include (e.g. one resolved through a search path).
+    let relative = path
+        .parent()
+        .and_then(|dir| fs::canonicalize(dir.join(text)).ok())
+        .filter(|p| includes.contains(p));
+    relative.or_else(|| includes.iter().find(|p| p.ends_with(text)).cloned())
+}
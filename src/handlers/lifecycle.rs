@@ -1,12 +1,17 @@
-use std::{fs, iter::once, path::PathBuf};
+use std::{fs, iter::once, path::PathBuf, sync::atomic::Ordering};
 
-use crate::{ext::duration::DurationFormat, server::Backend, utils::paths::uri_to_path_buf};
-use log::{debug, info};
+use crate::{
+    analysis::ProgressCallback, config::Config,
+    diagnostics::include_depth::DEFAULT_MAX_INCLUDE_DEPTH, ext::duration::DurationFormat,
+    lsp_logger, server::Backend, utils::paths::uri_to_path_buf,
+    utils::position_encoding::PositionEncoding,
+};
+use log::{debug, info, warn};
 use tokio::time::Instant;
 use tower_lsp_server::lsp_types::{
-    Diagnostic, DidChangeTextDocumentParams, DidChangeWorkspaceFoldersParams,
+    ConfigurationItem, Diagnostic, DidChangeTextDocumentParams, DidChangeWorkspaceFoldersParams,
     DidCloseTextDocumentParams, DidOpenTextDocumentParams, DidSaveTextDocumentParams,
-    InitializeParams,
+    InitializeParams, TraceValue,
 };
 
 pub async fn handle_did_open(
@@ -24,8 +29,13 @@ pub async fn handle_did_change(
     backend: &Backend,
     params: DidChangeTextDocumentParams,
 ) -> Vec<(PathBuf, Vec<Diagnostic>)> {
-    if let Some(path) = backend.documents.handle_did_change(params) {
-        backend.analyzer.parse(vec![path]).await
+    if let Some(path) = backend
+        .documents
+        .handle_did_change(params, backend.position_encoding())
+    {
+        let diagnostics = backend.analyzer.parse(vec![path.clone()]).await;
+        backend.schedule_revalidation(&path).await;
+        diagnostics
     } else {
         vec![]
     }
@@ -39,9 +49,7 @@ pub async fn handle_did_save(
         let mut files_to_reparse = vec![path.clone()];
         {
             let snapshot = backend.analyzer.snapshot().await;
-            if let Some(includers) = snapshot.dependencies.included_by.get(&path) {
-                files_to_reparse.extend(includers.clone());
-            }
+            files_to_reparse.extend(snapshot.dependencies.dependents(&path));
         }
         backend.analyzer.parse(files_to_reparse).await
     } else {
@@ -54,6 +62,32 @@ pub fn handle_did_close(backend: &Backend, params: &DidCloseTextDocumentParams)
 }
 
 pub async fn handle_initialize(backend: &Backend, params: InitializeParams) {
+    let config = Config::from_value(params.initialization_options.clone());
+    let supports_configuration = params
+        .capabilities
+        .workspace
+        .as_ref()
+        .and_then(|workspace| workspace.configuration)
+        .unwrap_or(false);
+    backend
+        .client_supports_configuration
+        .store(supports_configuration, Ordering::Release);
+    let supports_change_annotations = params
+        .capabilities
+        .workspace
+        .as_ref()
+        .and_then(|workspace| workspace.workspace_edit.as_ref())
+        .is_some_and(|edit| {
+            edit.document_changes == Some(true) && edit.change_annotation_support.is_some()
+        });
+    backend
+        .client_supports_change_annotations
+        .store(supports_change_annotations, Ordering::Release);
+    let encoding = PositionEncoding::negotiate(&params);
+    if backend.position_encoding.set(encoding).is_err() {
+        warn!("ignoring position encoding from repeated initialize");
+    }
+
     let roots = params
         .workspace_folders
         .as_deref()
@@ -64,11 +98,96 @@ pub async fn handle_initialize(backend: &Backend, params: InitializeParams) {
         .flatten()
         .collect::<Vec<_>>();
     // Important: do not trigger a parse until the client is initialized.
-    let mut layout = backend.analyzer.layout.write().await;
-    layout.add_roots(roots);
+    backend.analyzer.layout.write().await.add_roots(roots);
+    if let Some(trace) = params.trace {
+        handle_set_trace(backend, trace).await;
+    }
+    apply_config(backend, config).await;
+}
+
+/// Store the trace level and only record parse times while tracing.
+pub async fn handle_set_trace(backend: &Backend, trace: TraceValue) {
+    backend
+        .analyzer
+        .record_parse_times
+        .store(trace != TraceValue::Off, Ordering::Release);
+    *backend.trace.write().await = trace;
 }
 
-pub async fn handle_initialized(backend: &Backend) -> Vec<(PathBuf, Vec<Diagnostic>)> {
+/// Store `config` and pass the parts that affect analysis on to the analyzer.
+async fn apply_config(backend: &Backend, config: Config) {
+    if let Some(level) = &config.log_level {
+        if lsp_logger::set_level(level).is_none() {
+            warn!("ignoring unknown log level: {level}");
+        }
+    }
+    {
+        let mut layout = backend.analyzer.layout.write().await;
+        layout.include_dirs.clone_from(&config.include_dirs);
+        layout.ignore_patterns.clone_from(&config.ignore_patterns);
+        layout.respect_gitignore = !config.scan_gitignored;
+    }
+    backend
+        .analyzer
+        .report_unreferenced_types
+        .store(config.diagnostics.unreferenced_types, Ordering::Release);
+    backend
+        .analyzer
+        .report_namespace_mismatch
+        .store(config.diagnostics.namespace_mismatch, Ordering::Release);
+    backend.analyzer.max_include_depth.store(
+        config
+            .max_include_depth
+            .unwrap_or(DEFAULT_MAX_INCLUDE_DEPTH),
+        Ordering::Release,
+    );
+    *backend.config.write().await = config;
+}
+
+/// Pull the `flatbuffers` settings section with `workspace/configuration`.
+/// Keeps the current settings if the client does not support the request.
+pub async fn refresh_config(backend: &Backend) {
+    if !backend
+        .client_supports_configuration
+        .load(Ordering::Acquire)
+    {
+        return;
+    }
+    let items = vec![ConfigurationItem {
+        scope_uri: None,
+        section: Some("flatbuffers".to_string()),
+    }];
+    match backend.client.configuration(items).await {
+        Ok(values) => {
+            let config = Config::from_value(values.into_iter().next());
+            debug!("configuration: {config:?}");
+            apply_config(backend, config).await;
+        }
+        Err(err) => warn!("failed to fetch configuration: {err}"),
+    }
+}
+
+/// Apply new settings and reparse the workspace. Returns the diagnostics for every file,
+/// since toggling a diagnostic changes what should be published for unchanged files.
+pub async fn handle_did_change_configuration(backend: &Backend) -> Vec<(PathBuf, Vec<Diagnostic>)> {
+    refresh_config(backend).await;
+
+    let files = backend.analyzer.layout.write().await.discover_files();
+    backend.analyzer.parse(files).await;
+
+    let snapshot = backend.analyzer.snapshot().await;
+    snapshot
+        .diagnostics
+        .all()
+        .iter()
+        .map(|(path, diagnostics)| (path.clone(), diagnostics.clone()))
+        .collect()
+}
+
+pub async fn handle_initialized(
+    backend: &Backend,
+    progress: Option<&ProgressCallback<'_>>,
+) -> Vec<(PathBuf, Vec<Diagnostic>)> {
     let start = Instant::now();
 
     let files = {
@@ -77,7 +196,7 @@ pub async fn handle_initialized(backend: &Backend) -> Vec<(PathBuf, Vec<Diagnost
 
         layout.discover_files()
     };
-    let diagnostics = backend.analyzer.parse(files).await;
+    let diagnostics = backend.analyzer.parse_with_progress(files, progress).await;
 
     let snapshot = backend.analyzer.snapshot().await;
     debug!(
@@ -1,8 +1,15 @@
+pub mod call_hierarchy;
 pub mod code_action;
 pub mod completion;
+pub mod execute_command;
+pub mod goto_declaration;
 pub mod goto_definition;
+pub mod goto_implementation;
 pub mod hover;
+pub(crate) mod includes;
 pub mod lifecycle;
+pub mod on_type_formatting;
 pub mod references;
 pub mod rename;
+pub mod selection_range;
 pub mod workspace_symbol;
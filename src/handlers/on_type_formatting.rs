@@ -0,0 +1,168 @@
+use crate::analysis::WorkspaceSnapshot;
+use crate::ext::duration::DurationFormat;
+use crate::utils::paths::uri_to_path_buf;
+use crate::utils::scanner::{brace_depth, code_before};
+use crate::utils::{as_pos_idx, utf16_len};
+use log::debug;
+use regex::Regex;
+use ropey::Rope;
+use std::sync::LazyLock;
+use std::time::Instant;
+use tower_lsp_server::lsp_types::{
+    DocumentOnTypeFormattingParams, FormattingOptions, Position, Range, TextEdit,
+};
+
+/// Characters that trigger on-type formatting. The first is the required trigger.
+pub const TRIGGER_CHARACTERS: [&str; 3] = ["}", "\n", ";"];
+
+static FIELD_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\s*\w+\s*:)(\s*)\S").expect("field regex failed to compile"));
+
+/// Re-indent the line being edited, and after a `;` align its type with the field above it.
+/// Only the current line is edited so the cursor does not jump.
+pub fn handle_on_type_formatting(
+    snapshot: &WorkspaceSnapshot<'_>,
+    params: &DocumentOnTypeFormattingParams,
+) -> Option<Vec<TextEdit>> {
+    let start = Instant::now();
+    let position = params.text_document_position.position;
+    let path = uri_to_path_buf(&params.text_document_position.text_document.uri).ok()?;
+    let doc = snapshot.documents.get(&path)?;
+
+    let edit = match params.ch.as_str() {
+        "}" | "\n" => reindent_line(&doc, position.line, &params.options),
+        ";" => align_field_type(&doc, position.line),
+        _ => None,
+    };
+
+    let elapsed = start.elapsed();
+    debug!(
+        "on type formatting in {}: {} L{}C{} {:?} -> {}",
+        elapsed.log_str(),
+        path.display(),
+        position.line + 1,
+        position.character + 1,
+        params.ch,
+        if edit.is_some() { "edit" } else { "no edit" }
+    );
+
+    edit.map(|edit| vec![edit])
+}
+
+fn line_text(doc: &Rope, line: u32) -> Option<String> {
+    let line = doc.get_line(line as usize)?.to_string();
+    Some(line.trim_end_matches(['\n', '\r']).to_string())
+}
+
+fn leading_whitespace(line: &str) -> &str {
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// Set the indentation of `line` to the depth of the block it is in.
+fn reindent_line(doc: &Rope, line: u32, options: &FormattingOptions) -> Option<TextEdit> {
+    let text = line_text(doc, line)?;
+    let mut depth = brace_depth(&code_before(doc, Position::new(line, 0)));
+    if text.trim_start().starts_with('}') {
+        depth -= 1;
+    }
+    let unit = if options.insert_spaces {
+        " ".repeat(options.tab_size as usize)
+    } else {
+        "\t".to_string()
+    };
+    let indent = unit.repeat(usize::try_from(depth.max(0)).unwrap_or(0));
+
+    let current = leading_whitespace(&text);
+    (current != indent).then(|| TextEdit {
+        range: Range::new(
+            Position::new(line, 0),
+            Position::new(line, utf16_len(current)),
+        ),
+        new_text: indent,
+    })
+}
+
+/// When the field above `line` in the same block has its type padded into a column,
+/// pad the type of the field on `line` to the same column.
+fn align_field_type(doc: &Rope, line: u32) -> Option<TextEdit> {
+    let text = line_text(doc, line)?;
+    let captures = FIELD_RE.captures(&text)?;
+    let (name, padding) = (captures.get(1)?, captures.get(2)?);
+
+    let previous = (0..line)
+        .rev()
+        .filter_map(|l| line_text(doc, l))
+        .find(|l| !l.trim().is_empty())?;
+    let previous_captures = FIELD_RE.captures(&previous)?;
+    let previous_name = previous_captures.get(1)?;
+    let previous_padding = previous_captures.get(2)?.as_str();
+    if previous_padding.len() <= 1 || previous_padding.contains('\t') {
+        // The field above is not aligned.
+        return None;
+    }
+
+    let column = utf16_len(previous_name.as_str()) + as_pos_idx(previous_padding.len());
+    let name_width = utf16_len(name.as_str());
+    if column <= name_width {
+        return None;
+    }
+    let new_padding = " ".repeat((column - name_width) as usize);
+    (padding.as_str() != new_padding).then(|| TextEdit {
+        range: Range::new(
+            Position::new(line, name_width),
+            Position::new(line, name_width + utf16_len(padding.as_str())),
+        ),
+        new_text: new_padding,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> FormattingOptions {
+        FormattingOptions {
+            tab_size: 2,
+            insert_spaces: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_reindent_closing_brace() {
+        let doc = Rope::from_str("table T {\n  a: int;\n    }\n");
+        let edit = reindent_line(&doc, 2, &options()).unwrap();
+        assert_eq!(
+            edit.range,
+            Range::new(Position::new(2, 0), Position::new(2, 4))
+        );
+        assert_eq!(edit.new_text, "");
+    }
+
+    #[test]
+    fn test_reindent_new_line_in_block() {
+        let doc = Rope::from_str("table T {\n  a: int;\n\n}\n");
+        let edit = reindent_line(&doc, 2, &options()).unwrap();
+        assert_eq!(edit.new_text, "  ");
+
+        let doc = Rope::from_str("table T {\n  a: int;\n  \n}\n");
+        assert_eq!(reindent_line(&doc, 2, &options()), None);
+    }
+
+    #[test]
+    fn test_align_field_type() {
+        let doc = Rope::from_str("table T {\n  a:     int;\n  bcd: int;\n}\n");
+        let edit = align_field_type(&doc, 2).unwrap();
+        assert_eq!(
+            edit.range,
+            Range::new(Position::new(2, 6), Position::new(2, 7))
+        );
+        assert_eq!(edit.new_text, "   ");
+    }
+
+    #[test]
+    fn test_no_alignment_when_field_above_is_not_aligned() {
+        let doc = Rope::from_str("table T {\n  a: int;\n  bcd: int;\n}\n");
+        assert_eq!(align_field_type(&doc, 2), None);
+    }
+}
@@ -1,6 +1,5 @@
 use crate::analysis::WorkspaceSnapshot;
 use crate::ext::duration::DurationFormat;
-use crate::symbol_table;
 use crate::utils::paths::path_buf_to_uri;
 use log::debug;
 use std::time::Instant;
@@ -21,79 +20,20 @@ pub fn handle_references(
     }
 
     let target_name = resolved.ref_name;
-    let mut references = Vec::new();
-
-    // Find all references to this symbol across all files
-    for entry in &snapshot.symbols.global {
-        let symbol = entry.1;
-        let file_uri = path_buf_to_uri(&symbol.info.location.path).ok()?;
-
-        if let symbol_table::SymbolKind::Union(u) = &symbol.kind {
-            for variant in &u.variants {
-                if variant.name == target_name {
-                    references.push(Location::new(
-                        file_uri.clone(),
-                        variant.parsed_type.type_name.range,
-                    ));
-                }
-            }
-        }
-
-        if let symbol_table::SymbolKind::RpcService(r) = &symbol.kind {
-            for method in &r.methods {
-                if method.request_type.name == target_name {
-                    references.push(Location::new(
-                        file_uri.clone(),
-                        method.request_type.parsed.type_name.range,
-                    ));
-                }
-                if method.response_type.name == target_name {
-                    references.push(Location::new(
-                        file_uri.clone(),
-                        method.response_type.parsed.type_name.range,
-                    ));
-                }
-            }
-        }
-
-        let fields = match &symbol.kind {
-            symbol_table::SymbolKind::Table(t) => &t.fields,
-            symbol_table::SymbolKind::Struct(s) => &s.fields,
-            _ => continue,
-        };
-
-        for field in fields {
-            if let symbol_table::SymbolKind::Field(f) = &field.kind {
-                if f.type_name == target_name {
-                    references.push(Location::new(
-                        file_uri.clone(),
-                        f.parsed_type.type_name.range,
-                    ));
-                }
-            }
-        }
-    }
-
-    // Check for root_type declarations
-    for (path, root_type_info) in &snapshot.root_types.root_types {
-        let Ok(uri) = path_buf_to_uri(path) else {
-            continue;
-        };
-        if root_type_info.type_name == target_name {
-            references.push(Location {
-                uri,
-                range: root_type_info.parsed_type.type_name.range,
-            });
-        }
-    }
-
-    // Include the definition itself if requested
+    let mut references: Vec<Location> = snapshot
+        .symbols
+        .references(&target_name)
+        .iter()
+        .filter_map(|location| {
+            let uri = path_buf_to_uri(&location.path).ok()?;
+            Some(Location::new(uri, location.range))
+        })
+        .collect();
+
+    // Include the definition itself if requested. This is the resolved target rather
+    // than a lookup by name, so it is the same definition that the references point to.
     if params.context.include_declaration {
-        if let Some(def_symbol) = snapshot.symbols.global.get(&target_name) {
-            if !def_symbol.info.builtin {
-                references.push(def_symbol.info.location.clone().into());
-            }
-        }
+        references.push(resolved.target.info.location.clone().into());
     }
 
     let elapsed = start.elapsed();
@@ -1,34 +1,93 @@
 use crate::analysis::WorkspaceSnapshot;
 use crate::ext::duration::DurationFormat;
+use crate::symbol_table::{Symbol, SymbolInfo, SymbolKind};
 use log::debug;
 use std::collections::HashMap;
 use std::time::Instant;
+use tower_lsp_server::jsonrpc::{Error, Result};
 use tower_lsp_server::lsp_types::{
-    PartialResultParams, PrepareRenameResponse, ReferenceContext, ReferenceParams, RenameParams,
-    TextDocumentPositionParams, TextEdit, WorkspaceEdit,
+    AnnotatedTextEdit, ChangeAnnotation, DocumentChanges, Location, OneOf,
+    OptionalVersionedTextDocumentIdentifier, PartialResultParams, PrepareRenameResponse,
+    ReferenceContext, ReferenceParams, RenameParams, TextDocumentEdit, TextDocumentPositionParams,
+    TextEdit, Uri, WorkspaceEdit,
 };
 
+const FIELD_RENAME_ANNOTATION: &str = "field-rename";
+
+const BUILTIN_RENAME_ERROR: &str = "Cannot rename a built-in type.";
+
+/// Check that the symbol under the cursor can be renamed.
+///
+/// # Errors
+///
+/// Returns an invalid params error if the cursor is on a built-in type.
 pub fn prepare_rename(
     snapshot: &WorkspaceSnapshot<'_>,
     params: &TextDocumentPositionParams,
-) -> Option<PrepareRenameResponse> {
+) -> Result<Option<PrepareRenameResponse>> {
     let uri = &params.text_document.uri;
     let position = params.position;
 
-    let resolved = snapshot.resolve_symbol_at(uri, position)?;
+    let Some(resolved) = snapshot.resolve_symbol_at(uri, position) else {
+        return Ok(snapshot.find_field_at(uri, position).map(|(_, field)| {
+            PrepareRenameResponse::RangeWithPlaceholder {
+                range: field.info.location.range,
+                placeholder: field.info.name.clone(),
+            }
+        }));
+    };
 
     if resolved.target.info.builtin {
-        return None;
+        return Err(Error::invalid_params(BUILTIN_RENAME_ERROR));
     }
 
-    Some(PrepareRenameResponse::Range(resolved.range))
+    // The range is only the type name, without any namespace or brackets
+    // around it, so the placeholder is the unqualified name.
+    Ok(Some(PrepareRenameResponse::RangeWithPlaceholder {
+        range: resolved.range,
+        placeholder: resolved.target.info.name.clone(),
+    }))
 }
 
-pub fn rename(snapshot: &WorkspaceSnapshot<'_>, params: RenameParams) -> Option<WorkspaceEdit> {
+/// Rename the symbol under the cursor and all of its references. Field renames are
+/// annotated for confirmation when the client supports `change_annotations`.
+///
+/// # Errors
+///
+/// Returns an invalid params error if the cursor is on a built-in type, if a type
+/// would be renamed to a qualified name that is already defined, or if a field
+/// would be renamed to an invalid name or to one that collides with a sibling field.
+pub fn rename(
+    snapshot: &WorkspaceSnapshot<'_>,
+    params: RenameParams,
+    change_annotations: bool,
+) -> Result<Option<WorkspaceEdit>> {
     let start = Instant::now();
     let uri = &params.text_document_position.text_document.uri;
     let position = params.text_document_position.position;
 
+    match snapshot.resolve_symbol_at(uri, position) {
+        Some(resolved) if resolved.target.info.builtin => {
+            return Err(Error::invalid_params(BUILTIN_RENAME_ERROR));
+        }
+        Some(resolved) => {
+            check_name_collision(snapshot, resolved.target, &params.new_name)?;
+        }
+        None => {
+            if let Some((parent, field)) = snapshot.find_field_at(uri, position) {
+                return rename_field(
+                    snapshot,
+                    uri,
+                    parent,
+                    field,
+                    &params.new_name,
+                    change_annotations,
+                )
+                .map(Some);
+            }
+        }
+    }
+
     let reference_params = ReferenceParams {
         text_document_position: params.text_document_position.clone(),
         work_done_progress_params: params.work_done_progress_params,
@@ -38,8 +97,12 @@ pub fn rename(snapshot: &WorkspaceSnapshot<'_>, params: RenameParams) -> Option<
         },
     };
 
-    let references = super::references::handle_references(snapshot, reference_params)?;
+    let Some(references) = super::references::handle_references(snapshot, reference_params) else {
+        return Ok(None);
+    };
 
+    // Reference ranges cover only the type name, so the namespace
+    // of a qualified reference like `NS.Foo` is left as written.
     let new_name = params.new_name;
     #[allow(clippy::mutable_key_type, reason = "external type definition")]
     let mut changes = HashMap::new();
@@ -60,5 +123,145 @@ pub fn rename(snapshot: &WorkspaceSnapshot<'_>, params: RenameParams) -> Option<
         changes.len()
     );
 
-    Some(WorkspaceEdit::new(changes))
+    Ok(Some(WorkspaceEdit::new(changes)))
+}
+
+/// Reject renaming `target` to a name that is already defined in its namespace, in any file.
+/// The error data holds the location of the existing definition.
+fn check_name_collision(
+    snapshot: &WorkspaceSnapshot<'_>,
+    target: &Symbol,
+    new_name: &str,
+) -> Result<()> {
+    let info = SymbolInfo {
+        name: new_name.to_string(),
+        ..target.info.clone()
+    };
+    let qualified_name = info.qualified_name();
+    let Some(existing) = snapshot.symbols.global.get(&qualified_name) else {
+        return Ok(());
+    };
+    if existing.info.location == target.info.location {
+        return Ok(());
+    }
+
+    let message = format!(
+        "`{qualified_name}` is already defined at {}:{}.",
+        existing.info.location.path.display(),
+        existing.info.location.range.start.line + 1
+    );
+    let location = Location::from(existing.info.location.clone());
+    Err(Error {
+        data: serde_json::to_value(location).ok(),
+        ..Error::invalid_params(message)
+    })
+}
+
+/// Fields are not referenced by name elsewhere in a schema, so only the declaration changes.
+/// Without `change_annotations`, the edit is applied without asking for confirmation.
+fn rename_field(
+    snapshot: &WorkspaceSnapshot<'_>,
+    uri: &Uri,
+    parent: &Symbol,
+    field: &Symbol,
+    new_name: &str,
+    change_annotations: bool,
+) -> Result<WorkspaceEdit> {
+    validate_field_name(snapshot, parent, field, new_name).map_err(Error::invalid_params)?;
+
+    let text_edit = TextEdit::new(field.info.location.range, new_name.to_string());
+    if !change_annotations {
+        #[allow(clippy::mutable_key_type, reason = "external type definition")]
+        let changes = HashMap::from([(uri.clone(), vec![text_edit])]);
+        return Ok(WorkspaceEdit::new(changes));
+    }
+
+    // The binary format identifies fields by position, but the JSON
+    // representation and generated code use the name.
+    let annotation = ChangeAnnotation {
+        label: format!("Rename field `{}` to `{new_name}`", field.info.name),
+        needs_confirmation: Some(true),
+        description: Some(
+            "Renaming a field changes its JSON name and generated accessors.".to_string(),
+        ),
+    };
+
+    let edit = AnnotatedTextEdit {
+        text_edit,
+        annotation_id: FIELD_RENAME_ANNOTATION.to_string(),
+    };
+
+    Ok(WorkspaceEdit {
+        changes: None,
+        document_changes: Some(DocumentChanges::Edits(vec![TextDocumentEdit {
+            text_document: OptionalVersionedTextDocumentIdentifier {
+                uri: uri.clone(),
+                version: None,
+            },
+            edits: vec![OneOf::Right(edit)],
+        }])),
+        change_annotations: Some(HashMap::from([(
+            FIELD_RENAME_ANNOTATION.to_string(),
+            annotation,
+        )])),
+    })
+}
+
+fn validate_field_name(
+    snapshot: &WorkspaceSnapshot<'_>,
+    parent: &Symbol,
+    field: &Symbol,
+    new_name: &str,
+) -> std::result::Result<(), String> {
+    let mut chars = new_name.chars();
+    let is_identifier = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !is_identifier {
+        return Err(format!("`{new_name}` is not a valid field name."));
+    }
+
+    let siblings = match &parent.kind {
+        SymbolKind::Table(t) => &t.fields,
+        SymbolKind::Struct(s) => &s.fields,
+        _ => return Ok(()),
+    };
+
+    // Union fields have a hidden `<name>_type` field alongside them.
+    let is_union_field = |symbol: &Symbol| {
+        let SymbolKind::Field(f) = &symbol.kind else {
+            return false;
+        };
+        snapshot
+            .symbols
+            .global
+            .get(&f.type_name)
+            .is_some_and(|t| matches!(t.kind, SymbolKind::Union(_)))
+    };
+
+    for sibling in siblings {
+        if sibling.info.location == field.info.location {
+            continue;
+        }
+        let sibling_name = &sibling.info.name;
+        if sibling_name == new_name {
+            return Err(format!(
+                "`{}` already has a field named `{new_name}`.",
+                parent.info.name
+            ));
+        }
+        if is_union_field(sibling) && format!("{sibling_name}_type") == new_name {
+            return Err(format!(
+                "`{new_name}` is reserved for the type of union field `{sibling_name}`."
+            ));
+        }
+        if is_union_field(field) && format!("{new_name}_type") == *sibling_name {
+            return Err(format!(
+                "The type of union field `{new_name}` would collide with field `{sibling_name}`."
+            ));
+        }
+    }
+
+    Ok(())
 }
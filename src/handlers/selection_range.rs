@@ -0,0 +1,273 @@
+use crate::analysis::WorkspaceSnapshot;
+use crate::ext::duration::DurationFormat;
+use crate::symbol_table::{Symbol, SymbolKind};
+use crate::utils::as_pos_idx;
+use crate::utils::paths::uri_to_path_buf;
+use log::debug;
+use ropey::Rope;
+use std::path::Path;
+use std::time::Instant;
+use tower_lsp_server::lsp_types::{Position, Range, SelectionRange, SelectionRangeParams};
+
+pub fn handle_selection_range(
+    snapshot: &WorkspaceSnapshot<'_>,
+    params: &SelectionRangeParams,
+) -> Option<Vec<SelectionRange>> {
+    let start = Instant::now();
+    let path = uri_to_path_buf(&params.text_document.uri).ok()?;
+    let doc = snapshot.documents.get(&path)?;
+
+    // The response must contain exactly one entry per requested position.
+    let result: Vec<SelectionRange> = params
+        .positions
+        .iter()
+        .map(|&position| selection_range_at(snapshot, &path, &doc, position))
+        .collect();
+
+    let elapsed = start.elapsed();
+    debug!(
+        "selection range in {}: {} -> {} positions",
+        elapsed.log_str(),
+        path.display(),
+        result.len()
+    );
+
+    Some(result)
+}
+
+/// Build the chain: identifier → field declaration → definition body → whole definition.
+fn selection_range_at(
+    snapshot: &WorkspaceSnapshot<'_>,
+    path: &Path,
+    doc: &Rope,
+    position: Position,
+) -> SelectionRange {
+    // Innermost first.
+    let mut ranges = Vec::new();
+    ranges.extend(word_range_at(doc, position));
+
+    if let Some((symbol, definition)) = enclosing_definition(snapshot, path, doc, position) {
+        ranges.extend(member_ranges(symbol, doc, position));
+        ranges.push(definition.body);
+        ranges.push(definition.full);
+    }
+
+    // Each parent must strictly contain its child.
+    let mut nested: Vec<Range> = Vec::new();
+    for range in ranges {
+        if nested
+            .last()
+            .is_none_or(|inner| range != *inner && contains_range(&range, inner))
+        {
+            nested.push(range);
+        }
+    }
+
+    let mut selection: Option<SelectionRange> = None;
+    for range in nested.into_iter().rev() {
+        selection = Some(SelectionRange {
+            range,
+            parent: selection.map(Box::new),
+        });
+    }
+
+    selection.unwrap_or(SelectionRange {
+        range: Range::new(position, position),
+        parent: None,
+    })
+}
+
+struct DefinitionRanges {
+    /// From the opening to the closing brace, inclusive.
+    body: Range,
+    /// From the defining keyword to the closing brace, inclusive.
+    full: Range,
+}
+
+fn enclosing_definition<'a>(
+    snapshot: &'a WorkspaceSnapshot<'_>,
+    path: &Path,
+    doc: &Rope,
+    position: Position,
+) -> Option<(&'a Symbol, DefinitionRanges)> {
+    snapshot
+        .symbols
+        .per_file
+        .get(path)?
+        .iter()
+        .filter_map(|key| snapshot.symbols.global.get(key))
+        .filter(|symbol| {
+            matches!(
+                symbol.kind,
+                SymbolKind::Table(_)
+                    | SymbolKind::Struct(_)
+                    | SymbolKind::Enum(_)
+                    | SymbolKind::Union(_)
+                    | SymbolKind::RpcService(_)
+            )
+        })
+        .filter_map(|symbol| definition_ranges(doc, symbol).map(|ranges| (symbol, ranges)))
+        .find(|(_, ranges)| contains_position(&ranges.full, position))
+}
+
+fn definition_ranges(doc: &Rope, symbol: &Symbol) -> Option<DefinitionRanges> {
+    let name_range = symbol.info.location.range;
+    let keyword_line = line_chars(doc, name_range.start.line)?;
+    let keyword_start = keyword_line
+        .iter()
+        .position(|c| !c.is_whitespace())
+        .map_or(0, as_pos_idx);
+
+    let open = find_open_brace(doc, name_range.end)?;
+    let close = find_matching_close_brace(doc, open)?;
+
+    let end = Position::new(close.line, close.character + 1);
+    Some(DefinitionRanges {
+        body: Range::new(open, end),
+        full: Range::new(Position::new(name_range.start.line, keyword_start), end),
+    })
+}
+
+/// Ranges for the member (field, variant, or method) under the cursor, innermost first.
+fn member_ranges(symbol: &Symbol, doc: &Rope, position: Position) -> Vec<Range> {
+    let fields = match &symbol.kind {
+        SymbolKind::Table(t) => &t.fields,
+        SymbolKind::Struct(s) => &s.fields,
+        SymbolKind::Union(u) => {
+            return u
+                .variants
+                .iter()
+                .map(|v| v.location.range)
+                .filter(|range| contains_position(range, position))
+                .take(1)
+                .collect();
+        }
+        SymbolKind::RpcService(r) => {
+            return r
+                .methods
+                .iter()
+                .map(|m| Range::new(m.range.start, statement_end(doc, m.response_type.range.end)))
+                .filter(|range| contains_position(range, position))
+                .take(1)
+                .collect();
+        }
+        _ => return vec![],
+    };
+
+    for field in fields {
+        let SymbolKind::Field(f) = &field.kind else {
+            continue;
+        };
+        let start = field.info.location.range.start;
+        let declaration = Range::new(start, statement_end(doc, f.type_range.end));
+        if contains_position(&declaration, position) {
+            return if contains_position(&f.type_range, position) {
+                vec![f.type_range, declaration]
+            } else {
+                vec![declaration]
+            };
+        }
+    }
+    vec![]
+}
+
+/// Extend `pos` to just past the `;` that terminates the statement, if it is on the same line.
+fn statement_end(doc: &Rope, pos: Position) -> Position {
+    line_chars(doc, pos.line)
+        .and_then(|chars| {
+            let code = code_len(&chars);
+            (pos.character as usize..code).find(|&i| chars[i] == ';')
+        })
+        .map_or(pos, |i| Position::new(pos.line, as_pos_idx(i + 1)))
+}
+
+fn word_range_at(doc: &Rope, position: Position) -> Option<Range> {
+    let chars = line_chars(doc, position.line)?;
+    let is_word = |c: &char| c.is_alphanumeric() || *c == '_';
+    let cursor = (position.character as usize).min(chars.len());
+
+    let start = chars[..cursor]
+        .iter()
+        .rposition(|c| !is_word(c))
+        .map_or(0, |i| i + 1);
+    let end = chars[cursor..]
+        .iter()
+        .position(|c| !is_word(c))
+        .map_or(chars.len(), |i| cursor + i);
+
+    if start == end {
+        return None;
+    }
+    Some(Range::new(
+        Position::new(position.line, as_pos_idx(start)),
+        Position::new(position.line, as_pos_idx(end)),
+    ))
+}
+
+/// Find the first `{` at or after `from`, ignoring comments.
+fn find_open_brace(doc: &Rope, from: Position) -> Option<Position> {
+    for line in from.line as usize..doc.len_lines() {
+        let line = as_pos_idx(line);
+        let chars = line_chars(doc, line)?;
+        let start = if line == from.line {
+            from.character as usize
+        } else {
+            0
+        };
+        if let Some(col) = (start..code_len(&chars)).find(|&i| chars[i] == '{') {
+            return Some(Position::new(line, as_pos_idx(col)));
+        }
+    }
+    None
+}
+
+/// Find the `}` that balances the `{` at `open`, ignoring comments.
+fn find_matching_close_brace(doc: &Rope, open: Position) -> Option<Position> {
+    let mut depth = 0;
+    for line in open.line as usize..doc.len_lines() {
+        let line = as_pos_idx(line);
+        let chars = line_chars(doc, line)?;
+        let start = if line == open.line {
+            open.character as usize + 1
+        } else {
+            0
+        };
+        for (col, c) in chars.iter().enumerate().take(code_len(&chars)).skip(start) {
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => return Some(Position::new(line, as_pos_idx(col))),
+                '}' => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+    None
+}
+
+/// The characters of a line, excluding the line terminator.
+fn line_chars(doc: &Rope, line: u32) -> Option<Vec<char>> {
+    if line as usize >= doc.len_lines() {
+        return None;
+    }
+    let mut chars: Vec<char> = doc.line(line as usize).chars().collect();
+    while chars.last().is_some_and(|c| *c == '\n' || *c == '\r') {
+        chars.pop();
+    }
+    Some(chars)
+}
+
+/// The number of characters before a `//` comment (or the whole line if there is none).
+fn code_len(chars: &[char]) -> usize {
+    chars
+        .windows(2)
+        .position(|w| w[0] == '/' && w[1] == '/')
+        .unwrap_or(chars.len())
+}
+
+fn contains_position(range: &Range, pos: Position) -> bool {
+    pos >= range.start && pos <= range.end
+}
+
+fn contains_range(outer: &Range, inner: &Range) -> bool {
+    outer.start <= inner.start && inner.end <= outer.end
+}
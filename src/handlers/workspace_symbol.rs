@@ -1,21 +1,44 @@
 use crate::analysis::WorkspaceSnapshot;
 use crate::ext::duration::DurationFormat;
+use crate::symbol_table::{Symbol, SymbolKind};
 use log::debug;
 use nucleo_matcher::pattern::{CaseMatching, Normalization, Pattern};
 use nucleo_matcher::{Config, Matcher};
 use std::time::Instant;
 use tower_lsp_server::lsp_types::{OneOf, WorkspaceSymbol, WorkspaceSymbolParams};
 
-fn to_workspace_symbol(symbol: &crate::symbol_table::Symbol) -> WorkspaceSymbol {
+/// The size of the symbol's body, e.g. `3 fields`, for telling apart symbols
+/// with similar names in a long list.
+fn member_count(symbol: &Symbol) -> Option<String> {
+    let (count, noun) = match &symbol.kind {
+        SymbolKind::Table(t) => (t.fields.len(), "field"),
+        SymbolKind::Struct(s) => (s.fields.len(), "field"),
+        SymbolKind::Enum(e) => (e.variants.len(), "variant"),
+        SymbolKind::Union(u) => (u.variants.len(), "variant"),
+        SymbolKind::RpcService(r) => (r.methods.len(), "method"),
+        SymbolKind::Field(_) | SymbolKind::Scalar => return None,
+    };
+    Some(match count {
+        1 => format!("1 {noun}"),
+        n => format!("{n} {noun}s"),
+    })
+}
+
+/// The symbol's namespace followed by its [`member_count`], e.g. `Game · 3 fields`.
+fn container_name(symbol: &Symbol) -> Option<String> {
+    let namespace = symbol.info.namespace_str();
+    match (namespace, member_count(symbol)) {
+        (Some(namespace), Some(count)) => Some(format!("{namespace} · {count}")),
+        (namespace, count) => namespace.or(count),
+    }
+}
+
+fn to_workspace_symbol(symbol: &Symbol) -> WorkspaceSymbol {
     WorkspaceSymbol {
         name: symbol.info.name.clone(),
         kind: (&symbol.kind).into(),
         location: OneOf::Left(symbol.info.location.clone().into()),
-        container_name: if symbol.info.namespace.is_empty() {
-            None
-        } else {
-            Some(symbol.info.namespace.join("."))
-        },
+        container_name: container_name(symbol),
         tags: None,
         data: None,
     }
@@ -1,9 +1,13 @@
+use crate::ext::file_health::FileHealth;
 use crate::lsp_logger::LspLogger;
 use crate::server::Backend;
 use log::info;
+use tower_lsp_server::lsp_types::notification::{Notification, SetTrace};
+use tower_lsp_server::lsp_types::request::Request;
 use tower_lsp_server::{LspService, Server};
 
 pub mod analysis;
+pub mod config;
 pub mod diagnostics;
 pub mod document_store;
 pub mod ext;
@@ -20,7 +24,7 @@ pub async fn run() {
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
 
-    let (service, socket) = LspService::new(|client| {
+    let (service, socket) = LspService::build(|client| {
         let logger = LspLogger::new(client.clone());
         if let Err(e) = log::set_boxed_logger(Box::new(logger)) {
             eprintln!("Error setting logger: {e}");
@@ -28,7 +32,10 @@ pub async fn run() {
         log::set_max_level(log::LevelFilter::Debug);
 
         Backend::new(client)
-    });
+    })
+    .custom_method(SetTrace::METHOD, Backend::set_trace)
+    .custom_method(FileHealth::METHOD, Backend::file_health)
+    .finish();
 
     info!("Starting server v{}...", env!("CARGO_PKG_VERSION"));
     Server::new(stdin, stdout, socket).serve(service).await;
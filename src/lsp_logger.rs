@@ -1,4 +1,4 @@
-use log::{Level, Log, Metadata, Record};
+use log::{Level, LevelFilter, Log, Metadata, Record};
 use tower_lsp_server::lsp_types::MessageType;
 use tower_lsp_server::Client;
 
@@ -11,6 +11,15 @@ fn level_to_message_type(level: Level) -> MessageType {
     }
 }
 
+/// Set the most verbose level sent to the client from a name such as `"info"`
+/// (case-insensitive). Returns the applied level, or `None` if `level` is not
+/// one of `off`, `error`, `warn`, `info`, `debug` or `trace`.
+pub fn set_level(level: &str) -> Option<LevelFilter> {
+    let level = level.trim().parse::<LevelFilter>().ok()?;
+    log::set_max_level(level);
+    Some(level)
+}
+
 #[derive(Debug)]
 pub struct LspLogger {
     client: Client,
@@ -24,8 +33,8 @@ impl LspLogger {
 }
 
 impl Log for LspLogger {
-    fn enabled(&self, _metadata: &Metadata) -> bool {
-        true
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
     }
 
     fn log(&self, record: &Record) {
@@ -4,25 +4,32 @@ use crate::symbol_table::RpcMethod;
 use crate::symbol_table::RpcMethodType;
 use crate::symbol_table::RpcService;
 use crate::symbol_table::{
-    Enum, EnumVariant, Field, RootTypeInfo, Struct, Symbol, SymbolInfo, SymbolKind, SymbolTable,
-    Table, Union, UnionVariant,
+    DefaultValue, Enum, EnumVariant, Field, NestedFlatbuffer, RootTypeInfo, Struct, Symbol,
+    SymbolInfo, SymbolKind, SymbolTable, Table, Union, UnionVariant,
 };
-use crate::utils::as_pos_idx;
-use crate::utils::parsed_type::parse_type;
+use crate::utils::parsed_type::{parse_type, ParsedType};
+use crate::utils::paths::canonicalize_or_self;
+use crate::utils::{byte_offset_to_position, byte_to_utf16_col, utf16_len};
 use log::{debug, error};
+use regex::Regex;
+use ropey::Rope;
 use std::collections::HashMap;
 use std::ffi::c_char;
 use std::ffi::{CStr, CString};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::string::ToString;
-use tower_lsp_server::lsp_types::{Diagnostic, Position, Range};
+use std::sync::LazyLock;
+use tower_lsp_server::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
 
 #[derive(Default)]
 pub struct ParseResult {
     pub diagnostics: HashMap<PathBuf, Vec<Diagnostic>>,
     pub symbol_table: Option<SymbolTable>,
     pub includes: Vec<PathBuf>,
+    /// The files included by an include statement in this file, rather than
+    /// through another included file.
+    pub direct_includes: Vec<PathBuf>,
     pub root_type_info: Option<RootTypeInfo>,
     pub user_defined_attributes: HashMap<String, String>,
 }
@@ -35,43 +42,62 @@ pub trait Parser {
 #[derive(Debug, Clone, Copy)]
 pub struct FlatcFFIParser;
 
-impl Parser for FlatcFFIParser {
-    fn parse(&self, path: &Path, content: &str, search_paths: &[PathBuf]) -> ParseResult {
-        let Ok(c_content) = CString::new(content) else {
-            return ParseResult::default();
-        };
-        let Ok(c_filename) = CString::new(path.to_str().unwrap_or_default()) else {
-            return ParseResult::default();
-        };
-
-        let c_search_paths: Vec<CString> = search_paths
-            .iter()
-            .filter_map(|path| CString::new(path.to_str().unwrap_or_default()).ok())
-            .collect();
+impl ParseResult {
+    /// The result for content that cannot be given to flatc, with a single
+    /// diagnostic explaining why. Previously known symbols are kept.
+    fn unparsable(path: &Path, diagnostic: Diagnostic) -> Self {
+        Self {
+            diagnostics: HashMap::from([(path.to_path_buf(), vec![diagnostic])]),
+            ..Default::default()
+        }
+    }
 
-        let mut c_path_ptrs: Vec<*const c_char> =
-            c_search_paths.iter().map(|s| s.as_ptr()).collect();
-        c_path_ptrs.push(std::ptr::null());
+    /// The result for a file whose bytes are not valid UTF-8. `valid_up_to` is
+    /// the offset of the first invalid byte.
+    #[must_use]
+    pub fn invalid_utf8(path: &Path, bytes: &[u8], valid_up_to: usize) -> Self {
+        let content = String::from_utf8_lossy(&bytes[..valid_up_to.min(bytes.len())]);
+        let start = byte_offset_to_position(&content, content.len());
+        Self::unparsable(
+            path,
+            Diagnostic {
+                range: Range::new(start, Position::new(start.line, start.character + 1)),
+                severity: Some(DiagnosticSeverity::ERROR),
+                message: "File is not valid UTF-8".to_string(),
+                ..Default::default()
+            },
+        )
+    }
+}
 
-        unsafe {
-            let parser_ptr = ffi::parse_schema(
-                c_content.as_ptr(),
-                c_filename.as_ptr(),
-                c_path_ptrs.as_mut_ptr(),
+impl Parser for FlatcFFIParser {
+    fn parse(&self, path: &Path, content: &str, search_paths: &[PathBuf]) -> ParseResult {
+        // flatc reads the content as a C string, so it would stop at the first NUL.
+        if let Some(offset) = content.find('\0') {
+            let start = byte_offset_to_position(content, offset);
+            return ParseResult::unparsable(
+                path,
+                Diagnostic {
+                    range: Range::new(start, Position::new(start.line, start.character + 1)),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    message: "File contains a NUL character, which is not allowed in a schema"
+                        .to_string(),
+                    ..Default::default()
+                },
             );
-            if parser_ptr.is_null() {
-                return ParseResult::default();
-            }
+        }
 
+        with_flatc_parser(path, content, search_paths, |parser_ptr| unsafe {
             let mut diagnostics = parse_error_messages(parser_ptr, path, content);
 
+            let mut sources = SourceCache::new(path, content);
             let mut st = SymbolTable::new(path.to_path_buf());
-            extract_structs_and_tables(parser_ptr, &mut st);
-            extract_enums_and_unions(parser_ptr, &mut st);
-            extract_rpc_services(parser_ptr, &mut st);
+            extract_structs_and_tables(parser_ptr, &mut st, &mut sources, &mut diagnostics);
+            extract_enums_and_unions(parser_ptr, &mut st, &mut sources, &mut diagnostics);
+            extract_rpc_services(parser_ptr, &mut st, &mut sources, &mut diagnostics);
 
             let included_files = extract_all_included_files(parser_ptr); // recursive. includes transient includes.
-            let root_type_info = extract_root_type(parser_ptr);
+            let root_type_info = extract_root_type(parser_ptr, &mut sources, &mut diagnostics);
             let user_defined_attributes = extract_user_defined_attributes(parser_ptr);
 
             let include_graph = build_include_graph(parser_ptr); // direct includes only.
@@ -84,19 +110,88 @@ impl Parser for FlatcFFIParser {
                 &root_type_info,
             );
             diagnostics::semantic::analyze_deprecated_fields(&st, &mut diagnostics);
+            diagnostics::semantic::analyze_force_align(&st, content, &mut diagnostics);
+            diagnostics::semantic::analyze_nested_flatbuffers(&st, &mut diagnostics);
+            diagnostics::semantic::analyze_variant_names(&st, content, &mut diagnostics);
 
-            let result = ParseResult {
+            let direct_includes = include_graph
+                .get(path.to_string_lossy().as_ref())
+                .map(|includes| includes.iter().map(PathBuf::from).collect())
+                .unwrap_or_default();
+
+            ParseResult {
                 diagnostics,
                 symbol_table: Some(st),
                 includes: included_files,
+                direct_includes,
                 root_type_info,
                 user_defined_attributes,
-            };
+            }
+        })
+        .unwrap_or_default()
+    }
+}
 
-            ffi::delete_parser(parser_ptr);
+impl FlatcFFIParser {
+    /// Serialize the schema in `content` to a binary schema (`.bfbs`).
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` with flatc's error message if the schema fails to parse.
+    pub fn binary_schema(
+        &self,
+        path: &Path,
+        content: &str,
+        search_paths: &[PathBuf],
+    ) -> Result<Vec<u8>, String> {
+        with_flatc_parser(path, content, search_paths, |parser_ptr| unsafe {
+            let mut buffer: *const u8 = std::ptr::null();
+            let size = ffi::serialize_binary_schema(parser_ptr, &mut buffer);
+            if size == 0 || buffer.is_null() {
+                let error = c_str_to_string(ffi::get_parser_error(parser_ptr));
+                return Err(if error.is_empty() {
+                    format!("failed to serialize {}", path.display())
+                } else {
+                    error
+                });
+            }
+            Ok(std::slice::from_raw_parts(buffer, size).to_vec())
+        })
+        .unwrap_or_else(|| Err(format!("failed to parse {}", path.display())))
+    }
+}
 
-            result
+/// Run flatc's parser over `content` and pass it to `f`. The parser is deleted afterwards.
+fn with_flatc_parser<T>(
+    path: &Path,
+    content: &str,
+    search_paths: &[PathBuf],
+    f: impl FnOnce(*mut ffi::FlatbuffersParser) -> T,
+) -> Option<T> {
+    let c_content = CString::new(content).ok()?;
+    let c_filename = CString::new(path.to_str().unwrap_or_default()).ok()?;
+
+    let c_search_paths: Vec<CString> = search_paths
+        .iter()
+        .filter_map(|path| CString::new(path.to_str().unwrap_or_default()).ok())
+        .collect();
+
+    let mut c_path_ptrs: Vec<*const c_char> = c_search_paths.iter().map(|s| s.as_ptr()).collect();
+    c_path_ptrs.push(std::ptr::null());
+
+    unsafe {
+        let parser_ptr = ffi::parse_schema(
+            c_content.as_ptr(),
+            c_filename.as_ptr(),
+            c_path_ptrs.as_mut_ptr(),
+        );
+        if parser_ptr.is_null() {
+            return None;
         }
+
+        let result = f(parser_ptr);
+        ffi::delete_parser(parser_ptr);
+        Some(result)
     }
 }
 
@@ -141,13 +236,11 @@ unsafe fn extract_user_defined_attributes(
         {
             let doc = CString::new(attr_name.clone())
                 .map(|c_attr_name| {
-                    c_str_to_optional_string(ffi::get_user_defined_attribute_doc(
+                    c_str_to_documentation(ffi::get_user_defined_attribute_doc(
                         parser_ptr,
                         c_attr_name.as_ptr(),
                     ))
                     .unwrap_or_default()
-                    .trim()
-                    .to_string()
                 })
                 .unwrap_or_default();
             attributes.insert(attr_name, doc);
@@ -160,6 +253,8 @@ unsafe fn extract_user_defined_attributes(
 unsafe fn extract_structs_and_tables(
     parser_ptr: *mut ffi::FlatbuffersParser,
     st: &mut SymbolTable,
+    sources: &mut SourceCache,
+    diagnostics: &mut HashMap<PathBuf, Vec<Diagnostic>>,
 ) {
     let num_structs = ffi::get_num_structs(parser_ptr);
     for i in 0..num_structs {
@@ -185,10 +280,7 @@ unsafe fn extract_structs_and_tables(
         };
 
         let file = c_str_to_string(def_info.file);
-        let Ok(file_path) = fs::canonicalize(&file) else {
-            error!("failed to canonicalize file: {file} for struct/table named: {qualified_name}");
-            continue;
-        };
+        let file_path = canonicalize_or_self(Path::new(&file));
 
         if st.contains_key(&qualified_name) {
             // This should not happen. The flatbuffers parser returns rich errors for duplicate definitions.
@@ -209,21 +301,26 @@ unsafe fn extract_structs_and_tables(
 
             let type_source = c_str_to_string(field_info.type_source);
 
-            let type_range = field_info.type_range.into();
+            let type_range = sources.range(&file_path, field_info.type_range.into());
             let Some(parsed_type) = parse_type(&type_source, type_range) else {
-                error!("Failed to parse field type at {}:{}:{}. Please open a GitHub Issue: https://github.com/smpanaro/flatbuffers-language-server/issues",
-                    file_path.display(), type_range.end.line, type_range.end.character);
+                report_unparsed_type(
+                    diagnostics,
+                    "field type",
+                    &file_path,
+                    type_range,
+                    &type_source,
+                );
                 continue;
             };
 
-            let documentation = c_str_to_optional_string(field_info.documentation);
+            let documentation = c_str_to_documentation(field_info.documentation);
 
             let field_symbol = create_symbol(
                 &file_path,
                 field_name.clone(),
                 vec![], // Fields do not have namespaces themselves
                 field_info.line,
-                field_info.col,
+                sources.column(&file_path, field_info.line, field_info.col),
                 SymbolKind::Field(Field {
                     type_name,
                     type_display_name,
@@ -231,6 +328,22 @@ unsafe fn extract_structs_and_tables(
                     parsed_type,
                     deprecated: field_info.deprecated,
                     id: Some(field_info.id).take_if(|_| field_info.has_id),
+                    force_align: c_str_to_optional_string(field_info.force_align),
+                    nested_flatbuffer: c_str_to_optional_string(field_info.nested_flatbuffer)
+                        .and_then(|type_name| {
+                            let (source, start) =
+                                sources.nested_flatbuffer_value(&file_path, field_info.line)?;
+                            let parsed_type = parse_type(&source, Range::new(start, start))?;
+                            Some(NestedFlatbuffer {
+                                type_name,
+                                parsed_type,
+                            })
+                        }),
+                    default_value: sources.default_value(
+                        &file_path,
+                        field_info.line,
+                        field_info.col,
+                    ),
                 }),
                 documentation,
             );
@@ -247,14 +360,14 @@ unsafe fn extract_structs_and_tables(
             })
         };
 
-        let documentation = c_str_to_optional_string(def_info.documentation);
+        let documentation = c_str_to_documentation(def_info.documentation);
 
         let symbol = create_symbol(
             &file_path,
             name,
             namespace,
             def_info.line,
-            def_info.col,
+            sources.column(&file_path, def_info.line, def_info.col),
             symbol_kind,
             documentation,
         );
@@ -263,7 +376,12 @@ unsafe fn extract_structs_and_tables(
 }
 
 /// Extracts all enum and union definitions from the parser.
-unsafe fn extract_enums_and_unions(parser_ptr: *mut ffi::FlatbuffersParser, st: &mut SymbolTable) {
+unsafe fn extract_enums_and_unions(
+    parser_ptr: *mut ffi::FlatbuffersParser,
+    st: &mut SymbolTable,
+    sources: &mut SourceCache,
+    diagnostics: &mut HashMap<PathBuf, Vec<Diagnostic>>,
+) {
     let num_enums = ffi::get_num_enums(parser_ptr);
     for i in 0..num_enums {
         let def_info = ffi::get_enum_info(parser_ptr, i);
@@ -282,16 +400,7 @@ unsafe fn extract_enums_and_unions(parser_ptr: *mut ffi::FlatbuffersParser, st:
         };
 
         let file = c_str_to_string(def_info.file);
-        let Ok(file_path) = fs::canonicalize(&file) else {
-            error!("failed to canonicalize file: {file} for enum/union named: {qualified_name}");
-            continue;
-        };
-
-        if st.contains_key(&qualified_name) {
-            // This should not happen. The flatbuffers parser returns rich errors for duplicate definitions.
-            error!("found duplicate symbol while extracting enums: {qualified_name}");
-            continue;
-        }
+        let file_path = canonicalize_or_self(Path::new(&file));
 
         let mut variants = Vec::new();
         let num_vals = ffi::get_num_enum_vals(parser_ptr, i);
@@ -315,11 +424,18 @@ unsafe fn extract_enums_and_unions(parser_ptr: *mut ffi::FlatbuffersParser, st:
                     .into_iter()
                     .filter_map(|(name, val_info)| {
                         let type_source = c_str_to_string(val_info.type_source);
-                        let type_range = val_info.type_range.into();
-                        let Some(parsed_type) = parse_type(&type_source, type_range) else {
-                            error!("Failed to parse union variant type at {}:{}:{}. Please open a GitHub Issue: https://github.com/smpanaro/flatbuffers-language-server/issues",
-                                file_path.display(), type_range.end.line, type_range.end.character);
-                            return None
+                        let type_range = sources.range(&file_path, val_info.type_range.into());
+                        let Some(parsed_type) =
+                            parse_union_variant_type(&name, &type_source, type_range)
+                        else {
+                            report_unparsed_type(
+                                diagnostics,
+                                "union variant type",
+                                &file_path,
+                                type_range,
+                                &type_source,
+                            );
+                            return None;
                         };
                         let location = crate::symbol_table::Location {
                             path: file_path.clone(),
@@ -338,7 +454,7 @@ unsafe fn extract_enums_and_unions(parser_ptr: *mut ffi::FlatbuffersParser, st:
                 variants: variants
                     .into_iter()
                     .map(|(name, val_info)| {
-                        let documentation = c_str_to_optional_string(val_info.documentation);
+                        let documentation = c_str_to_documentation(val_info.documentation);
                         EnumVariant {
                             name,
                             value: val_info.value,
@@ -350,22 +466,41 @@ unsafe fn extract_enums_and_unions(parser_ptr: *mut ffi::FlatbuffersParser, st:
             })
         };
 
-        let documentation = c_str_to_optional_string(def_info.documentation);
+        let documentation = c_str_to_documentation(def_info.documentation);
 
         let symbol = create_symbol(
             &file_path,
             name,
             namespace,
             def_info.line,
-            def_info.col,
+            sources.column(&file_path, def_info.line, def_info.col),
             symbol_kind,
             documentation,
         );
+        if let Some(existing) = st.get(&qualified_name) {
+            if matches!(existing.kind, SymbolKind::Table(_) | SymbolKind::Struct(_)) {
+                diagnostics::semantic::analyze_type_name_collision(
+                    st,
+                    existing,
+                    &symbol,
+                    diagnostics,
+                );
+            } else {
+                // This should not happen. The flatbuffers parser returns rich errors for duplicate definitions.
+                error!("found duplicate symbol while extracting enums: {qualified_name}");
+            }
+            continue;
+        }
         st.insert(qualified_name, symbol);
     }
 }
 
-unsafe fn extract_rpc_services(parser_ptr: *mut ffi::FlatbuffersParser, st: &mut SymbolTable) {
+unsafe fn extract_rpc_services(
+    parser_ptr: *mut ffi::FlatbuffersParser,
+    st: &mut SymbolTable,
+    sources: &mut SourceCache,
+    diagnostics: &mut HashMap<PathBuf, Vec<Diagnostic>>,
+) {
     let num_services = ffi::get_num_rpc_services(parser_ptr);
     for i in 0..num_services {
         let def_info = ffi::get_rpc_service_info(parser_ptr, i);
@@ -384,10 +519,7 @@ unsafe fn extract_rpc_services(parser_ptr: *mut ffi::FlatbuffersParser, st: &mut
         };
 
         let file = c_str_to_string(def_info.file);
-        let Ok(file_path) = fs::canonicalize(&file) else {
-            error!("failed to canonicalize file: {file} for rpc_service named: {qualified_name}");
-            continue;
-        };
+        let file_path = canonicalize_or_self(Path::new(&file));
 
         if st.contains_key(&qualified_name) {
             // This should not happen. The flatbuffers parser returns rich errors for duplicate definitions.
@@ -404,31 +536,34 @@ unsafe fn extract_rpc_services(parser_ptr: *mut ffi::FlatbuffersParser, st: &mut
             let Some(method_name) = c_str_to_optional_string(method_info.name) else {
                 continue;
             };
+            let col = sources.column(&file_path, method_info.line, method_info.col);
             let range = Range::new(
-                Position::new(
-                    method_info.line,
-                    method_info.col - as_pos_idx(method_name.chars().count()),
-                ),
-                Position::new(method_info.line, method_info.col),
+                Position::new(method_info.line, col - utf16_len(&method_name)),
+                Position::new(method_info.line, col),
             );
-            let documentation = c_str_to_optional_string(method_info.documentation);
+            let documentation = c_str_to_documentation(method_info.documentation);
 
             // Request
             let Some(request_type_name) = c_str_to_optional_string(method_info.request_type_name)
             else {
                 continue;
             };
-            let request_range = method_info.request_range.into();
-            let Some(request_type) = c_str_to_optional_string(method_info.request_source)
-                .and_then(|source| parse_type(&source, request_range))
-                .map(|parsed| RpcMethodType {
+            let request_range = sources.range(&file_path, method_info.request_range.into());
+            let request_source = c_str_to_string(method_info.request_source);
+            let Some(request_type) =
+                parse_type(&request_source, request_range).map(|parsed| RpcMethodType {
                     name: request_type_name,
                     parsed,
                     range: request_range,
                 })
             else {
-                error!("Failed to parse rpc request type at {}:{}:{}. Please open a GitHub Issue: https://github.com/smpanaro/flatbuffers-language-server/issues",
-                        file_path.display(), request_range.end.line, request_range.end.character);
+                report_unparsed_type(
+                    diagnostics,
+                    "rpc request type",
+                    &file_path,
+                    request_range,
+                    &request_source,
+                );
                 continue;
             };
 
@@ -437,17 +572,22 @@ unsafe fn extract_rpc_services(parser_ptr: *mut ffi::FlatbuffersParser, st: &mut
             else {
                 continue;
             };
-            let response_range = method_info.response_range.into();
-            let Some(response_type) = c_str_to_optional_string(method_info.response_source)
-                .and_then(|source| parse_type(&source, response_range))
-                .map(|parsed| RpcMethodType {
+            let response_range = sources.range(&file_path, method_info.response_range.into());
+            let response_source = c_str_to_string(method_info.response_source);
+            let Some(response_type) =
+                parse_type(&response_source, response_range).map(|parsed| RpcMethodType {
                     name: response_type_name,
                     parsed,
                     range: response_range,
                 })
             else {
-                error!("Failed to parse rpc response type at {}:{}:{}. Please open a GitHub Issue: https://github.com/smpanaro/flatbuffers-language-server/issues",
-                        file_path.display(), response_range.end.line, response_range.end.character);
+                report_unparsed_type(
+                    diagnostics,
+                    "rpc response type",
+                    &file_path,
+                    response_range,
+                    &response_source,
+                );
                 continue;
             };
 
@@ -457,18 +597,19 @@ unsafe fn extract_rpc_services(parser_ptr: *mut ffi::FlatbuffersParser, st: &mut
                 documentation,
                 request_type,
                 response_type,
+                streaming: c_str_to_optional_string(method_info.streaming),
             });
         }
 
         let symbol_kind = SymbolKind::RpcService(RpcService { methods });
-        let documentation = c_str_to_optional_string(def_info.documentation);
+        let documentation = c_str_to_documentation(def_info.documentation);
 
         let symbol = create_symbol(
             &file_path,
             name,
             namespace,
             def_info.line,
-            def_info.col,
+            sources.column(&file_path, def_info.line, def_info.col),
             symbol_kind,
             documentation,
         );
@@ -477,7 +618,11 @@ unsafe fn extract_rpc_services(parser_ptr: *mut ffi::FlatbuffersParser, st: &mut
 }
 
 /// Extracts the root type definition from the parser.
-unsafe fn extract_root_type(parser_ptr: *mut ffi::FlatbuffersParser) -> Option<RootTypeInfo> {
+unsafe fn extract_root_type(
+    parser_ptr: *mut ffi::FlatbuffersParser,
+    sources: &mut SourceCache,
+    diagnostics: &mut HashMap<PathBuf, Vec<Diagnostic>>,
+) -> Option<RootTypeInfo> {
     if !ffi::has_root_type(parser_ptr) {
         return None;
     }
@@ -485,16 +630,18 @@ unsafe fn extract_root_type(parser_ptr: *mut ffi::FlatbuffersParser) -> Option<R
     let qualified_name = c_str_to_string(root_def.name);
     let file = c_str_to_string(root_def.file);
 
-    let Ok(file_path) = fs::canonicalize(&file) else {
-        error!("failed to canonicalize file: {file} for root_type named: {qualified_name}");
-        return None;
-    };
+    let file_path = canonicalize_or_self(Path::new(&file));
 
     let type_source = c_str_to_string(root_def.type_source);
-    let type_range = root_def.type_range.into();
+    let type_range = sources.range(&file_path, root_def.type_range.into());
     let Some(parsed_type) = parse_type(&type_source, type_range) else {
-        error!("Failed to parse root type at {}:{}:{}. Please open a GitHub Issue: https://github.com/smpanaro/flatbuffers-language-server/issues",
-            file_path.display(), type_range.end.line, type_range.end.character);
+        report_unparsed_type(
+            diagnostics,
+            "root type",
+            &file_path,
+            type_range,
+            &type_source,
+        );
         return None;
     };
 
@@ -518,10 +665,9 @@ unsafe fn build_include_graph(
         let Some((original_file_path, canonical_file_path)) = c_str_to_optional_string(
             ffi::get_file_with_includes_path(parser_ptr, i),
         )
-        .and_then(|original| {
-            fs::canonicalize(&original)
-                .ok()
-                .map(|canon| (original, canon.to_string_lossy().into_owned()))
+        .map(|original| {
+            let canon = canonicalize_or_self(Path::new(&original));
+            (original, canon.to_string_lossy().into_owned())
         }) else {
             continue;
         };
@@ -564,6 +710,183 @@ unsafe fn c_str_to_optional_string(ptr: *const std::os::raw::c_char) -> Option<S
         .filter(|s| !s.is_empty())
 }
 
+unsafe fn c_str_to_documentation(ptr: *const std::os::raw::c_char) -> Option<String> {
+    c_str_to_optional_string(ptr).and_then(|doc| normalize_documentation(&doc))
+}
+
+/// Clean up the lines of a `///` comment as collected by flatc so they render as markdown.
+///
+/// flatc keeps everything after the `///`, so lines retain the separating space and any
+/// extra slashes (e.g. `////`). Strip those and the common indentation, keeping relative
+/// indentation for things like nested lists and code blocks.
+fn normalize_documentation(doc: &str) -> Option<String> {
+    let lines: Vec<&str> = doc
+        .lines()
+        .map(|line| line.trim_start_matches('/').trim_end())
+        .collect();
+
+    let indent = lines
+        .iter()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    let lines: Vec<&str> = lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or_default())
+        .skip_while(|line| line.is_empty())
+        .collect();
+    let end = lines.iter().rposition(|line| !line.is_empty())?;
+
+    Some(lines[..=end].join("\n"))
+}
+
+static NESTED_FLATBUFFER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\bnested_flatbuffer\s*:\s*"([^"]*)""#)
+        .expect("nested_flatbuffer regex failed to compile")
+});
+
+/// The value after the `=` of a field declaration, before any attributes.
+static DEFAULT_VALUE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^[^=(]*=\s*("[^"]*"|[-+]?[\w.]+)"#)
+        .expect("default value regex failed to compile")
+});
+
+/// The text of each parsed file, used to convert flatc's byte columns to UTF-16 columns.
+///
+/// The root file uses the in-memory content. Included files are read from disk on first use.
+struct SourceCache {
+    documents: HashMap<PathBuf, Rope>,
+}
+
+impl SourceCache {
+    fn new(root_path: &Path, root_content: &str) -> Self {
+        let mut documents = HashMap::new();
+        documents.insert(root_path.to_path_buf(), Rope::from_str(root_content));
+        Self { documents }
+    }
+
+    fn document(&mut self, path: &Path) -> &Rope {
+        self.documents
+            .entry(path.to_path_buf())
+            .or_insert_with(|| Rope::from_str(&fs::read_to_string(path).unwrap_or_default()))
+    }
+
+    /// Convert a byte column on `line` of `path` to a UTF-16 column.
+    fn column(&mut self, path: &Path, line: u32, byte_col: u32) -> u32 {
+        let doc = self.document(path);
+        if (line as usize) >= doc.len_lines() {
+            return byte_col;
+        }
+        byte_to_utf16_col(doc.line(line as usize).chars(), byte_col)
+    }
+
+    /// The root type named by the `nested_flatbuffer` attribute of the field declared on
+    /// `line`, as written between the quotes, and the position of its first character.
+    fn nested_flatbuffer_value(&mut self, path: &Path, line: u32) -> Option<(String, Position)> {
+        let doc = self.document(path);
+        if (line as usize) >= doc.len_lines() {
+            return None;
+        }
+        let declaration: String = doc
+            .slice(doc.line_to_char(line as usize)..)
+            .chars()
+            .take_while(|&c| c != ';')
+            .collect();
+        let value = NESTED_FLATBUFFER_RE.captures(&declaration)?.get(1)?;
+        let start = byte_offset_to_position(&declaration, value.start());
+        Some((
+            value.as_str().to_string(),
+            Position::new(line + start.line, start.character),
+        ))
+    }
+
+    /// The default value of the field whose name starts at byte column `col` of `line`.
+    fn default_value(&mut self, path: &Path, line: u32, col: u32) -> Option<DefaultValue> {
+        let name_col = self.column(path, line, col);
+        let doc = self.document(path);
+        if (line as usize) >= doc.len_lines() {
+            return None;
+        }
+        let name_start = doc.line_to_char(line as usize)
+            + doc
+                .line(line as usize)
+                .try_byte_to_char(col as usize)
+                .ok()?;
+        let declaration: String = doc
+            .slice(name_start..)
+            .chars()
+            .take_while(|&c| c != ';')
+            .collect();
+        let value = DEFAULT_VALUE_RE.captures(&declaration)?.get(1)?;
+
+        // Positions in `declaration` are relative to the field name.
+        let position = |offset| {
+            let relative = byte_offset_to_position(&declaration, offset);
+            if relative.line == 0 {
+                Position::new(line, name_col + relative.character)
+            } else {
+                Position::new(line + relative.line, relative.character)
+            }
+        };
+        Some(DefaultValue {
+            text: value.as_str().to_string(),
+            range: Range::new(position(value.start()), position(value.end())),
+        })
+    }
+
+    /// Convert a range with byte columns, as reported by flatc, to UTF-16 columns.
+    fn range(&mut self, path: &Path, range: Range) -> Range {
+        Range::new(
+            Position::new(
+                range.start.line,
+                self.column(path, range.start.line, range.start.character),
+            ),
+            Position::new(
+                range.end.line,
+                self.column(path, range.end.line, range.end.character),
+            ),
+        )
+    }
+}
+
+/// Log a type that could not be understood and surface it as a diagnostic, so the
+/// definition that is dropped as a result does not disappear without explanation.
+fn report_unparsed_type(
+    diagnostics: &mut HashMap<PathBuf, Vec<Diagnostic>>,
+    kind: &str,
+    path: &Path,
+    range: Range,
+    source: &str,
+) {
+    error!("Failed to parse {kind} at {}:{}:{}. Please open a GitHub Issue: https://github.com/smpanaro/flatbuffers-language-server/issues",
+        path.display(), range.end.line, range.end.character);
+    diagnostics
+        .entry(path.to_path_buf())
+        .or_default()
+        .push(Diagnostic {
+            range,
+            severity: Some(DiagnosticSeverity::INFORMATION),
+            message: format!(
+                "The {kind} `{}` could not be understood, so its definition is ignored. Please open a GitHub Issue: https://github.com/smpanaro/flatbuffers-language-server/issues",
+                source.trim()
+            ),
+            ..Default::default()
+        });
+}
+
+/// Parse the type of a union variant, as written in the source.
+///
+/// `name` is the fully-qualified name flatc resolved the variant to. The written type may be
+/// shorter (e.g. relative to the current namespace) but its last part must be the same, otherwise
+/// the range flatc recorded does not cover the type name.
+fn parse_union_variant_type(name: &str, type_source: &str, range: Range) -> Option<ParsedType> {
+    let parsed_type = parse_type(type_source, range)?;
+    let short_name = name.rsplit('.').next().unwrap_or(name);
+    (parsed_type.type_name.text == short_name && !parsed_type.is_vector).then_some(parsed_type)
+}
+
 /// Helper to create a symbol and its location.
 fn create_symbol(
     file_path: &Path,
@@ -577,7 +900,7 @@ fn create_symbol(
     let location = crate::symbol_table::Location {
         path: file_path.to_path_buf(),
         range: Range::new(
-            Position::new(line, col - as_pos_idx(name.chars().count())),
+            Position::new(line, col - utf16_len(&name)),
             Position::new(line, col),
         ),
     };
@@ -590,3 +913,129 @@ fn create_symbol(
     };
     Symbol { info, kind }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nul_byte_is_reported() {
+        let path = PathBuf::from("a.fbs");
+        let result = FlatcFFIParser.parse(&path, "table A {}\n/* é */ table\0 B {}", &[]);
+        assert!(result.symbol_table.is_none());
+        let diagnostic = &result.diagnostics[&path][0];
+        assert_eq!(
+            diagnostic.range,
+            Range::new(Position::new(1, 13), Position::new(1, 14))
+        );
+    }
+
+    #[test]
+    fn test_invalid_utf8_is_reported() {
+        let path = PathBuf::from("a.fbs");
+        let bytes = b"table A {}\ntable \xff B {}";
+        let result = ParseResult::invalid_utf8(&path, bytes, 17);
+        let diagnostic = &result.diagnostics[&path][0];
+        assert_eq!(diagnostic.message, "File is not valid UTF-8");
+        assert_eq!(
+            diagnostic.range,
+            Range::new(Position::new(1, 6), Position::new(1, 7))
+        );
+    }
+
+    #[test]
+    fn test_report_unparsed_type() {
+        let mut diagnostics = HashMap::new();
+        let path = PathBuf::from("a.fbs");
+        let range = Range::new(Position::new(1, 7), Position::new(1, 9));
+        report_unparsed_type(&mut diagnostics, "field type", &path, range, "[]");
+
+        let diagnostic = &diagnostics[&path][0];
+        assert_eq!(diagnostic.range, range);
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::INFORMATION));
+        assert!(diagnostic
+            .message
+            .starts_with("The field type `[]` could not be understood"));
+    }
+
+    #[test]
+    fn test_parse_union_variant_type() {
+        let range = Range::new(Position::new(3, 18), Position::new(3, 28));
+        let parsed = parse_union_variant_type("Global.Foo", "Global.Foo", range).unwrap();
+        assert_eq!(
+            parsed.type_name.range,
+            Range::new(Position::new(3, 25), Position::new(3, 28))
+        );
+        assert_eq!(parsed.namespace.len(), 1);
+
+        let range = Range::new(Position::new(3, 18), Position::new(3, 21));
+        let parsed = parse_union_variant_type("Global.Foo", "Foo", range).unwrap();
+        assert_eq!(parsed.type_name.range, range);
+    }
+
+    #[test]
+    fn test_parse_union_variant_type_rejects_mismatched_source() {
+        let range = Range::new(Position::new(3, 12), Position::new(3, 16));
+        assert_eq!(parse_union_variant_type("Global.Foo", "GFoo", range), None);
+        assert_eq!(parse_union_variant_type("Global.Foo", "", range), None);
+    }
+
+    #[test]
+    fn test_nested_flatbuffer_value_spans_lines() {
+        let path = PathBuf::from("a.fbs");
+        let content = "table A {\n  b: [ubyte] (\n    nested_flatbuffer: \"NS.é\"\n  );\n}";
+        let mut sources = SourceCache::new(&path, content);
+        assert_eq!(
+            sources.nested_flatbuffer_value(&path, 1),
+            Some(("NS.é".to_string(), Position::new(2, 24)))
+        );
+        assert_eq!(sources.nested_flatbuffer_value(&path, 3), None);
+    }
+
+    #[test]
+    fn test_default_value() {
+        let path = PathBuf::from("a.fbs");
+        let content = "table A { é: int = 1; level: Game.Difficulty =\n  Hard (id: 2); b: int; }";
+        let mut sources = SourceCache::new(&path, content);
+        assert_eq!(
+            sources.default_value(&path, 0, 10),
+            Some(DefaultValue {
+                text: "1".to_string(),
+                range: Range::new(Position::new(0, 19), Position::new(0, 20)),
+            })
+        );
+        assert_eq!(
+            sources.default_value(&path, 0, 23),
+            Some(DefaultValue {
+                text: "Hard".to_string(),
+                range: Range::new(Position::new(1, 2), Position::new(1, 6)),
+            })
+        );
+        assert_eq!(sources.default_value(&path, 1, 16), None);
+    }
+
+    #[test]
+    fn test_normalize_documentation_strips_separator() {
+        assert_eq!(
+            normalize_documentation(" A table.\n Second line."),
+            Some("A table.\nSecond line.".to_string())
+        );
+    }
+
+    #[test]
+    fn test_normalize_documentation_keeps_relative_indent() {
+        assert_eq!(
+            normalize_documentation(" List:\n   - item\n"),
+            Some("List:\n  - item".to_string())
+        );
+    }
+
+    #[test]
+    fn test_normalize_documentation_extra_slashes_and_blank_lines() {
+        assert_eq!(
+            normalize_documentation("\n/ Banner.  \n\n"),
+            Some("Banner.".to_string())
+        );
+        assert_eq!(normalize_documentation(" \n  "), None);
+    }
+}
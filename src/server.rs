@@ -1,34 +1,53 @@
 use crate::analysis::Analyzer;
+use crate::config::Config;
 use crate::document_store::DocumentStore;
 #[cfg(any(test, feature = "test-harness"))]
 use crate::ext::all_diagnostics::AllDiagnostics;
+#[cfg(any(test, feature = "test-harness"))]
+use crate::ext::all_symbols::{AllSymbols, IndexedSymbol};
+use crate::ext::duration::DurationFormat;
+use crate::ext::file_health::{DiagnosticCounts, FileHealth};
 use crate::handlers::{
-    code_action, completion, goto_definition, hover, lifecycle, references, rename,
+    call_hierarchy, code_action, completion, execute_command, goto_declaration, goto_definition,
+    goto_implementation, hover, lifecycle, on_type_formatting, references, rename, selection_range,
     workspace_symbol,
 };
-use crate::utils::paths::path_buf_to_uri;
+use crate::utils::position_encoding::{Direction, PositionConverter, PositionEncoding};
 use log::{error, info, warn};
-use std::path::PathBuf;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-use tokio::sync::Notify;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::{Notify, RwLock};
 use tower_lsp_server::jsonrpc::Result;
 #[cfg(any(test, feature = "test-harness"))]
 use tower_lsp_server::lsp_types::request::Request;
-use tower_lsp_server::lsp_types::request::WorkDoneProgressCreate;
+use tower_lsp_server::lsp_types::request::{
+    GotoDeclarationParams, GotoDeclarationResponse, GotoImplementationParams,
+    GotoImplementationResponse, WorkDoneProgressCreate,
+};
 use tower_lsp_server::lsp_types::{
-    notification, CodeActionKind, CodeActionOptions, CodeActionParams,
-    CodeActionProviderCapability, CodeActionResponse, CompletionOptions, CompletionParams,
-    CompletionResponse, Diagnostic, DidChangeTextDocumentParams, DidChangeWatchedFilesParams,
+    notification, CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+    CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
+    CallHierarchyServerCapability, CodeActionKind, CodeActionOptions, CodeActionParams,
+    CodeActionProviderCapability, CodeActionResponse, CompletionItem, CompletionOptions,
+    CompletionParams, CompletionResponse, DeclarationCapability, Diagnostic,
+    DidChangeConfigurationParams, DidChangeTextDocumentParams, DidChangeWatchedFilesParams,
     DidChangeWatchedFilesRegistrationOptions, DidChangeWorkspaceFoldersParams,
     DidCloseTextDocumentParams, DidOpenTextDocumentParams, DidSaveTextDocumentParams,
-    FileSystemWatcher, GlobPattern, GotoDefinitionParams, GotoDefinitionResponse, Hover,
-    HoverParams, HoverProviderCapability, InitializeParams, InitializeResult, InitializedParams,
-    Location, NumberOrString, OneOf, PrepareRenameResponse, ProgressParams, ProgressParamsValue,
-    ReferenceParams, Registration, RenameOptions, RenameParams, ServerCapabilities, ServerInfo,
-    SymbolInformation, TextDocumentPositionParams, TextDocumentSyncCapability,
-    TextDocumentSyncKind, TextDocumentSyncOptions, WorkDoneProgress, WorkDoneProgressBegin,
-    WorkDoneProgressCreateParams, WorkDoneProgressEnd, WorkDoneProgressOptions, WorkspaceEdit,
+    DocumentOnTypeFormattingOptions, DocumentOnTypeFormattingParams, ExecuteCommandOptions,
+    ExecuteCommandParams, FileSystemWatcher, GlobPattern, GotoDefinitionParams,
+    GotoDefinitionResponse, Hover, HoverParams, HoverProviderCapability,
+    ImplementationProviderCapability, InitializeParams, InitializeResult, InitializedParams,
+    LSPAny, Location, LogTraceParams, NumberOrString, OneOf, PrepareRenameResponse, ProgressParams,
+    ProgressParamsValue, ReferenceParams, Registration, RenameOptions, RenameParams,
+    SelectionRange, SelectionRangeParams, SelectionRangeProviderCapability, ServerCapabilities,
+    ServerInfo, SetTraceParams, SymbolInformation, TextDocumentPositionParams,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions, TextEdit,
+    TraceValue, WorkDoneProgress, WorkDoneProgressBegin, WorkDoneProgressCreateParams,
+    WorkDoneProgressEnd, WorkDoneProgressOptions, WorkDoneProgressReport, WorkspaceEdit,
     WorkspaceFoldersServerCapabilities, WorkspaceServerCapabilities, WorkspaceSymbol,
     WorkspaceSymbolParams,
 };
@@ -39,6 +58,14 @@ pub struct Backend {
     pub client: Client,
     pub documents: Arc<DocumentStore>,
     pub analyzer: Arc<Analyzer>,
+    pub config: Arc<RwLock<Config>>,
+    pub client_supports_configuration: AtomicBool,
+    /// Whether the client applies workspace edits with `documentChanges` and change annotations.
+    pub client_supports_change_annotations: AtomicBool,
+    /// Set by the client through `initialize` and `$/setTrace`.
+    pub trace: Arc<RwLock<TraceValue>>,
+    /// Negotiated during initialize. Unset means UTF-16.
+    pub position_encoding: OnceLock<PositionEncoding>,
     // Initialize scan.
     ready: AtomicBool,
     notify_ready: Notify,
@@ -53,6 +80,11 @@ impl Backend {
             client,
             documents,
             analyzer: analysis,
+            config: Arc::new(RwLock::new(Config::default())),
+            client_supports_configuration: AtomicBool::new(false),
+            client_supports_change_annotations: AtomicBool::new(false),
+            trace: Arc::new(RwLock::new(TraceValue::Off)),
+            position_encoding: OnceLock::new(),
             ready: AtomicBool::new(false),
             notify_ready: Notify::new(),
         }
@@ -71,6 +103,7 @@ impl LanguageServer for Backend {
                 version: Some(env!("CARGO_PKG_VERSION").to_string()),
             }),
             capabilities: ServerCapabilities {
+                position_encoding: Some(self.position_encoding().kind()),
                 text_document_sync: Some(TextDocumentSyncCapability::Options(
                     TextDocumentSyncOptions {
                         open_close: Some(true),
@@ -95,16 +128,22 @@ impl LanguageServer for Backend {
                 definition_provider: Some(OneOf::Left(true)),
                 references_provider: Some(OneOf::Left(true)),
                 completion_provider: Some(CompletionOptions {
-                    resolve_provider: Some(false),
+                    resolve_provider: Some(true),
                     trigger_characters: Some(vec![
                         ":".to_string(),
                         " ".to_string(),
                         "(".to_string(),
+                        "[".to_string(),
                         ",".to_string(),
                         ".".to_string(),
                     ]),
                     work_done_progress_options: WorkDoneProgressOptions::default(),
-                    all_commit_characters: None,
+                    all_commit_characters: Some(
+                        completion::COMMIT_CHARACTERS
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect(),
+                    ),
                     completion_item: None,
                 }),
                 code_action_provider: Some(CodeActionProviderCapability::Options(
@@ -118,6 +157,26 @@ impl LanguageServer for Backend {
                     work_done_progress_options: WorkDoneProgressOptions::default(),
                 })),
                 workspace_symbol_provider: Some(OneOf::Left(true)),
+                selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+                call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+                declaration_provider: Some(DeclarationCapability::Simple(true)),
+                implementation_provider: Some(ImplementationProviderCapability::Simple(true)),
+                document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+                    first_trigger_character: on_type_formatting::TRIGGER_CHARACTERS[0].to_string(),
+                    more_trigger_character: Some(
+                        on_type_formatting::TRIGGER_CHARACTERS[1..]
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect(),
+                    ),
+                }),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: execute_command::COMMANDS
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect(),
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                }),
                 ..ServerCapabilities::default()
             },
         })
@@ -127,59 +186,39 @@ impl LanguageServer for Backend {
         info!("Client initialized!");
 
         let token = NumberOrString::String("initial-repo-scan".to_string());
-        if let Err(err) = self
-            .client
-            .send_request::<WorkDoneProgressCreate>(WorkDoneProgressCreateParams {
-                token: token.clone(),
-            })
-            .await
-        {
-            error!("failed to create initialized scan progress: {err}");
-        }
+        self.begin_scan_progress(token.clone()).await;
 
-        self.client
-            .send_notification::<notification::Progress>(ProgressParams {
-                token: token.clone(),
-                value: ProgressParamsValue::WorkDone(WorkDoneProgress::Begin(
-                    WorkDoneProgressBegin {
-                        title: "flatbuffers".to_string(),
-                        cancellable: Some(false),
-                        message: Some("discovering files".to_string()),
-                        percentage: None,
-                    },
-                )),
-            })
-            .await;
+        lifecycle::refresh_config(self).await;
 
-        let diagnostics = lifecycle::handle_initialized(self).await;
+        let report = |percentage| -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+            Box::pin(self.report_scan_progress(token.clone(), percentage))
+        };
+        let diagnostics = lifecycle::handle_initialized(self, Some(&report)).await;
         self.publish_diagnostics(diagnostics).await;
         self.mark_ready();
-
-        self.client
-            .send_notification::<notification::Progress>(ProgressParams {
-                token: token.clone(),
-                value: ProgressParamsValue::WorkDone(WorkDoneProgress::End(WorkDoneProgressEnd {
-                    message: Some("complete".to_string()),
-                })),
-            })
-            .await;
-
-        let register_result = self
-            .client
-            .register_capability(vec![Registration {
-                id: "fbs-watcher".to_string(),
-                method: "workspace/didChangeWatchedFiles".to_string(),
-                register_options: Some(
-                    serde_json::to_value(DidChangeWatchedFilesRegistrationOptions {
-                        watchers: vec![FileSystemWatcher {
-                            glob_pattern: GlobPattern::String("**/*.fbs".to_string()),
-                            kind: None, // None means all changes
-                        }],
-                    })
-                    .unwrap_or_default(),
-                ),
-            }])
-            .await;
+        self.end_scan_progress(token).await;
+
+        let mut registrations = vec![Registration {
+            id: "fbs-watcher".to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: Some(
+                serde_json::to_value(DidChangeWatchedFilesRegistrationOptions {
+                    watchers: vec![FileSystemWatcher {
+                        glob_pattern: GlobPattern::String("**/*.fbs".to_string()),
+                        kind: None, // None means all changes
+                    }],
+                })
+                .unwrap_or_default(),
+            ),
+        }];
+        if self.client_supports_configuration.load(Ordering::Acquire) {
+            registrations.push(Registration {
+                id: "fbs-configuration".to_string(),
+                method: "workspace/didChangeConfiguration".to_string(),
+                register_options: None,
+            });
+        }
+        let register_result = self.client.register_capability(registrations).await;
         if let Err(register_error) = register_result {
             warn!(
                 "Failed to register file watcher, some features will be unstable: {register_error}"
@@ -229,52 +268,189 @@ impl LanguageServer for Backend {
         self.publish_diagnostics(diagnostics).await;
     }
 
-    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+    async fn did_change_configuration(&self, _: DidChangeConfigurationParams) {
+        self.wait_until_ready().await;
+        let diagnostics = lifecycle::handle_did_change_configuration(self).await;
+        self.publish_diagnostics(diagnostics).await;
+    }
+
+    async fn hover(&self, mut params: HoverParams) -> Result<Option<Hover>> {
         self.wait_until_ready().await;
         let snapshot = self.analyzer.snapshot().await;
-        Ok(hover::handle_hover(&snapshot, params))
+        self.positions(Direction::ToServer)
+            .text_document_position(&mut params.text_document_position_params);
+        let uri = params
+            .text_document_position_params
+            .text_document
+            .uri
+            .clone();
+        let reference_counts = self.config.read().await.hover_reference_counts;
+        let mut result = hover::handle_hover(&snapshot, params, reference_counts);
+        if let Some(hover) = result.as_mut() {
+            self.positions(Direction::ToClient).hover(&uri, hover);
+        }
+        Ok(result)
     }
 
     async fn goto_definition(
         &self,
-        params: GotoDefinitionParams,
+        mut params: GotoDefinitionParams,
     ) -> Result<Option<GotoDefinitionResponse>> {
         self.wait_until_ready().await;
         let snapshot = self.analyzer.snapshot().await;
-        Ok(goto_definition::handle_goto_definition(&snapshot, params))
+        self.positions(Direction::ToServer)
+            .text_document_position(&mut params.text_document_position_params);
+        let uri = params
+            .text_document_position_params
+            .text_document
+            .uri
+            .clone();
+        let mut result = goto_definition::handle_goto_definition(&snapshot, params);
+        if let Some(response) = result.as_mut() {
+            self.positions(Direction::ToClient)
+                .goto_definition(&uri, response);
+        }
+        Ok(result)
+    }
+
+    async fn goto_declaration(
+        &self,
+        mut params: GotoDeclarationParams,
+    ) -> Result<Option<GotoDeclarationResponse>> {
+        self.wait_until_ready().await;
+        let snapshot = self.analyzer.snapshot().await;
+        self.positions(Direction::ToServer)
+            .text_document_position(&mut params.text_document_position_params);
+        let uri = params
+            .text_document_position_params
+            .text_document
+            .uri
+            .clone();
+        let mut result = goto_declaration::handle_goto_declaration(&snapshot, params);
+        if let Some(response) = result.as_mut() {
+            self.positions(Direction::ToClient)
+                .goto_definition(&uri, response);
+        }
+        Ok(result)
     }
 
-    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+    async fn goto_implementation(
+        &self,
+        mut params: GotoImplementationParams,
+    ) -> Result<Option<GotoImplementationResponse>> {
         self.wait_until_ready().await;
         let snapshot = self.analyzer.snapshot().await;
-        Ok(references::handle_references(&snapshot, params))
+        self.positions(Direction::ToServer)
+            .text_document_position(&mut params.text_document_position_params);
+        let uri = params
+            .text_document_position_params
+            .text_document
+            .uri
+            .clone();
+        let mut result = goto_implementation::handle_goto_implementation(&snapshot, params);
+        if let Some(response) = result.as_mut() {
+            self.positions(Direction::ToClient)
+                .goto_definition(&uri, response);
+        }
+        Ok(result)
     }
 
-    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+    async fn references(&self, mut params: ReferenceParams) -> Result<Option<Vec<Location>>> {
         self.wait_until_ready().await;
         let snapshot = self.analyzer.snapshot().await;
-        Ok(completion::handle_completion(&snapshot, &params))
+        self.positions(Direction::ToServer)
+            .text_document_position(&mut params.text_document_position);
+        let mut result = references::handle_references(&snapshot, params);
+        if let Some(locations) = result.as_mut() {
+            self.positions(Direction::ToClient).locations(locations);
+        }
+        Ok(result)
     }
 
-    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+    async fn completion(&self, mut params: CompletionParams) -> Result<Option<CompletionResponse>> {
         self.wait_until_ready().await;
         let snapshot = self.analyzer.snapshot().await;
-        Ok(code_action::handle_code_action(&snapshot, params))
+        self.positions(Direction::ToServer)
+            .text_document_position(&mut params.text_document_position);
+        let mut result = completion::handle_completion(&snapshot, &params);
+        if let Some(response) = result.as_mut() {
+            self.positions(Direction::ToClient)
+                .completion_response(&params.text_document_position.text_document.uri, response);
+        }
+        Ok(result)
+    }
+
+    async fn completion_resolve(&self, params: CompletionItem) -> Result<CompletionItem> {
+        self.wait_until_ready().await;
+        let snapshot = self.analyzer.snapshot().await;
+        let mut item = completion::handle_completion_resolve(&snapshot, params);
+        // Only the edits added here are converted, the rest came back from the client as-is.
+        if let Some(path) = completion::completion_item_path(&item) {
+            if let Some(edits) = item.additional_text_edits.as_mut() {
+                self.positions(Direction::ToClient)
+                    .text_edits_in(&path, edits);
+            }
+        }
+        Ok(item)
+    }
+
+    async fn code_action(
+        &self,
+        mut params: CodeActionParams,
+    ) -> Result<Option<CodeActionResponse>> {
+        self.wait_until_ready().await;
+        let snapshot = self.analyzer.snapshot().await;
+        let uri = params.text_document.uri.clone();
+        let to_server = self.positions(Direction::ToServer);
+        to_server.range(&uri, &mut params.range);
+        to_server.diagnostics(&uri, &mut params.context.diagnostics);
+        let mut result = code_action::handle_code_action(&snapshot, params);
+        if let Some(actions) = result.as_mut() {
+            self.positions(Direction::ToClient)
+                .code_actions(&uri, actions);
+        }
+        Ok(result)
     }
 
     async fn prepare_rename(
         &self,
-        params: TextDocumentPositionParams,
+        mut params: TextDocumentPositionParams,
     ) -> Result<Option<PrepareRenameResponse>> {
         self.wait_until_ready().await;
         let snapshot = self.analyzer.snapshot().await;
-        Ok(rename::prepare_rename(&snapshot, &params))
+        self.positions(Direction::ToServer)
+            .text_document_position(&mut params);
+        let mut result = rename::prepare_rename(&snapshot, &params)?;
+        if let Some(response) = result.as_mut() {
+            self.positions(Direction::ToClient)
+                .prepare_rename(&params.text_document.uri, response);
+        }
+        Ok(result)
     }
 
-    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+    async fn rename(&self, mut params: RenameParams) -> Result<Option<WorkspaceEdit>> {
         self.wait_until_ready().await;
         let snapshot = self.analyzer.snapshot().await;
-        Ok(rename::rename(&snapshot, params))
+        self.positions(Direction::ToServer)
+            .text_document_position(&mut params.text_document_position);
+        let change_annotations = self
+            .client_supports_change_annotations
+            .load(Ordering::Acquire);
+        let mut result =
+            rename::rename(&snapshot, params, change_annotations).map_err(|mut error| {
+                // A name collision carries the location of the existing definition.
+                if let Some(Ok(mut location)) =
+                    error.data.clone().map(serde_json::from_value::<Location>)
+                {
+                    self.positions(Direction::ToClient).location(&mut location);
+                    error.data = serde_json::to_value(location).ok();
+                }
+                error
+            })?;
+        if let Some(edit) = result.as_mut() {
+            self.positions(Direction::ToClient).workspace_edit(edit);
+        }
+        Ok(result)
     }
 
     async fn symbol(
@@ -283,26 +459,217 @@ impl LanguageServer for Backend {
     ) -> Result<Option<OneOf<Vec<SymbolInformation>, Vec<WorkspaceSymbol>>>> {
         self.wait_until_ready().await;
         let snapshot = self.analyzer.snapshot().await;
-        let result = workspace_symbol::handle_workspace_symbol(&snapshot, &params);
+        let mut result = workspace_symbol::handle_workspace_symbol(&snapshot, &params);
+        self.positions(Direction::ToClient)
+            .workspace_symbols(&mut result);
         Ok(Some(OneOf::Right(result)))
     }
+
+    async fn selection_range(
+        &self,
+        mut params: SelectionRangeParams,
+    ) -> Result<Option<Vec<SelectionRange>>> {
+        self.wait_until_ready().await;
+        let snapshot = self.analyzer.snapshot().await;
+        let uri = params.text_document.uri.clone();
+        let to_server = self.positions(Direction::ToServer);
+        for position in &mut params.positions {
+            to_server.position(&uri, position);
+        }
+        let mut result = selection_range::handle_selection_range(&snapshot, &params);
+        let to_client = self.positions(Direction::ToClient);
+        for selection in result.iter_mut().flatten() {
+            to_client.selection_range(&uri, selection);
+        }
+        Ok(result)
+    }
+
+    async fn prepare_call_hierarchy(
+        &self,
+        mut params: CallHierarchyPrepareParams,
+    ) -> Result<Option<Vec<CallHierarchyItem>>> {
+        self.wait_until_ready().await;
+        let snapshot = self.analyzer.snapshot().await;
+        self.positions(Direction::ToServer)
+            .text_document_position(&mut params.text_document_position_params);
+        let mut result = call_hierarchy::prepare_call_hierarchy(&snapshot, &params);
+        let to_client = self.positions(Direction::ToClient);
+        for item in result.iter_mut().flatten() {
+            to_client.call_hierarchy_item(item);
+        }
+        Ok(result)
+    }
+
+    async fn incoming_calls(
+        &self,
+        mut params: CallHierarchyIncomingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyIncomingCall>>> {
+        self.wait_until_ready().await;
+        let snapshot = self.analyzer.snapshot().await;
+        self.positions(Direction::ToServer)
+            .call_hierarchy_item(&mut params.item);
+        let mut result = call_hierarchy::incoming_calls(&snapshot, &params);
+        let to_client = self.positions(Direction::ToClient);
+        for call in result.iter_mut().flatten() {
+            to_client.incoming_call(call);
+        }
+        Ok(result)
+    }
+
+    async fn outgoing_calls(
+        &self,
+        mut params: CallHierarchyOutgoingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyOutgoingCall>>> {
+        self.wait_until_ready().await;
+        let snapshot = self.analyzer.snapshot().await;
+        self.positions(Direction::ToServer)
+            .call_hierarchy_item(&mut params.item);
+        let mut result = call_hierarchy::outgoing_calls(&snapshot, &params);
+        let to_client = self.positions(Direction::ToClient);
+        for call in result.iter_mut().flatten() {
+            to_client.outgoing_call(&params.item.uri, call);
+        }
+        Ok(result)
+    }
+
+    async fn on_type_formatting(
+        &self,
+        mut params: DocumentOnTypeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        self.wait_until_ready().await;
+        let snapshot = self.analyzer.snapshot().await;
+        self.positions(Direction::ToServer)
+            .text_document_position(&mut params.text_document_position);
+        let mut result = on_type_formatting::handle_on_type_formatting(&snapshot, &params);
+        if let Some(edits) = result.as_mut() {
+            self.positions(Direction::ToClient)
+                .text_edits(&params.text_document_position.text_document.uri, edits);
+        }
+        Ok(result)
+    }
+
+    async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<LSPAny>> {
+        self.wait_until_ready().await;
+        execute_command::handle_execute_command(self, params).await
+    }
 }
 
 // Convenience.
 impl Backend {
-    async fn publish_diagnostics(&self, diagnostics: Vec<(PathBuf, Vec<Diagnostic>)>) {
-        let uri_diagnostics = diagnostics
-            .into_iter()
-            .filter_map(|(pb, ds)| path_buf_to_uri(&pb).ok().map(|u| (u, ds)))
-            .collect::<Vec<_>>();
-
-        for (uri, diags) in uri_diagnostics {
-            self.client.publish_diagnostics(uri, diags, None).await;
+    pub(crate) fn position_encoding(&self) -> PositionEncoding {
+        self.position_encoding.get().copied().unwrap_or_default()
+    }
+
+    /// Converts positions to or from the encoding negotiated with the client.
+    pub(crate) fn positions(&self, direction: Direction) -> PositionConverter<'_> {
+        PositionConverter::new(self.position_encoding(), direction, &self.documents)
+    }
+
+    /// Publish diagnostics with the version of the document they were computed from,
+    /// so that clients can ignore them if the document has changed since.
+    pub(crate) async fn publish_diagnostics(&self, diagnostics: Vec<(PathBuf, Vec<Diagnostic>)>) {
+        self.publisher().publish(diagnostics).await;
+    }
+
+    /// Handles `$/setTrace`, which tower-lsp does not route to [`LanguageServer`].
+    pub async fn set_trace(&self, params: SetTraceParams) {
+        lifecycle::handle_set_trace(self, params.value).await;
+    }
+
+    fn publisher(&self) -> DiagnosticPublisher {
+        DiagnosticPublisher {
+            client: self.client.clone(),
+            analyzer: Arc::clone(&self.analyzer),
+            documents: Arc::clone(&self.documents),
+            config: Arc::clone(&self.config),
+            trace: Arc::clone(&self.trace),
+            position_encoding: self.position_encoding(),
+        }
+    }
+
+    /// Revalidate the files that include `path` once edits have paused for
+    /// [`REVALIDATION_DELAY`]. This runs in the background so that typing is not
+    /// slowed down by reparsing every dependent of a widely included file.
+    pub(crate) async fn schedule_revalidation(&self, path: &Path) {
+        let Some(generation) = self.analyzer.schedule_revalidation(path).await else {
+            return;
+        };
+        let publisher = self.publisher();
+        tokio::spawn(async move {
+            tokio::time::sleep(REVALIDATION_DELAY).await;
+            let diagnostics = publisher.analyzer.revalidate_dependents(generation).await;
+            publisher.publish(diagnostics).await;
+        });
+    }
+}
+
+/// How long edits must pause before the dependents of the edited files are revalidated.
+const REVALIDATION_DELAY: Duration = Duration::from_millis(500);
+
+/// The parts of [`Backend`] needed to publish diagnostics from a background task.
+struct DiagnosticPublisher {
+    client: Client,
+    analyzer: Arc<Analyzer>,
+    documents: Arc<DocumentStore>,
+    config: Arc<RwLock<Config>>,
+    trace: Arc<RwLock<TraceValue>>,
+    position_encoding: PositionEncoding,
+}
+
+impl DiagnosticPublisher {
+    async fn publish(&self, diagnostics: Vec<(PathBuf, Vec<Diagnostic>)>) {
+        self.log_parse_times().await;
+
+        let enabled = self.config.read().await.diagnostics.clone();
+        let uri_diagnostics = {
+            let snapshot = self.analyzer.snapshot().await;
+            diagnostics
+                .into_iter()
+                .filter_map(|(pb, ds)| {
+                    let version = snapshot.diagnostics.version(&pb);
+                    self.documents.uri(&pb).ok().map(|u| (u, ds, version))
+                })
+                .map(|(u, ds, version)| {
+                    (
+                        u,
+                        ds.into_iter().filter(|d| enabled.is_enabled(d)).collect(),
+                        version,
+                    )
+                })
+                .collect::<Vec<(_, Vec<_>, _)>>()
+        };
+
+        let to_client =
+            PositionConverter::new(self.position_encoding, Direction::ToClient, &self.documents);
+        for (uri, mut diags, version) in uri_diagnostics {
+            to_client.diagnostics(&uri, &mut diags);
+            self.client.publish_diagnostics(uri, diags, version).await;
+        }
+    }
+
+    /// Send a `$/logTrace` for each file parsed since the last publish.
+    async fn log_parse_times(&self) {
+        let trace = *self.trace.read().await;
+        if trace == TraceValue::Off {
+            return;
+        }
+        for (path, elapsed) in self.analyzer.take_parse_times() {
+            let name = path
+                .file_name()
+                .unwrap_or(path.as_os_str())
+                .to_string_lossy();
+            let params = LogTraceParams {
+                message: format!("parsed {name} in {}", elapsed.log_str()),
+                verbose: (trace == TraceValue::Verbose).then(|| path.display().to_string()),
+            };
+            self.client
+                .send_notification::<notification::LogTrace>(params)
+                .await;
         }
     }
 }
 
-// Initial scan.
+// Workspace scans.
 impl Backend {
     async fn wait_until_ready(&self) {
         if self.ready.load(Ordering::Acquire) {
@@ -315,6 +682,59 @@ impl Backend {
         self.ready.store(true, Ordering::Release);
         self.notify_ready.notify_waiters();
     }
+
+    /// Create the progress `token` and report the start of a workspace scan.
+    pub(crate) async fn begin_scan_progress(&self, token: NumberOrString) {
+        if let Err(err) = self
+            .client
+            .send_request::<WorkDoneProgressCreate>(WorkDoneProgressCreateParams {
+                token: token.clone(),
+            })
+            .await
+        {
+            error!("failed to create scan progress: {err}");
+        }
+
+        self.client
+            .send_notification::<notification::Progress>(ProgressParams {
+                token,
+                value: ProgressParamsValue::WorkDone(WorkDoneProgress::Begin(
+                    WorkDoneProgressBegin {
+                        title: "flatbuffers".to_string(),
+                        cancellable: Some(false),
+                        message: Some("discovering files".to_string()),
+                        percentage: Some(0),
+                    },
+                )),
+            })
+            .await;
+    }
+
+    pub(crate) async fn end_scan_progress(&self, token: NumberOrString) {
+        self.client
+            .send_notification::<notification::Progress>(ProgressParams {
+                token,
+                value: ProgressParamsValue::WorkDone(WorkDoneProgress::End(WorkDoneProgressEnd {
+                    message: Some("complete".to_string()),
+                })),
+            })
+            .await;
+    }
+
+    pub(crate) async fn report_scan_progress(&self, token: NumberOrString, percentage: u32) {
+        self.client
+            .send_notification::<notification::Progress>(ProgressParams {
+                token,
+                value: ProgressParamsValue::WorkDone(WorkDoneProgress::Report(
+                    WorkDoneProgressReport {
+                        cancellable: Some(false),
+                        message: Some("parsing files".to_string()),
+                        percentage: Some(percentage),
+                    },
+                )),
+            })
+            .await;
+    }
 }
 
 #[cfg(any(test, feature = "test-harness"))]
@@ -350,13 +770,68 @@ impl Backend {
     ) -> Result<<AllDiagnostics as Request>::Result> {
         let snapshot = self.analyzer.snapshot().await;
         let diagnostics = snapshot.diagnostics.all();
+        let to_client = self.positions(Direction::ToClient);
         #[allow(
             clippy::mutable_key_type,
             reason = "for consistency with lsp_types::notification::PublishDiagnosticsParams"
         )]
         let result = diagnostics
             .iter()
-            .filter_map(|(path, diags)| path_buf_to_uri(path).ok().map(|uri| (uri, diags.clone())))
+            .filter_map(|(path, diags)| {
+                let uri = self.documents.uri(path).ok()?;
+                let mut diags = diags.clone();
+                to_client.diagnostics(&uri, &mut diags);
+                Some((uri, diags))
+            })
+            .collect();
+        Ok(result)
+    }
+
+    /// Count the diagnostics of each file by severity, leaving out those disabled by the
+    /// configuration just as [`Backend::publish_diagnostics`] does.
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn file_health(
+        &self,
+        (): <FileHealth as Request>::Params,
+    ) -> Result<<FileHealth as Request>::Result> {
+        let enabled = self.config.read().await.diagnostics.clone();
+        let snapshot = self.analyzer.snapshot().await;
+        #[allow(
+            clippy::mutable_key_type,
+            reason = "for consistency with lsp_types::notification::PublishDiagnosticsParams"
+        )]
+        let result = snapshot
+            .diagnostics
+            .all()
+            .iter()
+            .filter_map(|(path, diags)| {
+                let uri = self.documents.uri(path).ok()?;
+                let counts = diags
+                    .iter()
+                    .filter(|d| enabled.is_enabled(d))
+                    .collect::<DiagnosticCounts>();
+                Some((uri, counts))
+            })
+            .collect();
+        Ok(result)
+    }
+
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn all_symbols(
+        &self,
+        (): <AllSymbols as Request>::Params,
+    ) -> Result<<AllSymbols as Request>::Result> {
+        let snapshot = self.analyzer.snapshot().await;
+        let to_client = self.positions(Direction::ToClient);
+        let result = snapshot
+            .symbols
+            .global
+            .iter()
+            .map(|(name, symbol)| {
+                let mut symbol = IndexedSymbol::from(symbol);
+                to_client.location(&mut symbol.location);
+                (name.clone(), symbol)
+            })
             .collect();
         Ok(result)
     }
@@ -107,6 +107,43 @@ pub struct Field {
     pub parsed_type: ParsedType,
     pub deprecated: bool,
     pub id: Option<i32>,
+    pub force_align: Option<String>,
+    pub nested_flatbuffer: Option<NestedFlatbuffer>,
+    pub default_value: Option<DefaultValue>,
+}
+
+impl Field {
+    /// Whether `pos` is on the root type named by the `nested_flatbuffer` attribute.
+    #[must_use]
+    pub fn nested_flatbuffer_contains(&self, pos: Position) -> bool {
+        self.nested_flatbuffer.as_ref().is_some_and(|nested| {
+            let parsed = &nested.parsed_type;
+            parsed.type_name.range.contains(pos)
+                || parsed.namespace.iter().any(|part| part.range.contains(pos))
+        })
+    }
+
+    /// Whether `pos` is on the default value.
+    #[must_use]
+    pub fn default_value_contains(&self, pos: Position) -> bool {
+        self.default_value
+            .as_ref()
+            .is_some_and(|default| default.range.contains(pos))
+    }
+}
+
+/// A field's default value as written after the `=`, e.g. `Hard` or `"A B"`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefaultValue {
+    pub text: String,
+    pub range: Range,
+}
+
+/// The root type named in a field's `nested_flatbuffer: "..."` attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NestedFlatbuffer {
+    pub type_name: String,       // fully-qualified name
+    pub parsed_type: ParsedType, // the type as written between the quotes
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -122,6 +159,21 @@ pub struct RpcMethod {
 
     pub request_type: RpcMethodType,
     pub response_type: RpcMethodType,
+    /// The value of the `streaming` attribute: `none`, `client`, `server` or `bidi`.
+    pub streaming: Option<String>,
+}
+
+impl RpcMethod {
+    /// How the method streams, e.g. "server-streaming", or `None` if it does not.
+    #[must_use]
+    pub fn streaming_description(&self) -> Option<&'static str> {
+        match self.streaming.as_deref()? {
+            "client" => Some("client-streaming"),
+            "server" => Some("server-streaming"),
+            "bidi" => Some("bidirectional streaming"),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -145,6 +197,18 @@ impl Symbol {
         }
     }
 
+    /// The size in bytes of a value of this type stored inline in a struct,
+    /// or `None` if it is stored by offset.
+    #[must_use]
+    pub fn inline_size(&self) -> Option<u64> {
+        match &self.kind {
+            SymbolKind::Scalar => scalar_size(&self.info.name),
+            SymbolKind::Struct(s) => Some(s.size),
+            SymbolKind::Enum(e) => scalar_size(&e.underlying_type),
+            _ => None,
+        }
+    }
+
     #[must_use]
     pub fn find_symbol<'a>(&'a self, path: &PathBuf, pos: Position) -> Option<&'a Symbol> {
         if self.info.location.path != *path {
@@ -159,7 +223,10 @@ impl Symbol {
             SymbolKind::Table(t) => {
                 for field in &t.fields {
                     if let SymbolKind::Field(f) = &field.kind {
-                        if f.type_range.contains(pos) {
+                        if f.type_range.contains(pos)
+                            || f.nested_flatbuffer_contains(pos)
+                            || f.default_value_contains(pos)
+                        {
                             return Some(field);
                         }
                     }
@@ -168,7 +235,10 @@ impl Symbol {
             SymbolKind::Struct(s) => {
                 for field in &s.fields {
                     if let SymbolKind::Field(f) = &field.kind {
-                        if f.type_range.contains(pos) {
+                        if f.type_range.contains(pos)
+                            || f.nested_flatbuffer_contains(pos)
+                            || f.default_value_contains(pos)
+                        {
                             return Some(field);
                         }
                     }
@@ -242,6 +312,16 @@ impl Symbol {
             }
         }
 
+        if let SymbolKind::Field(f) = &self.kind {
+            if f.deprecated {
+                markdown.push_str(
+                    "\n\n---\n\nDeprecated: this field's id and vtable slot are kept so \
+                    existing data stays readable. Leave the field in the schema and don't \
+                    reuse its id.",
+                );
+            }
+        }
+
         if let SymbolKind::Struct(s) = &self.kind {
             markdown.push_str(
                 format!(
@@ -256,6 +336,18 @@ impl Symbol {
     }
 }
 
+/// The size in bytes of the scalar type `type_name`.
+#[must_use]
+pub fn scalar_size(type_name: &str) -> Option<u64> {
+    match type_name {
+        "bool" | "byte" | "ubyte" | "int8" | "uint8" => Some(1),
+        "short" | "ushort" | "int16" | "uint16" => Some(2),
+        "int" | "uint" | "int32" | "uint32" | "float" | "float32" => Some(4),
+        "long" | "ulong" | "int64" | "uint64" | "double" | "float64" => Some(8),
+        _ => None,
+    }
+}
+
 impl SymbolTable {
     /// Create a new token map.
     #[must_use]
@@ -394,12 +486,21 @@ impl RpcService {
             "\n{}\n",
             self.methods
                 .iter()
-                .map(|v| format!(
-                    "  {}({}):{};",
-                    v.name,
-                    v.request_type.parsed.to_display_string(),
-                    v.response_type.parsed.to_display_string(),
-                ))
+                .map(|v| {
+                    let signature = format!(
+                        "  {}({}):{}",
+                        v.name,
+                        v.request_type.parsed.to_display_string(),
+                        v.response_type.parsed.to_display_string(),
+                    );
+                    match v.streaming_description() {
+                        Some(description) => format!(
+                            "{signature} (streaming: \"{}\"); // {description}",
+                            v.streaming.as_deref().unwrap_or_default()
+                        ),
+                        None => format!("{signature};"),
+                    }
+                })
                 .collect::<Vec<String>>()
                 .join("\n")
         )
@@ -1,5 +1,9 @@
 pub mod parsed_type;
 pub mod paths;
+pub mod position_encoding;
+pub mod scanner;
+
+use tower_lsp_server::lsp_types::Position;
 
 /// Convert a usize to a u32 for use in `lsp_types::Position`.
 #[allow(clippy::cast_possible_truncation)]
@@ -7,3 +11,92 @@ pub mod paths;
 pub fn as_pos_idx(x: usize) -> u32 {
     x as u32
 }
+
+/// The length of `text` in UTF-16 code units, as used by `lsp_types::Position`.
+#[must_use]
+pub fn utf16_len(text: &str) -> u32 {
+    as_pos_idx(text.encode_utf16().count())
+}
+
+/// Convert a byte offset within a line to a UTF-16 column, as used by `lsp_types::Position`.
+///
+/// flatc reports columns in bytes. Offsets past the end of `line` are passed through unchanged.
+#[must_use]
+pub fn byte_to_utf16_col(line: impl IntoIterator<Item = char>, byte_col: u32) -> u32 {
+    let byte_col = byte_col as usize;
+    let mut bytes = 0;
+    let mut units = 0;
+    for c in line {
+        if bytes >= byte_col {
+            break;
+        }
+        bytes += c.len_utf8();
+        units += c.len_utf16();
+    }
+    as_pos_idx(units + byte_col.saturating_sub(bytes))
+}
+
+/// The position of the byte at `offset` in `content`, with a UTF-16 column.
+#[must_use]
+pub fn byte_offset_to_position(content: &str, offset: usize) -> Position {
+    let before = &content[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Position::new(
+        as_pos_idx(before.matches('\n').count()),
+        utf16_len(&before[line_start..]),
+    )
+}
+
+/// The Levenshtein distance between `a` and `b`, counted in characters.
+#[must_use]
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_to_utf16_col_ascii() {
+        assert_eq!(byte_to_utf16_col("table A {}".chars(), 6), 6);
+    }
+
+    #[test]
+    fn test_byte_to_utf16_col_multibyte() {
+        // U+FEFF is 3 bytes and 1 unit, U+1F680 is 4 bytes and 2 units.
+        assert_eq!(byte_to_utf16_col("\u{feff}table A".chars(), 9), 7);
+        assert_eq!(byte_to_utf16_col("/*\u{1f680}*/ table A".chars(), 15), 13);
+    }
+
+    #[test]
+    fn test_utf16_len() {
+        assert_eq!(utf16_len("MyTable"), 7);
+        assert_eq!(utf16_len("e\u{301}\u{1f680}"), 4);
+    }
+
+    #[test]
+    fn test_byte_to_utf16_col_past_end() {
+        assert_eq!(byte_to_utf16_col("ab".chars(), 5), 5);
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("common.fbs", "common.fbs"), 0);
+        assert_eq!(edit_distance("comon.fbs", "common.fbs"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+}
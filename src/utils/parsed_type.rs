@@ -1,3 +1,4 @@
+use crate::utils::as_pos_idx;
 use tower_lsp_server::lsp_types::{Position, Range};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -39,7 +40,7 @@ impl<'a> TypeParser<'a> {
             self.current_pos.line += 1;
             self.current_pos.character = 0;
         } else {
-            self.current_pos.character += 1;
+            self.current_pos.character += as_pos_idx(char.len_utf16());
         }
         Some(char)
     }
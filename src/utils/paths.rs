@@ -46,17 +46,28 @@ where
 }
 
 /// Convert a `lsp_types::Uri` to `PathBuf`.
+///
+/// Files that do not exist on disk (e.g. unsaved buffers) keep the path from the URI.
 /// # Errors
 ///
-/// Will return `Err` if `uri` is not a file path or it cannot be canonicalized
-/// (e.g. does not exist on disk).
+/// Will return `Err` if `uri` is not a file path.
 pub fn uri_to_path_buf(uri: &Uri) -> Result<PathBuf, String> {
     uri.to_file_path()
         .ok_or(format!("URL is not a file path: {uri:?}"))
-        .and_then(|p| {
-            fs::canonicalize(&p)
-                .map_err(|err| format!("Failed to canonicalize path '{}': {err}", p.display()))
-        })
+        .map(|p| canonicalize_or_self(&p))
+}
+
+/// Canonicalize `path`. If it does not exist on disk, canonicalize its parent
+/// directory instead, and if that fails too return `path` unchanged.
+#[must_use]
+pub fn canonicalize_or_self(path: &Path) -> PathBuf {
+    if let Ok(canonical) = fs::canonicalize(path) {
+        return canonical;
+    }
+    path.parent()
+        .zip(path.file_name())
+        .and_then(|(parent, name)| fs::canonicalize(parent).ok().map(|p| p.join(name)))
+        .unwrap_or_else(|| path.to_path_buf())
 }
 
 /// Convert a `PathBuf` to `lsp_types::Uri`.
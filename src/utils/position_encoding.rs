@@ -0,0 +1,415 @@
+use crate::document_store::DocumentStore;
+use crate::utils::paths::uri_to_path_buf;
+use crate::utils::{as_pos_idx, byte_to_utf16_col};
+use std::path::Path;
+use tower_lsp_server::lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall, CodeActionOrCommand,
+    CompletionItem, CompletionResponse, CompletionTextEdit, Diagnostic, DocumentChangeOperation,
+    DocumentChanges, GotoDefinitionResponse, Hover, InitializeParams, Location, OneOf, Position,
+    PositionEncodingKind, PrepareRenameResponse, Range, SelectionRange, TextDocumentEdit,
+    TextDocumentPositionParams, TextEdit, Uri, WorkspaceEdit, WorkspaceSymbol,
+};
+
+/// The unit that `Position.character` is counted in, as negotiated with the client.
+///
+/// Everything inside the server uses UTF-16, so positions are only converted
+/// when they cross the protocol boundary.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PositionEncoding {
+    Utf8,
+    #[default]
+    Utf16,
+}
+
+impl PositionEncoding {
+    /// Prefer UTF-8 when the client supports it, and fall back to the mandatory UTF-16 otherwise.
+    #[must_use]
+    pub fn negotiate(params: &InitializeParams) -> Self {
+        let supports_utf8 = params
+            .capabilities
+            .general
+            .as_ref()
+            .and_then(|general| general.position_encodings.as_ref())
+            .is_some_and(|encodings| encodings.contains(&PositionEncodingKind::UTF8));
+        if supports_utf8 {
+            Self::Utf8
+        } else {
+            Self::Utf16
+        }
+    }
+
+    #[must_use]
+    pub fn kind(self) -> PositionEncodingKind {
+        match self {
+            Self::Utf8 => PositionEncodingKind::UTF8,
+            Self::Utf16 => PositionEncodingKind::UTF16,
+        }
+    }
+
+    /// The number of chars in `line` before the column `col`, counted in this encoding.
+    /// A column inside a multi-unit character counts that character.
+    #[must_use]
+    pub fn char_col(self, line: impl IntoIterator<Item = char>, col: u32) -> usize {
+        let col = col as usize;
+        let mut units = 0;
+        let mut chars = 0;
+        for c in line {
+            if units >= col {
+                return chars;
+            }
+            units += match self {
+                Self::Utf8 => c.len_utf8(),
+                Self::Utf16 => c.len_utf16(),
+            };
+            chars += 1;
+        }
+        chars + col.saturating_sub(units)
+    }
+}
+
+/// Convert a UTF-16 column within a line to a byte offset.
+///
+/// Columns past the end of `line` are passed through unchanged.
+#[must_use]
+pub fn utf16_to_byte_col(line: impl IntoIterator<Item = char>, utf16_col: u32) -> u32 {
+    let utf16_col = utf16_col as usize;
+    let mut units = 0;
+    let mut bytes = 0;
+    for c in line {
+        if units >= utf16_col {
+            break;
+        }
+        units += c.len_utf16();
+        bytes += c.len_utf8();
+    }
+    as_pos_idx(bytes).saturating_add(as_pos_idx(utf16_col.saturating_sub(units)))
+}
+
+/// Which side of the protocol boundary positions are being moved to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// From the client's encoding to UTF-16.
+    ToServer,
+    /// From UTF-16 to the client's encoding.
+    ToClient,
+}
+
+/// Rewrites the positions in requests and responses between the server's
+/// UTF-16 columns and the negotiated encoding, using the text of each document.
+/// Towards the client, the URIs in responses are also replaced by the ones the
+/// client opened their documents with.
+pub struct PositionConverter<'a> {
+    encoding: PositionEncoding,
+    direction: Direction,
+    documents: &'a DocumentStore,
+}
+
+impl<'a> PositionConverter<'a> {
+    #[must_use]
+    pub fn new(
+        encoding: PositionEncoding,
+        direction: Direction,
+        documents: &'a DocumentStore,
+    ) -> Self {
+        Self {
+            encoding,
+            direction,
+            documents,
+        }
+    }
+
+    fn is_identity(&self) -> bool {
+        self.encoding == PositionEncoding::Utf16
+    }
+
+    fn convert_in(&self, path: &Path, position: &mut Position) {
+        let Some(doc) = self.documents.document_map.get(path) else {
+            return;
+        };
+        let Some(line) = doc.get_line(position.line as usize) else {
+            return;
+        };
+        position.character = match self.direction {
+            Direction::ToServer => byte_to_utf16_col(line.chars(), position.character),
+            Direction::ToClient => utf16_to_byte_col(line.chars(), position.character),
+        };
+    }
+
+    fn uri(&self, uri: &mut Uri) {
+        if self.direction == Direction::ToClient {
+            self.documents.to_client_uri(uri);
+        }
+    }
+
+    pub fn position(&self, uri: &Uri, position: &mut Position) {
+        if self.is_identity() {
+            return;
+        }
+        if let Ok(path) = uri_to_path_buf(uri) {
+            self.convert_in(&path, position);
+        }
+    }
+
+    pub fn text_document_position(&self, params: &mut TextDocumentPositionParams) {
+        self.position(&params.text_document.uri, &mut params.position);
+    }
+
+    pub fn range(&self, uri: &Uri, range: &mut Range) {
+        if let Ok(path) = uri_to_path_buf(uri) {
+            self.range_in(&path, range);
+        }
+    }
+
+    pub fn range_in(&self, path: &Path, range: &mut Range) {
+        if self.is_identity() {
+            return;
+        }
+        self.convert_in(path, &mut range.start);
+        self.convert_in(path, &mut range.end);
+    }
+
+    pub fn location(&self, location: &mut Location) {
+        self.range(&location.uri, &mut location.range);
+        self.uri(&mut location.uri);
+    }
+
+    pub fn locations(&self, locations: &mut [Location]) {
+        for location in locations {
+            self.location(location);
+        }
+    }
+
+    pub fn text_edits(&self, uri: &Uri, edits: &mut [TextEdit]) {
+        for edit in edits {
+            self.range(uri, &mut edit.range);
+        }
+    }
+
+    pub fn text_edits_in(&self, path: &Path, edits: &mut [TextEdit]) {
+        for edit in edits {
+            self.range_in(path, &mut edit.range);
+        }
+    }
+
+    pub fn diagnostics(&self, uri: &Uri, diagnostics: &mut [Diagnostic]) {
+        for diagnostic in diagnostics {
+            self.range(uri, &mut diagnostic.range);
+            for related in diagnostic.related_information.iter_mut().flatten() {
+                self.location(&mut related.location);
+            }
+        }
+    }
+
+    pub fn hover(&self, uri: &Uri, hover: &mut Hover) {
+        if let Some(range) = hover.range.as_mut() {
+            self.range(uri, range);
+        }
+    }
+
+    /// `uri` is the document the request was made in, which link origins refer to.
+    pub fn goto_definition(&self, uri: &Uri, response: &mut GotoDefinitionResponse) {
+        match response {
+            GotoDefinitionResponse::Scalar(location) => self.location(location),
+            GotoDefinitionResponse::Array(locations) => self.locations(locations),
+            GotoDefinitionResponse::Link(links) => {
+                for link in links {
+                    if let Some(range) = link.origin_selection_range.as_mut() {
+                        self.range(uri, range);
+                    }
+                    self.range(&link.target_uri, &mut link.target_range);
+                    self.range(&link.target_uri, &mut link.target_selection_range);
+                    self.uri(&mut link.target_uri);
+                }
+            }
+        }
+    }
+
+    pub fn completion_item(&self, uri: &Uri, item: &mut CompletionItem) {
+        match item.text_edit.as_mut() {
+            Some(CompletionTextEdit::Edit(edit)) => self.range(uri, &mut edit.range),
+            Some(CompletionTextEdit::InsertAndReplace(edit)) => {
+                self.range(uri, &mut edit.insert);
+                self.range(uri, &mut edit.replace);
+            }
+            None => {}
+        }
+        if let Some(edits) = item.additional_text_edits.as_mut() {
+            self.text_edits(uri, edits);
+        }
+    }
+
+    pub fn completion_response(&self, uri: &Uri, response: &mut CompletionResponse) {
+        let items = match response {
+            CompletionResponse::Array(items) => items,
+            CompletionResponse::List(list) => &mut list.items,
+        };
+        for item in items {
+            self.completion_item(uri, item);
+        }
+    }
+
+    fn text_document_edit(&self, edit: &mut TextDocumentEdit) {
+        let uri = &edit.text_document.uri;
+        for text_edit in &mut edit.edits {
+            match text_edit {
+                OneOf::Left(text_edit) => self.range(uri, &mut text_edit.range),
+                OneOf::Right(text_edit) => self.range(uri, &mut text_edit.text_edit.range),
+            }
+        }
+        self.uri(&mut edit.text_document.uri);
+    }
+
+    #[allow(
+        clippy::mutable_key_type,
+        reason = "the uris are not modified while they are keys"
+    )]
+    pub fn workspace_edit(&self, edit: &mut WorkspaceEdit) {
+        if let Some(changes) = edit.changes.take() {
+            edit.changes = Some(
+                changes
+                    .into_iter()
+                    .map(|(mut uri, mut edits)| {
+                        self.text_edits(&uri, &mut edits);
+                        self.uri(&mut uri);
+                        (uri, edits)
+                    })
+                    .collect(),
+            );
+        }
+        match edit.document_changes.as_mut() {
+            Some(DocumentChanges::Edits(edits)) => {
+                for edit in edits {
+                    self.text_document_edit(edit);
+                }
+            }
+            Some(DocumentChanges::Operations(operations)) => {
+                for operation in operations {
+                    if let DocumentChangeOperation::Edit(edit) = operation {
+                        self.text_document_edit(edit);
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+
+    pub fn code_actions(&self, uri: &Uri, actions: &mut [CodeActionOrCommand]) {
+        for action in actions {
+            if let CodeActionOrCommand::CodeAction(action) = action {
+                if let Some(diagnostics) = action.diagnostics.as_mut() {
+                    self.diagnostics(uri, diagnostics);
+                }
+                if let Some(edit) = action.edit.as_mut() {
+                    self.workspace_edit(edit);
+                }
+            }
+        }
+    }
+
+    pub fn prepare_rename(&self, uri: &Uri, response: &mut PrepareRenameResponse) {
+        match response {
+            PrepareRenameResponse::Range(range)
+            | PrepareRenameResponse::RangeWithPlaceholder { range, .. } => self.range(uri, range),
+            PrepareRenameResponse::DefaultBehavior { .. } => {}
+        }
+    }
+
+    pub fn workspace_symbols(&self, symbols: &mut [WorkspaceSymbol]) {
+        for symbol in symbols {
+            if let OneOf::Left(location) = &mut symbol.location {
+                self.location(location);
+            }
+        }
+    }
+
+    pub fn selection_range(&self, uri: &Uri, selection: &mut SelectionRange) {
+        self.range(uri, &mut selection.range);
+        if let Some(parent) = selection.parent.as_mut() {
+            self.selection_range(uri, parent);
+        }
+    }
+
+    pub fn call_hierarchy_item(&self, item: &mut CallHierarchyItem) {
+        self.range(&item.uri, &mut item.range);
+        self.range(&item.uri, &mut item.selection_range);
+        self.uri(&mut item.uri);
+    }
+
+    /// `from_ranges` are in the caller, which is `from`.
+    pub fn incoming_call(&self, call: &mut CallHierarchyIncomingCall) {
+        for range in &mut call.from_ranges {
+            self.range(&call.from.uri, range);
+        }
+        self.call_hierarchy_item(&mut call.from);
+    }
+
+    /// `from_ranges` are in the caller, which is the item the request was made for.
+    pub fn outgoing_call(&self, caller: &Uri, call: &mut CallHierarchyOutgoingCall) {
+        for range in &mut call.from_ranges {
+            self.range(caller, range);
+        }
+        self.call_hierarchy_item(&mut call.to);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp_server::lsp_types::{ClientCapabilities, GeneralClientCapabilities};
+
+    fn params_with_encodings(encodings: Option<Vec<PositionEncodingKind>>) -> InitializeParams {
+        InitializeParams {
+            capabilities: ClientCapabilities {
+                general: Some(GeneralClientCapabilities {
+                    position_encodings: encodings,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_negotiate_prefers_utf8() {
+        let params = params_with_encodings(Some(vec![
+            PositionEncodingKind::UTF16,
+            PositionEncodingKind::UTF8,
+        ]));
+        assert_eq!(PositionEncoding::negotiate(&params), PositionEncoding::Utf8);
+    }
+
+    #[test]
+    fn test_negotiate_defaults_to_utf16() {
+        let params = params_with_encodings(Some(vec![PositionEncodingKind::UTF32]));
+        assert_eq!(
+            PositionEncoding::negotiate(&params),
+            PositionEncoding::Utf16
+        );
+        assert_eq!(
+            PositionEncoding::negotiate(&InitializeParams::default()),
+            PositionEncoding::Utf16
+        );
+    }
+
+    #[test]
+    fn test_utf16_to_byte_col() {
+        // 'é' is 2 bytes and 1 UTF-16 unit, '😀' is 4 bytes and 2 units.
+        let line = "é😀a";
+        assert_eq!(utf16_to_byte_col(line.chars(), 0), 0);
+        assert_eq!(utf16_to_byte_col(line.chars(), 1), 2);
+        assert_eq!(utf16_to_byte_col(line.chars(), 3), 6);
+        assert_eq!(utf16_to_byte_col(line.chars(), 4), 7);
+        assert_eq!(utf16_to_byte_col(line.chars(), 6), 9);
+        assert_eq!(utf16_to_byte_col(line.chars(), u32::MAX), u32::MAX);
+    }
+
+    #[test]
+    fn test_column_round_trip() {
+        let line = "a 😀 b é c";
+        for byte_col in [0, 1, 2, 6, 7, 8, 11] {
+            let utf16_col = byte_to_utf16_col(line.chars(), byte_col);
+            assert_eq!(utf16_to_byte_col(line.chars(), utf16_col), byte_col);
+        }
+    }
+}
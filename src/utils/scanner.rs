@@ -0,0 +1,140 @@
+//! Lightweight lexical helpers for reasoning about raw schema text without a full parse.
+
+use ropey::Rope;
+use tower_lsp_server::lsp_types::Position;
+
+/// Replace comments and the contents of string literals with spaces.
+///
+/// Line breaks and character offsets are preserved, so positions in the
+/// returned text match positions in the original.
+#[must_use]
+pub fn strip_comments_and_strings(text: &str) -> String {
+    enum State {
+        Code,
+        LineComment,
+        BlockComment,
+        /// Inside a string opened with the given quote.
+        String(char),
+    }
+
+    let mut state = State::Code;
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match state {
+            State::Code => match (c, chars.peek()) {
+                ('/', Some('/')) => {
+                    state = State::LineComment;
+                    out.push(' ');
+                }
+                ('/', Some('*')) => {
+                    chars.next();
+                    state = State::BlockComment;
+                    out.push_str("  ");
+                }
+                ('"' | '\'', _) => {
+                    state = State::String(c);
+                    out.push(c);
+                }
+                _ => out.push(c),
+            },
+            State::LineComment => {
+                if c == '\n' {
+                    state = State::Code;
+                    out.push(c);
+                } else {
+                    out.push(' ');
+                }
+            }
+            State::BlockComment => {
+                if c == '*' && chars.peek() == Some(&'/') {
+                    chars.next();
+                    state = State::Code;
+                    out.push_str("  ");
+                } else {
+                    out.push(if c == '\n' { c } else { ' ' });
+                }
+            }
+            State::String(quote) => match c {
+                '\\' => {
+                    out.push(' ');
+                    if let Some(escaped) = chars.next() {
+                        out.push(if escaped == '\n' { escaped } else { ' ' });
+                    }
+                }
+                _ if c == quote => {
+                    state = State::Code;
+                    out.push(c);
+                }
+                // Strings cannot span lines.
+                '\n' => {
+                    state = State::Code;
+                    out.push(c);
+                }
+                _ => out.push(' '),
+            },
+        }
+    }
+    out
+}
+
+/// The code in `doc` before `position`, with comments and string contents removed.
+#[must_use]
+pub fn code_before(doc: &Rope, position: Position) -> String {
+    let line = position.line as usize;
+    let end = if line >= doc.len_lines() {
+        doc.len_chars()
+    } else {
+        let line_len = doc.line(line).len_chars();
+        doc.line_to_char(line) + (position.character as usize).min(line_len)
+    };
+    strip_comments_and_strings(&doc.slice(..end).to_string())
+}
+
+/// The net number of unclosed `{` in `code`.
+#[must_use]
+pub fn brace_depth(code: &str) -> i32 {
+    code.chars().fold(0, |depth, c| match c {
+        '{' => depth + 1,
+        '}' => depth - 1,
+        _ => depth,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_line_comment_brace() {
+        let code = strip_comments_and_strings("table T {\n  // } not a real brace\n");
+        assert_eq!(brace_depth(&code), 1);
+        assert_eq!(code.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_strip_block_comment_brace() {
+        let code = strip_comments_and_strings("table T { /* }\n } */\n");
+        assert_eq!(brace_depth(&code), 1);
+    }
+
+    #[test]
+    fn test_strip_string_default_braces() {
+        let code = strip_comments_and_strings("table T {\n  s: string = \"}{}\";\n");
+        assert_eq!(code, "table T {\n  s: string = \"   \";\n");
+        assert_eq!(brace_depth(&code), 1);
+    }
+
+    #[test]
+    fn test_strip_escaped_quote() {
+        let code = strip_comments_and_strings(r#"a = "\"}" }"#);
+        assert_eq!(brace_depth(&code), -1);
+    }
+
+    #[test]
+    fn test_code_before_position() {
+        let doc = Rope::from_str("table T {\n  a: int; // {\n}\n");
+        assert_eq!(brace_depth(&code_before(&doc, Position::new(1, 4))), 1);
+        assert_eq!(brace_depth(&code_before(&doc, Position::new(2, 1))), 0);
+    }
+}
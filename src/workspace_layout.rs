@@ -1,6 +1,7 @@
 use dashmap::DashSet;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use ignore::{WalkBuilder, WalkState};
-use log::{debug, error};
+use log::{debug, error, warn};
 use std::{
     collections::HashSet,
     fs,
@@ -13,12 +14,23 @@ use crate::{
     utils::paths::{get_intermediate_paths, is_flatbuffer_schema_path},
 };
 
+/// Directories that are never scanned unless re-included with a `!` pattern,
+/// since they hold build output or installed packages rather than sources.
+pub const DEFAULT_IGNORE_PATTERNS: [&str; 2] = ["target/", "node_modules/"];
+
 /// Maintains the workspace file and folder layout.
 #[derive(Debug)]
 pub struct WorkspaceLayout {
     /// Paths that have a `known_file` as a descendant.
     pub search_paths: HashSet<PathBuf>,
     pub workspace_roots: HashSet<PathBuf>,
+    /// Extra directories to search from the `includeDirs` setting.
+    pub include_dirs: Vec<PathBuf>,
+    /// Gitignore-style patterns from the `ignorePatterns` setting, applied
+    /// after [`DEFAULT_IGNORE_PATTERNS`] within each workspace root.
+    pub ignore_patterns: Vec<String>,
+    /// Skip files excluded by `.gitignore`, even outside of a git repository.
+    pub respect_gitignore: bool,
     /// Known `FlatBuffers` schema files.
     known_files: HashSet<PathBuf>,
 }
@@ -35,6 +47,9 @@ impl WorkspaceLayout {
         Self {
             search_paths: HashSet::new(),
             workspace_roots: HashSet::new(),
+            include_dirs: Vec::new(),
+            ignore_patterns: Vec::new(),
+            respect_gitignore: true,
             known_files: HashSet::new(),
         }
     }
@@ -85,6 +100,24 @@ impl WorkspaceLayout {
             }
         }
 
+        builder
+            .require_git(false)
+            .git_ignore(self.respect_gitignore)
+            .git_exclude(self.respect_gitignore)
+            .git_global(self.respect_gitignore);
+
+        let matchers: Vec<(PathBuf, Gitignore)> = paths
+            .iter()
+            .map(|root| (root.clone(), ignore_matcher(root, &self.ignore_patterns)))
+            .collect();
+        builder.filter_entry(move |entry| {
+            let path = entry.path();
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            !matchers.iter().any(|(root, matcher)| {
+                path.starts_with(root) && matcher.matched(path, is_dir).is_ignore()
+            })
+        });
+
         let new_files = DashSet::new();
 
         builder.build_parallel().run(|| {
@@ -111,6 +144,23 @@ impl WorkspaceLayout {
         self.update_search_paths();
     }
 
+    /// Whether `path` is skipped by the ignore patterns of the root that contains it,
+    /// or by a `.gitignore` within that root.
+    #[must_use]
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let Some(root) = self.root_for(path) else {
+            return false;
+        };
+        let is_dir = path.is_dir();
+        if ignore_matcher(root, &self.ignore_patterns)
+            .matched_path_or_any_parents(path, is_dir)
+            .is_ignore()
+        {
+            return true;
+        }
+        self.respect_gitignore && is_gitignored(root, path, is_dir)
+    }
+
     /// Add a new file. Returns true if the file was not already known.
     pub fn add_file(&mut self, path: PathBuf) {
         if is_flatbuffer_schema_path(&path) {
@@ -142,6 +192,50 @@ impl WorkspaceLayout {
             .collect()
     }
 
+    /// The workspace root that contains `path`. When roots are nested,
+    /// the innermost one is used.
+    #[must_use]
+    pub fn root_for(&self, path: &Path) -> Option<&PathBuf> {
+        self.workspace_roots
+            .iter()
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+    }
+
+    /// The directories to search for files included by `path`: the derived
+    /// `search_paths` within the root that contains it, followed by the
+    /// `include_dirs`, with relative ones resolved against that root.
+    /// Files outside every root search all roots.
+    #[must_use]
+    pub fn search_paths_for(&self, path: &Path) -> Vec<PathBuf> {
+        let roots: Vec<&PathBuf> = match self.root_for(path) {
+            Some(root) => vec![root],
+            None => self.workspace_roots.iter().collect(),
+        };
+
+        let mut paths: Vec<PathBuf> = self
+            .search_paths
+            .iter()
+            .filter(|sp| roots.iter().any(|root| sp.starts_with(root)))
+            .cloned()
+            .collect();
+        paths.sort();
+
+        let include_dirs = self.include_dirs.iter().flat_map(|dir| {
+            if dir.is_absolute() {
+                vec![dir.clone()]
+            } else {
+                roots.iter().map(|root| root.join(dir)).collect()
+            }
+        });
+        for dir in include_dirs.filter_map(|dir| fs::canonicalize(dir).ok()) {
+            if !paths.contains(&dir) {
+                paths.push(dir);
+            }
+        }
+        paths
+    }
+
     /// Update `search_paths` so it contains every directory that is
     /// both an ancestor of a `known_file` and a descendant of a
     /// `workspace_root` (include the roots themselves).
@@ -158,3 +252,37 @@ impl WorkspaceLayout {
         get_intermediate_paths(path, &self.workspace_roots)
     }
 }
+
+/// Whether the closest `.gitignore` between `path` and `root` that mentions `path`
+/// excludes it.
+fn is_gitignored(root: &Path, path: &Path, is_dir: bool) -> bool {
+    path.ancestors()
+        .skip(1)
+        .take_while(|dir| dir.starts_with(root))
+        .map(|dir| dir.join(".gitignore"))
+        .filter(|file| file.is_file())
+        .map(|file| {
+            Gitignore::new(file)
+                .0
+                .matched_path_or_any_parents(path, is_dir)
+        })
+        .find(|matched| !matched.is_none())
+        .is_some_and(|matched| matched.is_ignore())
+}
+
+/// Match paths under `root` against the default ignore patterns followed by `patterns`.
+fn ignore_matcher(root: &Path, patterns: &[String]) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(root);
+    for pattern in DEFAULT_IGNORE_PATTERNS
+        .into_iter()
+        .chain(patterns.iter().map(String::as_str))
+    {
+        if let Err(err) = builder.add_line(None, pattern) {
+            warn!("skipping invalid ignore pattern {pattern:?}: {err}");
+        }
+    }
+    builder.build().unwrap_or_else(|err| {
+        warn!("failed to build ignore patterns: {err}");
+        Gitignore::empty()
+    })
+}
@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use flatbuffers_language_server::analysis::Analyzer;
 use flatbuffers_language_server::document_store::DocumentStore;
+use flatbuffers_language_server::symbol_table::{Location, SymbolKind};
 use flatbuffers_language_server::workspace_layout::WorkspaceLayout;
 use tempfile::tempdir;
 
@@ -151,3 +152,147 @@ async fn test_analyzer_symbol_index() {
     assert_eq!(importer_symbols.len(), 1);
     assert!(importer_symbols.contains(&"ImporterTable".to_string()));
 }
+
+/// Find the uses of `name` by walking every symbol, the way references were
+/// found before the reference index existed.
+fn scan_references(
+    snapshot: &flatbuffers_language_server::analysis::WorkspaceSnapshot<'_>,
+    name: &str,
+) -> Vec<Location> {
+    let mut references = Vec::new();
+    for symbol in snapshot.symbols.global.values() {
+        let path = symbol.info.location.path.clone();
+        let mut push = |type_name: &str, range| {
+            if type_name == name {
+                references.push(Location {
+                    path: path.clone(),
+                    range,
+                });
+            }
+        };
+        let fields = match &symbol.kind {
+            SymbolKind::Table(t) => t.fields.as_slice(),
+            SymbolKind::Struct(s) => s.fields.as_slice(),
+            SymbolKind::Union(u) => {
+                for variant in &u.variants {
+                    push(&variant.name, variant.parsed_type.type_name.range);
+                }
+                continue;
+            }
+            SymbolKind::RpcService(r) => {
+                for method in &r.methods {
+                    push(
+                        &method.request_type.name,
+                        method.request_type.parsed.type_name.range,
+                    );
+                    push(
+                        &method.response_type.name,
+                        method.response_type.parsed.type_name.range,
+                    );
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        for field in fields {
+            if let SymbolKind::Field(f) = &field.kind {
+                push(&f.type_name, f.parsed_type.type_name.range);
+                if let Some(nested) = &f.nested_flatbuffer {
+                    push(&nested.type_name, nested.parsed_type.type_name.range);
+                }
+            }
+        }
+    }
+    for (path, root_type_info) in &snapshot.root_types.root_types {
+        if root_type_info.type_name == name {
+            references.push(Location {
+                path: path.clone(),
+                range: root_type_info.parsed_type.type_name.range,
+            });
+        }
+    }
+    references
+}
+
+fn sorted(mut locations: Vec<Location>) -> Vec<Location> {
+    locations.sort_by_key(|l| (l.path.clone(), l.range.start.line, l.range.start.character));
+    locations
+}
+
+#[tokio::test]
+async fn test_reference_index_matches_scan() {
+    let dir = tempdir().unwrap();
+
+    let types_fbs_path = dir.path().join("types.fbs");
+    let types_fbs_content = r"
+        namespace Types;
+        table Inner { value: int; }
+        struct Point { x: float; y: float; }
+        union Payload { Inner }
+    ";
+    fs::write(&types_fbs_path, types_fbs_content).unwrap();
+
+    let main_fbs_path = dir.path().join("main.fbs");
+    let main_fbs_content = r#"
+        include "types.fbs";
+        table Outer {
+            inner: Types.Inner;
+            points: [Types.Point];
+            payload: Types.Payload;
+            nested: [ubyte] (nested_flatbuffer: "Types.Inner");
+        }
+        rpc_service Service {
+            Get(Types.Inner): Outer;
+        }
+        root_type Outer;
+    "#;
+    fs::write(&main_fbs_path, main_fbs_content).unwrap();
+
+    let document_store = DocumentStore::new();
+    let canonical_types_path = fs::canonicalize(&types_fbs_path).unwrap();
+    let canonical_main_path = fs::canonicalize(&main_fbs_path).unwrap();
+    document_store
+        .document_map
+        .insert(canonical_types_path.clone(), types_fbs_content.into());
+    document_store
+        .document_map
+        .insert(canonical_main_path.clone(), main_fbs_content.into());
+    let document_store = Arc::new(document_store);
+
+    let analyzer = Analyzer::new(document_store.clone());
+    let mut layout = WorkspaceLayout::new();
+    layout.add_root(fs::canonicalize(dir.path()).unwrap());
+    analyzer.parse(layout.discover_files()).await;
+
+    let names = ["Types.Inner", "Types.Point", "Types.Payload", "Outer"];
+    {
+        let snapshot = analyzer.snapshot().await;
+        for name in names {
+            let indexed = snapshot.symbols.references(name).to_vec();
+            assert!(!indexed.is_empty(), "no references to {name}");
+            assert_eq!(sorted(indexed), sorted(scan_references(&snapshot, name)));
+        }
+        assert_eq!(snapshot.symbols.references("Types.Inner").len(), 4);
+        assert_eq!(snapshot.symbols.references("Outer").len(), 2);
+    }
+
+    // Reparsing a file replaces its entries in the index.
+    let edited_main_fbs_content = r#"
+        include "types.fbs";
+        table Outer {
+            point: Types.Point;
+        }
+    "#;
+    document_store
+        .document_map
+        .insert(canonical_main_path.clone(), edited_main_fbs_content.into());
+    analyzer.parse([canonical_main_path.clone()]).await;
+
+    let snapshot = analyzer.snapshot().await;
+    for name in names {
+        let indexed = snapshot.symbols.references(name).to_vec();
+        assert_eq!(sorted(indexed), sorted(scan_references(&snapshot, name)));
+    }
+    assert_eq!(snapshot.symbols.references("Types.Inner").len(), 1);
+    assert!(snapshot.symbols.references("Outer").is_empty());
+}
@@ -0,0 +1,133 @@
+use crate::harness::TestHarness;
+use tower_lsp_server::lsp_types::{
+    request, CallHierarchyIncomingCallsParams, CallHierarchyItem, CallHierarchyOutgoingCallsParams,
+    CallHierarchyPrepareParams, PartialResultParams, Position, Range, SymbolKind,
+    TextDocumentIdentifier, TextDocumentPositionParams, WorkDoneProgressParams,
+};
+
+const MAIN: &str = r#"include "common.fbs";
+
+table Main {
+    c: Common;
+}
+"#;
+
+const COMMON: &str = r#"include "base.fbs";
+
+table Common {
+    b: Base;
+}
+"#;
+
+const BASE: &str = r"table Base {
+    a: int;
+}
+";
+
+async fn prepare(harness: &mut TestHarness, file: &str, position: Position) -> CallHierarchyItem {
+    let uri = harness.file_uri(file);
+    let items = harness
+        .call::<request::CallHierarchyPrepare>(CallHierarchyPrepareParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    items.into_iter().next().unwrap()
+}
+
+#[tokio::test]
+async fn prepare_call_hierarchy_on_file() {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[
+            ("main.fbs", MAIN),
+            ("common.fbs", COMMON),
+            ("base.fbs", BASE),
+        ])
+        .await;
+
+    let item = prepare(&mut harness, "common.fbs", Position::new(3, 4)).await;
+    assert_eq!(item.name, "common.fbs");
+    assert_eq!(item.kind, SymbolKind::FILE);
+    assert_eq!(item.uri, harness.file_uri("common.fbs"));
+}
+
+#[tokio::test]
+async fn prepare_call_hierarchy_on_include_line() {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[
+            ("main.fbs", MAIN),
+            ("common.fbs", COMMON),
+            ("base.fbs", BASE),
+        ])
+        .await;
+
+    let item = prepare(&mut harness, "main.fbs", Position::new(0, 12)).await;
+    assert_eq!(item.name, "common.fbs");
+    assert_eq!(item.uri, harness.file_uri("common.fbs"));
+}
+
+#[tokio::test]
+async fn incoming_calls_are_direct_includers() {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[
+            ("main.fbs", MAIN),
+            ("common.fbs", COMMON),
+            ("base.fbs", BASE),
+        ])
+        .await;
+
+    let item = prepare(&mut harness, "base.fbs", Position::new(0, 0)).await;
+    let calls = harness
+        .call::<request::CallHierarchyIncomingCalls>(CallHierarchyIncomingCallsParams {
+            item,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        })
+        .await
+        .unwrap();
+
+    // main.fbs only includes base.fbs transitively.
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].from.uri, harness.file_uri("common.fbs"));
+    assert_eq!(
+        calls[0].from_ranges,
+        vec![Range::new(Position::new(0, 0), Position::new(0, 19))]
+    );
+}
+
+#[tokio::test]
+async fn outgoing_calls_are_direct_includes() {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[
+            ("main.fbs", MAIN),
+            ("common.fbs", COMMON),
+            ("base.fbs", BASE),
+        ])
+        .await;
+
+    let item = prepare(&mut harness, "main.fbs", Position::new(3, 4)).await;
+    let calls = harness
+        .call::<request::CallHierarchyOutgoingCalls>(CallHierarchyOutgoingCallsParams {
+            item,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].to.uri, harness.file_uri("common.fbs"));
+    assert_eq!(
+        calls[0].from_ranges,
+        vec![Range::new(Position::new(0, 0), Position::new(0, 21))]
+    );
+}
@@ -1,8 +1,8 @@
 use crate::harness::TestHarness;
 use insta::assert_snapshot;
 use tower_lsp_server::lsp_types::{
-    request, CodeActionContext, CodeActionParams, PartialResultParams, TextDocumentIdentifier,
-    WorkDoneProgressParams,
+    request, CodeActionContext, CodeActionOrCommand, CodeActionParams, PartialResultParams,
+    Position, Range, TextDocumentIdentifier, TextEdit, WorkDoneProgressParams,
 };
 
 /// Gets code actions for a multi-file workspace, waiting for a specific diagnostic to appear first.
@@ -158,6 +158,81 @@ table MyTable {}
     assert_snapshot!(redacted_response);
 }
 
+#[tokio::test]
+async fn import_undefined_nested_flatbuffer_root_type() {
+    let definition_fixture = r"namespace MyNamespace;
+
+table MyTable {}
+";
+    let schema_fixture = r#"table T {
+    f: [ubyte] (nested_flatbuffer: "MyNamespace.MyTable");
+}
+"#;
+    let mut harness = TestHarness::new();
+    let response = get_code_actions_for_workspace(
+        &mut harness,
+        &[
+            ("definitions.fbs", definition_fixture),
+            ("schema.fbs", schema_fixture),
+        ],
+        "schema.fbs",
+        "type referenced but not defined",
+    )
+    .await;
+
+    let actions: Vec<CodeActionOrCommand> = serde_json::from_str(&response).unwrap();
+    assert_eq!(actions.len(), 1);
+    let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+        panic!("expected a code action");
+    };
+    assert_eq!(action.title, "Import `MyTable` from `definitions.fbs`");
+    assert_eq!(
+        action.diagnostics.as_ref().unwrap()[0].range,
+        Range::new(Position::new(1, 36), Position::new(1, 55))
+    );
+}
+
+#[tokio::test]
+async fn add_ids_to_all_fields() {
+    let schema_fixture = r"table Monster {
+    hp: short (id: 0);
+    mana: short = 150;
+    name: string (required);
+}
+";
+    let mut harness = TestHarness::new();
+    let response = get_code_actions_for_workspace(
+        &mut harness,
+        &[("schema.fbs", schema_fixture)],
+        "schema.fbs",
+        "either all fields or no fields must have an `id` attribute",
+    )
+    .await;
+
+    let actions: Vec<CodeActionOrCommand> = serde_json::from_str(&response).unwrap();
+    assert_eq!(actions.len(), 1);
+    let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+        panic!("expected a code action");
+    };
+    assert_eq!(action.title, "Add ids to all fields");
+    #[allow(clippy::mutable_key_type, reason = "external type definition")]
+    let changes = action.edit.as_ref().unwrap().changes.as_ref().unwrap();
+    let edits = &changes[&harness.file_uri("schema.fbs")];
+    assert_eq!(
+        edits,
+        &vec![
+            TextEdit::new(
+                Range::new(Position::new(2, 21), Position::new(2, 21)),
+                " (id: 1)".to_string()
+            ),
+            TextEdit::new(
+                Range::new(Position::new(3, 18), Position::new(3, 18)),
+                "id: 2, ".to_string()
+            ),
+        ]
+    );
+}
+
 #[tokio::test]
 async fn import_undefined_type_with_existing_namespace() {
     let definition_fixture = r"namespace MyNamespace;
@@ -346,3 +421,170 @@ async fn code_action_for_undefined_type_in_unopened_file() {
     let redacted_response = response_str.replace(harness.root_uri().as_str(), "[ROOT_URI]");
     assert_snapshot!(redacted_response);
 }
+
+#[tokio::test]
+async fn change_misspelled_type_to_suggestion() {
+    let schema_fixture = "table MyTable {\n    w: Widgt;\n}\n";
+    let widget_fixture = "table Widget {}";
+
+    let mut harness = TestHarness::new();
+    let response = get_code_actions_for_workspace(
+        &mut harness,
+        &[
+            ("schema.fbs", schema_fixture),
+            ("widget.fbs", widget_fixture),
+        ],
+        "schema.fbs",
+        "did you mean `Widget`?",
+    )
+    .await;
+
+    let actions: Vec<CodeActionOrCommand> = serde_json::from_str(&response).unwrap();
+    assert_eq!(actions.len(), 1);
+    let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+        panic!("expected a code action");
+    };
+    assert_eq!(
+        action.title,
+        "Change to `Widget` and add `include \"widget.fbs\"`"
+    );
+    assert_eq!(action.is_preferred, Some(true));
+
+    #[allow(clippy::mutable_key_type, reason = "external type definition")]
+    let changes = action.edit.as_ref().unwrap().changes.as_ref().unwrap();
+    let edits = &changes[&harness.file_uri("schema.fbs")];
+    let new_texts: Vec<&str> = edits.iter().map(|edit| edit.new_text.as_str()).collect();
+    assert_eq!(new_texts, vec!["include \"widget.fbs\";\n\n", "Widget"]);
+    assert_eq!(
+        edits[1].range,
+        Range::new(Position::new(1, 7), Position::new(1, 12))
+    );
+}
+
+async fn get_declare_attribute_edit(schema_fixture: &str) -> (String, TextEdit) {
+    let mut harness = TestHarness::new();
+    let response = get_code_actions_for_workspace(
+        &mut harness,
+        &[("schema.fbs", schema_fixture)],
+        "schema.fbs",
+        "unknown attribute `mystery`",
+    )
+    .await;
+
+    let actions: Vec<CodeActionOrCommand> = serde_json::from_str(&response).unwrap();
+    assert_eq!(actions.len(), 1);
+    let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+        panic!("expected a code action");
+    };
+
+    #[allow(clippy::mutable_key_type, reason = "external type definition")]
+    let changes = action.edit.as_ref().unwrap().changes.as_ref().unwrap();
+    let edits = &changes[&harness.file_uri("schema.fbs")];
+    assert_eq!(edits.len(), 1);
+    (action.title.clone(), edits[0].clone())
+}
+
+#[tokio::test]
+async fn declare_unknown_attribute() {
+    let (title, edit) =
+        get_declare_attribute_edit("table MyTable {\n    a: int (mystery);\n}\n").await;
+    assert_eq!(title, "Declare attribute `mystery`");
+    assert_eq!(edit.new_text, "attribute \"mystery\";\n\n");
+    assert_eq!(
+        edit.range,
+        Range::new(Position::new(0, 0), Position::new(0, 0))
+    );
+}
+
+#[tokio::test]
+async fn declare_unknown_attribute_after_namespace() {
+    let (_, edit) = get_declare_attribute_edit(
+        "namespace Game;\n\ntable MyTable {\n    a: int (mystery);\n}\n",
+    )
+    .await;
+    assert_eq!(edit.new_text, "\nattribute \"mystery\";\n");
+    assert_eq!(
+        edit.range,
+        Range::new(Position::new(1, 0), Position::new(1, 0))
+    );
+}
+
+#[tokio::test]
+async fn declare_unknown_attribute_after_other_attributes() {
+    let (_, edit) = get_declare_attribute_edit(
+        "namespace Game;\n\nattribute \"priority\";\n\ntable MyTable {\n    a: int (priority, mystery);\n}\n",
+    )
+    .await;
+    assert_eq!(edit.new_text, "attribute \"mystery\";\n");
+    assert_eq!(
+        edit.range,
+        Range::new(Position::new(3, 0), Position::new(3, 0))
+    );
+}
+
+async fn get_reserved_keyword_edits(
+    schema_fixture: &str,
+    diagnostic_message: &str,
+) -> (String, Vec<TextEdit>) {
+    let mut harness = TestHarness::new();
+    let response = get_code_actions_for_workspace(
+        &mut harness,
+        &[("schema.fbs", schema_fixture)],
+        "schema.fbs",
+        diagnostic_message,
+    )
+    .await;
+
+    let actions: Vec<CodeActionOrCommand> = serde_json::from_str(&response).unwrap();
+    assert_eq!(actions.len(), 1);
+    let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+        panic!("expected a code action");
+    };
+    assert_eq!(action.is_preferred, Some(true));
+
+    #[allow(clippy::mutable_key_type, reason = "external type definition")]
+    let changes = action.edit.as_ref().unwrap().changes.as_ref().unwrap();
+    let mut edits = changes[&harness.file_uri("schema.fbs")].clone();
+    edits.sort_by_key(|edit| edit.range.start);
+    (action.title.clone(), edits)
+}
+
+#[tokio::test]
+async fn rename_keyword_type_and_its_references() {
+    let (title, edits) = get_reserved_keyword_edits(
+        "table table {}\ntable Monster {\n    t: table;\n}\n",
+        "`table` is a reserved keyword",
+    )
+    .await;
+    assert_eq!(title, "Rename `table` to `table_`");
+    assert_eq!(
+        edits,
+        vec![
+            TextEdit::new(
+                Range::new(Position::new(0, 6), Position::new(0, 11)),
+                "table_".to_string()
+            ),
+            TextEdit::new(
+                Range::new(Position::new(2, 7), Position::new(2, 12)),
+                "table_".to_string()
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn rename_keyword_field() {
+    let (title, edits) = get_reserved_keyword_edits(
+        "table Monster {\n    namespace: int;\n}\n",
+        "`namespace` is a reserved keyword",
+    )
+    .await;
+    assert_eq!(title, "Rename `namespace` to `namespace_`");
+    assert_eq!(
+        edits,
+        vec![TextEdit::new(
+            Range::new(Position::new(1, 4), Position::new(1, 13)),
+            "namespace_".to_string()
+        )]
+    );
+}
@@ -3,16 +3,17 @@ use crate::helpers::parse_fixture;
 use flatbuffers_language_server::ext::all_diagnostics::AllDiagnostics;
 use insta::assert_snapshot;
 use tower_lsp_server::lsp_types::{
-    notification, request, CompletionContext, CompletionParams, CompletionTriggerKind,
-    PartialResultParams, TextDocumentIdentifier, TextDocumentPositionParams,
-    VersionedTextDocumentIdentifier, WorkDoneProgressParams,
+    notification, request, CompletionContext, CompletionItem, CompletionItemKind, CompletionParams,
+    CompletionTextEdit, CompletionTriggerKind, Documentation, InsertTextFormat,
+    PartialResultParams, Position, Range, TextDocumentIdentifier, TextDocumentPositionParams,
+    TextEdit, VersionedTextDocumentIdentifier, WorkDoneProgressParams,
 };
 
-async fn get_completion_list(
+async fn get_completion_items(
     harness: &mut TestHarness,
     main_fixture: &str,
     other_files: &[(&str, &str)],
-) -> String {
+) -> Vec<CompletionItem> {
     let (final_content, position) = parse_fixture(main_fixture);
 
     let cursor_line = position.line as usize;
@@ -76,7 +77,15 @@ async fn get_completion_list(
         })
         .unwrap_or_default();
     items.sort_by_key(|item| item.sort_text.as_ref().unwrap_or(&item.label).to_owned());
+    items
+}
 
+async fn get_completion_list(
+    harness: &mut TestHarness,
+    main_fixture: &str,
+    other_files: &[(&str, &str)],
+) -> String {
+    let items = get_completion_items(harness, main_fixture, other_files).await;
     let completion_labels: Vec<String> = items.into_iter().map(|item| item.label).collect();
 
     serde_json::to_string_pretty(&completion_labels).unwrap()
@@ -118,6 +127,22 @@ root_type $0
     assert_snapshot!(response);
 }
 
+#[tokio::test]
+async fn completion_for_root_type_offers_only_tables() {
+    let fixture = r"
+table MyTable {}
+struct MyStruct { a: int; }
+enum MyEnum: byte { A }
+union MyUnion { MyTable }
+
+root_type My$0
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+    let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+    assert_eq!(labels, vec!["MyTable"]);
+}
+
 #[tokio::test]
 async fn completion_for_keywords() {
     let fixture = r"
@@ -129,6 +154,20 @@ t$0
     assert_snapshot!(response);
 }
 
+#[tokio::test]
+async fn completion_for_file_keywords() {
+    let fixture = r"
+table T {}
+root_type T;
+file_$0
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+    let mut labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+    labels.sort_unstable();
+    assert_eq!(labels, vec!["file_extension", "file_identifier"]);
+}
+
 #[tokio::test]
 async fn no_completion_on_new_line_in_table_block() {
     let fixture = r"
@@ -178,6 +217,48 @@ table MyTable {
     assert_snapshot!(response);
 }
 
+#[tokio::test]
+async fn no_type_completion_in_field_attributes() {
+    let fixture = r"
+table MyTable {
+    foo: int ($0
+}
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+    assert!(items.iter().any(|item| item.label == "deprecated"));
+    assert!(!items
+        .iter()
+        .any(|item| item.label == "int" || item.label == "MyTable"));
+}
+
+#[tokio::test]
+async fn no_type_completion_in_field_default() {
+    let fixture = r"
+table MyTable {
+    foo: int = $0
+}
+";
+    let mut harness = TestHarness::new();
+    let response = get_completion_list(&mut harness, fixture, &[]).await;
+    assert_eq!(response, "[]");
+}
+
+#[tokio::test]
+async fn completion_for_bool_default_value() {
+    let fixture = r"
+table MyTable {
+    enabled: bool = $0
+}
+";
+    let mut harness = TestHarness::new();
+    let response = get_completion_list(&mut harness, fixture, &[]).await;
+    assert_eq!(
+        response,
+        serde_json::to_string_pretty(&["true", "false"]).unwrap()
+    );
+}
+
 #[tokio::test]
 async fn completion_from_included_file() {
     let included_fixture = r"
@@ -261,6 +342,89 @@ table MyTable {
     assert_snapshot!(response);
 }
 
+#[tokio::test]
+async fn completion_for_native_attribute_on_struct_field() {
+    let fixture = r"
+struct Vec3 { x: float; }
+table Monster {
+    pos: Vec3 (native$0);
+}
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+    let mut labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+    labels.sort_unstable();
+    assert_eq!(labels, vec!["native_default", "native_inline"]);
+}
+
+#[tokio::test]
+async fn completion_for_native_attribute_on_struct_declaration() {
+    let fixture = r"
+struct Vec3 (native$0) { x: float; }
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+    let mut labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+    labels.sort_unstable();
+    assert_eq!(
+        labels,
+        vec!["native_default", "native_type", "native_type_pack_name"]
+    );
+}
+
+#[tokio::test]
+async fn completion_for_deprecated_with_replacement_note() {
+    let fixture = r"
+table MyTable {
+    my_field: int (dep$0);
+}
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+    let deprecated: Vec<_> = items
+        .iter()
+        .filter(|item| item.label == "deprecated")
+        .collect();
+    assert_eq!(deprecated.len(), 2);
+    assert_eq!(deprecated[0].insert_text.as_deref(), Some("deprecated"));
+    assert_eq!(
+        deprecated[1].insert_text_format,
+        Some(InsertTextFormat::SNIPPET)
+    );
+    assert_eq!(
+        deprecated[1].text_edit,
+        Some(CompletionTextEdit::Edit(TextEdit {
+            range: Range::new(Position::new(2, 19), Position::new(2, 24)),
+            new_text: "deprecated); // replaced by ${1:field}".to_string(),
+        }))
+    );
+}
+
+#[tokio::test]
+async fn completion_for_deprecated_with_replacement_note_before_multibyte_char() {
+    let fixture = r"
+table MyTable {
+    my_field: int (dep$0); /* café */
+}
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+    let with_note = items
+        .iter()
+        .find(|item| {
+            item.label == "deprecated" && item.insert_text_format == Some(InsertTextFormat::SNIPPET)
+        })
+        .unwrap();
+    // `é` is two bytes but one UTF-16 code unit.
+    assert_eq!(
+        with_note.text_edit,
+        Some(CompletionTextEdit::Edit(TextEdit {
+            range: Range::new(Position::new(2, 19), Position::new(2, 35)),
+            new_text: "deprecated); /* café */ // replaced by ${1:field}".to_string(),
+        }))
+    );
+}
+
 #[tokio::test]
 async fn completion_for_partial_filtered_attribute_on_field() {
     let fixture = r"
@@ -287,6 +451,84 @@ table MyTable {
     assert_snapshot!(response);
 }
 
+#[tokio::test]
+async fn completion_for_id_attribute_matches_style_of_whole_table() {
+    let fixture = r"
+table MyTable {
+    first_field: int
+        (id:0, deprecated);
+    second_field: int ($0
+}
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+    assert_eq!(items[0].label, "id:1");
+}
+
+#[tokio::test]
+async fn completion_for_id_attribute_after_field_type() {
+    let fixture = r"
+table MyTable {
+    first_field: int (id: 0);
+    second_field: short $0
+}
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+    let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+    assert_eq!(labels, vec!["(id: 1)"]);
+    assert_eq!(
+        items[0].text_edit,
+        Some(CompletionTextEdit::Edit(TextEdit {
+            range: Range::new(Position::new(3, 24), Position::new(3, 24)),
+            new_text: "(id: 1)".to_string(),
+        }))
+    );
+}
+
+#[tokio::test]
+async fn no_id_attribute_after_struct_field_type() {
+    let fixture = r"
+struct MyStruct {
+    x: float;
+    y: float $0
+}
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+    assert!(items.iter().all(|item| !item.label.contains("id")));
+}
+
+#[tokio::test]
+async fn completion_for_key_attribute_warns_about_existing_key() {
+    let fixture = r"
+table Monster {
+    name: string (key);
+    hp: short (k$0
+}
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+    let key = items.iter().find(|item| item.label == "key").unwrap();
+    assert_eq!(key.detail.as_deref(), Some("`name` is already the key"));
+    assert_eq!(key.sort_text.as_deref(), Some("3_key"));
+}
+
+#[tokio::test]
+async fn completion_for_key_attribute_without_existing_key() {
+    let fixture = r"
+table Monster {
+    name: string (id: 0);
+    hp: short (k$0
+}
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+    let key = items.iter().find(|item| item.label == "key").unwrap();
+    assert_eq!(key.detail, None);
+    assert_eq!(key.sort_text.as_deref(), Some("0_key"));
+}
+
 #[tokio::test]
 async fn completion_for_second_attribute() {
     let fixture = r"
@@ -299,6 +541,42 @@ table MyTable {
     assert_snapshot!(response);
 }
 
+#[tokio::test]
+async fn completion_for_force_align_value() {
+    let fixture = r"
+table MyTable {
+    data: [ubyte] (force_align: $0);
+}
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+    let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+    assert_eq!(labels, vec!["1", "2", "4", "8", "16", "32"]);
+}
+
+#[tokio::test]
+async fn completion_for_hash_value() {
+    let fixture = r#"
+table MyTable {
+    name_hash: uint (id: 0, hash: "fnv1a$0
+}
+"#;
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+    let edits: Vec<_> = items
+        .iter()
+        .map(|item| match &item.text_edit {
+            Some(CompletionTextEdit::Edit(edit)) => (edit.new_text.as_str(), edit.range),
+            _ => panic!("expected a text edit"),
+        })
+        .collect();
+    let range = Range::new(Position::new(2, 34), Position::new(2, 40));
+    assert_eq!(
+        edits,
+        vec![("\"fnv1a_32\"", range), ("\"fnv1a_64\"", range)]
+    );
+}
+
 #[tokio::test]
 async fn completion_for_enum_variant_attribute() {
     let fixture = r"
@@ -454,6 +732,59 @@ table Forest {
     assert_snapshot!(response);
 }
 
+const NESTED_NAMESPACES: &str = r"
+namespace One;
+table W {}
+
+namespace One.Two;
+table X {}
+
+namespace One.Two.Three;
+table Y {}
+";
+
+#[tokio::test]
+async fn completion_after_namespace_and_dot() {
+    let fixture = r"
+table Forest {
+    oak: One.Two.$0
+}
+";
+    let mut harness = TestHarness::new();
+    let items =
+        get_completion_items(&mut harness, fixture, &[("other.fbs", NESTED_NAMESPACES)]).await;
+    let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+    assert_eq!(labels, vec!["X", "Three"]);
+    assert_eq!(
+        items[0].text_edit,
+        Some(CompletionTextEdit::Edit(TextEdit {
+            range: Range::new(Position::new(2, 17), Position::new(2, 17)),
+            new_text: "X".to_string(),
+        }))
+    );
+}
+
+#[tokio::test]
+async fn completion_after_outer_namespace_and_dot() {
+    let fixture = r"
+table Forest {
+    oak: One.$0
+}
+";
+    let mut harness = TestHarness::new();
+    let items =
+        get_completion_items(&mut harness, fixture, &[("other.fbs", NESTED_NAMESPACES)]).await;
+    let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+    assert_eq!(labels, vec!["W", "Two"]);
+    assert_eq!(
+        items[1].text_edit,
+        Some(CompletionTextEdit::Edit(TextEdit {
+            range: Range::new(Position::new(2, 13), Position::new(2, 13)),
+            new_text: "Two".to_string(),
+        }))
+    );
+}
+
 #[tokio::test]
 async fn completion_for_rpc_service_request() {
     let fixture = r"
@@ -488,3 +819,360 @@ rpc_service Service {
     let response = get_completion_list(&mut harness, fixture, &[]).await;
     assert_snapshot!(response);
 }
+
+#[tokio::test]
+async fn completion_for_definition_snippets() {
+    let fixture = r"
+table T {}
+r$0
+";
+    let mut harness = TestHarness::new();
+    let response = get_completion_list(&mut harness, fixture, &[]).await;
+    // The rpc_service snippet takes the place of the keyword.
+    let expected: Vec<&str> = vec!["root_type", "rpc_service"];
+    assert_eq!(response, serde_json::to_string_pretty(&expected).unwrap());
+}
+
+#[tokio::test]
+async fn no_definition_snippets_inside_block() {
+    let fixture = r"
+table T {
+    // {
+    r$0
+}
+";
+    let mut harness = TestHarness::new();
+    let response = get_completion_list(&mut harness, fixture, &[]).await;
+    let expected: Vec<&str> = vec!["root_type", "rpc_service"];
+    assert_eq!(response, serde_json::to_string_pretty(&expected).unwrap());
+}
+
+#[tokio::test]
+async fn completion_resolve_adds_documentation_and_include() {
+    let included_fixture = r"
+/// A table from another file.
+table IncludedTable {}
+";
+
+    let main_fixture = r"
+table MyTable {
+    a: In$0
+}
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(
+        &mut harness,
+        main_fixture,
+        &[("included.fbs", included_fixture)],
+    )
+    .await;
+    let item = items
+        .into_iter()
+        .find(|item| item.label == "IncludedTable")
+        .unwrap();
+    assert!(item.documentation.is_none());
+    assert!(item.additional_text_edits.is_none());
+
+    let resolved = harness.call::<request::ResolveCompletionItem>(item).await;
+    let Some(Documentation::MarkupContent(doc)) = resolved.documentation else {
+        panic!("expected markdown documentation");
+    };
+    assert!(doc.value.contains("A table from another file."));
+    let edits = resolved.additional_text_edits.unwrap();
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].new_text, "include \"included.fbs\";\n");
+}
+
+#[tokio::test]
+async fn completion_no_include_for_transitive_type() {
+    let middle_fixture = r#"include "leaf.fbs";
+table Middle {
+    l: Leaf;
+}
+"#;
+    let leaf_fixture = "table Leaf {}";
+
+    let main_fixture = r#"include "middle.fbs";
+table MyTable {
+    m: Middle;
+    a: Le$0
+}
+"#;
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(
+        &mut harness,
+        main_fixture,
+        &[("middle.fbs", middle_fixture), ("leaf.fbs", leaf_fixture)],
+    )
+    .await;
+    let item = items.into_iter().find(|item| item.label == "Leaf").unwrap();
+    assert!(item
+        .label_details
+        .as_ref()
+        .and_then(|d| d.description.as_ref())
+        .is_none());
+
+    let resolved = harness.call::<request::ResolveCompletionItem>(item).await;
+    assert!(resolved.additional_text_edits.is_none());
+}
+
+#[tokio::test]
+async fn completion_commit_characters_accept_types() {
+    let fixture = r"
+namespace MyNamespace;
+table Widget {}
+table Line {
+    widget: Wid$0
+}
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+
+    let options = harness
+        .server_capabilities
+        .clone()
+        .unwrap()
+        .completion_provider
+        .unwrap();
+    assert_eq!(
+        options.all_commit_characters,
+        Some(vec![";".to_string(), "(".to_string(), " ".to_string()])
+    );
+
+    // Typing `;` accepts the name and then inserts itself, so the edit must not end with one.
+    let widget = items.iter().find(|item| item.label == "Widget").unwrap();
+    assert_eq!(widget.commit_characters, None);
+    let Some(CompletionTextEdit::Edit(edit)) = &widget.text_edit else {
+        panic!("expected a text edit");
+    };
+    assert_eq!(edit.new_text, "Widget");
+}
+
+#[tokio::test]
+async fn completion_commit_characters_continue_namespaces() {
+    let fixture = r"
+namespace MyNamespace;
+table Widget {}
+table Line {
+    widget: MyNa$0
+}
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+
+    // `.` accepts the namespace and then starts completing its members.
+    let namespace = items
+        .iter()
+        .find(|item| item.label == "MyNamespace")
+        .unwrap();
+    assert_eq!(namespace.commit_characters, Some(vec![".".to_string()]));
+}
+
+#[tokio::test]
+async fn completion_snippets_have_no_commit_characters() {
+    let fixture = r"
+table Widget {}
+tab$0
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+
+    let snippet = items
+        .iter()
+        .find(|item| item.label == "table" && item.insert_text_format.is_some())
+        .unwrap();
+    assert_eq!(snippet.commit_characters, Some(vec![]));
+    // It replaces the keyword item, so it carries the keyword's documentation.
+    assert!(snippet.documentation.is_some());
+}
+
+#[tokio::test]
+async fn completion_merges_namespace_and_table_with_same_name() {
+    let fixture = r"
+table Foo {}
+table Line {
+    foo: Fo$0
+}
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(
+        &mut harness,
+        fixture,
+        &[("other.fbs", "namespace Foo;\ntable Bar {}")],
+    )
+    .await;
+
+    let foos: Vec<_> = items.iter().filter(|item| item.label == "Foo").collect();
+    assert_eq!(foos.len(), 1, "{foos:#?}");
+    assert_eq!(foos[0].kind, Some(CompletionItemKind::CLASS));
+}
+
+#[tokio::test]
+async fn completion_for_user_defined_attribute_has_doc_comment() {
+    let fixture = r#"
+/// Only used by the server.
+attribute "internal";
+table Monster {
+    secret: int (inte$0);
+}
+"#;
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+
+    let internal = items.iter().find(|item| item.label == "internal").unwrap();
+    let Some(Documentation::MarkupContent(doc)) = &internal.documentation else {
+        panic!("expected markdown documentation");
+    };
+    assert_eq!(doc.value, "Only used by the server.");
+}
+
+#[tokio::test]
+async fn completion_for_qualified_enum_default_value() {
+    let fixture = r"
+namespace Game;
+enum Difficulty : byte { Easy, Hard }
+namespace Other;
+table Level {
+    difficulty: Game.Difficulty = $0
+}
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+
+    let labels: Vec<_> = items.iter().map(|item| item.label.as_str()).collect();
+    assert_eq!(labels, vec!["Easy", "Hard"]);
+    assert_eq!(items[1].kind, Some(CompletionItemKind::ENUM_MEMBER));
+    assert_eq!(items[1].detail.as_deref(), Some("Difficulty.Hard = 1"));
+}
+
+#[tokio::test]
+async fn completion_for_enum_default_value_in_enclosing_namespace() {
+    let fixture = r"
+namespace Game;
+enum Difficulty : byte { Easy, Hard }
+namespace Game.Levels;
+table Level {
+    difficulty: Difficulty = H$0
+}
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+
+    let labels: Vec<_> = items.iter().map(|item| item.label.as_str()).collect();
+    assert_eq!(labels, vec!["Hard"]);
+    let Some(CompletionTextEdit::Edit(edit)) = &items[0].text_edit else {
+        panic!("expected a text edit");
+    };
+    assert_eq!(edit.new_text, "Hard");
+    assert_eq!(
+        edit.range,
+        Range::new(Position::new(5, 29), Position::new(5, 30))
+    );
+}
+
+/// The insert range of the `Widget` completion for a vector field.
+async fn vector_element_widget_range(fixture: &str) -> Range {
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+    let widget = items
+        .iter()
+        .find(|item| item.label == "Widget")
+        .expect("Widget should be offered as an element type");
+    let Some(CompletionTextEdit::Edit(edit)) = &widget.text_edit else {
+        panic!("expected a text edit");
+    };
+    assert_eq!(edit.new_text, "Widget");
+    edit.range
+}
+
+#[tokio::test]
+async fn completion_for_vector_element_type() {
+    let fixture = r"
+table Widget {}
+table Inventory {
+    items: [$0
+}
+";
+    assert_eq!(
+        vector_element_widget_range(fixture).await,
+        Range::new(Position::new(3, 12), Position::new(3, 12))
+    );
+}
+
+#[tokio::test]
+async fn completion_for_vector_element_type_prefix() {
+    let fixture = r"
+table Widget {}
+table Inventory {
+    items: [Wid$0
+}
+";
+    assert_eq!(
+        vector_element_widget_range(fixture).await,
+        Range::new(Position::new(3, 12), Position::new(3, 15))
+    );
+}
+
+#[tokio::test]
+async fn completion_for_vector_element_after_namespace_and_dot() {
+    let fixture = r"
+table Forest {
+    items: [One.$0
+}
+";
+    let mut harness = TestHarness::new();
+    let items =
+        get_completion_items(&mut harness, fixture, &[("other.fbs", NESTED_NAMESPACES)]).await;
+    let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+    assert_eq!(labels, vec!["W", "Two"]);
+    assert_eq!(
+        items[0].text_edit,
+        Some(CompletionTextEdit::Edit(TextEdit {
+            range: Range::new(Position::new(2, 16), Position::new(2, 16)),
+            new_text: "W".to_string(),
+        }))
+    );
+}
+
+#[tokio::test]
+async fn completion_for_rpc_method_attribute() {
+    let fixture = r"
+table Req {}
+table Res {}
+rpc_service Service {
+    Read(Req):Res;
+    Watch(Req):Res ($0
+}
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+    let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+    assert_eq!(labels, vec!["streaming", "idempotent"]);
+    assert_eq!(items[0].insert_text.as_deref(), Some("streaming:"));
+}
+
+#[tokio::test]
+async fn completion_for_rpc_method_streaming_value() {
+    let fixture = r"
+table Req {}
+table Res {}
+rpc_service Service {
+    Read(Req):Res;
+    Watch(Req):Res (streaming: $0
+}
+";
+    let mut harness = TestHarness::new();
+    let items = get_completion_items(&mut harness, fixture, &[]).await;
+    let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+    assert_eq!(
+        labels,
+        vec!["\"none\"", "\"client\"", "\"server\"", "\"bidi\""]
+    );
+    assert_eq!(
+        items[2].text_edit,
+        Some(CompletionTextEdit::Edit(TextEdit {
+            range: Range::new(Position::new(5, 31), Position::new(5, 31)),
+            new_text: "\"server\"".to_string(),
+        }))
+    );
+}
@@ -0,0 +1,229 @@
+#![allow(
+    clippy::mutable_key_type,
+    reason = "lsp_types::PublishDiagnosticParams uses Uri, which AllDiagnostics mimics"
+)]
+
+use crate::harness::TestHarness;
+use flatbuffers_language_server::{
+    diagnostics::codes::DiagnosticCode, ext::all_diagnostics::AllDiagnostics,
+};
+use serde_json::json;
+use std::fs;
+use tempfile::TempDir;
+use tower_lsp_server::lsp_types::{
+    notification, request, CodeActionContext, CodeActionOrCommand, CodeActionParams, DiagnosticTag,
+    PartialResultParams, Position, Range, SetTraceParams, TextDocumentIdentifier, TextEdit,
+    TraceValue, VersionedTextDocumentIdentifier, WorkDoneProgressParams,
+};
+
+#[tokio::test]
+async fn configuration_disables_diagnostics() {
+    let content = "table MyTable { furryWombat:string; }";
+    let mut harness =
+        TestHarness::new().with_configuration(json!({ "diagnostics": { "nonSnakeCase": false } }));
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let params = harness
+        .notification::<notification::PublishDiagnostics>()
+        .await;
+    assert_eq!(params.uri, harness.file_uri("schema.fbs"));
+    assert!(params.diagnostics.is_empty());
+}
+
+#[tokio::test]
+async fn configuration_include_dirs() {
+    let external = TempDir::new().unwrap();
+    fs::write(external.path().join("external.fbs"), "table External {}").unwrap();
+
+    let content = "include \"external.fbs\";\ntable MyTable { e: External; }";
+    let mut harness =
+        TestHarness::new().with_configuration(json!({ "includeDirs": [external.path()] }));
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let schema_uri = harness.file_uri("schema.fbs");
+    let params = loop {
+        let params = harness
+            .notification::<notification::PublishDiagnostics>()
+            .await;
+        if params.uri == schema_uri {
+            break params;
+        }
+    };
+    assert!(params.diagnostics.is_empty(), "{:?}", params.diagnostics);
+}
+
+#[tokio::test]
+async fn configuration_enables_unreferenced_types() {
+    let content = r"table Root { a: Used; }
+table Used {}
+table Unused {}
+root_type Root;
+";
+    let mut harness = TestHarness::new()
+        .with_configuration(json!({ "diagnostics": { "unreferencedTypes": true } }));
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let schema_uri = harness.file_uri("schema.fbs");
+    let diagnostic = harness.get_first_diagnostic_for_file(&schema_uri).await;
+    assert_eq!(
+        diagnostic.code,
+        Some(DiagnosticCode::UnreferencedType.into())
+    );
+    assert_eq!(diagnostic.tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(2, 6), Position::new(2, 12))
+    );
+
+    let all_diagnostics = harness.call::<AllDiagnostics>(()).await;
+    assert_eq!(all_diagnostics[&schema_uri].len(), 1);
+}
+
+#[tokio::test]
+async fn configuration_enables_namespace_mismatch() {
+    let content = "namespace game.engine;\ntable Player {}";
+    let mut harness = TestHarness::new()
+        .with_configuration(json!({ "diagnostics": { "namespaceMismatch": true } }));
+    harness
+        .initialize_and_open(&[("game/core/player.fbs", content)])
+        .await;
+
+    let uri = harness.file_uri("game/core/player.fbs");
+    let diagnostic = harness.get_first_diagnostic_for_file(&uri).await;
+    assert_eq!(
+        diagnostic.code,
+        Some(DiagnosticCode::NamespaceMismatch.into())
+    );
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(0, 10), Position::new(0, 21))
+    );
+
+    let code_actions = harness
+        .call::<request::CodeActionRequest>(CodeActionParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            range: diagnostic.range,
+            context: CodeActionContext {
+                diagnostics: vec![diagnostic.clone()],
+                ..Default::default()
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        })
+        .await;
+
+    let CodeActionOrCommand::CodeAction(code_action) = code_actions.unwrap()[0].clone() else {
+        panic!("expected a code action");
+    };
+    assert_eq!(code_action.title, "Set file namespace to `game.core`");
+    let changes = code_action
+        .edit
+        .and_then(|e| e.changes)
+        .and_then(|c| c.get(&uri).cloned())
+        .unwrap();
+    assert_eq!(
+        changes,
+        vec![TextEdit::new(diagnostic.range, "game.core".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn set_trace_logs_parse_times() {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", "table Main {}")])
+        .await;
+    assert!(harness
+        .pending_notifications::<notification::LogTrace>()
+        .is_empty());
+
+    harness
+        .send_notification::<notification::SetTrace>(SetTraceParams {
+            value: TraceValue::Verbose,
+        })
+        .await;
+    let uri = harness.file_uri("schema.fbs");
+    harness
+        .change_file_sync(
+            VersionedTextDocumentIdentifier {
+                uri: uri.clone(),
+                version: 2,
+            },
+            "table Main { a: int; }",
+        )
+        .await;
+
+    let trace = harness.notification::<notification::LogTrace>().await;
+    assert!(
+        trace.message.starts_with("parsed schema.fbs in "),
+        "{}",
+        trace.message
+    );
+    assert_eq!(
+        trace.verbose,
+        Some(harness.root_path.join("schema.fbs").display().to_string())
+    );
+}
+
+#[tokio::test]
+async fn configuration_limits_include_depth() {
+    let mut harness = TestHarness::new().with_configuration(json!({ "maxIncludeDepth": 2 }));
+    harness
+        .initialize_and_open_some(
+            &[
+                ("a.fbs", "include \"b.fbs\";\ntable A { b: B; }"),
+                ("b.fbs", "include \"c.fbs\";\ntable B { c: C; }"),
+                ("c.fbs", "include \"d.fbs\";\ntable C { d: D; }"),
+                ("d.fbs", "table D {}"),
+            ],
+            &["a.fbs"],
+        )
+        .await;
+
+    // Depths are counted from a.fbs, the only file that nothing includes,
+    // whichever file the workspace scan happens to parse first.
+    let c_uri = harness.file_uri("c.fbs");
+    let diagnostic = harness.get_first_diagnostic_for_file(&c_uri).await;
+    assert_eq!(diagnostic.code, Some(DiagnosticCode::IncludeTooDeep.into()));
+    assert_eq!(
+        diagnostic.message,
+        "include is nested more than 2 levels deep"
+    );
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(0, 9), Position::new(0, 14))
+    );
+}
+
+#[tokio::test]
+async fn configuration_stops_following_includes_past_the_limit() {
+    let mut harness = TestHarness::new().with_configuration(json!({ "maxIncludeDepth": 1 }));
+    harness
+        .initialize_with_workspace_folders(
+            &["root"],
+            &[
+                ("root/a.fbs", "include \"../lib/b.fbs\";\ntable A { b: B; }"),
+                ("lib/b.fbs", "include \"c.fbs\";\ntable B { c: C; }"),
+                ("lib/c.fbs", "table C {}"),
+            ],
+            &[],
+        )
+        .await;
+
+    // lib/c.fbs is outside the workspace, so it is only reached through
+    // lib/b.fbs, which is already as deep as the limit allows.
+    let all_diagnostics = harness.call::<AllDiagnostics>(()).await;
+    let b_diagnostics = &all_diagnostics[&harness.file_uri("lib/b.fbs")];
+    assert_eq!(b_diagnostics.len(), 1);
+    assert_eq!(
+        b_diagnostics[0].code,
+        Some(DiagnosticCode::IncludeTooDeep.into())
+    );
+    assert!(!all_diagnostics.contains_key(&harness.file_uri("lib/c.fbs")));
+}
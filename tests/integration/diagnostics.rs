@@ -5,12 +5,16 @@
 
 use crate::harness::TestHarness;
 use flatbuffers_language_server::{
-    diagnostics::codes::DiagnosticCode, ext::all_diagnostics::AllDiagnostics,
+    diagnostics::codes::DiagnosticCode,
+    ext::{
+        all_diagnostics::AllDiagnostics,
+        file_health::{DiagnosticCounts, FileHealth},
+    },
 };
 use tower_lsp_server::lsp_types::{
     notification, request, CodeActionContext, CodeActionOrCommand, CodeActionParams,
     DiagnosticSeverity, DiagnosticTag, PartialResultParams, Position, Range,
-    TextDocumentIdentifier, WorkDoneProgressParams,
+    TextDocumentIdentifier, VersionedTextDocumentIdentifier, WorkDoneProgressParams,
 };
 
 #[tokio::test]
@@ -41,6 +45,24 @@ async fn diagnostic_error_has_correct_range() {
     assert_eq!(all.len(), 1);
 }
 
+#[tokio::test]
+async fn diagnostic_range_with_crlf_and_bom() {
+    let content = "\u{feff}table MyTable { a: invalid_type; }\r\ntable Other {}\r\n";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let params = harness
+        .notification::<notification::PublishDiagnostics>()
+        .await;
+    assert_eq!(params.diagnostics.len(), 1);
+
+    // flatc reports byte columns, but the BOM is a single UTF-16 code unit.
+    let expected_range = Range::new(Position::new(0, 20), Position::new(0, 32)); // "invalid_type"
+    assert_eq!(params.diagnostics[0].range, expected_range);
+}
+
 #[tokio::test]
 async fn multiple_files() {
     let content_a = r"
@@ -115,6 +137,100 @@ enum MyEnum: byte { C, D }
     );
 }
 
+#[tokio::test]
+async fn duplicate_definition_in_unrelated_files() {
+    let content = "namespace NS;\ntable Foo {}";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("a.fbs", content), ("b.fbs", content)])
+        .await;
+
+    let a_uri = harness.file_uri("a.fbs");
+    let b_uri = harness.file_uri("b.fbs");
+    let diagnostics = harness.call::<AllDiagnostics>(()).await;
+    for (uri, other_uri) in [(&a_uri, &b_uri), (&b_uri, &a_uri)] {
+        assert_eq!(diagnostics[uri].len(), 1);
+        let diagnostic = &diagnostics[uri][0];
+        assert_eq!(
+            diagnostic.code,
+            Some(DiagnosticCode::DuplicateDefinition.into())
+        );
+        assert_eq!(
+            diagnostic.range,
+            Range::new(Position::new(1, 6), Position::new(1, 9))
+        );
+        let related_information = diagnostic.related_information.as_ref().unwrap();
+        assert_eq!(related_information.len(), 1);
+        assert_eq!(related_information[0].location.uri, *other_uri);
+    }
+
+    // Renaming one of the definitions clears the diagnostic in both files.
+    harness
+        .change_file_sync(
+            VersionedTextDocumentIdentifier {
+                uri: b_uri.clone(),
+                version: 2,
+            },
+            "namespace NS;\ntable Bar {}",
+        )
+        .await;
+
+    let diagnostics = harness.call::<AllDiagnostics>(()).await;
+    assert!(diagnostics[&a_uri].is_empty());
+    assert!(diagnostics[&b_uri].is_empty());
+}
+
+#[tokio::test]
+async fn undefined_nested_flatbuffer_root_type() {
+    let content = r#"
+table Outer {
+    inner: [ubyte] (nested_flatbuffer: "Missing");
+}
+"#;
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let diagnostic = harness
+        .wait_for_diagnostic("type referenced but not defined")
+        .await
+        .unwrap();
+    assert_eq!(diagnostic.code, Some(DiagnosticCode::UndefinedType.into()));
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(2, 40), Position::new(2, 47))
+    );
+}
+
+#[tokio::test]
+async fn nested_flatbuffer_root_type_must_be_a_table() {
+    let content = r#"
+struct Point { x: float; }
+
+table Outer {
+    inner: [ubyte] (nested_flatbuffer: "Point");
+}
+"#;
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let diagnostic = harness
+        .wait_for_diagnostic("nested_flatbuffer root type must be a table")
+        .await
+        .unwrap();
+    assert_eq!(
+        diagnostic.code,
+        Some(DiagnosticCode::InvalidNestedFlatbuffer.into())
+    );
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(4, 40), Position::new(4, 45))
+    );
+}
+
 #[tokio::test]
 async fn duplicate_enum_variant() {
     let content = "enum MyEnum: byte { A, B, A }";
@@ -637,6 +753,69 @@ root_type Pen;
     }
 }
 
+#[tokio::test]
+async fn defining_a_type_in_included_file_updates_dependents() {
+    let included = "table Ink {}";
+    let main = r#"
+include "included.fbs";
+table Pen { brand: Brand; }
+"#;
+
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", main), ("included.fbs", included)])
+        .await;
+
+    let main_uri = harness.file_uri("schema.fbs");
+    let diagnostics = harness.call::<AllDiagnostics>(()).await;
+    assert_eq!(diagnostics[&main_uri].len(), 1);
+
+    // Only the included file changes, the dependent is not touched.
+    let included_uri = harness.file_uri("included.fbs");
+    harness
+        .change_file_sync(
+            VersionedTextDocumentIdentifier {
+                uri: included_uri,
+                version: 2,
+            },
+            "table Ink {}\ntable Brand {}",
+        )
+        .await;
+
+    let diagnostics = harness.call::<AllDiagnostics>(()).await;
+    assert!(diagnostics[&main_uri].is_empty());
+}
+
+#[tokio::test]
+async fn editing_an_included_file_revalidates_dependents() {
+    let included = "struct Point { x: float; }";
+    let main = r#"
+include "included.fbs";
+struct Line { start: Point; }
+"#;
+
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", main), ("included.fbs", included)])
+        .await;
+
+    // The names defined by the included file do not change, only the kind of `Point`.
+    let included_uri = harness.file_uri("included.fbs");
+    harness
+        .change_file_sync(
+            VersionedTextDocumentIdentifier {
+                uri: included_uri,
+                version: 2,
+            },
+            "table Point { x: float; }",
+        )
+        .await;
+
+    let main_uri = harness.file_uri("schema.fbs");
+    let diagnostic = harness.get_first_diagnostic_for_file(&main_uri).await;
+    assert_eq!(diagnostic.range.start.line, 2);
+}
+
 #[tokio::test]
 async fn undefined_vector_type_in_included_file() {
     let included = r"
@@ -826,3 +1005,773 @@ table MyTable {}
     assert_eq!(diagnostic[0].range.start, Position::new(1, 0));
     assert_eq!(diagnostic[0].range.end.line, 1);
 }
+
+#[tokio::test]
+async fn undefined_type_suggests_nearest_type() {
+    let content = "include \"widget.fbs\";\ntable Foo { w: Widgt; }";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content), ("widget.fbs", "table Widget {}")])
+        .await;
+
+    let schema_uri = harness.file_uri("schema.fbs");
+    let diagnostics = harness.call::<AllDiagnostics>(()).await;
+    let diagnostic = diagnostics[&schema_uri]
+        .iter()
+        .find(|d| d.code == Some(DiagnosticCode::UndefinedType.into()))
+        .cloned()
+        .expect("expected an undefined type diagnostic");
+    assert!(
+        diagnostic.message.ends_with("(did you mean `Widget`?)"),
+        "{}",
+        diagnostic.message
+    );
+    let related_information = diagnostic.related_information.unwrap();
+    assert_eq!(related_information.len(), 1);
+    assert_eq!(
+        related_information[0].location.uri,
+        harness.file_uri("widget.fbs")
+    );
+    assert_eq!(
+        related_information[0].location.range,
+        Range::new(Position::new(0, 6), Position::new(0, 12))
+    );
+}
+
+#[tokio::test]
+async fn include_not_found_suggests_nearest_file() {
+    let content = "include \"comon.fbs\";\ntable Foo { c: Common; }";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content), ("common.fbs", "table Common {}")])
+        .await;
+
+    let schema_uri = harness.file_uri("schema.fbs");
+    let diagnostics = loop {
+        let params = harness
+            .notification::<notification::PublishDiagnostics>()
+            .await;
+        if params.uri == schema_uri {
+            break params.diagnostics;
+        }
+    };
+
+    let diagnostic = diagnostics
+        .iter()
+        .find(|d| d.code == Some(DiagnosticCode::IncludeNotFound.into()))
+        .cloned()
+        .expect("expected an include-not-found diagnostic");
+    assert_eq!(diagnostic.message, "cannot find included file `comon.fbs`");
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(0, 9), Position::new(0, 18))
+    );
+    let related_information = diagnostic.related_information.clone().unwrap();
+    assert_eq!(related_information.len(), 1);
+    assert_eq!(
+        related_information[0].location.uri,
+        harness.file_uri("common.fbs")
+    );
+    assert_eq!(related_information[0].message, "did you mean `common.fbs`?");
+
+    let code_actions = harness
+        .call::<request::CodeActionRequest>(CodeActionParams {
+            text_document: TextDocumentIdentifier {
+                uri: schema_uri.clone(),
+            },
+            range: diagnostic.range,
+            context: CodeActionContext {
+                diagnostics: vec![diagnostic.clone()],
+                ..Default::default()
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        })
+        .await;
+
+    let CodeActionOrCommand::CodeAction(code_action) = code_actions.unwrap()[0].clone() else {
+        panic!("expected a code action");
+    };
+    assert_eq!(code_action.title, "Change include to `common.fbs`");
+    let changes = code_action
+        .edit
+        .and_then(|e| e.changes)
+        .and_then(|c| c.get(&schema_uri).cloned())
+        .unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].new_text, "common.fbs");
+    assert_eq!(changes[0].range, diagnostic.range);
+}
+
+#[tokio::test]
+async fn shadowed_builtin_is_error() {
+    let content = "table int {}";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let diagnostic = harness
+        .get_first_diagnostic_for_file(&harness.file_uri("schema.fbs"))
+        .await;
+    assert_eq!(
+        diagnostic.code,
+        Some(DiagnosticCode::ShadowedBuiltin.into())
+    );
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(diagnostic.message, "`int` shadows the built-in type `int`");
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(0, 6), Position::new(0, 9))
+    );
+}
+
+#[tokio::test]
+async fn shadowed_builtin_in_namespace_is_warning() {
+    let content = "namespace MyGame;\nenum string:byte { A }";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let diagnostic = harness
+        .get_first_diagnostic_for_file(&harness.file_uri("schema.fbs"))
+        .await;
+    assert_eq!(
+        diagnostic.code,
+        Some(DiagnosticCode::ShadowedBuiltin.into())
+    );
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(
+        diagnostic.message,
+        "`MyGame.string` shadows the built-in type `string`"
+    );
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(1, 5), Position::new(1, 11))
+    );
+}
+
+#[tokio::test]
+async fn root_type_must_be_a_table() {
+    let content = "struct MyStruct { a: int; }\nroot_type MyStruct;";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let schema_uri = harness.file_uri("schema.fbs");
+    let diagnostic = harness.get_first_diagnostic_for_file(&schema_uri).await;
+    assert_eq!(
+        diagnostic.code,
+        Some(DiagnosticCode::InvalidRootType.into())
+    );
+    assert_eq!(
+        diagnostic.message,
+        "root_type must be a table, not struct `MyStruct`"
+    );
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(1, 10), Position::new(1, 18))
+    );
+
+    let related_information = diagnostic.related_information.unwrap();
+    assert_eq!(related_information.len(), 1);
+    assert_eq!(related_information[0].location.uri, schema_uri);
+    assert_eq!(
+        related_information[0].location.range,
+        Range::new(Position::new(0, 7), Position::new(0, 15))
+    );
+}
+
+#[tokio::test]
+async fn root_type_enum_is_not_a_table() {
+    let content = "enum Color: byte { Red }\nroot_type Color;";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let diagnostic = harness
+        .get_first_diagnostic_for_file(&harness.file_uri("schema.fbs"))
+        .await;
+    assert_eq!(
+        diagnostic.code,
+        Some(DiagnosticCode::InvalidRootType.into())
+    );
+    assert_eq!(
+        diagnostic.message,
+        "root_type must be a table, not enum `Color`"
+    );
+}
+
+#[tokio::test]
+async fn root_type_undefined_is_quickfixable() {
+    let content = "root_type Other;";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content), ("other.fbs", "table Other {}")])
+        .await;
+
+    let schema_uri = harness.file_uri("schema.fbs");
+    let diagnostic = harness.get_first_diagnostic_for_file(&schema_uri).await;
+    assert_eq!(diagnostic.code, Some(DiagnosticCode::UndefinedType.into()));
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(0, 10), Position::new(0, 15))
+    );
+
+    let code_actions = harness
+        .call::<request::CodeActionRequest>(CodeActionParams {
+            text_document: TextDocumentIdentifier {
+                uri: schema_uri.clone(),
+            },
+            range: diagnostic.range,
+            context: CodeActionContext {
+                diagnostics: vec![diagnostic.clone()],
+                ..Default::default()
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        })
+        .await;
+
+    let CodeActionOrCommand::CodeAction(code_action) = code_actions.unwrap()[0].clone() else {
+        panic!("expected a code action");
+    };
+    assert_eq!(code_action.title, "Import `Other` from `other.fbs`");
+    let changes = code_action
+        .edit
+        .and_then(|e| e.changes)
+        .and_then(|c| c.get(&schema_uri).cloned())
+        .unwrap();
+    assert_eq!(changes[0].new_text, "include \"other.fbs\";\n\n");
+}
+
+#[tokio::test]
+async fn struct_force_align_must_be_a_power_of_two() {
+    let content = "struct Vec3 (force_align: 12) {\n    x: float;\n    y: float;\n    z: float;\n}";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let schema_uri = harness.file_uri("schema.fbs");
+    let diagnostic = harness.get_first_diagnostic_for_file(&schema_uri).await;
+    assert_eq!(
+        diagnostic.code,
+        Some(DiagnosticCode::InvalidForceAlign.into())
+    );
+    assert_eq!(
+        diagnostic.message,
+        "force_align must be a power of two ≥ 4 and ≤ 32"
+    );
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(0, 26), Position::new(0, 28))
+    );
+}
+
+#[tokio::test]
+async fn vector_force_align_must_cover_element_alignment() {
+    let content = "table Mesh {\n    points: [double] (force_align: 4);\n    bytes: [ubyte] (force_align: 16);\n}";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let schema_uri = harness.file_uri("schema.fbs");
+    let diagnostic = harness.get_first_diagnostic_for_file(&schema_uri).await;
+    assert_eq!(
+        diagnostic.code,
+        Some(DiagnosticCode::InvalidForceAlign.into())
+    );
+    assert_eq!(
+        diagnostic.message,
+        "force_align must be a power of two ≥ 8 and ≤ 32"
+    );
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(1, 35), Position::new(1, 36))
+    );
+}
+
+#[tokio::test]
+async fn vector_force_align_must_cover_struct_alignment() {
+    let content = "struct Vec3 (force_align: 16) {\n    x: float;\n    y: float;\n    z: float;\n}\ntable Mesh {\n    points: [Vec3] (force_align: 8);\n}";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let schema_uri = harness.file_uri("schema.fbs");
+    let diagnostic = harness.get_first_diagnostic_for_file(&schema_uri).await;
+    assert_eq!(
+        diagnostic.message,
+        "force_align must be a power of two ≥ 16 and ≤ 32"
+    );
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(6, 33), Position::new(6, 34))
+    );
+}
+
+#[tokio::test]
+async fn diagnostics_are_published_with_document_version() {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", "table Foo {}")])
+        .await;
+    harness.pending_notifications::<notification::PublishDiagnostics>();
+
+    let uri = harness.file_uri("schema.fbs");
+    harness
+        .change_file_sync(
+            VersionedTextDocumentIdentifier {
+                uri: uri.clone(),
+                version: 7,
+            },
+            "table Foo { a: Bar; }",
+        )
+        .await;
+
+    let params = harness
+        .notification::<notification::PublishDiagnostics>()
+        .await;
+    assert_eq!(params.uri, uri);
+    assert_eq!(params.version, Some(7));
+    assert_eq!(params.diagnostics.len(), 1);
+}
+
+#[tokio::test]
+async fn undeclared_attribute_is_unknown() {
+    let content = "table Monster {\n    hp: short (id: 0, mystery);\n}";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let schema_uri = harness.file_uri("schema.fbs");
+    let diagnostic = harness.get_first_diagnostic_for_file(&schema_uri).await;
+    assert_eq!(
+        diagnostic.code,
+        Some(DiagnosticCode::UnknownAttribute.into())
+    );
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(diagnostic.message, "unknown attribute `mystery`");
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(1, 22), Position::new(1, 29))
+    );
+}
+
+#[tokio::test]
+async fn declared_attribute_is_known() {
+    let content = "attribute \"mystery\";\ntable Monster {\n    hp: short (mystery);\n}";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let schema_uri = harness.file_uri("schema.fbs");
+    let diagnostics = harness.call::<AllDiagnostics>(()).await;
+    assert!(diagnostics[&schema_uri].is_empty());
+}
+
+#[tokio::test]
+async fn enum_value_named_like_its_enum() {
+    let content = r"
+enum Color: byte {
+    Red,
+    // Color is also a comment here.
+    Color
+}
+";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let diagnostic = harness
+        .wait_for_diagnostic("has the same name as its enum")
+        .await
+        .unwrap();
+    assert_eq!(
+        diagnostic.code,
+        Some(DiagnosticCode::VariantNameCollision.into())
+    );
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(4, 4), Position::new(4, 9))
+    );
+}
+
+#[tokio::test]
+async fn union_member_named_like_its_union() {
+    let content = r"
+namespace Items;
+table Weapon {}
+
+namespace Game;
+union Weapon { Items.Weapon }
+";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let diagnostic = harness
+        .wait_for_diagnostic("has the same name as its union")
+        .await
+        .unwrap();
+    assert_eq!(
+        diagnostic.code,
+        Some(DiagnosticCode::VariantNameCollision.into())
+    );
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(5, 21), Position::new(5, 27))
+    );
+}
+
+#[tokio::test]
+async fn mixed_explicit_and_implicit_field_ids() {
+    let content = r"
+table Monster {
+    hp: short (id: 0);
+    mana: short;
+    name: string;
+}
+";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let diagnostic = harness
+        .wait_for_diagnostic("either all fields or no fields must have an `id` attribute")
+        .await
+        .unwrap();
+    assert_eq!(diagnostic.code, Some(DiagnosticCode::MixedFieldIds.into()));
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(1, 6), Position::new(1, 13))
+    );
+    assert!(diagnostic.message.ends_with("missing on `mana`, `name`"));
+    let related: Vec<Range> = diagnostic
+        .related_information
+        .unwrap()
+        .into_iter()
+        .map(|info| info.location.range)
+        .collect();
+    assert_eq!(
+        related,
+        vec![
+            Range::new(Position::new(3, 4), Position::new(3, 8)),
+            Range::new(Position::new(4, 4), Position::new(4, 8)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn unknown_default_for_namespaced_enum() {
+    let content = "namespace Game;\nenum Difficulty : byte { Easy, Hard }\nnamespace Other;\ntable Level {\n    difficulty: Game.Difficulty = Hardest;\n}";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let schema_uri = harness.file_uri("schema.fbs");
+    let diagnostic = harness.get_first_diagnostic_for_file(&schema_uri).await;
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(
+        diagnostic.message,
+        "`Hardest` is not a value of enum `Game.Difficulty`"
+    );
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(4, 34), Position::new(4, 41))
+    );
+}
+
+#[tokio::test]
+async fn unqualified_default_for_namespaced_enum_is_valid() {
+    let content = "namespace Game;\nenum Difficulty : byte { Easy, Hard }\nnamespace Other;\ntable Level {\n    difficulty: Game.Difficulty = Hard;\n}";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let schema_uri = harness.file_uri("schema.fbs");
+    let diagnostics = harness
+        .notification::<notification::PublishDiagnostics>()
+        .await;
+    assert_eq!(diagnostics.uri, schema_uri);
+    assert_eq!(diagnostics.diagnostics, vec![]);
+}
+
+#[tokio::test]
+async fn keyword_as_type_name() {
+    let content = "table table {}";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let diagnostic = harness
+        .wait_for_diagnostic("is a reserved keyword")
+        .await
+        .unwrap();
+    assert_eq!(
+        diagnostic.code,
+        Some(DiagnosticCode::ReservedKeyword.into())
+    );
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(
+        diagnostic.message,
+        "`table` is a reserved keyword and cannot be used as a table name"
+    );
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(0, 6), Position::new(0, 11))
+    );
+}
+
+#[tokio::test]
+async fn keyword_as_field_name() {
+    let content = "table Monster {\n    namespace: int;\n}";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let diagnostic = harness
+        .wait_for_diagnostic("is a reserved keyword")
+        .await
+        .unwrap();
+    assert_eq!(
+        diagnostic.message,
+        "`namespace` is a reserved keyword and cannot be used as a field name"
+    );
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(1, 4), Position::new(1, 13))
+    );
+}
+
+#[tokio::test]
+async fn union_named_like_a_table() {
+    let content = "namespace Items;\ntable Sword {}\ntable Weapon {}\nunion Weapon { Sword }";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let diagnostic = harness
+        .wait_for_diagnostic("generates an enum that collides")
+        .await
+        .unwrap();
+    assert_eq!(
+        diagnostic.code,
+        Some(DiagnosticCode::TypeNameCollision.into())
+    );
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(
+        diagnostic.message,
+        "union `Items.Weapon` generates an enum that collides with table `Items.Weapon`"
+    );
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(3, 6), Position::new(3, 12))
+    );
+    let related = diagnostic.related_information.unwrap();
+    assert_eq!(related.len(), 1);
+    assert_eq!(
+        related[0].location.range,
+        Range::new(Position::new(2, 6), Position::new(2, 12))
+    );
+}
+
+#[tokio::test]
+async fn rpc_request_must_be_a_table() {
+    // flatc rejects a struct request, unless the struct is defined after the service.
+    let content = r"
+rpc_service Service {
+    Read(Point):Res;
+}
+table Res {}
+struct Point { x: int; }
+";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let diagnostic = harness
+        .wait_for_diagnostic("rpc request type must be a table")
+        .await
+        .unwrap();
+    assert_eq!(diagnostic.code, Some(DiagnosticCode::InvalidRpcType.into()));
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(
+        diagnostic.message,
+        "rpc request type must be a table, not struct `Point`"
+    );
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(2, 9), Position::new(2, 14))
+    );
+    let related = diagnostic.related_information.unwrap();
+    assert_eq!(
+        related[0].location.range,
+        Range::new(Position::new(5, 7), Position::new(5, 12))
+    );
+}
+
+#[tokio::test]
+async fn ignored_directory_does_not_cause_duplicate_definitions() {
+    let content = "namespace NS;\ntable Foo {}";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open_some(
+            &[("a.fbs", content), ("target/copy/a.fbs", content)],
+            &["a.fbs"],
+        )
+        .await;
+
+    let a_uri = harness.file_uri("a.fbs");
+    let diagnostics = harness.call::<AllDiagnostics>(()).await;
+    assert!(diagnostics[&a_uri].is_empty());
+    assert!(!diagnostics.contains_key(&harness.file_uri("target/copy/a.fbs")));
+}
+
+#[tokio::test]
+async fn ignore_file_directive_suppresses_diagnostics() {
+    let generated = "// fbls:ignore-file\ntable Generated {\n    namespace: int;\n}";
+    let schema = "include \"generated.fbs\";\ntable Pen { generated: Generated; }";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("generated.fbs", generated), ("schema.fbs", schema)])
+        .await;
+
+    let diagnostics = harness.call::<AllDiagnostics>(()).await;
+    assert_eq!(diagnostics.len(), 2);
+    for (uri, diagnostics) in diagnostics {
+        assert!(diagnostics.is_empty(), "{uri:?}: {diagnostics:?}");
+    }
+}
+
+#[tokio::test]
+async fn allow_comment_suppresses_diagnostic_on_its_line() {
+    let content = r#"
+include "coffee.fbs"; // fbls:allow(unused-include)
+include "pastries.fbs";
+"#;
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[
+            ("schema.fbs", content),
+            ("coffee.fbs", "namespace coffee;"),
+            ("pastries.fbs", "namespace pastries;"),
+        ])
+        .await;
+
+    let schema_uri = harness.file_uri("schema.fbs");
+    let diagnostics = loop {
+        let param = harness
+            .notification::<notification::PublishDiagnostics>()
+            .await;
+        if param.uri == schema_uri {
+            break param.diagnostics;
+        }
+        assert!(param.diagnostics.is_empty());
+    };
+    assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+    assert_eq!(
+        diagnostics[0].code,
+        Some(DiagnosticCode::UnusedInclude.into())
+    );
+    assert_eq!(diagnostics[0].range.start.line, 2);
+}
+
+#[tokio::test]
+async fn file_health_counts_diagnostics_by_severity() {
+    let schema = "include \"unused.fbs\";\ntable Pen { Color: int; }";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", schema), ("unused.fbs", "table Unused {}")])
+        .await;
+
+    harness.wait_for_diagnostic("unused include").await.unwrap();
+
+    let health = harness.call::<FileHealth>(()).await;
+    assert_eq!(health.len(), 2);
+    assert_eq!(
+        health[&harness.file_uri("unused.fbs")],
+        DiagnosticCounts::default()
+    );
+    assert_eq!(
+        health[&harness.file_uri("schema.fbs")],
+        DiagnosticCounts {
+            errors: 0,
+            warnings: 1,
+            information: 0,
+            hints: 1,
+        }
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn diagnostics_use_the_uri_a_symlinked_file_was_opened_with() {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open_some(&[("real/schema.fbs", "table Pen {}")], &[])
+        .await;
+    std::os::unix::fs::symlink(
+        harness.root_path.join("real"),
+        harness.root_path.join("link"),
+    )
+    .unwrap();
+
+    let link_uri = harness
+        .open_unsaved_file("link/schema.fbs", "table Pen { ink: Missing; }")
+        .await;
+    let diagnostic = harness.get_first_diagnostic_for_file(&link_uri).await;
+    assert!(
+        diagnostic.message.contains("Missing"),
+        "{}",
+        diagnostic.message
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn diagnostics_use_the_canonical_uri_after_a_symlinked_file_is_closed() {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open_some(&[("real/schema.fbs", "table Pen {}")], &[])
+        .await;
+    std::os::unix::fs::symlink(
+        harness.root_path.join("real"),
+        harness.root_path.join("link"),
+    )
+    .unwrap();
+
+    let link_uri = harness
+        .open_unsaved_file("link/schema.fbs", "table Pen { ink: Missing; }")
+        .await;
+    harness.get_first_diagnostic_for_file(&link_uri).await;
+    harness.close_file(link_uri).await;
+
+    let real_uri = harness
+        .open_unsaved_file("real/schema.fbs", "table Pen { nib: Absent; }")
+        .await;
+    let diagnostic = harness.get_first_diagnostic_for_file(&real_uri).await;
+    assert!(
+        diagnostic.message.contains("Absent"),
+        "{}",
+        diagnostic.message
+    );
+}
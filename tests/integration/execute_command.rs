@@ -0,0 +1,330 @@
+use crate::harness::TestHarness;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+use tower_lsp_server::lsp_types::{
+    notification, request, ExecuteCommandParams, Location, MessageType, NumberOrString, Position,
+    ProgressParamsValue, WorkDoneProgress, WorkDoneProgressParams,
+};
+use tower_lsp_server::UriExt;
+
+async fn execute(harness: &mut TestHarness, command: &str) -> Option<Value> {
+    harness
+        .call::<request::ExecuteCommand>(ExecuteCommandParams {
+            command: command.to_string(),
+            arguments: vec![],
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        })
+        .await
+}
+
+#[tokio::test]
+async fn show_include_graph() {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[
+            (
+                "main.fbs",
+                "include \"common.fbs\";\ntable Main { c: Common; }",
+            ),
+            (
+                "common.fbs",
+                "include \"base.fbs\";\ntable Common { b: Base; }",
+            ),
+            ("base.fbs", "table Base {}"),
+        ])
+        .await;
+
+    let graph = execute(&mut harness, "flatbuffers.showIncludeGraph")
+        .await
+        .unwrap();
+
+    let main = harness.file_uri("main.fbs");
+    let common = harness.file_uri("common.fbs");
+    let base = harness.file_uri("base.fbs");
+    assert_eq!(
+        graph["nodes"],
+        json!([{ "uri": base }, { "uri": common }, { "uri": main }])
+    );
+    assert_eq!(
+        graph["edges"],
+        json!([
+            { "from": common, "to": base, "direct": true },
+            { "from": main, "to": base, "direct": false },
+            { "from": main, "to": common, "direct": true },
+        ])
+    );
+}
+
+#[tokio::test]
+async fn unknown_command_is_error() {
+    let mut harness = TestHarness::new();
+    harness.initialize_and_open(&[]).await;
+
+    let result = harness
+        .try_call::<request::ExecuteCommand>(ExecuteCommandParams {
+            command: "flatbuffers.unknown".to_string(),
+            arguments: vec![],
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        })
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn generate_binary_schema() {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", "table Main { a: int; }\nroot_type Main;")])
+        .await;
+
+    let uri = harness.file_uri("schema.fbs");
+    let output = harness
+        .call::<request::ExecuteCommand>(ExecuteCommandParams {
+            command: "flatbuffers.generateBinarySchema".to_string(),
+            arguments: vec![json!(uri)],
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        })
+        .await
+        .unwrap();
+
+    let output = PathBuf::from(output.as_str().unwrap());
+    assert_eq!(output, uri.to_file_path().unwrap().with_extension("bfbs"));
+    let bytes = fs::read(output).unwrap();
+    assert_eq!(&bytes[4..8], b"BFBS");
+}
+
+#[tokio::test]
+async fn generate_binary_schema_reports_errors() {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", "table Main { a: Missing; }")])
+        .await;
+
+    let uri = harness.file_uri("schema.fbs");
+    let output = harness
+        .call::<request::ExecuteCommand>(ExecuteCommandParams {
+            command: "flatbuffers.generateBinarySchema".to_string(),
+            arguments: vec![json!(uri)],
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        })
+        .await;
+    assert!(output.is_none());
+
+    let message = harness.notification::<notification::ShowMessage>().await;
+    assert_eq!(message.typ, MessageType::ERROR);
+    assert!(message.message.contains("Missing"));
+    assert!(!uri.to_file_path().unwrap().with_extension("bfbs").exists());
+}
+
+#[tokio::test]
+async fn preview_generated_code_requires_flatc_path() {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", "table Main { a: int; }")])
+        .await;
+
+    let uri = harness.file_uri("schema.fbs");
+    let output = harness
+        .call::<request::ExecuteCommand>(ExecuteCommandParams {
+            command: "flatbuffers.previewGeneratedCode".to_string(),
+            arguments: vec![json!(uri), json!("rust")],
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        })
+        .await;
+    assert!(output.is_none());
+
+    let message = harness.notification::<notification::ShowMessage>().await;
+    assert_eq!(message.typ, MessageType::ERROR);
+    assert!(message.message.contains("flatcPath"));
+}
+
+#[tokio::test]
+async fn preview_generated_code_unknown_language_is_error() {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", "table Main { a: int; }")])
+        .await;
+
+    let uri = harness.file_uri("schema.fbs");
+    let result = harness
+        .try_call::<request::ExecuteCommand>(ExecuteCommandParams {
+            command: "flatbuffers.previewGeneratedCode".to_string(),
+            arguments: vec![json!(uri), json!("cobol")],
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        })
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn reindex_workspace_reads_changes_made_on_disk() {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open_some(
+            &[
+                (
+                    "main.fbs",
+                    "include \"common.fbs\";\ntable Main { c: Common; }",
+                ),
+                ("common.fbs", "table Common {}"),
+            ],
+            &["main.fbs"],
+        )
+        .await;
+
+    // Change a file without notifying the server, as if it were not running.
+    let common = harness.file_uri("common.fbs");
+    fs::write(common.to_file_path().unwrap(), "table Renamed {}").unwrap();
+
+    let result = execute(&mut harness, "flatbuffers.reindexWorkspace").await;
+    assert_eq!(result, None);
+
+    let diagnostic = harness
+        .wait_for_diagnostic("type referenced but not defined")
+        .await
+        .unwrap();
+    assert!(diagnostic.message.contains("Common"));
+
+    let progress = harness.pending_notifications::<notification::Progress>();
+    let reindex_progress: Vec<_> = progress
+        .iter()
+        .filter(|p| p.token == NumberOrString::String("reindex-workspace".to_string()))
+        .collect();
+    assert!(matches!(
+        reindex_progress.last().map(|p| &p.value),
+        Some(ProgressParamsValue::WorkDone(WorkDoneProgress::End(_)))
+    ));
+}
+
+async fn goto_root_type(harness: &mut TestHarness, file: &str) -> Option<Location> {
+    let uri = harness.file_uri(file);
+    harness
+        .call::<request::ExecuteCommand>(ExecuteCommandParams {
+            command: "flatbuffers.gotoRootType".to_string(),
+            arguments: vec![json!(uri)],
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        })
+        .await
+        .map(|value| serde_json::from_value(value).unwrap())
+}
+
+#[tokio::test]
+async fn goto_root_type_in_same_file() {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[(
+            "schema.fbs",
+            "table Other {}\ntable Main {}\nroot_type Main;",
+        )])
+        .await;
+
+    let location = goto_root_type(&mut harness, "schema.fbs").await.unwrap();
+    assert_eq!(location.uri, harness.file_uri("schema.fbs"));
+    assert_eq!(location.range.start, Position::new(1, 6));
+    assert_eq!(location.range.end, Position::new(1, 10));
+}
+
+#[tokio::test]
+async fn goto_root_type_from_include() {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[
+            (
+                "main.fbs",
+                "include \"root.fbs\";\ntable Extra { r: Root; }",
+            ),
+            ("root.fbs", "table Root {}\nroot_type Root;"),
+        ])
+        .await;
+
+    let location = goto_root_type(&mut harness, "main.fbs").await.unwrap();
+    assert_eq!(location.uri, harness.file_uri("root.fbs"));
+    assert_eq!(location.range.start, Position::new(0, 6));
+
+    let message = harness.notification::<notification::ShowMessage>().await;
+    assert_eq!(message.typ, MessageType::INFO);
+    assert!(message.message.contains("root.fbs"));
+}
+
+#[tokio::test]
+async fn goto_root_type_without_root_type() {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", "table Main {}")])
+        .await;
+
+    assert_eq!(goto_root_type(&mut harness, "schema.fbs").await, None);
+}
+
+async fn copy_qualified_name(
+    harness: &mut TestHarness,
+    file: &str,
+    position: Position,
+) -> Option<Value> {
+    let uri = harness.file_uri(file);
+    harness
+        .call::<request::ExecuteCommand>(ExecuteCommandParams {
+            command: "flatbuffers.copyQualifiedName".to_string(),
+            arguments: vec![json!(uri), json!(position)],
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        })
+        .await
+}
+
+#[tokio::test]
+async fn copy_qualified_name_of_definition_and_reference() {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[(
+            "schema.fbs",
+            "namespace My.Game;\ntable Weapon {}\ntable Monster { weapon: Weapon; }",
+        )])
+        .await;
+
+    let definition = copy_qualified_name(&mut harness, "schema.fbs", Position::new(1, 8)).await;
+    assert_eq!(definition, Some(json!("My.Game.Weapon")));
+
+    let reference = copy_qualified_name(&mut harness, "schema.fbs", Position::new(2, 26)).await;
+    assert_eq!(reference, Some(json!("My.Game.Weapon")));
+}
+
+#[tokio::test]
+async fn copy_qualified_name_without_symbol() {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", "table Main {}\n")])
+        .await;
+
+    let name = copy_qualified_name(&mut harness, "schema.fbs", Position::new(1, 0)).await;
+    assert_eq!(name, None);
+}
+
+#[tokio::test]
+async fn set_log_level() {
+    let mut harness = TestHarness::new();
+    harness.initialize_and_open(&[]).await;
+    let previous = log::max_level();
+
+    let applied = harness
+        .call::<request::ExecuteCommand>(ExecuteCommandParams {
+            command: "flatbuffers.setLogLevel".to_string(),
+            arguments: vec![json!("Warn")],
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        })
+        .await;
+    assert_eq!(applied, Some(json!("warn")));
+    assert_eq!(log::max_level(), log::LevelFilter::Warn);
+
+    let invalid = harness
+        .try_call::<request::ExecuteCommand>(ExecuteCommandParams {
+            command: "flatbuffers.setLogLevel".to_string(),
+            arguments: vec![json!("verbose")],
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        })
+        .await;
+    assert!(invalid.is_err());
+    assert_eq!(log::max_level(), log::LevelFilter::Warn);
+
+    log::set_max_level(previous);
+}
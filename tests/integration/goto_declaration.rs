@@ -0,0 +1,112 @@
+use crate::harness::TestHarness;
+use crate::helpers::parse_fixture;
+use tower_lsp_server::lsp_types::request::{GotoDeclaration, GotoDeclarationResponse};
+use tower_lsp_server::lsp_types::{
+    GotoDefinitionParams, Location, PartialResultParams, Position, Range, TextDocumentIdentifier,
+    TextDocumentPositionParams, WorkDoneProgressParams,
+};
+
+async fn get_declaration(
+    harness: &mut TestHarness,
+    main_fixture: &str,
+    other_files: &[(&str, &str)],
+) -> Option<GotoDeclarationResponse> {
+    let (content, position) = parse_fixture(main_fixture);
+
+    let mut workspace = vec![("schema.fbs", content.as_str())];
+    workspace.extend_from_slice(other_files);
+    harness.initialize_and_open(&workspace).await;
+
+    let main_file_uri = harness.file_uri("schema.fbs");
+    harness
+        .call::<GotoDeclaration>(GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: main_file_uri },
+                position,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        })
+        .await
+}
+
+#[tokio::test]
+async fn declaration_of_included_type_is_include_line() {
+    let main_fixture = r#"
+include "other.fbs";
+
+table MyTable {
+    a: Oth$0er;
+}
+"#;
+    let mut harness = TestHarness::new();
+    let response = get_declaration(
+        &mut harness,
+        main_fixture,
+        &[("other.fbs", "table Other {}")],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        response,
+        GotoDeclarationResponse::Scalar(Location {
+            uri: harness.file_uri("schema.fbs"),
+            range: Range::new(Position::new(1, 0), Position::new(1, 20)),
+        })
+    );
+}
+
+#[tokio::test]
+async fn declaration_of_transitively_included_type_is_include_line() {
+    let main_fixture = r#"
+include "middle.fbs";
+
+table MyTable {
+    m: Middle;
+    a: Le$0af;
+}
+"#;
+    let middle_fixture = r#"include "leaf.fbs";
+table Middle { l: Leaf; }
+"#;
+    let mut harness = TestHarness::new();
+    let response = get_declaration(
+        &mut harness,
+        main_fixture,
+        &[
+            ("middle.fbs", middle_fixture),
+            ("leaf.fbs", "table Leaf {}"),
+        ],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        response,
+        GotoDeclarationResponse::Scalar(Location {
+            uri: harness.file_uri("schema.fbs"),
+            range: Range::new(Position::new(1, 0), Position::new(1, 21)),
+        })
+    );
+}
+
+#[tokio::test]
+async fn declaration_of_local_type_is_definition() {
+    let main_fixture = r"
+table Local {}
+
+table MyTable {
+    a: Loc$0al;
+}
+";
+    let mut harness = TestHarness::new();
+    let response = get_declaration(&mut harness, main_fixture, &[])
+        .await
+        .unwrap();
+    assert_eq!(
+        response,
+        GotoDeclarationResponse::Scalar(Location {
+            uri: harness.file_uri("schema.fbs"),
+            range: Range::new(Position::new(1, 6), Position::new(1, 11)),
+        })
+    );
+}
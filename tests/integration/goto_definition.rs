@@ -0,0 +1,123 @@
+use crate::harness::TestHarness;
+use crate::helpers::parse_fixture;
+use tower_lsp_server::lsp_types::request::GotoDefinition;
+use tower_lsp_server::lsp_types::{
+    GotoDefinitionParams, GotoDefinitionResponse, Location, PartialResultParams, Position, Range,
+    TextDocumentIdentifier, TextDocumentPositionParams, VersionedTextDocumentIdentifier,
+    WorkDoneProgressParams,
+};
+
+async fn get_definition(
+    harness: &mut TestHarness,
+    main_fixture: &str,
+) -> Option<GotoDefinitionResponse> {
+    let (content, position) = parse_fixture(main_fixture);
+    harness
+        .initialize_and_open(&[("schema.fbs", content.as_str())])
+        .await;
+
+    let main_file_uri = harness.file_uri("schema.fbs");
+    harness
+        .call::<GotoDefinition>(GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: main_file_uri },
+                position,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        })
+        .await
+}
+
+#[tokio::test]
+async fn ambiguous_unqualified_reference_offers_every_definition() {
+    let fixture = r"
+namespace One;
+table Foo {}
+namespace Two;
+table Foo {}
+table Main { foo: F$0oo; }
+";
+    let mut harness = TestHarness::new();
+    let response = get_definition(&mut harness, fixture).await.unwrap();
+
+    let uri = harness.file_uri("schema.fbs");
+    assert_eq!(
+        response,
+        GotoDefinitionResponse::Array(vec![
+            Location {
+                uri: uri.clone(),
+                range: Range::new(Position::new(4, 6), Position::new(4, 9)),
+            },
+            Location {
+                uri,
+                range: Range::new(Position::new(2, 6), Position::new(2, 9)),
+            },
+        ])
+    );
+}
+
+#[tokio::test]
+async fn qualified_reference_has_one_definition() {
+    let fixture = r"
+namespace One;
+table Foo {}
+namespace Two;
+table Foo {}
+table Main { foo: One.F$0oo; }
+";
+    let mut harness = TestHarness::new();
+    let response = get_definition(&mut harness, fixture).await.unwrap();
+
+    assert_eq!(
+        response,
+        GotoDefinitionResponse::Scalar(Location {
+            uri: harness.file_uri("schema.fbs"),
+            range: Range::new(Position::new(2, 6), Position::new(2, 9)),
+        })
+    );
+}
+
+#[tokio::test]
+async fn definition_survives_removal_from_an_unrelated_file() {
+    let (content, position) =
+        parse_fixture("namespace NS;\ntable Foo {}\ntable Main { foo: F$0oo; }");
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[
+            ("a.fbs", content.as_str()),
+            ("b.fbs", "namespace NS;\ntable Foo {}"),
+        ])
+        .await;
+
+    // b.fbs renames its `Foo`, leaving a.fbs as the only definition.
+    harness
+        .change_file_sync(
+            VersionedTextDocumentIdentifier {
+                uri: harness.file_uri("b.fbs"),
+                version: 2,
+            },
+            "namespace NS;\ntable Bar {}",
+        )
+        .await;
+
+    let a_uri = harness.file_uri("a.fbs");
+    let response = harness
+        .call::<GotoDefinition>(GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: a_uri.clone() },
+                position,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        })
+        .await;
+
+    assert_eq!(
+        response,
+        Some(GotoDefinitionResponse::Scalar(Location {
+            uri: a_uri,
+            range: Range::new(Position::new(1, 6), Position::new(1, 9)),
+        }))
+    );
+}
@@ -0,0 +1,89 @@
+use crate::harness::TestHarness;
+use crate::helpers::parse_fixture;
+use tower_lsp_server::lsp_types::request::{GotoImplementation, GotoImplementationResponse};
+use tower_lsp_server::lsp_types::{
+    GotoDefinitionParams, Location, PartialResultParams, Position, Range, TextDocumentIdentifier,
+    TextDocumentPositionParams, WorkDoneProgressParams,
+};
+
+async fn get_implementation(
+    harness: &mut TestHarness,
+    fixture: &str,
+) -> Option<GotoImplementationResponse> {
+    let (content, position) = parse_fixture(fixture);
+    harness
+        .initialize_and_open(&[("schema.fbs", &content)])
+        .await;
+
+    let uri = harness.file_uri("schema.fbs");
+    harness
+        .call::<GotoImplementation>(GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        })
+        .await
+}
+
+#[tokio::test]
+async fn implementation_of_union_is_its_members() {
+    let fixture = r"
+table Sword {}
+table Shield {}
+union Eq$0uipment { Sword, Shield }
+";
+    let mut harness = TestHarness::new();
+    let response = get_implementation(&mut harness, fixture).await;
+    let uri = harness.file_uri("schema.fbs");
+    assert_eq!(
+        response,
+        Some(GotoImplementationResponse::Array(vec![
+            Location {
+                uri: uri.clone(),
+                range: Range::new(Position::new(1, 6), Position::new(1, 11)),
+            },
+            Location {
+                uri,
+                range: Range::new(Position::new(2, 6), Position::new(2, 12)),
+            },
+        ]))
+    );
+}
+
+#[tokio::test]
+async fn implementation_of_table_is_unions_containing_it() {
+    let fixture = r"
+table Sw$0ord {}
+table Shield {}
+union Equipment { Sword, Shield }
+union Weapon { Sword }
+";
+    let mut harness = TestHarness::new();
+    let response = get_implementation(&mut harness, fixture).await;
+    let uri = harness.file_uri("schema.fbs");
+    assert_eq!(
+        response,
+        Some(GotoImplementationResponse::Array(vec![
+            Location {
+                uri: uri.clone(),
+                range: Range::new(Position::new(3, 6), Position::new(3, 15)),
+            },
+            Location {
+                uri,
+                range: Range::new(Position::new(4, 6), Position::new(4, 12)),
+            },
+        ]))
+    );
+}
+
+#[tokio::test]
+async fn no_implementation_for_struct() {
+    let fixture = r"
+struct Ve$0c { x: float; }
+";
+    let mut harness = TestHarness::new();
+    assert_eq!(get_implementation(&mut harness, fixture).await, None);
+}
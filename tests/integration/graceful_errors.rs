@@ -2,8 +2,8 @@ use crate::harness::TestHarness;
 use crate::helpers::parse_fixture;
 use flatbuffers_language_server::diagnostics::codes::DiagnosticCode;
 use tower_lsp_server::lsp_types::{
-    notification, request, Hover, HoverParams, TextDocumentIdentifier, TextDocumentPositionParams,
-    WorkDoneProgressParams,
+    notification, request, Hover, HoverParams, Position, Range, TextDocumentIdentifier,
+    TextDocumentPositionParams, WorkDoneProgressParams,
 };
 
 async fn get_hover_response(
@@ -142,3 +142,22 @@ table Monster {
         "Expected hover information for pre-declared table"
     );
 }
+
+#[tokio::test]
+async fn nul_character_is_reported() {
+    let content = "table Monster {}\ntable Weapon\0 {}";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+
+    let params = harness
+        .notification::<notification::PublishDiagnostics>()
+        .await;
+    assert_eq!(params.diagnostics.len(), 1);
+    assert!(params.diagnostics[0].message.contains("NUL"));
+    assert_eq!(
+        params.diagnostics[0].range,
+        Range::new(Position::new(1, 12), Position::new(1, 13))
+    );
+}
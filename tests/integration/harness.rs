@@ -1,4 +1,6 @@
 use flatbuffers_language_server::ext::all_diagnostics::AllDiagnostics;
+use flatbuffers_language_server::ext::all_symbols::AllSymbols;
+use flatbuffers_language_server::ext::file_health::FileHealth;
 use flatbuffers_language_server::ext::sync::{
     DidChangeSync, DidOpenSync, DidSaveSync, InitializedSync,
 };
@@ -9,12 +11,13 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 use tokio::io::{self, AsyncReadExt, AsyncWriteExt, DuplexStream};
-use tower_lsp_server::jsonrpc::{Id, Request, Response};
+use tower_lsp_server::jsonrpc::{self, Id, Request, Response};
 use tower_lsp_server::lsp_types::notification::{
-    DidCloseTextDocument, DidSaveTextDocument, Notification,
+    DidCloseTextDocument, DidSaveTextDocument, Notification, SetTrace,
 };
 use tower_lsp_server::lsp_types::request::{
     Initialize, RegisterCapability, Request as LspRequest, WorkDoneProgressCreate,
+    WorkspaceConfiguration,
 };
 use tower_lsp_server::{lsp_types::*, UriExt};
 use tower_lsp_server::{LspService, Server};
@@ -41,6 +44,15 @@ pub struct TestHarness {
     #[allow(dead_code)] // Unused, but keep so the directory isn't cleaned up.
     temp_dir: TempDir,
     pub root_path: PathBuf,
+    /// The `flatbuffers` section returned for `workspace/configuration`.
+    /// The capability is only advertised when this is set.
+    configuration: Option<serde_json::Value>,
+    /// The `general.positionEncodings` advertised in `initialize`.
+    position_encodings: Option<Vec<PositionEncodingKind>>,
+    /// The `workspace.workspaceEdit` capabilities advertised in `initialize`.
+    workspace_edit: Option<WorkspaceEditClientCapabilities>,
+    /// The capabilities the server returned from `initialize`.
+    pub server_capabilities: Option<ServerCapabilities>,
 }
 
 impl TestHarness {
@@ -55,6 +67,9 @@ impl TestHarness {
             .custom_method(DidChangeSync::METHOD, Backend::did_change_sync)
             .custom_method(DidSaveSync::METHOD, Backend::did_save_sync)
             .custom_method(AllDiagnostics::METHOD, Backend::all_diagnostics)
+            .custom_method(AllSymbols::METHOD, Backend::all_symbols)
+            .custom_method(SetTrace::METHOD, Backend::set_trace)
+            .custom_method(FileHealth::METHOD, Backend::file_health)
             .finish();
 
         tokio::spawn(Server::new(req_server, resp_server, socket).serve(service));
@@ -71,9 +86,31 @@ impl TestHarness {
             request_id: 0,
             temp_dir,
             root_path,
+            configuration: None,
+            position_encodings: None,
+            workspace_edit: None,
+            server_capabilities: None,
         }
     }
 
+    #[must_use]
+    pub fn with_configuration(mut self, configuration: serde_json::Value) -> Self {
+        self.configuration = Some(configuration);
+        self
+    }
+
+    #[must_use]
+    pub fn with_position_encodings(mut self, encodings: Vec<PositionEncodingKind>) -> Self {
+        self.position_encodings = Some(encodings);
+        self
+    }
+
+    #[must_use]
+    pub fn with_workspace_edit(mut self, workspace_edit: WorkspaceEditClientCapabilities) -> Self {
+        self.workspace_edit = Some(workspace_edit);
+        self
+    }
+
     pub fn file_uri<P: AsRef<Path>>(&self, path: P) -> Uri {
         Uri::from_file_path(self.root_path.join(path)).unwrap()
     }
@@ -186,6 +223,25 @@ impl TestHarness {
 
         // 2. Send "initialize" request.
         let mut params = InitializeParams::default();
+        if self.configuration.is_some() {
+            params.capabilities.workspace = Some(WorkspaceClientCapabilities {
+                configuration: Some(true),
+                ..Default::default()
+            });
+        }
+        if let Some(encodings) = &self.position_encodings {
+            params.capabilities.general = Some(GeneralClientCapabilities {
+                position_encodings: Some(encodings.clone()),
+                ..Default::default()
+            });
+        }
+        if let Some(workspace_edit) = &self.workspace_edit {
+            params
+                .capabilities
+                .workspace
+                .get_or_insert_with(WorkspaceClientCapabilities::default)
+                .workspace_edit = Some(workspace_edit.clone());
+        }
         #[allow(deprecated)]
         {
             params.root_uri = Some(Uri::from_file_path(self.root_path.clone()).unwrap());
@@ -206,6 +262,9 @@ impl TestHarness {
             }
         };
         assert!(resp.is_ok());
+        let (_, result) = resp.into_parts();
+        let result: InitializeResult = serde_json::from_value(result.unwrap()).unwrap();
+        self.server_capabilities = Some(result.capabilities);
 
         // 3. Send "initialized" notification.
         let params = InitializedParams {};
@@ -254,10 +313,16 @@ impl TestHarness {
             fs::write(path, content).unwrap();
         }
 
-        let params = InitializeParams {
+        let mut params = InitializeParams {
             workspace_folders: Some(workspace_folders),
             ..Default::default()
         };
+        if self.configuration.is_some() {
+            params.capabilities.workspace = Some(WorkspaceClientCapabilities {
+                configuration: Some(true),
+                ..Default::default()
+            });
+        }
 
         let id = self.next_request_id();
         let req = Request::build(Initialize::METHOD)
@@ -290,6 +355,20 @@ impl TestHarness {
         }
     }
 
+    /// Open a document that only exists in the editor, without writing it to disk.
+    pub async fn open_unsaved_file(&mut self, name: &str, content: &str) -> Uri {
+        let uri = self.file_uri(name);
+        let text_document = TextDocumentItem {
+            uri: uri.clone(),
+            language_id: "flatbuffers".to_string(),
+            version: 1,
+            text: content.to_owned(),
+        };
+        self.call::<DidOpenSync>(DidOpenTextDocumentParams { text_document })
+            .await;
+        uri
+    }
+
     pub async fn change_file_sync(
         &mut self,
         identifier: VersionedTextDocumentIdentifier,
@@ -356,6 +435,17 @@ impl TestHarness {
     }
 
     pub async fn call<R: LspRequest>(&mut self, params: R::Params) -> R::Result
+    where
+        R::Result: DeserializeOwned,
+    {
+        self.try_call::<R>(params).await.expect("Request failed")
+    }
+
+    /// Like `call`, but returns the error response instead of panicking.
+    pub async fn try_call<R: LspRequest>(
+        &mut self,
+        params: R::Params,
+    ) -> Result<R::Result, jsonrpc::Error>
     where
         R::Result: DeserializeOwned,
     {
@@ -371,9 +461,11 @@ impl TestHarness {
                 ServerMessage::Response(resp) => {
                     // Check if this is the response we are waiting for.
                     if resp.id() == &Id::Number(id) {
-                        let value = resp.result().expect("Request failed").clone();
-                        return serde_json::from_value(value)
-                            .expect("Failed to deserialize response result");
+                        let (_, result) = resp.into_parts();
+                        return result.map(|value| {
+                            serde_json::from_value(value)
+                                .expect("Failed to deserialize response result")
+                        });
                     }
                     // This is a response for a different request. This shouldn't happen in a
                     // single-threaded test harness, so we'll panic.
@@ -512,6 +604,17 @@ impl TestHarness {
                     .await
                     .unwrap();
             }
+            WorkspaceConfiguration::METHOD => {
+                let id = req.id().unwrap().clone();
+                let result = serde_json::json!([self.configuration]);
+                let response = Response::from_ok(id, result);
+                let response_str = serde_json::to_string(&response).unwrap();
+                let encoded_response = Self::encode(&response_str);
+                self.req_stream
+                    .write_all(encoded_response.as_bytes())
+                    .await
+                    .unwrap();
+            }
             _ => {
                 panic!("Received unhandled server request: {}", req.method());
             }
@@ -10,7 +10,7 @@ pub fn parse_fixture(fixture: &str) -> (String, Position) {
         if let Some(col) = line.find("$0") {
             assert!(!found, "fixture must contain exactly one $0 cursor marker");
             position.line = as_pos_idx(line_num);
-            position.character = as_pos_idx(col);
+            position.character = as_pos_idx(line[..col].encode_utf16().count());
             content.push_str(&line.replace("$0", ""));
             found = true;
         } else {
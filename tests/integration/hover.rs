@@ -1,9 +1,10 @@
 use crate::harness::TestHarness;
 use crate::helpers::parse_fixture;
 use insta::assert_snapshot;
+use serde_json::json;
 use tower_lsp_server::lsp_types::{
-    request, Hover, HoverParams, TextDocumentIdentifier, TextDocumentPositionParams,
-    WorkDoneProgressParams,
+    request, Hover, HoverContents, HoverParams, Position, Range, TextDocumentIdentifier,
+    TextDocumentPositionParams, WorkDoneProgressParams,
 };
 
 async fn get_hover_response(
@@ -53,6 +54,27 @@ $0root_type MyTable;
     assert_snapshot!(serde_json::to_string_pretty(&response).unwrap());
 }
 
+#[tokio::test]
+async fn hover_on_file_extension_keyword() {
+    let fixture = r#"
+table MyTable { a:int; }
+root_type MyTable;
+file_ext$0ension "mon";
+"#;
+    let mut harness = TestHarness::new();
+    let response = get_hover_response(&mut harness, fixture, &[])
+        .await
+        .unwrap();
+    let HoverContents::Markup(content) = response.contents else {
+        panic!("expected markup hover");
+    };
+    assert!(content.value.starts_with("Sets the default file extension"));
+    assert_eq!(
+        response.range,
+        Some(Range::new(Position::new(3, 0), Position::new(3, 14)))
+    );
+}
+
 #[tokio::test]
 async fn hover_on_field_named_table() {
     let fixture = r"
@@ -123,6 +145,32 @@ table Line {
     assert_snapshot!(serde_json::to_string_pretty(&response).unwrap());
 }
 
+#[tokio::test]
+async fn hover_on_vector_brackets_shows_element() {
+    let fixture = r"
+/// Something to collect.
+table Widget {}
+
+table Collection {
+    items: $0[Widget];
+}
+";
+    let mut harness = TestHarness::new();
+    let response = get_hover_response(&mut harness, fixture, &[])
+        .await
+        .unwrap();
+    let HoverContents::Markup(content) = response.contents else {
+        panic!("expected markup hover");
+    };
+    assert!(content.value.starts_with("```flatbuffers\ntable Widget"));
+    assert!(content.value.contains("Something to collect."));
+    assert!(content.value.ends_with("Vector of `Widget`"));
+    assert_eq!(
+        response.range,
+        Some(Range::new(Position::new(5, 11), Position::new(5, 19)))
+    );
+}
+
 #[tokio::test]
 async fn hover_on_field_array_type() {
     let fixture = r"
@@ -135,6 +183,32 @@ struct MyStruct {
     assert_snapshot!(serde_json::to_string_pretty(&response).unwrap());
 }
 
+#[tokio::test]
+async fn hover_on_array_of_structs_shows_total_size() {
+    let fixture = r"
+struct Vec3 {
+    x: float;
+    y: float;
+    z: float;
+}
+
+struct Triangle {
+    vertices: [Vec3$0:3];
+}
+";
+    let mut harness = TestHarness::new();
+    let response = get_hover_response(&mut harness, fixture, &[])
+        .await
+        .unwrap();
+    let HoverContents::Markup(content) = response.contents else {
+        panic!("expected markup hover");
+    };
+    assert!(content.value.starts_with("```flatbuffers\nstruct Vec3"));
+    assert!(content
+        .value
+        .ends_with("Array of 3 `Vec3`\n\nSize: 36 bytes (12 × 3)"));
+}
+
 #[tokio::test]
 async fn hover_on_field_enum_type() {
     let fixture = r"
@@ -489,3 +563,403 @@ table RootTable {
     let response = get_hover_response(&mut harness, fixture, &[]).await;
     assert!(response.is_some());
 }
+
+#[tokio::test]
+async fn hover_multiline_doc_comment() {
+    let fixture = r"
+///   A table with
+///   a multi-line comment.
+///
+///     - nested item
+table $0MyTable {
+    b: bool;
+}
+";
+    let mut harness = TestHarness::new();
+    let response = get_hover_response(&mut harness, fixture, &[]).await;
+    let HoverContents::Markup(content) = response.unwrap().contents else {
+        panic!("expected markup hover");
+    };
+    assert!(content
+        .value
+        .ends_with("---\n\nA table with\na multi-line comment.\n\n  - nested item"));
+}
+
+#[tokio::test]
+async fn hover_ignores_plain_comment() {
+    let fixture = r"
+// Not documentation.
+table $0MyTable {
+    b: bool;
+}
+";
+    let mut harness = TestHarness::new();
+    let response = get_hover_response(&mut harness, fixture, &[]).await;
+    let HoverContents::Markup(content) = response.unwrap().contents else {
+        panic!("expected markup hover");
+    };
+    assert!(!content.value.contains("Not documentation."));
+}
+
+#[tokio::test]
+async fn hover_in_unsaved_file() {
+    let fixture = r"
+table MyTable {
+    a: int;
+}
+root_type My$0Table;
+";
+    let (content, position) = parse_fixture(fixture);
+    let mut harness = TestHarness::new();
+    harness.initialize_and_open(&[]).await;
+    let uri = harness.open_unsaved_file("untitled.fbs", &content).await;
+
+    let response = harness
+        .call::<request::HoverRequest>(HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        })
+        .await;
+    let HoverContents::Markup(content) = response.unwrap().contents else {
+        panic!("expected markup hover");
+    };
+    assert!(content.value.contains("table MyTable"));
+}
+
+#[tokio::test]
+async fn hover_with_crlf_and_bom() {
+    let content = "\u{feff}table MyTable {\r\n    a: int;\r\n}\r\n\r\nroot_type MyTable;\r\n";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+    let uri = harness.file_uri("schema.fbs");
+
+    // The BOM is a single UTF-16 code unit but three bytes.
+    for (position, expected) in [
+        (
+            Position::new(0, 8),
+            Range::new(Position::new(0, 7), Position::new(0, 14)),
+        ),
+        (
+            Position::new(4, 12),
+            Range::new(Position::new(4, 10), Position::new(4, 17)),
+        ),
+    ] {
+        let response = harness
+            .call::<request::HoverRequest>(HoverParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: uri.clone() },
+                    position,
+                },
+                work_done_progress_params: WorkDoneProgressParams::default(),
+            })
+            .await;
+        assert_eq!(response.and_then(|h| h.range), Some(expected));
+    }
+}
+
+#[tokio::test]
+async fn hover_after_emoji_on_same_line() {
+    let fixture = r"
+/// Ready for 🚀 launch.
+/* 🚀 */ table My$0Table {
+    a: int;
+}
+";
+    let mut harness = TestHarness::new();
+    let response = get_hover_response(&mut harness, fixture, &[])
+        .await
+        .unwrap();
+    let HoverContents::Markup(content) = response.contents else {
+        panic!("expected markup hover");
+    };
+    assert!(content.value.ends_with("---\n\nReady for 🚀 launch."));
+    // The emoji is two UTF-16 code units.
+    assert_eq!(
+        response.range,
+        Some(Range::new(Position::new(2, 15), Position::new(2, 22)))
+    );
+}
+
+#[tokio::test]
+async fn hover_range_covers_only_union_member_type_name() {
+    let schema = r"namespace Global;
+table Foo {}
+table Bar {}
+table Baz {}
+union Any { GFoo: Global.Foo, Bar,
+  Global.
+    Baz }
+";
+    let mut harness = TestHarness::new();
+    harness.initialize_and_open(&[("schema.fbs", schema)]).await;
+    let uri = harness.file_uri("schema.fbs");
+
+    for (position, expected) in [
+        // Aliased and namespaced.
+        (
+            Position::new(4, 26),
+            Range::new(Position::new(4, 25), Position::new(4, 28)),
+        ),
+        // Unqualified.
+        (
+            Position::new(4, 31),
+            Range::new(Position::new(4, 30), Position::new(4, 33)),
+        ),
+        // Namespaced across lines.
+        (
+            Position::new(6, 5),
+            Range::new(Position::new(6, 4), Position::new(6, 7)),
+        ),
+    ] {
+        let response = harness
+            .call::<request::HoverRequest>(HoverParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: uri.clone() },
+                    position,
+                },
+                work_done_progress_params: WorkDoneProgressParams::default(),
+            })
+            .await;
+        assert_eq!(response.and_then(|h| h.range), Some(expected));
+    }
+}
+
+#[tokio::test]
+async fn hover_on_deprecated_field_explains_id_retention() {
+    let fixture = r"
+table Monster {
+    hp: short;
+    m$0ana: short (deprecated);
+}
+";
+    let mut harness = TestHarness::new();
+    let response = get_hover_response(&mut harness, fixture, &[])
+        .await
+        .unwrap();
+    let HoverContents::Markup(markup) = response.contents else {
+        panic!("expected markup hover contents");
+    };
+    assert!(markup.value.contains("mana:short;"), "{}", markup.value);
+    assert!(
+        markup.value.contains("don't reuse its id"),
+        "{}",
+        markup.value
+    );
+    assert_eq!(
+        response.range,
+        Some(Range::new(Position::new(2, 4), Position::new(2, 8)))
+    );
+}
+
+#[tokio::test]
+async fn no_hover_on_field_that_is_not_deprecated() {
+    let fixture = r"
+table Monster {
+    h$0p: short;
+}
+";
+    let mut harness = TestHarness::new();
+    assert!(get_hover_response(&mut harness, fixture, &[])
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn hover_on_root_type_keyword_shows_file_identifier() {
+    let fixture = r#"
+namespace Game;
+table Monster { hp: short; mana: short; }
+// file_identifier "XXXX";
+file_identifier "MONS";
+root_$0type Monster;
+"#;
+    let mut harness = TestHarness::new();
+    let response = get_hover_response(&mut harness, fixture, &[])
+        .await
+        .unwrap();
+    let HoverContents::Markup(markup) = response.contents else {
+        panic!("expected markup hover contents");
+    };
+    assert!(
+        markup.value.starts_with(
+            "Root type: `Game.Monster` (2 fields)\n\nFile identifier: `MONS`\n\n---\n\n"
+        ),
+        "{}",
+        markup.value
+    );
+}
+
+#[tokio::test]
+async fn hover_on_user_defined_attribute_shows_its_doc_comment() {
+    let fixture = r#"
+/// Only used by the server.
+attribute "internal";
+table Monster {
+    secret: int (inter$0nal);
+}
+"#;
+    let mut harness = TestHarness::new();
+    let response = get_hover_response(&mut harness, fixture, &[])
+        .await
+        .unwrap();
+    let HoverContents::Markup(markup) = response.contents else {
+        panic!("expected markup hover contents");
+    };
+    assert_eq!(
+        markup.value,
+        "```flatbuffers\nattribute \"internal\";\n```\n\n---\n\nOnly used by the server."
+    );
+    assert_eq!(
+        response.range,
+        Some(Range::new(Position::new(4, 17), Position::new(4, 25)))
+    );
+}
+
+#[tokio::test]
+async fn hover_on_builtin_attribute() {
+    let fixture = r"
+table Monster {
+    hp: int (depre$0cated);
+}
+";
+    let mut harness = TestHarness::new();
+    let response = get_hover_response(&mut harness, fixture, &[])
+        .await
+        .unwrap();
+    let HoverContents::Markup(markup) = response.contents else {
+        panic!("expected markup hover contents");
+    };
+    assert_eq!(
+        markup.value,
+        "```flatbuffers\nattribute \"deprecated\"; // built-in\n```\n\n---\n\nOmit generated code for this field."
+    );
+}
+
+#[tokio::test]
+async fn no_hover_inside_multibyte_char_of_doc_comment() {
+    let fixture = r"
+table Monster {
+    /// Café$0 au lait
+    hp: int;
+}
+";
+    let mut harness = TestHarness::new();
+    assert!(get_hover_response(&mut harness, fixture, &[])
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn hover_on_attribute_after_multibyte_char() {
+    let fixture = r#"
+attribute "internal";
+table Monster {
+    /* é */ secret: int (inter$0nal);
+}
+"#;
+    let mut harness = TestHarness::new();
+    let response = get_hover_response(&mut harness, fixture, &[])
+        .await
+        .unwrap();
+    // `é` is two bytes but one UTF-16 code unit.
+    assert_eq!(
+        response.range,
+        Some(Range::new(Position::new(3, 25), Position::new(3, 33)))
+    );
+}
+
+#[tokio::test]
+async fn hover_on_default_of_namespaced_enum() {
+    let fixture = r"
+namespace Game;
+enum Difficulty : byte { Easy, Hard }
+namespace Other;
+table Level {
+    difficulty: Game.Difficulty = Ha$0rd;
+}
+";
+    let mut harness = TestHarness::new();
+    let response = get_hover_response(&mut harness, fixture, &[])
+        .await
+        .unwrap();
+    let HoverContents::Markup(markup) = response.contents else {
+        panic!("expected markup hover contents");
+    };
+    assert!(
+        markup.value.contains("enum Difficulty : byte"),
+        "{}",
+        markup.value
+    );
+    assert_eq!(
+        response.range,
+        Some(Range::new(Position::new(5, 34), Position::new(5, 38)))
+    );
+}
+
+#[tokio::test]
+async fn hover_on_type_shows_reference_counts_when_enabled() {
+    let fixture = r#"
+include "widget.fbs";
+table Box {
+    a: Wid$0get;
+    b: [Widget];
+}
+"#;
+    let mut harness =
+        TestHarness::new().with_configuration(json!({ "hoverReferenceCounts": true }));
+    let response = get_hover_response(
+        &mut harness,
+        fixture,
+        &[
+            ("widget.fbs", "table Widget {}"),
+            (
+                "shelf.fbs",
+                "include \"widget.fbs\";\ntable Shelf { w: Widget; }",
+            ),
+        ],
+    )
+    .await
+    .unwrap();
+    let HoverContents::Markup(markup) = response.contents else {
+        panic!("expected markup hover contents");
+    };
+    assert!(
+        markup
+            .value
+            .ends_with("\n\n---\n\nReferenced in 2 files (3 uses)"),
+        "{}",
+        markup.value
+    );
+}
+
+#[tokio::test]
+async fn hover_on_rpc_service_shows_streaming_methods() {
+    let fixture = r#"
+table Req {}
+table Res {}
+
+rpc_service Ser$0vice {
+    Read(Req):Res;
+    Watch(Req):Res (streaming: "server");
+}
+"#;
+    let mut harness = TestHarness::new();
+    let response = get_hover_response(&mut harness, fixture, &[])
+        .await
+        .unwrap();
+    let HoverContents::Markup(markup) = response.contents else {
+        panic!("expected markup hover contents");
+    };
+    assert!(
+        markup.value.contains(
+            "  Read(Req):Res;\n  Watch(Req):Res (streaming: \"server\"); // server-streaming\n"
+        ),
+        "{}",
+        markup.value
+    );
+}
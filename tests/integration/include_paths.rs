@@ -50,3 +50,36 @@ root_type ApiRequest;
     }
     assert_eq!(harness.call::<AllDiagnostics>(()).await.len(), 2);
 }
+
+#[tokio::test]
+async fn include_paths_are_resolved_per_workspace_root() {
+    let mut harness = TestHarness::new();
+    let folders = ["root1", "root2"];
+    let files = [
+        ("root1/lib/common.fbs", "table One {}"),
+        (
+            "root1/main.fbs",
+            "include \"common.fbs\";\ntable Main { one: One; }",
+        ),
+        ("root2/lib/common.fbs", "table Two {}"),
+        (
+            "root2/main.fbs",
+            "include \"common.fbs\";\ntable Main2 { two: Two; }",
+        ),
+    ];
+    harness
+        .initialize_with_workspace_folders(&folders, &files, &[])
+        .await;
+
+    #[allow(
+        clippy::mutable_key_type,
+        reason = "lsp_types::PublishDiagnosticParams uses Uri, which AllDiagnostics mimics"
+    )]
+    let all_diagnostics = harness.call::<AllDiagnostics>(()).await;
+    for name in ["root1/main.fbs", "root2/main.fbs"] {
+        let diagnostics = all_diagnostics
+            .get(&harness.file_uri(name))
+            .unwrap_or_else(|| panic!("{name} should have been parsed"));
+        assert!(diagnostics.is_empty(), "{name}: {diagnostics:?}");
+    }
+}
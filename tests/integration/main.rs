@@ -3,17 +3,26 @@
 use harness::TestHarness;
 
 mod analyzer;
+mod call_hierarchy;
 mod code_action;
 mod completion;
+mod configuration;
 mod diagnostics;
+mod execute_command;
+mod goto_declaration;
+mod goto_definition;
+mod goto_implementation;
 mod graceful_errors;
 mod harness;
 mod helpers;
 mod hover;
 mod include_paths;
+mod on_type_formatting;
+mod position_encoding;
 mod references;
 mod rename;
 mod scenarios;
+mod selection_range;
 mod test_logger;
 mod workspace;
 mod workspace_layout;
@@ -0,0 +1,66 @@
+use crate::harness::TestHarness;
+use tower_lsp_server::lsp_types::{
+    request, DocumentOnTypeFormattingParams, FormattingOptions, Position, Range,
+    TextDocumentIdentifier, TextDocumentPositionParams, TextEdit,
+};
+
+async fn on_type_formatting(content: &str, position: Position, ch: &str) -> Option<Vec<TextEdit>> {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content)])
+        .await;
+    let uri = harness.file_uri("schema.fbs");
+    harness
+        .call::<request::OnTypeFormatting>(DocumentOnTypeFormattingParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position,
+            },
+            ch: ch.to_string(),
+            options: FormattingOptions {
+                tab_size: 4,
+                insert_spaces: true,
+                ..Default::default()
+            },
+        })
+        .await
+}
+
+#[tokio::test]
+async fn closing_brace_is_dedented() {
+    let content = "table T {\n    a: int;\n    }\n";
+    let edits = on_type_formatting(content, Position::new(2, 5), "}").await;
+    assert_eq!(
+        edits,
+        Some(vec![TextEdit {
+            range: Range::new(Position::new(2, 0), Position::new(2, 4)),
+            new_text: String::new(),
+        }])
+    );
+}
+
+#[tokio::test]
+async fn new_line_is_indented_in_block() {
+    let content = "table T {\n    a: int;\n\n}\n";
+    let edits = on_type_formatting(content, Position::new(2, 0), "\n").await;
+    assert_eq!(
+        edits,
+        Some(vec![TextEdit {
+            range: Range::new(Position::new(2, 0), Position::new(2, 0)),
+            new_text: "    ".to_string(),
+        }])
+    );
+}
+
+#[tokio::test]
+async fn field_type_is_aligned_after_semicolon() {
+    let content = "table T {\n    a:     int;\n    bcd: int;\n}\n";
+    let edits = on_type_formatting(content, Position::new(2, 13), ";").await;
+    assert_eq!(
+        edits,
+        Some(vec![TextEdit {
+            range: Range::new(Position::new(2, 8), Position::new(2, 9)),
+            new_text: "   ".to_string(),
+        }])
+    );
+}
@@ -0,0 +1,99 @@
+use crate::harness::TestHarness;
+use tower_lsp_server::lsp_types::{
+    request, HoverParams, Location, Position, PositionEncodingKind, Range, RenameParams,
+    TextDocumentIdentifier, TextDocumentPositionParams, WorkDoneProgressParams,
+};
+
+// '😀' is 4 bytes in UTF-8 but 2 code units in UTF-16, so `Bar` starts at
+// byte 26 and UTF-16 column 24.
+const SCHEMA: &str = "table Bar {}\ntable Foo { /* 😀 */ b: Bar; }\n";
+
+async fn hover_range(harness: &mut TestHarness, position: Position) -> Option<Range> {
+    harness.initialize_and_open(&[("schema.fbs", SCHEMA)]).await;
+    let uri = harness.file_uri("schema.fbs");
+    harness
+        .call::<request::HoverRequest>(HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        })
+        .await
+        .and_then(|hover| hover.range)
+}
+
+#[tokio::test]
+async fn utf8_is_used_when_the_client_supports_it() {
+    let mut harness = TestHarness::new().with_position_encodings(vec![
+        PositionEncodingKind::UTF16,
+        PositionEncodingKind::UTF8,
+    ]);
+    let range = hover_range(&mut harness, Position::new(1, 27)).await;
+
+    assert_eq!(
+        harness.server_capabilities.unwrap().position_encoding,
+        Some(PositionEncodingKind::UTF8)
+    );
+    assert_eq!(
+        range,
+        Some(Range::new(Position::new(1, 26), Position::new(1, 29)))
+    );
+}
+
+#[tokio::test]
+async fn utf16_is_used_by_default() {
+    let mut harness = TestHarness::new();
+    let range = hover_range(&mut harness, Position::new(1, 25)).await;
+
+    assert_eq!(
+        harness.server_capabilities.unwrap().position_encoding,
+        Some(PositionEncodingKind::UTF16)
+    );
+    assert_eq!(
+        range,
+        Some(Range::new(Position::new(1, 24), Position::new(1, 27)))
+    );
+}
+
+#[tokio::test]
+async fn diagnostics_are_published_in_utf8() {
+    let schema = "table Foo { /* 😀 */ b: Baz; }\n";
+    let mut harness = TestHarness::new().with_position_encodings(vec![PositionEncodingKind::UTF8]);
+    harness.initialize_and_open(&[("schema.fbs", schema)]).await;
+
+    let uri = harness.file_uri("schema.fbs");
+    let diagnostic = harness.get_first_diagnostic_for_file(&uri).await;
+    assert_eq!(
+        diagnostic.range,
+        Range::new(Position::new(0, 26), Position::new(0, 29))
+    );
+}
+
+#[tokio::test]
+async fn rename_collision_location_is_in_utf8() {
+    let other = "/* 😀 */ table Bar {}\n";
+    let mut harness = TestHarness::new().with_position_encodings(vec![PositionEncodingKind::UTF8]);
+    harness
+        .initialize_and_open(&[("schema.fbs", "table Foo {}\n"), ("other.fbs", other)])
+        .await;
+
+    let error = harness
+        .try_call::<request::Rename>(RenameParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: harness.file_uri("schema.fbs"),
+                },
+                position: Position::new(0, 7),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            new_name: "Bar".to_string(),
+        })
+        .await
+        .unwrap_err();
+    let location: Location = serde_json::from_value(error.data.unwrap()).unwrap();
+    assert_eq!(
+        location.range,
+        Range::new(Position::new(0, 17), Position::new(0, 20))
+    );
+}
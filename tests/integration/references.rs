@@ -6,6 +6,16 @@ use tower_lsp_server::lsp_types::{
 };
 
 async fn get_references(fixture: &str, other_files: &[(&str, &str)]) -> Vec<Location> {
+    try_get_references(fixture, other_files, true)
+        .await
+        .unwrap()
+}
+
+async fn try_get_references(
+    fixture: &str,
+    other_files: &[(&str, &str)],
+    include_declaration: bool,
+) -> Option<Vec<Location>> {
     let (content, position) = parse_fixture(fixture);
 
     let mut workspace = vec![("schema.fbs", content.as_str())];
@@ -24,11 +34,27 @@ async fn get_references(fixture: &str, other_files: &[(&str, &str)]) -> Vec<Loca
             work_done_progress_params: WorkDoneProgressParams::default(),
             partial_result_params: PartialResultParams::default(),
             context: ReferenceContext {
-                include_declaration: true,
+                include_declaration,
             },
         })
         .await
-        .unwrap()
+}
+
+/// Check that the declaration at `declaration` is only listed when it is requested,
+/// and that the other references are the same either way.
+async fn assert_declaration_is_optional(fixture: &str, declaration: Range) {
+    let with = try_get_references(fixture, &[], true)
+        .await
+        .unwrap_or_default();
+    let without = try_get_references(fixture, &[], false)
+        .await
+        .unwrap_or_default();
+
+    let with_ranges: Vec<Range> = with.iter().map(|l| l.range).collect();
+    let without_ranges: Vec<Range> = without.iter().map(|l| l.range).collect();
+    assert!(with_ranges.contains(&declaration), "{with_ranges:?}");
+    assert!(!without_ranges.contains(&declaration), "{without_ranges:?}");
+    assert_eq!(with.len(), without.len() + 1);
 }
 
 #[tokio::test]
@@ -615,3 +641,174 @@ union Types {
         Range::new(Position::new(9, 8), Position::new(9, 14))
     );
 }
+
+#[tokio::test]
+async fn find_references_includes_nested_flatbuffer_root_type() {
+    let fixture = r#"
+table Inn$0er {
+    a: int;
+}
+
+table Outer {
+    inner: [ubyte] (nested_flatbuffer: "Inner");
+}
+"#;
+    let mut locations = get_references(fixture, &[]).await;
+    locations.sort_by_key(|loc| loc.range.start.line);
+
+    assert_eq!(locations.len(), 2);
+    assert_eq!(
+        locations[1].range,
+        Range::new(Position::new(6, 40), Position::new(6, 45))
+    );
+}
+
+#[tokio::test]
+async fn find_references_from_nested_flatbuffer_root_type() {
+    let fixture = r#"
+namespace NS;
+
+table Inner {
+    a: int;
+}
+
+table Outer {
+    inner: [ubyte] (nested_flatbuffer: "NS.In$0ner");
+}
+"#;
+    let mut locations = get_references(fixture, &[]).await;
+    locations.sort_by_key(|loc| loc.range.start.line);
+
+    assert_eq!(locations.len(), 2);
+    // Definition
+    assert_eq!(
+        locations[0].range,
+        Range::new(Position::new(3, 6), Position::new(3, 11))
+    );
+    assert_eq!(
+        locations[1].range,
+        Range::new(Position::new(8, 43), Position::new(8, 48))
+    );
+}
+
+// Fields and enum variants have no references to toggle the declaration of.
+// Fields are not referenced by name elsewhere in a schema, and enum variants
+// have no declaration location of their own.
+#[tokio::test]
+async fn no_references_for_field() {
+    let fixture = r"
+table Bar { col$0or: int; }
+";
+    for include_declaration in [true, false] {
+        assert!(try_get_references(fixture, &[], include_declaration)
+            .await
+            .is_none());
+    }
+}
+
+#[tokio::test]
+async fn include_declaration_for_table() {
+    let fixture = r"
+table Fo$0o {}
+table Bar { foo: Foo; }
+";
+    assert_declaration_is_optional(
+        fixture,
+        Range::new(Position::new(1, 6), Position::new(1, 9)),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn include_declaration_for_struct() {
+    let fixture = r"
+struct Poi$0nt { x: float; }
+table Bar { point: Point; }
+";
+    assert_declaration_is_optional(
+        fixture,
+        Range::new(Position::new(1, 7), Position::new(1, 12)),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn include_declaration_for_enum() {
+    let fixture = r"
+enum Col$0or: byte { Red, Green }
+table Bar { color: Color; }
+";
+    assert_declaration_is_optional(
+        fixture,
+        Range::new(Position::new(1, 5), Position::new(1, 10)),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn include_declaration_for_union() {
+    let fixture = r"
+table Foo {}
+union An$0y { Foo }
+table Bar { any: Any; }
+";
+    assert_declaration_is_optional(
+        fixture,
+        Range::new(Position::new(2, 6), Position::new(2, 9)),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn include_declaration_for_rpc_service() {
+    let fixture = r"
+table Req {}
+table Res {}
+rpc_service Serv$0ice {
+    Read(Req):Res;
+}
+";
+    assert_declaration_is_optional(
+        fixture,
+        Range::new(Position::new(3, 12), Position::new(3, 19)),
+    )
+    .await;
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn references_use_the_uri_a_symlinked_file_was_opened_with() {
+    let content = "table Pen {}\ntable Case { pen: Pen; }";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open_some(&[("real/schema.fbs", content)], &[])
+        .await;
+    std::os::unix::fs::symlink(
+        harness.root_path.join("real"),
+        harness.root_path.join("link"),
+    )
+    .unwrap();
+
+    let link_uri = harness.open_unsaved_file("link/schema.fbs", content).await;
+    let locations = harness
+        .call::<request::References>(ReferenceParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: link_uri.clone(),
+                },
+                position: Position::new(0, 7),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: ReferenceContext {
+                include_declaration: true,
+            },
+        })
+        .await
+        .unwrap();
+    assert_eq!(locations.len(), 2);
+    assert!(
+        locations.iter().all(|location| location.uri == link_uri),
+        "{locations:?}"
+    );
+}
@@ -8,8 +8,10 @@ use crate::helpers::parse_fixture;
 use std::collections::HashMap;
 use std::str::FromStr;
 use tower_lsp_server::lsp_types::{
-    request, Position, Range, RenameParams, TextDocumentIdentifier, TextDocumentPositionParams,
-    TextEdit, Uri, WorkDoneProgressParams,
+    request, ChangeAnnotationWorkspaceEditClientCapabilities, DocumentChanges, Location, OneOf,
+    Position, PrepareRenameResponse, Range, RenameParams, TextDocumentIdentifier,
+    TextDocumentPositionParams, TextEdit, Uri, WorkDoneProgressParams, WorkspaceEdit,
+    WorkspaceEditClientCapabilities,
 };
 
 async fn get_rename_edits(
@@ -175,3 +177,401 @@ table OtherTable {
         )
     );
 }
+
+#[tokio::test]
+async fn rename_edits_unopened_files() {
+    let (content, position) = parse_fixture(
+        r"
+table Weap$0on {}
+",
+    );
+    let monster = "include \"schema.fbs\";\ntable Monster { weapon: Weapon; }";
+    let armory =
+        "include \"monsters/monster.fbs\";\ntable Armory { weapons: [Weapon]; }\nroot_type Armory;";
+
+    // Only the file being renamed is opened. The others are indexed by the initial scan.
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open_some(
+            &[
+                ("schema.fbs", content.as_str()),
+                ("monsters/monster.fbs", monster),
+                ("armory/armory.fbs", armory),
+            ],
+            &["schema.fbs"],
+        )
+        .await;
+
+    let changes = harness
+        .call::<request::Rename>(RenameParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: harness.file_uri("schema.fbs"),
+                },
+                position,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            new_name: "Sword".to_string(),
+        })
+        .await
+        .unwrap()
+        .changes
+        .unwrap();
+
+    assert_eq!(changes.len(), 3);
+    assert_eq!(
+        changes[&harness.file_uri("monsters/monster.fbs")],
+        vec![TextEdit::new(
+            Range::new(Position::new(1, 24), Position::new(1, 30)),
+            "Sword".to_string()
+        )]
+    );
+    assert_eq!(
+        changes[&harness.file_uri("armory/armory.fbs")],
+        vec![TextEdit::new(
+            Range::new(Position::new(1, 25), Position::new(1, 31)),
+            "Sword".to_string()
+        )]
+    );
+}
+
+/// Rename with a client that supports annotated `documentChanges`.
+async fn try_rename_field(
+    fixture: &str,
+    new_name: &str,
+) -> Result<Option<WorkspaceEdit>, tower_lsp_server::jsonrpc::Error> {
+    let harness = TestHarness::new().with_workspace_edit(WorkspaceEditClientCapabilities {
+        document_changes: Some(true),
+        change_annotation_support: Some(ChangeAnnotationWorkspaceEditClientCapabilities {
+            groups_on_label: None,
+        }),
+        ..Default::default()
+    });
+    try_rename_field_in(harness, fixture, new_name).await
+}
+
+async fn try_rename_field_in(
+    mut harness: TestHarness,
+    fixture: &str,
+    new_name: &str,
+) -> Result<Option<WorkspaceEdit>, tower_lsp_server::jsonrpc::Error> {
+    let (content, position) = parse_fixture(fixture);
+
+    harness
+        .initialize_and_open(&[("schema.fbs", content.as_str())])
+        .await;
+
+    let main_file_uri = harness.file_uri("schema.fbs");
+    harness
+        .try_call::<request::Rename>(RenameParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: main_file_uri },
+                position,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            new_name: new_name.to_string(),
+        })
+        .await
+}
+
+#[tokio::test]
+async fn rename_field_declaration() {
+    let fixture = r"
+table MyTable {
+    old$0_name: int;
+    other: int;
+}
+";
+    let edit = try_rename_field(fixture, "new_name")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(edit.changes.is_none());
+    assert!(edit
+        .change_annotations
+        .unwrap()
+        .values()
+        .all(|a| a.needs_confirmation == Some(true)));
+
+    let Some(DocumentChanges::Edits(document_edits)) = edit.document_changes else {
+        panic!("expected document edits");
+    };
+    assert_eq!(document_edits.len(), 1);
+    let edits: Vec<TextEdit> = document_edits[0]
+        .edits
+        .iter()
+        .map(|e| match e {
+            OneOf::Left(edit) => edit.clone(),
+            OneOf::Right(annotated) => annotated.text_edit.clone(),
+        })
+        .collect();
+    assert_eq!(
+        edits,
+        vec![TextEdit::new(
+            Range::new(Position::new(2, 4), Position::new(2, 12)),
+            "new_name".to_string()
+        )]
+    );
+}
+
+#[tokio::test]
+async fn rename_field_declaration_without_change_annotation_support() {
+    let fixture = r"
+table MyTable {
+    old$0_name: int;
+}
+";
+    let edit = try_rename_field_in(TestHarness::new(), fixture, "new_name")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(edit.document_changes.is_none());
+    assert!(edit.change_annotations.is_none());
+    assert_eq!(
+        edit.changes.unwrap().into_values().collect::<Vec<_>>(),
+        vec![vec![TextEdit::new(
+            Range::new(Position::new(2, 4), Position::new(2, 12)),
+            "new_name".to_string()
+        )]]
+    );
+}
+
+#[tokio::test]
+async fn rename_field_rejects_sibling_collision() {
+    let fixture = r"
+table MyTable {
+    old$0_name: int;
+    other: int;
+}
+";
+    assert!(try_rename_field(fixture, "other").await.is_err());
+}
+
+#[tokio::test]
+async fn rename_field_rejects_union_type_collision() {
+    let fixture = r"
+table A {}
+union U { A }
+table MyTable {
+    old$0_name: int;
+    u: U;
+}
+";
+    assert!(try_rename_field(fixture, "u_type").await.is_err());
+}
+
+#[tokio::test]
+async fn rename_field_rejects_invalid_name() {
+    let fixture = r"
+table MyTable {
+    old$0_name: int;
+}
+";
+    assert!(try_rename_field(fixture, "1bad").await.is_err());
+}
+
+#[tokio::test]
+async fn prepare_rename_builtin_is_error() {
+    let (content, position) = parse_fixture(
+        r"
+table MyTable {
+    a: in$0t;
+}
+",
+    );
+
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content.as_str())])
+        .await;
+
+    let result = harness
+        .try_call::<request::PrepareRenameRequest>(TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier {
+                uri: harness.file_uri("schema.fbs"),
+            },
+            position,
+        })
+        .await;
+    assert!(result.is_err());
+}
+
+async fn prepare_rename(fixture: &str) -> Option<PrepareRenameResponse> {
+    let (content, position) = parse_fixture(fixture);
+
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content.as_str())])
+        .await;
+
+    harness
+        .call::<request::PrepareRenameRequest>(TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier {
+                uri: harness.file_uri("schema.fbs"),
+            },
+            position,
+        })
+        .await
+}
+
+#[tokio::test]
+async fn prepare_rename_qualified_vector_element_covers_only_the_name() {
+    let fixture = r"
+namespace NS;
+table Foo {}
+table Bar {
+    foos: [NS.F$0oo];
+}
+";
+    assert_eq!(
+        prepare_rename(fixture).await,
+        Some(PrepareRenameResponse::RangeWithPlaceholder {
+            range: Range::new(Position::new(4, 14), Position::new(4, 17)),
+            placeholder: "Foo".to_string(),
+        })
+    );
+}
+
+#[tokio::test]
+async fn prepare_rename_field_uses_its_name_as_placeholder() {
+    let fixture = r"
+table Bar {
+    my_fi$0eld: int;
+}
+";
+    assert_eq!(
+        prepare_rename(fixture).await,
+        Some(PrepareRenameResponse::RangeWithPlaceholder {
+            range: Range::new(Position::new(2, 4), Position::new(2, 12)),
+            placeholder: "my_field".to_string(),
+        })
+    );
+}
+
+#[tokio::test]
+async fn rename_builtin_is_error() {
+    let (content, position) = parse_fixture(
+        r"
+table MyTable {
+    a: str$0ing;
+}
+",
+    );
+
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content.as_str())])
+        .await;
+
+    let result = harness
+        .try_call::<request::Rename>(RenameParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: harness.file_uri("schema.fbs"),
+                },
+                position,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            new_name: "text".to_string(),
+        })
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn rename_rejects_existing_qualified_name() {
+    let (content, position) = parse_fixture(
+        r"
+namespace MyGame;
+table Fo$0o {}
+",
+    );
+    let other = "namespace MyGame;\ntable Bar {}";
+
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content.as_str()), ("other.fbs", other)])
+        .await;
+
+    let result = harness
+        .try_call::<request::Rename>(RenameParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: harness.file_uri("schema.fbs"),
+                },
+                position,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            new_name: "Bar".to_string(),
+        })
+        .await;
+
+    let error = result.unwrap_err();
+    assert!(error.message.contains("`MyGame.Bar` is already defined"));
+    let location: Location = serde_json::from_value(error.data.unwrap()).unwrap();
+    assert_eq!(location.uri, harness.file_uri("other.fbs"));
+    assert_eq!(
+        location.range,
+        Range::new(Position::new(1, 6), Position::new(1, 9))
+    );
+}
+
+/// The rename edits in `schema.fbs`, sorted by position.
+async fn get_sorted_rename_ranges(fixture: &str, new_name: &str) -> Vec<Range> {
+    let changes = get_rename_edits(fixture, &[], new_name).await;
+    assert_eq!(changes.len(), 1);
+    let mut edits = changes.into_values().next().unwrap();
+    assert!(edits.iter().all(|e| e.new_text == new_name));
+    edits.sort_by_key(|e| e.range.start);
+    edits.into_iter().map(|e| e.range).collect()
+}
+
+#[tokio::test]
+async fn rename_qualified_union_variant() {
+    let fixture = r"
+namespace NS;
+table Fo$0o {}
+union U { NS.Foo }
+";
+    assert_eq!(
+        get_sorted_rename_ranges(fixture, "Baz").await,
+        vec![
+            Range::new(Position::new(2, 6), Position::new(2, 9)),
+            Range::new(Position::new(3, 13), Position::new(3, 16)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn rename_qualified_vector_field() {
+    let fixture = r"
+namespace NS;
+table Fo$0o {}
+table T {
+    v: [NS.Foo];
+}
+";
+    assert_eq!(
+        get_sorted_rename_ranges(fixture, "Baz").await,
+        vec![
+            Range::new(Position::new(2, 6), Position::new(2, 9)),
+            Range::new(Position::new(4, 11), Position::new(4, 14)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn rename_qualified_root_type() {
+    let fixture = r"
+namespace NS;
+table Fo$0o {}
+root_type NS.Foo;
+";
+    assert_eq!(
+        get_sorted_rename_ranges(fixture, "Baz").await,
+        vec![
+            Range::new(Position::new(2, 6), Position::new(2, 9)),
+            Range::new(Position::new(3, 13), Position::new(3, 16)),
+        ]
+    );
+}
@@ -0,0 +1,94 @@
+use crate::harness::TestHarness;
+use crate::helpers::parse_fixture;
+use tower_lsp_server::lsp_types::{
+    request, PartialResultParams, Position, Range, SelectionRange, SelectionRangeParams,
+    TextDocumentIdentifier, WorkDoneProgressParams,
+};
+
+async fn get_selection_ranges(fixture: &str) -> Vec<Range> {
+    let (content, position) = parse_fixture(fixture);
+
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", content.as_str())])
+        .await;
+
+    let main_file_uri = harness.file_uri("schema.fbs");
+    let response = harness
+        .call::<request::SelectionRangeRequest>(SelectionRangeParams {
+            text_document: TextDocumentIdentifier { uri: main_file_uri },
+            positions: vec![position],
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(response.len(), 1);
+
+    let mut ranges = Vec::new();
+    let mut current: Option<&SelectionRange> = response.first();
+    while let Some(selection) = current {
+        ranges.push(selection.range);
+        current = selection.parent.as_deref();
+    }
+    ranges
+}
+
+#[tokio::test]
+async fn selection_range_expands_from_field_type() {
+    let fixture = r"
+table MyTable {
+    my_field: in$0t;
+}
+";
+    let ranges = get_selection_ranges(fixture).await;
+    assert_eq!(
+        ranges,
+        vec![
+            // Identifier
+            Range::new(Position::new(2, 14), Position::new(2, 17)),
+            // Field declaration
+            Range::new(Position::new(2, 4), Position::new(2, 18)),
+            // Table body
+            Range::new(Position::new(1, 14), Position::new(3, 1)),
+            // Whole definition
+            Range::new(Position::new(1, 0), Position::new(3, 1)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn selection_range_expands_from_definition_name() {
+    let fixture = r"
+struct Vec2 {
+    x: float;
+    y: float;
+}
+
+table My$0Table {
+    position: Vec2; // { unbalanced braces in comments are ignored
+}
+";
+    let ranges = get_selection_ranges(fixture).await;
+    assert_eq!(
+        ranges,
+        vec![
+            Range::new(Position::new(6, 6), Position::new(6, 13)),
+            Range::new(Position::new(6, 0), Position::new(8, 1)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn selection_range_outside_definition() {
+    let fixture = r"
+namespace My$0Namespace;
+
+table MyTable {}
+";
+    let ranges = get_selection_ranges(fixture).await;
+    assert_eq!(
+        ranges,
+        vec![Range::new(Position::new(1, 10), Position::new(1, 21))]
+    );
+}
@@ -1,11 +1,15 @@
 use crate::harness::TestHarness;
 use crate::helpers::parse_fixture;
+use flatbuffers_language_server::diagnostics::codes::DiagnosticCode;
+use flatbuffers_language_server::ext::all_diagnostics::AllDiagnostics;
+use flatbuffers_language_server::ext::all_symbols::AllSymbols;
 use tower_lsp_server::lsp_types::{
     notification::{self, DidChangeWatchedFiles, DidChangeWorkspaceFolders},
     request, CompletionContext, CompletionParams, CompletionTriggerKind,
     DidChangeWatchedFilesParams, DidChangeWorkspaceFoldersParams, FileChangeType, FileEvent,
-    PartialResultParams, TextDocumentIdentifier, TextDocumentPositionParams,
-    WorkDoneProgressParams, WorkspaceFolder, WorkspaceFoldersChangeEvent,
+    PartialResultParams, Position, ProgressParamsValue, Range, SymbolKind, TextDocumentIdentifier,
+    TextDocumentPositionParams, WorkDoneProgress, WorkDoneProgressParams, WorkspaceFolder,
+    WorkspaceFoldersChangeEvent,
 };
 use tower_lsp_server::UriExt;
 
@@ -191,6 +195,52 @@ async fn diagnostics_are_cleared_on_workspace_folder_removal() {
     assert!(params.diagnostics.is_empty());
 }
 
+#[tokio::test]
+async fn dependents_error_on_workspace_folder_removal() {
+    let mut harness = TestHarness::new();
+    let folders = vec!["root1", "root2"];
+    let files = vec![
+        ("root1/types.fbs", "table Shared {}"),
+        (
+            "scratch/main.fbs",
+            "include \"types.fbs\";\ntable Main { shared: Shared; }",
+        ),
+    ];
+    harness
+        .initialize_with_workspace_folders(&folders, &files, &["scratch/main.fbs"])
+        .await;
+
+    // The file outside every root finds its include in root1.
+    let main_uri = harness.file_uri("scratch/main.fbs");
+    #[allow(
+        clippy::mutable_key_type,
+        reason = "lsp_types::PublishDiagnosticParams uses Uri, which AllDiagnostics mimics"
+    )]
+    let all_diagnostics = harness.call::<AllDiagnostics>(()).await;
+    assert_eq!(all_diagnostics.get(&main_uri), Some(&vec![]));
+
+    let removed_folder = WorkspaceFolder {
+        uri: harness.file_uri("root1/"),
+        name: "root1".to_string(),
+    };
+    harness
+        .send_notification::<DidChangeWorkspaceFolders>(DidChangeWorkspaceFoldersParams {
+            event: WorkspaceFoldersChangeEvent {
+                added: vec![],
+                removed: vec![removed_folder],
+            },
+        })
+        .await;
+
+    let diagnostic = harness.get_first_diagnostic_for_file(&main_uri).await;
+    assert_eq!(diagnostic.range.start.line, 0);
+    assert!(
+        diagnostic.message.contains("types.fbs"),
+        "{}",
+        diagnostic.message
+    );
+}
+
 #[tokio::test]
 async fn completions_are_removed_on_workspace_folder_removal() {
     let mut harness = TestHarness::new();
@@ -300,3 +350,155 @@ table T {
     };
     assert!(!labels.contains(&"TypeFromRemovedFile".to_string()));
 }
+
+#[tokio::test]
+async fn initial_scan_reports_progress() {
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[
+            ("a.fbs", "table A {}"),
+            ("b.fbs", "table B {}"),
+            ("c.fbs", "table C {}"),
+            ("d.fbs", "table D {}"),
+        ])
+        .await;
+
+    let percentages: Vec<u32> = harness
+        .pending_notifications::<notification::Progress>()
+        .into_iter()
+        .filter_map(|params| match params.value {
+            ProgressParamsValue::WorkDone(WorkDoneProgress::Report(report)) => report.percentage,
+            _ => None,
+        })
+        .collect();
+    assert_eq!(percentages, vec![25, 50, 75, 100]);
+}
+
+#[tokio::test]
+async fn changing_an_included_file_on_disk_updates_dependents() {
+    let leaf = "table Brand {}";
+    let schema = r#"
+include "leaf.fbs";
+table Pen { brand: Brand; }
+"#;
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open_some(
+            &[("schema.fbs", schema), ("leaf.fbs", leaf)],
+            &["schema.fbs"],
+        )
+        .await;
+
+    let schema_uri = harness.file_uri("schema.fbs");
+    let leaf_uri = harness.file_uri("leaf.fbs");
+    std::fs::write(leaf_uri.to_file_path().unwrap(), "table Ink {}").unwrap();
+    harness
+        .send_notification::<DidChangeWatchedFiles>(DidChangeWatchedFilesParams {
+            changes: vec![FileEvent {
+                uri: leaf_uri,
+                typ: FileChangeType::CHANGED,
+            }],
+        })
+        .await;
+
+    let diagnostic = harness.get_first_diagnostic_for_file(&schema_uri).await;
+    assert!(
+        diagnostic.message.contains("Brand"),
+        "{}",
+        diagnostic.message
+    );
+}
+
+#[tokio::test]
+async fn all_symbols_lists_indexed_definitions() {
+    let included = "namespace Lib;\n/// A color.\nenum Color : byte { Red, Green }\n";
+    let schema =
+        "include \"lib.fbs\";\ntable Pixel { color: Lib.Color; x: int; }\nunion Any { Pixel }\n";
+    let mut harness = TestHarness::new();
+    harness
+        .initialize_and_open(&[("schema.fbs", schema), ("lib.fbs", included)])
+        .await;
+
+    let symbols = harness.call::<AllSymbols>(()).await;
+    let mut names: Vec<&str> = symbols.keys().map(String::as_str).collect();
+    names.sort_unstable();
+    assert_eq!(names, vec!["Any", "Lib.Color", "Pixel"]);
+
+    let color = &symbols["Lib.Color"];
+    assert_eq!(color.kind, SymbolKind::ENUM);
+    assert_eq!(color.namespace, vec!["Lib"]);
+    assert_eq!(color.location.uri, harness.file_uri("lib.fbs"));
+    assert_eq!(
+        color.location.range,
+        Range::new(Position::new(2, 5), Position::new(2, 10))
+    );
+    assert_eq!(color.documentation.as_deref(), Some("A color."));
+    assert_eq!(color.members, vec!["Red", "Green"]);
+
+    assert_eq!(symbols["Pixel"].members, vec!["color", "x"]);
+    assert_eq!(symbols["Any"].members, vec!["Pixel"]);
+}
+
+#[tokio::test]
+async fn creating_a_missing_include_clears_the_error() {
+    let schema = "include \"included.fbs\";\ntable Pen { brand: Brand; }\n";
+    let mut harness = TestHarness::new();
+    harness.initialize_and_open(&[("schema.fbs", schema)]).await;
+
+    let schema_uri = harness.file_uri("schema.fbs");
+    let diagnostic = harness.get_first_diagnostic_for_file(&schema_uri).await;
+    assert!(
+        diagnostic.message.contains("cannot find included file"),
+        "{}",
+        diagnostic.message
+    );
+
+    let included_uri = harness.file_uri("included.fbs");
+    std::fs::write(included_uri.to_file_path().unwrap(), "table Brand {}").unwrap();
+    harness
+        .send_notification::<DidChangeWatchedFiles>(DidChangeWatchedFilesParams {
+            changes: vec![FileEvent {
+                uri: included_uri,
+                typ: FileChangeType::CREATED,
+            }],
+        })
+        .await;
+
+    loop {
+        let params = harness
+            .notification::<notification::PublishDiagnostics>()
+            .await;
+        if params.uri == schema_uri {
+            assert!(params.diagnostics.is_empty(), "{:?}", params.diagnostics);
+            break;
+        }
+    }
+}
+
+#[tokio::test]
+async fn creating_a_suppressed_missing_include_revalidates_the_file() {
+    let schema = "include \"included.fbs\"; // fbls:allow(include-not-found)\ntable Pen {}\n";
+    let mut harness = TestHarness::new();
+    harness.initialize_and_open(&[("schema.fbs", schema)]).await;
+
+    let included_uri = harness.file_uri("included.fbs");
+    std::fs::write(included_uri.to_file_path().unwrap(), "table Brand {}").unwrap();
+    harness
+        .send_notification::<DidChangeWatchedFiles>(DidChangeWatchedFilesParams {
+            changes: vec![FileEvent {
+                uri: included_uri,
+                typ: FileChangeType::CREATED,
+            }],
+        })
+        .await;
+
+    // Once the include resolves, it is reported as unused.
+    let schema_uri = harness.file_uri("schema.fbs");
+    let diagnostic = harness.get_first_diagnostic_for_file(&schema_uri).await;
+    assert_eq!(
+        diagnostic.code,
+        Some(DiagnosticCode::UnusedInclude.into()),
+        "{}",
+        diagnostic.message
+    );
+}
@@ -272,3 +272,97 @@ fn test_overlapping_roots() {
         .collect();
     assert_eq!(layout.search_paths, expected_search_paths);
 }
+
+#[test]
+fn test_search_paths_for_file_in_root() {
+    let dir = tempdir().unwrap();
+    let root1 = dir.path().join("root1");
+    let root2 = dir.path().join("root2");
+    let subdir = root1.join("subdir");
+    fs::create_dir_all(&subdir).unwrap();
+    fs::create_dir_all(&root2).unwrap();
+    File::create(subdir.join("a.fbs")).unwrap();
+    File::create(root2.join("b.fbs")).unwrap();
+
+    let canonical_root1 = fs::canonicalize(&root1).unwrap();
+    let canonical_root2 = fs::canonicalize(&root2).unwrap();
+    let canonical_subdir = fs::canonicalize(&subdir).unwrap();
+
+    let mut layout = WorkspaceLayout::new();
+    layout.add_roots(vec![canonical_root1.clone(), canonical_root2.clone()]);
+    layout.discover_files();
+
+    assert_eq!(
+        layout.search_paths_for(&canonical_subdir.join("a.fbs")),
+        vec![canonical_root1, canonical_subdir.clone()]
+    );
+    assert_eq!(
+        layout.search_paths_for(&canonical_root2.join("b.fbs")),
+        vec![canonical_root2.clone()]
+    );
+    assert_eq!(
+        layout
+            .search_paths_for(&dir.path().join("outside.fbs"))
+            .len(),
+        layout.search_paths.len()
+    );
+}
+
+#[test]
+fn test_discover_files_skips_ignored_directories() {
+    let dir = tempdir().unwrap();
+    let root = fs::canonicalize(dir.path()).unwrap();
+    for name in [
+        "schema.fbs",
+        "target/schema.fbs",
+        "gen/schema.fbs",
+        "src/other.fbs",
+    ] {
+        let path = root.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        File::create(path).unwrap();
+    }
+
+    let mut layout = WorkspaceLayout::new();
+    layout.add_root(root.clone());
+    layout.ignore_patterns = vec!["gen/".to_string()];
+    let known_files: HashSet<PathBuf> = layout.discover_files().into_iter().collect();
+    let expected_files: HashSet<PathBuf> =
+        [root.join("schema.fbs"), root.join("src/other.fbs")].into();
+    assert_eq!(known_files, expected_files);
+    assert!(!layout.search_paths.contains(&root.join("target")));
+    assert!(layout.is_ignored(&root.join("gen/schema.fbs")));
+    assert!(!layout.is_ignored(&root.join("src/other.fbs")));
+
+    // A `!` pattern scans a directory that is ignored by default.
+    layout.ignore_patterns.push("!target/".to_string());
+    let known_files: HashSet<PathBuf> = layout.discover_files().into_iter().collect();
+    assert!(known_files.contains(&root.join("target/schema.fbs")));
+}
+
+#[test]
+fn test_discover_files_respects_gitignore() {
+    let dir = tempdir().unwrap();
+    let root = fs::canonicalize(dir.path()).unwrap();
+    for name in ["schema.fbs", "build/schema.fbs", "nested/out/schema.fbs"] {
+        let path = root.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        File::create(path).unwrap();
+    }
+    // Not a git repository, but the .gitignore files still apply.
+    fs::write(root.join(".gitignore"), "build/\n").unwrap();
+    fs::write(root.join("nested/.gitignore"), "out/\n").unwrap();
+
+    let mut layout = WorkspaceLayout::new();
+    layout.add_root(root.clone());
+    let known_files: HashSet<PathBuf> = layout.discover_files().into_iter().collect();
+    assert_eq!(known_files, [root.join("schema.fbs")].into());
+    assert!(layout.is_ignored(&root.join("build/schema.fbs")));
+    assert!(layout.is_ignored(&root.join("nested/out/schema.fbs")));
+    assert!(!layout.is_ignored(&root.join("schema.fbs")));
+
+    layout.respect_gitignore = false;
+    let known_files: HashSet<PathBuf> = layout.discover_files().into_iter().collect();
+    assert_eq!(known_files.len(), 3);
+    assert!(!layout.is_ignored(&root.join("build/schema.fbs")));
+}
@@ -92,3 +92,15 @@ rpc_service Service {
     let response = get_workspace_symbols(workspace, "MyT").await;
     assert_snapshot!(response);
 }
+
+#[tokio::test]
+async fn workspace_symbol_container_has_namespace_and_member_count() {
+    let workspace = &[(
+        "schema.fbs",
+        "namespace Game;\ntable Monster { hp: short; mana: short; }",
+    )];
+
+    let response: serde_json::Value =
+        serde_json::from_str(&get_workspace_symbols(workspace, "Monster").await).unwrap();
+    assert_eq!(response[0]["containerName"], "Game · 2 fields");
+}